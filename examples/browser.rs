@@ -0,0 +1,81 @@
+//! A box running and jumping around a level drawn on an HTML canvas
+//!
+//! Build with
+//! `cargo build --example browser --target wasm32-unknown-unknown --release`,
+//! run `wasm-bindgen --target web --out-dir examples/browser/pkg` on the resulting `browser.wasm`,
+//! then serve `examples/browser` over HTTP and open `index.html`.
+//!
+//! Drawing happens in `index.html`, which reads the map and the body through
+//! the exported getters, so the Rust side only needs `wasm-bindgen`, which is
+//! a development dependency for the wasm32 target only. On other targets
+//! this example is empty.
+#![cfg(target_arch = "wasm32")]
+use tile_net::{AabbCollider, TileNet};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+mod common;
+
+const LEVEL: &str = "
+XXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+X............................X
+X...............XXXX.........X
+X............................X
+X.........XXX..........XXX...X
+X............................X
+X....XXX...........XXX.......X
+X.................X..........X
+X..XX........XXXXXX......XX..X
+X............X....X..........X
+XXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+";
+
+/// The level and the body, stepped once per animation frame
+#[wasm_bindgen]
+pub struct Demo {
+	net: TileNet<bool>,
+	body: AabbCollider,
+}
+
+#[wasm_bindgen]
+impl Demo {
+	/// Create the level with the body in the top left corner
+	#[wasm_bindgen(constructor)]
+	pub fn new() -> Demo {
+		Demo {
+			net: TileNet::from_str_map(LEVEL, |x| x == 'X').expect("the level is rectangular"),
+			body: common::spawn(),
+		}
+	}
+
+	/// Advance by `dt` seconds with the given keys held down
+	pub fn tick(&mut self, dt: f32, left: bool, right: bool, jump: bool) {
+		common::tick(&mut self.body, &self.net, dt, left, right, jump);
+	}
+
+	/// Number of columns of the level
+	pub fn cols(&self) -> usize {
+		self.net.get_size().0
+	}
+
+	/// Number of rows of the level
+	pub fn rows(&self) -> usize {
+		self.net.get_size().1
+	}
+
+	/// Check whether a tile is a wall
+	pub fn is_solid(&self, x: usize, y: usize) -> bool {
+		self.net.get((x, y)).cloned().unwrap_or(false)
+	}
+
+	/// The body as `[x, y, width, height]`, in tiles
+	pub fn body(&self) -> Vec<f32> {
+		let rect = self.body.rect();
+		vec![rect.pos.0, rect.pos.1, rect.size.0, rect.size.1]
+	}
+}
+
+impl Default for Demo {
+	fn default() -> Demo {
+		Demo::new()
+	}
+}
@@ -0,0 +1,35 @@
+//! Movement shared by the platformer and browser examples
+use tile_net::{AabbCollider, Collable, Rect, TileNet, Vector};
+
+const GRAVITY: f32 = 40.0;
+const RUN: f32 = 8.0;
+const JUMP: f32 = 16.0;
+
+/// Create the body in the top left corner of a level
+pub fn spawn() -> AabbCollider {
+	let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(0.8, 1.6)));
+	body.step_height = 0.5;
+	body
+}
+
+/// Advance the body by `dt` seconds with the given keys held down
+pub fn tick(body: &mut AabbCollider, net: &TileNet<bool>, dt: f32, left: bool, right: bool, jump: bool) {
+	// Long frames would let the body cover several tiles per step, cap them
+	let dt = dt.min(1.0 / 30.0);
+	let grounded = body.contacts(net).ground;
+	body.vel.0 = match (left, right) {
+		(true, false) => -RUN,
+		(false, true) => RUN,
+		_ => 0.0,
+	};
+	body.vel.1 += GRAVITY * dt;
+	if grounded && jump {
+		body.vel.1 = -JUMP;
+	}
+	// The collider moves by its velocity, so scale it to this frame
+	let vel = body.vel;
+	body.vel = vel * dt;
+	body.solve(net, &mut ());
+	body.vel = Vector(if body.vel.0 == 0.0 { 0.0 } else { vel.0 },
+	                  if body.vel.1 == 0.0 { 0.0 } else { vel.1 });
+}
@@ -31,10 +31,10 @@ fn main() {
 		let side_speed = 0.4;
 		let vert_speed = 0.45;
 		if Key::A.is_pressed() {
-			coller.enqueue(Vector(-side_speed, 0.0));
+			coller.accelerate(Vector(-side_speed, 0.0));
 		}
 		if Key::D.is_pressed() {
-			coller.enqueue(Vector(side_speed, 0.0));
+			coller.accelerate(Vector(side_speed, 0.0));
 		}
 
 		coller.solve(&net, &mut ColState::check_x());
@@ -44,10 +44,10 @@ fn main() {
 			coller.jmp = false;
 		}
 		if Key::S.is_pressed() {
-			coller.enqueue(Vector(0.0, vert_speed * 100000.0));
+			coller.accelerate(Vector(0.0, vert_speed * 100000.0));
 		}
 
-		coller.enqueue(Vector(0.0, gravity));
+		coller.accelerate(Vector(0.0, gravity));
 		coller.solve(&net, &mut ColState::default());
 
 		window.clear(&Color::new_rgb(255, 255, 255));
@@ -85,10 +85,10 @@ fn create_window() -> RenderWindow {
 
 fn create_tilenet() -> tile_net::TileNet<usize> {
 	let mut net: TileNet<usize> = tile_net::TileNet::new(10, 10);
-	net.set_box(&0, (0, 0), (10, 10));
-	net.set_box(&1, (1, 1), (9, 9));
-	net.set_box(&0, (2, 2), (8, 8));
-	net.set_box(&1, (4, 4), (6, 6));
+	net.set_box_filled(&0, (0, 0), (10, 10));
+	net.set_box_filled(&1, (1, 1), (9, 9));
+	net.set_box_filled(&0, (2, 2), (8, 8));
+	net.set_box_filled(&1, (4, 4), (6, 6));
 	net
 }
 
@@ -127,7 +127,7 @@ struct Rects {
 impl Rects {
 	fn new() -> Rects {
 		Rects {
-			pts: vec![(0.0, 0.0), (0.99, 0.0), (0.0, 0.99), (0.99, 0.99)],
+			pts: Rect::new(Vector(0.0, 0.0), Vector(1.0, 1.0)).collision_points(0.01),
 			pos: Vector(2.0, 2.0),
 			mov: Vector(0.0, 0.0),
 			jmp: false,
@@ -144,7 +144,7 @@ impl Rects {
 		self.pos
 	}
 
-	fn enqueue(&mut self, vector: Vector) {
+	fn accelerate(&mut self, vector: Vector) {
 		self.mov = self.mov + vector;
 	}
 }
@@ -202,6 +202,10 @@ impl Collable<usize, ColState> for Rects {
 		self.mov
 	}
 
+	fn enqueue(&mut self, vector: Vector) {
+		self.mov = vector;
+	}
+
 	fn resolve<I>(&mut self, mut set: TileSet<usize, I>, state: &mut ColState) -> bool
 		where I: Iterator<Item = (i32, i32)>
 	{
@@ -0,0 +1,64 @@
+//! A box running and jumping around a small level
+//!
+//! Run with `cargo run --example platformer --features macroquad`.
+extern crate macroquad;
+extern crate tile_net;
+
+use macroquad::camera::{set_camera, set_default_camera, Camera2D};
+use macroquad::color::{BLACK, DARKGRAY, ORANGE, WHITE};
+use macroquad::input::{is_key_down, is_key_pressed, KeyCode};
+use macroquad::math::vec2;
+use macroquad::text::draw_text;
+use macroquad::time::get_frame_time;
+use macroquad::window::{clear_background, next_frame, screen_height, screen_width};
+use tile_net::{draw_rect, draw_view, Camera, GridSpace, TileNet, Vector};
+
+mod common;
+
+const TILE: f32 = 24.0;
+
+const LEVEL: &str = "
+XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+X......................................X
+X......................................X
+X...................XXXX...............X
+X......................................X
+X.............XXX...........XXX........X
+X......................................X
+X........XXX..................XXX......X
+X..............................XXX.....X
+X....XX...........XXXXX................X
+X.................X...X..........XXX...X
+X.........XXX.....X...X................X
+XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+";
+
+#[macroquad::main("tile_net platformer")]
+async fn main() {
+	let net = TileNet::from_str_map(LEVEL, |x| x == 'X').expect("the level is rectangular");
+	let mut body = common::spawn();
+	loop {
+		common::tick(&mut body,
+		             &net,
+		             get_frame_time(),
+		             is_key_down(KeyCode::A) || is_key_down(KeyCode::Left),
+		             is_key_down(KeyCode::D) || is_key_down(KeyCode::Right),
+		             is_key_pressed(KeyCode::W) || is_key_pressed(KeyCode::Space));
+
+		clear_background(WHITE);
+		let rect = body.rect();
+		let center = rect.pos + rect.size * 0.5;
+		let space = GridSpace::new(Vector(0.0, 0.0), Vector(TILE, TILE));
+		let view = Camera::new(center * TILE, Vector(screen_width(), screen_height()), space);
+		set_camera(&Camera2D {
+			target: vec2(center.0 * TILE, center.1 * TILE),
+			zoom: vec2(2.0 / screen_width(), 2.0 / screen_height()),
+			..Default::default()
+		});
+		draw_view(view.visible_view(&net), TILE, |x| if *x { Some(DARKGRAY) } else { None });
+		draw_rect(rect, TILE, ORANGE);
+		set_default_camera();
+		draw_text("A/D to run, W to jump", 10.0, 24.0, 24.0, BLACK);
+		next_frame().await
+	}
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use super::TileView;
+
+/// Frame sequences for animated tile values, such as water, lava or torches
+///
+/// Every animated value has a list of frame ids, for instance indices into
+/// a sprite sheet, shown for `frame_time` seconds each and looping. All
+/// animations share one clock, so equal tiles animate in step.
+///
+/// ```
+/// use tile_net::{TileAnimator, TileNet};
+/// let mut net: TileNet<char> = TileNet::new(3, 1);
+/// net.set(&'~', (1, 0));
+/// let mut animator = TileAnimator::new();
+/// animator.animate('~', vec![10, 11, 12], 0.25);
+/// animator.tick(0.6);
+/// assert_eq!(animator.frame(&'~'), Some(12));
+///
+/// let frames: Vec<Option<usize>> = net.view_all().animated(&animator).map(|x| x.1).collect();
+/// assert_eq!(frames, vec![None, Some(12), None]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TileAnimator<T> {
+	animations: BTreeMap<T, (Vec<usize>, f32)>,
+	elapsed: f64,
+}
+
+/// Tiles of a view together with their current frame, see `TileView::animated`
+///
+/// Yields `(tile, frame, x, y)`, where `frame` is `None` for tiles without an
+/// animation.
+pub struct AnimatedView<'a, T>
+	where T: 'a
+{
+	view: TileView<'a, T>,
+	animator: &'a TileAnimator<T>,
+}
+
+impl<T> TileAnimator<T>
+	where T: Ord
+{
+	/// Create an animator without animations, at time zero
+	pub fn new() -> TileAnimator<T> {
+		TileAnimator {
+			animations: BTreeMap::new(),
+			elapsed: 0.0,
+		}
+	}
+
+	/// Animate a tile value, replacing its previous animation
+	///
+	/// # Panics #
+	/// Panics if there are no frames, or `frame_time` is not positive.
+	pub fn animate(&mut self, tile: T, frames: Vec<usize>, frame_time: f32) {
+		assert!(!frames.is_empty(), "an animation needs at least one frame");
+		assert!(frame_time > 0.0, "the frame time of an animation must be positive");
+		self.animations.insert(tile, (frames, frame_time));
+	}
+
+	/// Remove the animation of a tile value
+	pub fn remove(&mut self, tile: &T) {
+		self.animations.remove(tile);
+	}
+
+	/// Advance the clock by `dt` seconds
+	pub fn tick(&mut self, dt: f32) {
+		self.elapsed += f64::from(dt);
+	}
+
+	/// Set the clock back to zero, showing the first frame of every animation
+	pub fn reset(&mut self) {
+		self.elapsed = 0.0;
+	}
+
+	/// Get the current frame of a tile value, or `None` if it is not animated
+	pub fn frame(&self, tile: &T) -> Option<usize> {
+		self.animations.get(tile).map(|&(ref frames, frame_time)| {
+			let index = (self.elapsed / f64::from(frame_time)) as u64 % frames.len() as u64;
+			frames[index as usize]
+		})
+	}
+}
+
+impl<T> Default for TileAnimator<T>
+	where T: Ord
+{
+	fn default() -> TileAnimator<T> {
+		TileAnimator::new()
+	}
+}
+
+impl<'a, T> TileView<'a, T>
+	where T: 'a
+{
+	/// Pair every tile with its current frame in an animator
+	pub fn animated(self, animator: &'a TileAnimator<T>) -> AnimatedView<'a, T> {
+		AnimatedView {
+			view: self,
+			animator,
+		}
+	}
+}
+
+impl<'a, T> Iterator for AnimatedView<'a, T>
+	where T: 'a + Ord
+{
+	type Item = (&'a T, Option<usize>, usize, usize);
+	fn next(&mut self) -> Option<Self::Item> {
+		self.view.next().map(|(tile, x, y)| (tile, self.animator.frame(tile), x, y))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn frames_loop() {
+		let mut animator = TileAnimator::new();
+		animator.animate(3, vec![7, 8], 0.5);
+		animator.animate(4, vec![1], 10.0);
+		let frames = |animator: &TileAnimator<usize>| (animator.frame(&3), animator.frame(&4), animator.frame(&5));
+		assert_eq!(frames(&animator), (Some(7), Some(1), None));
+		animator.tick(0.5);
+		assert_eq!(frames(&animator), (Some(8), Some(1), None));
+		for _ in 0..3 {
+			animator.tick(0.5);
+		}
+		assert_eq!(frames(&animator), (Some(7), Some(1), None));
+		animator.remove(&3);
+		assert_eq!(animator.frame(&3), None);
+	}
+
+	#[test]
+	#[should_panic(expected = "at least one frame")]
+	fn no_frames() {
+		TileAnimator::new().animate(0, vec![], 1.0);
+	}
+}
@@ -0,0 +1,271 @@
+use std::cmp::min;
+use super::{OutOfBounds, TileNet};
+
+const WORD: usize = 64;
+
+/// A grid of booleans storing one bit per tile
+///
+/// Works like a `TileNet<bool>` for collision masks, using an eighth of the
+/// memory. Tiles are read by value, since there is no `bool` to borrow, so
+/// `collide_set` yields `bool`s instead of references. Counting or searching
+/// boxes handles 64 tiles at a time.
+///
+/// ```
+/// use tile_net::{BitTileNet, Line, Vector};
+/// let mut mask = BitTileNet::new(100, 100);
+/// mask.set_row(true, 50);
+/// mask.set_box_filled(true, (10, 10), (19, 19));
+/// assert_eq!(mask.count_ones(), 200);
+/// assert!(mask.any_in_box((0, 100, 45, 55)));
+/// let line = Line(Vector(15.5, 0.5), Vector(15.5, 60.5));
+/// let hits = mask.collide_set(line.supercover()).filter(|x| *x).count();
+/// assert_eq!(hits, 11);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitTileNet {
+	words: Vec<u64>,
+	cols: usize,
+	rows: usize,
+	border: OutOfBounds<bool>,
+}
+
+impl BitTileNet {
+	/// Create a mask of the size (cols, rows) with every tile cleared
+	pub fn new(cols: usize, rows: usize) -> BitTileNet {
+		BitTileNet {
+			words: vec![0; (cols * rows).div_ceil(WORD)],
+			cols,
+			rows,
+			border: OutOfBounds::Empty,
+		}
+	}
+
+	/// Create a mask of the tiles of a net for which `f` returns true
+	///
+	/// The out-of-bounds policy is not copied; set it with `set_out_of_bounds`.
+	pub fn from_net<T, F>(net: &TileNet<T>, mut f: F) -> BitTileNet
+		where F: FnMut(&T) -> bool
+	{
+		let (cols, rows) = net.get_size();
+		let mut mask = BitTileNet::new(cols, rows);
+		for (index, tile) in net.get_raw().iter().enumerate() {
+			if f(tile) {
+				mask.words[index / WORD] |= 1 << (index % WORD);
+			}
+		}
+		mask
+	}
+
+	/// Convert into a `TileNet<bool>`
+	pub fn to_tile_net(&self) -> TileNet<bool> {
+		TileNet::from_iter(self.cols, (0..self.cols * self.rows).map(|x| self.bit(x)))
+	}
+
+	/// Get the size as (cols, rows)
+	pub fn get_size(&self) -> (usize, usize) {
+		(self.cols, self.rows)
+	}
+
+	/// Get a tile, or `None` outside of the mask
+	pub fn get(&self, p: (usize, usize)) -> Option<bool> {
+		if p.0 < self.cols && p.1 < self.rows {
+			Some(self.bit(p.0 + p.1 * self.cols))
+		} else {
+			None
+		}
+	}
+
+	/// Set a single tile
+	///
+	/// Out-of-bounds coordinates are silently ignored.
+	pub fn set(&mut self, value: bool, p: (usize, usize)) {
+		if p.0 < self.cols && p.1 < self.rows {
+			let index = p.0 + p.1 * self.cols;
+			self.set_range(index, index + 1, value);
+		}
+	}
+
+	/// Set every tile in the box from `start` to `stop`, both corners included
+	///
+	/// Parts of the box outside of the mask are ignored, see `TileNet::set_box_filled`.
+	pub fn set_box_filled(&mut self, value: bool, start: (usize, usize), stop: (usize, usize)) {
+		if start.0 >= self.cols || start.0 > stop.0 {
+			return;
+		}
+		let stop_x = min(stop.0, self.cols - 1);
+		for y in start.1..min(stop.1.saturating_add(1), self.rows) {
+			self.set_range(start.0 + y * self.cols, stop_x + 1 + y * self.cols, value);
+		}
+	}
+
+	/// Set a row
+	pub fn set_row(&mut self, value: bool, row: usize) {
+		self.set_box_filled(value, (0, row), (self.cols.saturating_sub(1), row));
+	}
+
+	/// Set a column
+	pub fn set_col(&mut self, value: bool, col: usize) {
+		self.set_box_filled(value, (col, 0), (col, self.rows.saturating_sub(1)));
+	}
+
+	/// Set every tile
+	pub fn fill(&mut self, value: bool) {
+		let len = self.cols * self.rows;
+		self.set_range(0, len, value);
+	}
+
+	/// Set the value of tiles outside of the mask, see `TileNet::set_out_of_bounds`
+	pub fn set_out_of_bounds(&mut self, border: OutOfBounds<bool>) {
+		self.border = border;
+	}
+
+	/// Count the set tiles
+	pub fn count_ones(&self) -> usize {
+		self.words.iter().map(|x| x.count_ones() as usize).sum()
+	}
+
+	/// Count the set tiles in a box
+	///
+	/// The box is given as (x_start, x_stop, y_start, y_stop) with exclusive
+	/// stops, like in `TileNet::view_box`, and is clipped to the mask.
+	pub fn count_in_box(&self, rect: (usize, usize, usize, usize)) -> usize {
+		let (x_stop, y_stop) = (min(rect.1, self.cols), min(rect.3, self.rows));
+		if rect.0 >= x_stop {
+			return 0;
+		}
+		(rect.2..y_stop).map(|y| self.count_range(rect.0 + y * self.cols, x_stop + y * self.cols)).sum()
+	}
+
+	/// Check whether any tile in a box is set, see `count_in_box`
+	pub fn any_in_box(&self, rect: (usize, usize, usize, usize)) -> bool {
+		let (x_stop, y_stop) = (min(rect.1, self.cols), min(rect.3, self.rows));
+		rect.0 < x_stop && (rect.2..y_stop).any(|y| self.count_range(rect.0 + y * self.cols, x_stop + y * self.cols) > 0)
+	}
+
+	/// Iterate over the tiles of a box row by row, see `count_in_box`
+	pub fn view_box(&self, rect: (usize, usize, usize, usize)) -> impl Iterator<Item = bool> + '_ {
+		let (x_stop, y_stop) = (min(rect.1, self.cols), min(rect.3, self.rows));
+		let cols = self.cols;
+		(rect.2..y_stop).flat_map(move |y| (rect.0..x_stop).map(move |x| self.bit(x + y * cols)))
+	}
+
+	/// Get the tiles at a list of coordinates, like `TileNet::collide_set`
+	///
+	/// Coordinates outside of the mask follow the out-of-bounds policy, where
+	/// `OutOfBounds::Solid` yields `true` and `Empty` skips the coordinate.
+	pub fn collide_set<I>(&self, list: I) -> BitSet<'_, I>
+		where I: Iterator<Item = (i32, i32)>
+	{
+		BitSet {
+			mask: self,
+			points: list,
+			last_coord: (0, 0),
+		}
+	}
+
+	fn bit(&self, index: usize) -> bool {
+		self.words[index / WORD] >> (index % WORD) & 1 == 1
+	}
+
+	// Words covering a range of bit indices, each with a mask of its bits
+	// inside the range.
+	fn words_of(start: usize, stop: usize) -> impl Iterator<Item = (usize, u64)> {
+		(start / WORD..stop.div_ceil(WORD)).map(move |word| {
+			let low = start.max(word * WORD) - word * WORD;
+			let high = stop.min((word + 1) * WORD) - word * WORD;
+			let mask = if high - low == WORD { !0 } else { ((1u64 << (high - low)) - 1) << low };
+			(word, mask)
+		})
+	}
+
+	fn set_range(&mut self, start: usize, stop: usize, value: bool) {
+		for (word, mask) in BitTileNet::words_of(start, stop) {
+			if value {
+				self.words[word] |= mask;
+			} else {
+				self.words[word] &= !mask;
+			}
+		}
+	}
+
+	fn count_range(&self, start: usize, stop: usize) -> usize {
+		BitTileNet::words_of(start, stop).map(|(word, mask)| (self.words[word] & mask).count_ones() as usize).sum()
+	}
+}
+
+/// Tile iterator of a `BitTileNet`, see `BitTileNet::collide_set`
+#[derive(Clone)]
+pub struct BitSet<'a, I> {
+	mask: &'a BitTileNet,
+	points: I,
+	last_coord: (i32, i32),
+}
+
+impl<'a, I> BitSet<'a, I> {
+	/// Get the coordinate of the last tile, see `TileSet::get_coords`
+	pub fn get_coords(&self) -> (i32, i32) {
+		self.last_coord
+	}
+}
+
+impl<'a, I> Iterator for BitSet<'a, I>
+	where I: Iterator<Item = (i32, i32)>
+{
+	type Item = bool;
+	fn next(&mut self) -> Option<Self::Item> {
+		let (cols, rows) = self.mask.get_size();
+		for point in self.points.by_ref() {
+			self.last_coord = point;
+			if point.0 >= 0 && point.1 >= 0 && (point.0 as usize) < cols && (point.1 as usize) < rows {
+				return self.mask.get((point.0 as usize, point.1 as usize));
+			}
+			match self.mask.border {
+				OutOfBounds::Empty => {}
+				OutOfBounds::Solid => return Some(true),
+				OutOfBounds::Custom(value) => return Some(value),
+				OutOfBounds::Wrap => {
+					if cols > 0 && rows > 0 {
+						let x = (point.0 as i64).rem_euclid(cols as i64) as usize;
+						let y = (point.1 as i64).rem_euclid(rows as i64) as usize;
+						return self.mask.get((x, y));
+					}
+				}
+			}
+		}
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ranges_across_words() {
+		let mut mask = BitTileNet::new(70, 3);
+		mask.set_box_filled(true, (60, 0), (69, 2));
+		mask.set(false, (65, 1));
+		assert_eq!(mask.count_ones(), 29);
+		assert_eq!(mask.count_in_box((62, 100, 1, 2)), 7);
+		assert!(!mask.any_in_box((0, 60, 0, 3)));
+		assert_eq!(mask.view_box((64, 67, 1, 2)).collect::<Vec<_>>(), vec![true, false, true]);
+		mask.fill(false);
+		assert_eq!(mask.count_ones(), 0);
+		mask.set_col(true, 69);
+		assert_eq!(mask.get((69, 2)), Some(true));
+		assert_eq!(mask.get((70, 2)), None);
+	}
+
+	#[test]
+	fn matches_tile_net() {
+		let net = TileNet::sample();
+		let mut mask = BitTileNet::from_net(&net, |x| *x != 0);
+		assert_eq!(mask.to_tile_net().get_raw(), net.map_ref(|x| *x != 0).get_raw());
+		let points = [(3, 4), (3, 5), (-1, 5), (12, 9)];
+		let set: Vec<bool> = mask.collide_set(points.iter().cloned()).collect();
+		assert_eq!(set, vec![false, true]);
+		mask.set_out_of_bounds(OutOfBounds::Wrap);
+		let set: Vec<bool> = mask.collide_set(points.iter().cloned()).collect();
+		assert_eq!(set, vec![false, true, true, true]);
+	}
+}
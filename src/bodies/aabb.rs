@@ -0,0 +1,710 @@
+use super::super::{Collable, ContactFlags, Direction, Points, Rect, Resolution, TileKind, TileNet, TileSet, TileShape, Vector, YAxis};
+
+// Distance the collision points are pulled inside the rectangle, so a body
+// resting exactly on a tile boundary does not count as overlapping the tile.
+const INSET: f32 = 1e-3;
+
+/// An axis-aligned box that slides along solid tiles
+///
+/// Set the velocity, then call `solve` with a predicate for solid tiles. The
+/// box moves as far as it can; when it hits a wall, floor or ceiling it is
+/// placed flush against it and keeps moving along the other axis. The
+/// blocked component of the velocity is zeroed.
+///
+/// Tiles with a `TileShape` other than full are floors: a box on or above
+/// them is lifted onto their surface, so it walks up slopes. The lift is
+/// applied to the end of each move, so keep moves across slopes short.
+/// Which way is up for slopes and `step_height` follows the `YAxis` of the
+/// net.
+///
+/// ```
+/// use tile_net::{AabbCollider, Collable, Rect, TileNet, Vector};
+/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+/// net.set_row(&1, 8);
+/// let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 2.0)));
+/// body.vel = Vector(1.0, 10.0);
+/// body.solve(&net, &mut ());
+/// // Landed on the floor at row 8 and slid one tile to the right
+/// assert_eq!(body.rect().pos, Vector(3.0, 6.0));
+/// assert_eq!(body.vel, Vector(1.0, 0.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct AabbCollider {
+	/// Movement applied by the next `solve`
+	pub vel: Vector,
+	/// Highest ledge the box steps onto when blocked horizontally, in tiles
+	///
+	/// A box walking against a ledge no higher than this is raised onto it
+	/// and keeps moving. If the raised box is still blocked, it is lowered
+	/// again. Zero, the default, disables stepping.
+	pub step_height: f32,
+	rect: Rect,
+	mov: Vector,
+	pts: Vec<(f32, f32)>,
+	// Height the box was raised by in the current solve
+	raised: f32,
+	// Horizontal move waiting for the box to be raised
+	pending: Option<f32>,
+	// Whether `mov` holds the move being solved, set by `enqueue` and `presolve`
+	solving: bool,
+	sensors: Vec<(i32, i32)>,
+}
+
+impl AabbCollider {
+	/// Create a resting collider
+	pub fn new(rect: Rect) -> AabbCollider {
+		AabbCollider {
+			vel: Vector(0.0, 0.0),
+			step_height: 0.0,
+			rect,
+			mov: Vector(0.0, 0.0),
+			pts: perimeter(rect.size),
+			raised: 0.0,
+			pending: None,
+			solving: false,
+			sensors: vec![],
+		}
+	}
+
+	/// Get the current rectangle
+	pub fn rect(&self) -> Rect {
+		self.rect
+	}
+
+	/// Teleport the collider, ignoring tiles on the way
+	pub fn set_position(&mut self, pos: Vector) {
+		self.rect.set_place(pos);
+	}
+
+	/// Change the size of the collider, keeping its top-left corner
+	pub fn set_size(&mut self, size: Vector) {
+		self.rect.size = size;
+		self.pts = perimeter(size);
+	}
+
+	/// Get the sensor tiles passed through during the last `solve`
+	///
+	/// Tiles are listed once, in the order they were reached. See
+	/// `TileKind::is_sensor`.
+	pub fn sensors(&self) -> &[(i32, i32)] {
+		&self.sensors
+	}
+
+	/// Find the sides of the collider touching solid tiles
+	///
+	/// See `TileNet::contact_flags`. After landing, `ground` is set.
+	pub fn contacts<T>(&self, net: &TileNet<T>) -> ContactFlags
+		where T: TileKind
+	{
+		net.contact_flags(Points::new(self.rect.pos, &self.pts), 10.0 * INSET, TileKind::is_solid)
+	}
+
+	/// Push the collider out of solid tiles it overlaps
+	///
+	/// Recovers a collider for which `solve` returns `Stuck`, see
+	/// `TileNet::depenetrate`. Returns the applied push.
+	pub fn depenetrate<T>(&mut self, net: &TileNet<T>) -> Vector
+		where T: TileKind
+	{
+		let push = net.depenetrate(Points::new(self.rect.pos, &self.pts), TileKind::is_solid);
+		self.rect.pos += push;
+		push
+	}
+
+	// Whether a solve is running or a move was enqueued for the next one
+	pub(crate) fn solving(&self) -> bool {
+		self.solving
+	}
+
+	// Tile columns or rows currently covered by the box along one axis.
+	fn span(min: f32, size: f32) -> (i32, i32) {
+		(min.floor() as i32, (min + size - INSET).floor() as i32)
+	}
+
+	// Clamp the queued move so it ends flush against `tile`, returning the
+	// resulting resolution. `None` means the box already overlaps the tile.
+	fn clamp_to(&mut self, tile: (i32, i32)) -> Option<Resolution> {
+		let (pos, size, mov) = (self.rect.pos, self.rect.size, self.mov);
+		let columns = AabbCollider::span(pos.0, size.0);
+		let rows = AabbCollider::span(pos.1, size.1);
+		// Free distance before touching the tile along each axis, if it lies beyond the box
+		let gap = |tile: i32, span: (i32, i32), min: f32, size: f32, mov: f32| {
+			if tile > span.1 && mov > 0.0 {
+				Some((tile as f32 - (min + size)).max(0.0))
+			} else if tile < span.0 && mov < 0.0 {
+				Some((tile as f32 + 1.0 - min).min(0.0))
+			} else {
+				None
+			}
+		};
+		let x = gap(tile.0, columns, pos.0, size.0, mov.0);
+		let y = gap(tile.1, rows, pos.1, size.1, mov.1);
+		let x_inside = tile.0 >= columns.0 && tile.0 <= columns.1;
+		let y_inside = tile.1 >= rows.0 && tile.1 <= rows.1;
+		let axis_x = match (x, y) {
+			(Some(_), None) if y_inside || mov.1 == 0.0 => true,
+			(None, Some(_)) if x_inside || mov.0 == 0.0 => false,
+			// Diagonal approach: the axis entered last is the one that blocks
+			(Some(dx), Some(dy)) => dx / mov.0 > dy / mov.1,
+			_ => return None,
+		};
+		let normal;
+		let t;
+		if axis_x {
+			let dx = x.unwrap();
+			t = dx / mov.0;
+			normal = Vector(-mov.0.signum(), 0.0);
+			self.mov = Vector(dx, mov.1);
+			self.vel = Vector(0.0, self.vel.1);
+		} else {
+			let dy = y.unwrap();
+			t = dy / mov.1;
+			normal = Vector(0.0, -mov.1.signum());
+			self.mov = Vector(mov.0, dy);
+			self.vel = Vector(self.vel.0, 0.0);
+		}
+		Some(Resolution::Blocked {
+			first_hit: tile,
+			normal,
+			t,
+		})
+	}
+
+	// Vertical position of the bottom of the box at `pos`.
+	fn bottom(&self, pos: Vector, y_axis: YAxis) -> f32 {
+		match y_axis {
+			YAxis::Down => pos.1 + self.rect.size.1,
+			YAxis::Up => pos.1,
+		}
+	}
+
+	// React to a horizontal block by `tile` while stepping is enabled. `mov`
+	// and `vel` are the values from before the block. `None` keeps the block.
+	fn step(&mut self, tile: (i32, i32), mov: Vector, vel: Vector, y_axis: YAxis) -> Option<Resolution> {
+		let down = y_axis.down();
+		if self.raised > 0.0 {
+			// Still blocked after being raised: go back down along the wall
+			self.mov = Vector(self.mov.0, self.raised * down);
+			self.raised = 0.0;
+			return None;
+		}
+		// The top of the tile is its far side when going up
+		let top = match y_axis {
+			YAxis::Down => tile.1 as f32,
+			YAxis::Up => tile.1 as f32 + 1.0,
+		};
+		let rise = (self.bottom(self.rect.pos, y_axis) - top) * down;
+		if rise <= 0.0 || rise > self.step_height || mov.1 * down < 0.0 {
+			return None;
+		}
+		self.raised = rise;
+		self.pending = Some(mov.0);
+		self.mov = Vector(0.0, -rise * down);
+		self.vel = vel;
+		Some(Resolution::Refused)
+	}
+
+	// Vertical position of the surface of a shaped tile below a box at `x`.
+	fn surface(&self, tile: (i32, i32), shape: TileShape, x: f32, y_axis: YAxis) -> f32 {
+		let left = x - tile.0 as f32;
+		let height = shape.max_height(left, left + self.rect.size.0);
+		match y_axis {
+			YAxis::Down => tile.1 as f32 + 1.0 - height,
+			YAxis::Up => tile.1 as f32 + height,
+		}
+	}
+
+	// Whether the box starts on or above the surface of a shaped tile.
+	fn above(&self, tile: (i32, i32), shape: TileShape, y_axis: YAxis) -> bool {
+		let depth = self.bottom(self.rect.pos, y_axis) - self.surface(tile, shape, self.rect.pos.0, y_axis);
+		depth * y_axis.down() <= INSET
+	}
+
+	// Lift the queued move so it ends on the surface of a shaped tile. `None`
+	// means the box stays above the surface.
+	fn clamp_shape(&mut self, tile: (i32, i32), shape: TileShape, y_axis: YAxis) -> Option<Resolution> {
+		let (pos, size, mov) = (self.rect.pos, self.rect.size, self.mov);
+		let down = y_axis.down();
+		let after = pos + mov;
+		let lift = (self.bottom(after, y_axis) - self.surface(tile, shape, after.0, y_axis)) * down;
+		if lift <= 0.0 {
+			return None;
+		}
+		// Movement and velocity towards the ground
+		let fall = mov.1 * down;
+		let t = if fall > 0.0 { ((fall - lift) / fall).max(0.0) } else { 1.0 };
+		self.mov = Vector(mov.0, mov.1 - lift * down);
+		if self.vel.1 * down > 0.0 {
+			self.vel = Vector(self.vel.0, 0.0);
+		}
+		Some(Resolution::Blocked {
+			first_hit: tile,
+			normal: shape.normal(after.0 + size.0 / 2.0 - tile.0 as f32, y_axis),
+			t,
+		})
+	}
+
+	// Like `clamp_to`, but for a tile only blocking through one side. `None`
+	// means the box passes: it does not cross that side from the outside.
+	fn clamp_one_way(&mut self, tile: (i32, i32), side: Direction) -> Option<Resolution> {
+		let (pos, size, mov) = (self.rect.pos, self.rect.size, self.mov);
+		let normal = side.normal();
+		let vertical = normal.0 == 0.0;
+		// Position of the tile edge, the leading box edge and the movement on the crossing axis
+		let (edge, lead, delta) = match side {
+			Direction::Up => (tile.1 as f32, pos.1 + size.1, mov.1),
+			Direction::Down => (tile.1 as f32 + 1.0, pos.1, mov.1),
+			Direction::Left => (tile.0 as f32, pos.0 + size.0, mov.0),
+			Direction::Right => (tile.0 as f32 + 1.0, pos.0, mov.0),
+		};
+		let outward = if vertical { normal.1 } else { normal.0 };
+		if delta * outward >= 0.0 || (lead - edge) * outward < -INSET {
+			return None;
+		}
+		let gap = if delta > 0.0 { (edge - lead).max(0.0) } else { (edge - lead).min(0.0) };
+		let t = gap / delta;
+		if t > 1.0 {
+			return None;
+		}
+		// The box must overlap the tile along the other axis when crossing the edge
+		let (start, length, first) = if vertical {
+			(pos.0 + mov.0 * t, size.0, tile.0)
+		} else {
+			(pos.1 + mov.1 * t, size.1, tile.1)
+		};
+		if start >= first as f32 + 1.0 || start + length <= first as f32 {
+			return None;
+		}
+		if vertical {
+			self.mov = Vector(mov.0, gap);
+			self.vel = Vector(self.vel.0, 0.0);
+		} else {
+			self.mov = Vector(gap, mov.1);
+			self.vel = Vector(0.0, self.vel.1);
+		}
+		Some(Resolution::Blocked {
+			first_hit: tile,
+			normal,
+			t,
+		})
+	}
+}
+
+// Points along the border of a box of the given size
+fn perimeter(size: Vector) -> Vec<(f32, f32)> {
+	Rect::new(Vector(0.0, 0.0), size).collision_points(INSET)
+}
+
+impl<T> Collable<T, ()> for AabbCollider
+	where T: TileKind
+{
+	fn points<'a>(&'a self) -> Points<'a> {
+		Points::new(self.rect.pos, &self.pts)
+	}
+
+	fn queued(&self) -> Vector {
+		if self.solving {
+			self.mov
+		} else {
+			self.vel
+		}
+	}
+
+	/// Replace the move of the next `solve`, which then ignores `vel`
+	fn enqueue(&mut self, vector: Vector) {
+		self.mov = vector;
+		self.solving = true;
+	}
+
+	fn presolve(&mut self, _: &mut ()) {
+		if !self.solving {
+			self.mov = self.vel;
+			self.solving = true;
+		}
+		self.raised = 0.0;
+		self.pending = None;
+		self.sensors.clear();
+	}
+
+	fn postsolve(&mut self, _: bool, _: bool, _: &mut ()) {
+		self.solving = false;
+	}
+
+	fn sensors(&self) -> &[(i32, i32)] {
+		&self.sensors
+	}
+
+	fn resolve<I>(&mut self, set: TileSet<T, I>, state: &mut ()) -> bool
+		where I: Iterator<Item = (i32, i32)>
+	{
+		self.resolve_detailed(set, state).is_moved()
+	}
+
+	fn resolve_detailed<I>(&mut self, mut set: TileSet<T, I>, _: &mut ()) -> Resolution
+		where I: Iterator<Item = (i32, i32)>
+	{
+		let (mov, vel, y_axis) = (self.mov, self.vel, set.y_axis());
+		let mut blocked = None;
+		let mut sensors = vec![];
+		while let Some(tile) = set.next_or_border() {
+			let coords = set.get_coords();
+			if tile.is_some_and(TileKind::is_sensor) {
+				sensors.push(coords);
+			}
+			// A solid border acts like a full tile
+			let (shape, one_way) = match tile {
+				Some(tile) if !tile.is_solid() => continue,
+				Some(tile) => (tile.shape(), tile.one_way()),
+				None => (TileShape::Full, None),
+			};
+			let resolution = if shape != TileShape::Full && self.above(coords, shape, y_axis) {
+				self.clamp_shape(coords, shape, y_axis)
+			} else {
+				match one_way {
+					Some(side) => self.clamp_one_way(coords, side),
+					None => {
+						match self.clamp_to(coords) {
+							Some(resolution) => Some(resolution),
+							None => return Resolution::Stuck,
+						}
+					}
+				}
+			};
+			if resolution.is_some() {
+				blocked = resolution;
+				break;
+			}
+		}
+		match blocked {
+			Some(resolution) => {
+				if let Resolution::Blocked { first_hit, normal, .. } = resolution {
+					// Blocked by a wall, not by a slope
+					if normal.1 == 0.0 && self.step_height > 0.0 {
+						if let Some(stepped) = self.step(first_hit, mov, vel, y_axis) {
+							return stepped;
+						}
+					}
+				}
+				resolution
+			}
+			None => {
+				self.rect.pos += self.mov;
+				for coords in sensors {
+					if !self.sensors.contains(&coords) {
+						self.sensors.push(coords);
+					}
+				}
+				if let Some(dx) = self.pending.take() {
+					// Raised onto the ledge, now make the horizontal move
+					self.mov = Vector(dx, 0.0);
+					return Resolution::Refused;
+				}
+				Resolution::Moved { by: self.mov }
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::super::{AxisOrder, GridSpace, OutOfBounds, Resolver, SolveOptions, SolveStrategy};
+
+	#[test]
+	fn perimeter_spacing() {
+		assert_eq!(perimeter(Vector(1.0, 1.0)).len(), 4);
+		let points = perimeter(Vector(2.5, 1.0));
+		assert_eq!(points.len(), 8);
+		assert!(points.iter().all(|x| x.0 < 2.5 && x.1 < 1.0));
+	}
+
+	#[test]
+	fn slide_along_wall() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_col(&1, 5);
+		let mut body = AabbCollider::new(Rect::new(Vector(3.5, 1.0), Vector(1.0, 1.0)));
+		body.vel = Vector(2.0, 3.0);
+		let resolution = body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(4.0, 4.0));
+		assert_eq!(resolution, Resolution::Moved { by: Vector(0.5, 3.0) });
+		assert_eq!(body.vel, Vector(0.0, 3.0));
+		let contacts = body.contacts(&net);
+		assert!(contacts.right && !contacts.ground && !contacts.left);
+	}
+
+	#[test]
+	fn solve_axes() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_col(&1, 5);
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+		body.vel = Vector(4.0, 1.0);
+		let report = body.solve_axes(&net, AxisOrder::XThenY, &mut ());
+		assert!(report.blocked_x() && !report.blocked_y());
+		assert_eq!(body.rect().pos, Vector(4.0, 3.0));
+		assert_eq!(body.vel, Vector(0.0, 1.0));
+		// The enqueued move is used up, the next solve moves by `vel` again
+		assert_eq!(body.solve(&net, &mut ()), Resolution::Moved { by: Vector(0.0, 1.0) });
+	}
+
+	#[test]
+	fn solve_with_resolver() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_col(&1, 5);
+		for resolver in [Resolver::Scale(0.5), Resolver::default()] {
+			let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+			body.vel = Vector(4.0, 0.0);
+			assert!(body.solve_with_resolver(&net, resolver, |x| *x != 0, &mut ()).is_moved());
+			let x = body.rect().pos.0;
+			assert!(x > 3.9 && x < 4.01);
+		}
+	}
+
+	#[test]
+	fn solid_border() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_out_of_bounds(OutOfBounds::Solid);
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+		body.vel = Vector(-5.0, 20.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(0.0, 9.0));
+		assert!(body.contacts(&net).ground);
+	}
+
+	#[test]
+	fn wide_body_hits_thin_pillar() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set(&1, (5, 0));
+		let mut body = AabbCollider::new(Rect::new(Vector(4.0, 3.0), Vector(3.0, 1.0)));
+		body.vel = Vector(0.0, -5.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(4.0, 1.0));
+	}
+
+	#[test]
+	fn sensors_behind_walls_are_not_reached() {
+		#[derive(Clone, Debug, Default)]
+		struct Tile(u8);
+		impl TileKind for Tile {
+			fn is_solid(&self) -> bool {
+				self.0 == 1
+			}
+			fn is_sensor(&self) -> bool {
+				self.0 == 2
+			}
+		}
+		let mut net: TileNet<Tile> = TileNet::new(10, 10);
+		net.set(&Tile(2), (3, 1));
+		net.set(&Tile(1), (5, 1));
+		net.set(&Tile(2), (6, 1));
+		let mut body = AabbCollider::new(Rect::new(Vector(0.0, 1.0), Vector(1.0, 1.0)));
+		body.vel = Vector(8.0, 0.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(4.0, 1.0));
+		assert_eq!(body.sensors(), &[(3, 1)]);
+		body.vel = Vector(0.0, 1.0);
+		body.solve(&net, &mut ());
+		assert!(body.sensors().is_empty());
+		// Both axes end up in the report
+		net.set(&Tile(2), (3, 2));
+		net.set(&Tile(2), (2, 4));
+		body.vel = Vector(-2.0, 3.0);
+		let options = SolveOptions {
+			strategy: SolveStrategy::Axes(AxisOrder::XThenY),
+			..SolveOptions::default()
+		};
+		let report = body.solve_with(&net, options, &mut ());
+		assert_eq!(body.rect().pos, Vector(2.0, 5.0));
+		assert_eq!(report.sensors, vec![(3, 2), (2, 4)]);
+	}
+
+	#[derive(Clone, Debug, Default)]
+	struct Terrain(u8);
+
+	impl TileKind for Terrain {
+		fn is_solid(&self) -> bool {
+			self.0 != 0
+		}
+
+		fn shape(&self) -> TileShape {
+			match self.0 {
+				2 => TileShape::SlopeNE,
+				3 => TileShape::HalfBottom,
+				4 => TileShape::Heights([1, 2, 3, 4, 4, 3, 2, 1]),
+				_ => TileShape::Full,
+			}
+		}
+	}
+
+	#[test]
+	fn walk_up_slope() {
+		let mut net: TileNet<Terrain> = TileNet::new(10, 10);
+		net.set_row(&Terrain(1), 8);
+		net.set(&Terrain(2), (4, 7));
+		net.set(&Terrain(1), (5, 7));
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 7.0), Vector(1.0, 1.0)));
+		let mut heights = vec![];
+		for _ in 0..6 {
+			body.vel = Vector(0.5, 0.0);
+			body.solve(&net, &mut ());
+			heights.push(body.rect().pos.1);
+		}
+		assert_eq!(heights, vec![7.0, 7.0, 6.5, 6.0, 6.0, 6.0]);
+		assert_eq!(body.rect().pos.0, 5.0);
+	}
+
+	#[test]
+	fn walk_up_slope_with_y_up() {
+		// The net of `walk_up_slope`, upside down
+		let mut net: TileNet<Terrain> = TileNet::new(10, 10);
+		net.set_space(GridSpace::default().with_y_axis(YAxis::Up));
+		net.set_row(&Terrain(1), 1);
+		net.set(&Terrain(2), (4, 2));
+		net.set(&Terrain(1), (5, 2));
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+		let mut heights = vec![];
+		for _ in 0..6 {
+			body.vel = Vector(0.5, 0.0);
+			body.solve(&net, &mut ());
+			heights.push(body.rect().pos.1);
+		}
+		assert_eq!(heights, vec![2.0, 2.0, 2.5, 3.0, 3.0, 3.0]);
+		assert_eq!(body.rect().pos.0, 5.0);
+		// Falling onto the slope, towards negative y
+		body.set_position(Vector(4.0, 6.0));
+		body.vel = Vector(0.0, -4.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(4.0, 3.0));
+		assert_eq!(body.vel, Vector(0.0, 0.0));
+	}
+
+	#[test]
+	fn land_on_half_tile() {
+		let mut net: TileNet<Terrain> = TileNet::new(10, 10);
+		net.set_row(&Terrain(3), 8);
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+		body.vel = Vector(0.0, 10.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(2.0, 7.5));
+		assert_eq!(body.vel, Vector(0.0, 0.0));
+		// Full tiles next to half tiles still block
+		net.set(&Terrain(1), (5, 8));
+		net.set(&Terrain(1), (5, 7));
+		body.vel = Vector(5.0, 0.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(4.0, 7.5));
+	}
+
+	#[test]
+	fn step_up() {
+		let mut net: TileNet<usize> = TileNet::new(20, 10);
+		net.set_row(&1, 8);
+		net.set_box_filled(&1, (6, 7), (9, 7));
+		net.set_box_filled(&1, (9, 5), (12, 7));
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 6.0), Vector(1.0, 2.0)));
+		body.step_height = 1.0;
+		for _ in 0..16 {
+			body.vel = Vector(0.5, 0.0);
+			body.solve(&net, &mut ());
+		}
+		// Stepped onto the low ledge, then stopped by the high one
+		assert_eq!(body.rect().pos, Vector(8.0, 5.0));
+		assert_eq!(body.vel, Vector(0.0, 0.0));
+		body.step_height = 0.0;
+		body.set_position(Vector(2.0, 6.0));
+		body.vel = Vector(5.0, 0.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(5.0, 6.0));
+		// No room for the box on the ledge: lowered back down
+		net.set(&1, (6, 5));
+		body.step_height = 1.0;
+		body.set_position(Vector(4.5, 6.0));
+		body.vel = Vector(1.0, 0.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(5.0, 6.0));
+	}
+
+	#[test]
+	fn step_up_with_y_up() {
+		// The net of `step_up`, upside down
+		let mut net: TileNet<usize> = TileNet::new(20, 10);
+		net.set_space(GridSpace::default().with_y_axis(YAxis::Up));
+		net.set_row(&1, 1);
+		net.set_box_filled(&1, (6, 2), (9, 2));
+		net.set_box_filled(&1, (9, 2), (12, 4));
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 2.0)));
+		body.step_height = 1.0;
+		for _ in 0..16 {
+			body.vel = Vector(0.5, 0.0);
+			body.solve(&net, &mut ());
+		}
+		assert_eq!(body.rect().pos, Vector(8.0, 3.0));
+		assert_eq!(body.vel, Vector(0.0, 0.0));
+		// No room for the box on the ledge: lowered back down
+		net.set(&1, (6, 4));
+		body.set_position(Vector(4.5, 2.0));
+		body.vel = Vector(1.0, 0.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(5.0, 2.0));
+	}
+
+	#[test]
+	fn heightmap() {
+		let mut net: TileNet<Terrain> = TileNet::new(10, 10);
+		net.set_row(&Terrain(4), 8);
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(0.25, 1.0)));
+		body.vel = Vector(0.0, 10.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(2.0, 7.75));
+		let mut positions = vec![];
+		for _ in 0..4 {
+			body.vel = Vector(0.125, 0.0);
+			body.solve(&net, &mut ());
+			positions.push(body.rect().pos.1);
+		}
+		assert_eq!(positions, vec![7.625, 7.5, 7.5, 7.5]);
+	}
+
+	#[derive(Clone, Debug, Default)]
+	struct Ledge(bool);
+
+	impl TileKind for Ledge {
+		fn is_solid(&self) -> bool {
+			self.0
+		}
+
+		fn one_way(&self) -> Option<Direction> {
+			Some(Direction::Left)
+		}
+	}
+
+	#[test]
+	fn one_way_from_the_side() {
+		let mut net: TileNet<Ledge> = TileNet::new(10, 10);
+		net.set_col(&Ledge(true), 5);
+		let mut body = AabbCollider::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+		body.vel = Vector(4.0, 1.0);
+		body.solve(&net, &mut ());
+		assert_eq!(body.rect().pos, Vector(4.0, 3.0));
+		let mut body = AabbCollider::new(Rect::new(Vector(8.0, 2.0), Vector(1.0, 1.0)));
+		body.vel = Vector(-5.0, 0.0);
+		assert!(body.solve(&net, &mut ()).is_moved());
+		// Overlapping a one-way tile is not being stuck
+		let mut overlapping = AabbCollider::new(Rect::new(Vector(4.5, 2.0), Vector(1.0, 1.0)));
+		overlapping.vel = Vector(1.0, 0.0);
+		assert!(overlapping.solve(&net, &mut ()).is_moved());
+	}
+
+	#[test]
+	fn stuck_inside() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set(&1, (2, 2));
+		let mut body = AabbCollider::new(Rect::new(Vector(2.2, 2.2), Vector(0.5, 0.5)));
+		body.vel = Vector(1.0, 0.0);
+		assert_eq!(body.solve(&net, &mut ()), Resolution::Stuck);
+		assert_eq!(body.rect().pos, Vector(2.2, 2.2));
+		// Pushed out through the closest side, then free to move again
+		let push = body.depenetrate(&net);
+		assert!((push - Vector(-0.6991, 0.0)).norm2sq() < 1e-6);
+		assert!(body.solve(&net, &mut ()).is_moved());
+	}
+}
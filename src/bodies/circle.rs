@@ -0,0 +1,428 @@
+use std::f32::consts::PI;
+use super::super::{Circle, Collable, Direction, Points, Resolution, TileKind, TileSet, Vector};
+
+// Gap kept between the circle and the surface it stops against, so a
+// resting circle does not touch the tile it rests on.
+const SKIN: f32 = 1e-3;
+
+// Largest distance between two neighbouring perimeter samples, in tiles.
+const SPACING: f32 = 0.25;
+
+/// How a `CircleCollider` reacts to hitting a solid tile
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CircleResponse {
+	/// Remove the velocity into the surface and keep moving along it
+	Slide,
+	/// Reflect the velocity off the surface
+	Bounce {
+		/// Fraction of the velocity along the normal that is kept, 1.0 is perfectly elastic
+		restitution: f32,
+	},
+}
+
+/// A circle that slides along or bounces off solid tiles
+///
+/// The perimeter is sampled with a density depending on the radius and the
+/// length of the move, and only the half facing the movement is traced. Hits are then resolved exactly:
+/// the swept circle is tested against every solid tile the samples reported.
+/// Every solid tile counts as full, whatever its `TileShape`.
+///
+/// ```
+/// use tile_net::{CircleCollider, CircleResponse, Collable, TileNet, Vector};
+/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+/// net.set_row(&1, 8);
+/// let mut ball = CircleCollider::new(Vector(3.0, 3.0), 0.5);
+/// ball.response = CircleResponse::Bounce { restitution: 1.0 };
+/// ball.vel = Vector(0.0, 10.0);
+/// ball.solve(&net, &mut ());
+/// assert!(ball.vel.1 < 0.0);
+/// assert!(ball.center().1 <= 7.5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CircleCollider {
+	/// Movement applied by the next `solve`
+	pub vel: Vector,
+	/// Reaction to hitting a solid tile
+	pub response: CircleResponse,
+	center: Vector,
+	radius: f32,
+	mov: Vector,
+	pts: Vec<(f32, f32)>,
+	// Whether `mov` holds the move being solved, set by `enqueue` and `presolve`
+	solving: bool,
+	sensors: Vec<(i32, i32)>,
+}
+
+impl CircleCollider {
+	/// Create a resting circle that slides
+	pub fn new(center: Vector, radius: f32) -> CircleCollider {
+		let mut circle = CircleCollider {
+			vel: Vector(0.0, 0.0),
+			response: CircleResponse::Slide,
+			center,
+			radius,
+			mov: Vector(0.0, 0.0),
+			pts: vec![],
+			solving: false,
+			sensors: vec![],
+		};
+		circle.sample();
+		circle
+	}
+
+	/// Get the sensor tiles passed through during the last `solve`
+	///
+	/// See `AabbCollider::sensors`.
+	pub fn sensors(&self) -> &[(i32, i32)] {
+		&self.sensors
+	}
+
+	/// Get the center of the circle
+	pub fn center(&self) -> Vector {
+		self.center
+	}
+
+	/// Get the radius of the circle
+	pub fn radius(&self) -> f32 {
+		self.radius
+	}
+
+	/// Teleport the circle, ignoring tiles on the way
+	pub fn set_center(&mut self, center: Vector) {
+		self.center = center;
+	}
+
+	// Sample the half of the perimeter facing the queued move, or the whole
+	// perimeter when not moving. Longer moves get more samples, so lines
+	// fanning out from the perimeter do not pass by a tile.
+	fn sample(&mut self) {
+		let mov = self.mov;
+		let length = mov.norm2sq().sqrt();
+		let count = ((2.0 * PI * self.radius + length) / SPACING).ceil().max(8.0) as usize;
+		let radius = self.radius;
+		self.pts.clear();
+		let outline = Circle::new(self.center, radius).collision_points(count);
+		self.pts.extend(outline.into_iter().filter(|x| Vector::from(*x).dot(mov) >= 0.0));
+	}
+
+	// Time of impact and contact normal of the swept circle against a tile.
+	fn sweep(&self, tile: (i32, i32)) -> Option<(f32, Vector)> {
+		let (c, d, r) = (self.center, self.mov, self.radius);
+		let min = Vector(tile.0 as f32, tile.1 as f32);
+		let max = min + Vector(1.0, 1.0);
+		let closest = Vector(c.0.max(min.0).min(max.0), c.1.max(min.1).min(max.1));
+		let offset = c - closest;
+		if offset.norm2sq() < r * r {
+			// Already touching: only block movement going further in
+			let normal = if offset.norm2sq() == 0.0 {
+				penetration_normal(c, min, max)
+			} else {
+				normalized(offset)
+			};
+			return if d.dot(normal) < 0.0 { Some((0.0, normal)) } else { None };
+		}
+		// Slabs of the tile grown by the radius
+		let mut enter = 0.0f32;
+		let mut exit = 1.0f32;
+		let mut axis = Vector(0.0, 0.0);
+		for &(start, delta, low, high, normal) in &[(c.0, d.0, min.0 - r, max.0 + r, Vector(1.0, 0.0)),
+		                                           (c.1, d.1, min.1 - r, max.1 + r, Vector(0.0, 1.0))] {
+			if delta == 0.0 {
+				if start < low || start > high {
+					return None;
+				}
+				continue;
+			}
+			let (mut near, mut far) = ((low - start) / delta, (high - start) / delta);
+			if near > far {
+				::std::mem::swap(&mut near, &mut far);
+			}
+			if near > enter {
+				enter = near;
+				axis = normal * -delta.signum();
+			}
+			exit = exit.min(far);
+			if enter > exit {
+				return None;
+			}
+		}
+		let hit = c + d * enter;
+		let inside_x = hit.0 >= min.0 && hit.0 <= max.0;
+		let inside_y = hit.1 >= min.1 && hit.1 <= max.1;
+		if inside_x || inside_y {
+			return Some((enter, axis));
+		}
+		// Rounded corner of the grown tile
+		let corner = Vector(if hit.0 < min.0 { min.0 } else { max.0 },
+		                    if hit.1 < min.1 { min.1 } else { max.1 });
+		let f = c - corner;
+		let a = d.norm2sq();
+		let b = f.dot(d);
+		let discriminant = b * b - a * (f.norm2sq() - r * r);
+		if discriminant < 0.0 {
+			return None;
+		}
+		let t = (-b - discriminant.sqrt()) / a;
+		if !(0.0..=1.0).contains(&t) {
+			return None;
+		}
+		Some((t, normalized(c + d * t - corner)))
+	}
+
+	// Whether a hit with the given normal enters a one-way tile through its
+	// blocking side, starting completely outside of it.
+	fn crosses(&self, tile: (i32, i32), side: Direction, normal: Vector) -> bool {
+		let outward = side.normal();
+		let edge = match side {
+			Direction::Up => -tile.1 as f32,
+			Direction::Down => tile.1 as f32 + 1.0,
+			Direction::Left => -tile.0 as f32,
+			Direction::Right => tile.0 as f32 + 1.0,
+		};
+		normal.dot(outward) > 0.0 && self.center.dot(outward) - self.radius >= edge - SKIN
+	}
+
+	fn respond(&self, velocity: Vector, normal: Vector) -> Vector {
+		let into = velocity.dot(normal);
+		if into >= 0.0 {
+			return velocity;
+		}
+		match self.response {
+			CircleResponse::Slide => velocity - normal * into,
+			CircleResponse::Bounce { restitution } => velocity - normal * ((1.0 + restitution) * into),
+		}
+	}
+}
+
+// Normal of the side of a tile closest to a point inside of it
+fn penetration_normal(point: Vector, min: Vector, max: Vector) -> Vector {
+	let sides = [(point.0 - min.0, Vector(-1.0, 0.0)),
+	             (max.0 - point.0, Vector(1.0, 0.0)),
+	             (point.1 - min.1, Vector(0.0, -1.0)),
+	             (max.1 - point.1, Vector(0.0, 1.0))];
+	let mut closest = sides[0];
+	for side in &sides[1..] {
+		if side.0 < closest.0 {
+			closest = *side;
+		}
+	}
+	closest.1
+}
+
+fn normalized(vector: Vector) -> Vector {
+	let length = vector.norm2sq().sqrt();
+	if length == 0.0 {
+		vector
+	} else {
+		vector * (1.0 / length)
+	}
+}
+
+impl<T> Collable<T, ()> for CircleCollider
+	where T: TileKind
+{
+	fn points<'a>(&'a self) -> Points<'a> {
+		Points::new(self.center, &self.pts)
+	}
+
+	fn queued(&self) -> Vector {
+		if self.solving {
+			self.mov
+		} else {
+			self.vel
+		}
+	}
+
+	/// Replace the move of the next `solve`, which then ignores `vel`
+	fn enqueue(&mut self, vector: Vector) {
+		self.mov = vector;
+		self.solving = true;
+		self.sample();
+	}
+
+	fn presolve(&mut self, _: &mut ()) {
+		if !self.solving {
+			self.mov = self.vel;
+			self.solving = true;
+		}
+		self.sample();
+		self.sensors.clear();
+	}
+
+	fn postsolve(&mut self, _: bool, _: bool, _: &mut ()) {
+		self.solving = false;
+	}
+
+	fn sensors(&self) -> &[(i32, i32)] {
+		&self.sensors
+	}
+
+	fn resolve<I>(&mut self, set: TileSet<T, I>, state: &mut ()) -> bool
+		where I: Iterator<Item = (i32, i32)>
+	{
+		self.resolve_detailed(set, state).is_moved()
+	}
+
+	fn resolve_detailed<I>(&mut self, mut set: TileSet<T, I>, _: &mut ()) -> Resolution
+		where I: Iterator<Item = (i32, i32)>
+	{
+		let mut earliest: Option<(f32, Vector, (i32, i32))> = None;
+		let mut sensors = vec![];
+		while let Some(tile) = set.next_or_border() {
+			let coords = set.get_coords();
+			if tile.is_some_and(TileKind::is_sensor) {
+				// Reached along the move, before any solid tile
+				if let Some((t, _)) = self.sweep(coords) {
+					sensors.push((t, coords));
+				}
+			}
+			// A solid border acts like a full tile
+			let one_way = match tile {
+				Some(tile) if !tile.is_solid() => continue,
+				Some(tile) => tile.one_way(),
+				None => None,
+			};
+			let hit = self.sweep(coords).filter(|x| match one_way {
+				Some(side) => self.crosses(coords, side, x.1),
+				None => true,
+			});
+			if let Some((t, normal)) = hit {
+				if earliest.map_or(true, |x| t < x.0) {
+					earliest = Some((t, normal, coords));
+				}
+			}
+		}
+		let reached = earliest.map_or(1.0, |x| x.0);
+		for (t, coords) in sensors {
+			if t <= reached && !self.sensors.contains(&coords) {
+				self.sensors.push(coords);
+			}
+		}
+		let (t, normal, tile) = match earliest {
+			Some(hit) => hit,
+			None => {
+				self.center += self.mov;
+				return Resolution::Moved { by: self.mov };
+			}
+		};
+		let length = self.mov.norm2sq().sqrt();
+		let safe = (t - SKIN / length).max(0.0);
+		self.center += self.mov * safe;
+		let remaining = self.mov * (1.0 - safe);
+		self.mov = self.respond(remaining, normal);
+		self.vel = self.respond(self.vel, normal);
+		self.sample();
+		Resolution::Blocked {
+			first_hit: tile,
+			normal,
+			t,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::super::{AxisOrder, TileNet};
+
+	#[test]
+	fn samples_leading_half() {
+		let mut circle = CircleCollider::new(Vector(0.0, 0.0), 2.0);
+		assert_eq!(circle.pts.len(), 51);
+		circle.mov = Vector(1.0, 0.0);
+		circle.sample();
+		assert!(circle.pts.len() < 30);
+		assert!(circle.pts.iter().all(|x| x.0 >= -1e-6));
+	}
+
+	#[test]
+	fn lands_and_slides() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_row(&1, 8);
+		let mut circle = CircleCollider::new(Vector(3.0, 3.0), 0.5);
+		circle.vel = Vector(1.0, 10.0);
+		circle.solve(&net, &mut ());
+		assert!((circle.center().1 - 7.5).abs() < 0.01);
+		assert!(circle.center().0 > 3.9);
+		assert_eq!(circle.vel, Vector(1.0, 0.0));
+	}
+
+	#[test]
+	fn samples_grow_with_movement() {
+		let mut circle = CircleCollider::new(Vector(0.0, 0.0), 0.5);
+		circle.mov = Vector(0.0, 1.0);
+		circle.sample();
+		let short = circle.pts.len();
+		circle.mov = Vector(0.0, 20.0);
+		circle.sample();
+		assert!(circle.pts.len() > 4 * short);
+	}
+
+	#[test]
+	fn center_inside_tile() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set(&1, (5, 5));
+		let mut circle = CircleCollider::new(Vector(5.3, 5.5), 0.1);
+		circle.vel = Vector(1.0, 0.0);
+		circle.solve(&net, &mut ());
+		// Pushed against the closest side instead of passing through
+		assert_eq!(circle.center(), Vector(5.3, 5.5));
+		assert_eq!(circle.vel, Vector(0.0, 0.0));
+		// Leaving through the closest side is allowed
+		circle.vel = Vector(-1.0, 0.0);
+		assert!(circle.solve(&net, &mut ()).is_moved());
+	}
+
+	#[test]
+	fn solve_axes() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_col(&1, 5);
+		let mut circle = CircleCollider::new(Vector(2.5, 2.5), 0.5);
+		circle.vel = Vector(4.0, 1.0);
+		let report = circle.solve_axes(&net, AxisOrder::XThenY, &mut ());
+		assert!(report.blocked_x() && !report.blocked_y());
+		assert!((circle.center() - Vector(4.5, 3.5)).norm2sq() < 1e-4);
+		assert_eq!(circle.vel, Vector(0.0, 1.0));
+	}
+
+	#[derive(Clone, Debug, Default)]
+	struct Platform(bool);
+
+	impl TileKind for Platform {
+		fn is_solid(&self) -> bool {
+			self.0
+		}
+
+		fn one_way(&self) -> Option<Direction> {
+			Some(Direction::Up)
+		}
+	}
+
+	#[test]
+	fn one_way_platform() {
+		let mut net: TileNet<Platform> = TileNet::new(10, 10);
+		net.set_row(&Platform(true), 5);
+		let mut circle = CircleCollider::new(Vector(3.0, 7.0), 0.5);
+		circle.vel = Vector(0.0, -4.0);
+		assert!(circle.solve(&net, &mut ()).is_moved());
+		assert_eq!(circle.center(), Vector(3.0, 3.0));
+		circle.vel = Vector(0.0, 4.0);
+		circle.solve(&net, &mut ());
+		assert!((circle.center().1 - 4.5).abs() < 0.01);
+	}
+
+	#[test]
+	fn corner_does_not_tunnel() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set(&1, (5, 5));
+		let mut circle = CircleCollider::new(Vector(3.0, 3.0), 0.9);
+		circle.vel = Vector(4.0, 4.0);
+		match circle.solve(&net, &mut ()) {
+			Resolution::Moved { .. } | Resolution::Blocked { .. } => {}
+			Resolution::Refused | Resolution::Stuck => panic!("circle should move"),
+		}
+		let center = circle.center();
+		let closest = Vector(center.0.clamp(5.0, 6.0), center.1.clamp(5.0, 6.0));
+		assert!((center - closest).norm2sq() >= 0.9 * 0.9 - 1e-3);
+	}
+}
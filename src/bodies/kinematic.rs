@@ -0,0 +1,225 @@
+use super::AabbCollider;
+use super::super::{Collable, Points, Rect, Resolution, TileKind, TileNet, TileSet, Vector};
+
+/// A box integrating acceleration and gravity over time
+///
+/// `step` advances the velocity, caps it and moves the box through the net
+/// with an `AabbCollider`. The movement is split into sub-steps of at most
+/// `max_step` tiles, so slopes and small gaps stay accurate at high speeds.
+/// Velocity into a blocking surface is removed.
+///
+/// As a `Collable`, the body moves by `vel` once per `solve`, like an
+/// `AabbCollider`, without applying acceleration or splitting the move.
+///
+/// ```
+/// use tile_net::{KinematicBody, Rect, TileNet, Vector};
+/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+/// net.set_row(&1, 8);
+/// let mut body = KinematicBody::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+/// body.gravity = Vector(0.0, 30.0);
+/// body.terminal = 20.0;
+/// for _ in 0..60 {
+///     body.step(1.0 / 60.0, &net);
+/// }
+/// assert_eq!(body.position(), Vector(2.0, 7.0));
+/// assert_eq!(body.vel, Vector(0.0, 0.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct KinematicBody {
+	/// Velocity in tiles per unit of time
+	pub vel: Vector,
+	/// Acceleration applied by the body itself, like walking or thrust
+	pub acc: Vector,
+	/// Acceleration applied by the world
+	pub gravity: Vector,
+	/// Highest downward speed, positive y
+	pub terminal: f32,
+	/// Highest horizontal speed
+	pub max_speed: f32,
+	/// Longest distance moved in a single sub-step, in tiles
+	pub max_step: f32,
+	/// Highest ledge to step onto, see `AabbCollider::step_height`
+	pub step_height: f32,
+	collider: AabbCollider,
+	sensors: Vec<(i32, i32)>,
+}
+
+impl KinematicBody {
+	/// Create a resting body without gravity or speed limits
+	pub fn new(rect: Rect) -> KinematicBody {
+		KinematicBody {
+			vel: Vector(0.0, 0.0),
+			acc: Vector(0.0, 0.0),
+			gravity: Vector(0.0, 0.0),
+			terminal: f32::INFINITY,
+			max_speed: f32::INFINITY,
+			max_step: 0.5,
+			step_height: 0.0,
+			collider: AabbCollider::new(rect),
+			sensors: vec![],
+		}
+	}
+
+	/// Get the top-left corner of the body
+	pub fn position(&self) -> Vector {
+		self.collider.rect().pos
+	}
+
+	/// Get the rectangle of the body
+	pub fn rect(&self) -> Rect {
+		self.collider.rect()
+	}
+
+	/// Teleport the body, ignoring tiles on the way
+	pub fn set_position(&mut self, pos: Vector) {
+		self.collider.set_position(pos);
+	}
+
+	/// Get the underlying collider
+	pub fn collider(&self) -> &AabbCollider {
+		&self.collider
+	}
+
+	/// Get the sensor tiles passed through during the last `step`
+	///
+	/// See `AabbCollider::sensors`.
+	pub fn sensors(&self) -> &[(i32, i32)] {
+		&self.sensors
+	}
+
+	/// Advance the body by `dt` units of time
+	///
+	/// Returns `Stuck` if the body could not move at all, or else `Moved` with
+	/// the whole movement. Components of `vel` are zeroed by surfaces hit on
+	/// the way.
+	pub fn step<T>(&mut self, dt: f32, net: &TileNet<T>) -> Resolution
+		where T: TileKind
+	{
+		let mut vel = self.vel + (self.acc + self.gravity) * dt;
+		vel.0 = vel.0.clamp(-self.max_speed, self.max_speed);
+		vel.1 = vel.1.min(self.terminal);
+		self.vel = vel;
+
+		let distance = (vel * dt).norm2sq().sqrt();
+		let count = (distance / self.max_step).ceil().max(1.0);
+		let mut step = vel * (dt / count);
+		let start = self.position();
+		self.collider.step_height = self.step_height;
+		self.sensors.clear();
+		for _ in 0..count as usize {
+			self.collider.vel = step;
+			let resolution = self.collider.solve(net, &mut ());
+			for coords in self.collider.sensors() {
+				if !self.sensors.contains(coords) {
+					self.sensors.push(*coords);
+				}
+			}
+			if resolution == Resolution::Stuck {
+				self.collider.vel = Vector(0.0, 0.0);
+				return Resolution::Stuck;
+			}
+			if self.collider.vel.0 == 0.0 {
+				step.0 = 0.0;
+				self.vel.0 = 0.0;
+			}
+			if self.collider.vel.1 == 0.0 {
+				step.1 = 0.0;
+				self.vel.1 = 0.0;
+			}
+		}
+		self.collider.vel = Vector(0.0, 0.0);
+		Resolution::Moved { by: self.position() - start }
+	}
+}
+
+impl<T> Collable<T, ()> for KinematicBody
+	where T: TileKind
+{
+	fn points<'a>(&'a self) -> Points<'a> {
+		Collable::<T, ()>::points(&self.collider)
+	}
+
+	fn queued(&self) -> Vector {
+		if self.collider.solving() {
+			Collable::<T, ()>::queued(&self.collider)
+		} else {
+			self.vel
+		}
+	}
+
+	fn enqueue(&mut self, vector: Vector) {
+		Collable::<T, ()>::enqueue(&mut self.collider, vector);
+	}
+
+	fn presolve(&mut self, state: &mut ()) {
+		self.collider.vel = self.vel;
+		self.collider.step_height = self.step_height;
+		Collable::<T, ()>::presolve(&mut self.collider, state);
+	}
+
+	fn postsolve(&mut self, collided_once: bool, resolved: bool, state: &mut ()) {
+		Collable::<T, ()>::postsolve(&mut self.collider, collided_once, resolved, state);
+		self.vel = self.collider.vel;
+		self.collider.vel = Vector(0.0, 0.0);
+		self.sensors.clear();
+		self.sensors.extend_from_slice(self.collider.sensors());
+	}
+
+	fn sensors(&self) -> &[(i32, i32)] {
+		&self.sensors
+	}
+
+	fn resolve<I>(&mut self, set: TileSet<T, I>, state: &mut ()) -> bool
+		where I: Iterator<Item = (i32, i32)>
+	{
+		self.collider.resolve(set, state)
+	}
+
+	fn resolve_detailed<I>(&mut self, set: TileSet<T, I>, state: &mut ()) -> Resolution
+		where I: Iterator<Item = (i32, i32)>
+	{
+		self.collider.resolve_detailed(set, state)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::super::AxisOrder;
+
+	#[test]
+	fn speed_limits() {
+		let net: TileNet<usize> = TileNet::new(100, 100);
+		let mut body = KinematicBody::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+		body.gravity = Vector(0.0, 100.0);
+		body.acc = Vector(100.0, 0.0);
+		body.terminal = 10.0;
+		body.max_speed = 5.0;
+		assert!(body.step(1.0, &net).is_moved());
+		assert_eq!(body.vel, Vector(5.0, 10.0));
+		assert!((body.position() - Vector(7.0, 12.0)).norm2sq() < 1e-6);
+	}
+
+	#[test]
+	fn sub_steps_do_not_skip_walls() {
+		let mut net: TileNet<usize> = TileNet::new(100, 10);
+		net.set_col(&1, 50);
+		let mut body = KinematicBody::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+		body.vel = Vector(200.0, 0.0);
+		assert_eq!(body.step(1.0, &net), Resolution::Moved { by: Vector(47.0, 0.0) });
+		assert_eq!(body.position(), Vector(49.0, 2.0));
+		assert_eq!(body.vel, Vector(0.0, 0.0));
+	}
+
+	#[test]
+	fn solve_axes() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_col(&1, 5);
+		let mut body = KinematicBody::new(Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0)));
+		body.vel = Vector(4.0, 1.0);
+		let report = body.solve_axes(&net, AxisOrder::XThenY, &mut ());
+		assert!(report.blocked_x() && !report.blocked_y());
+		assert_eq!(body.position(), Vector(4.0, 3.0));
+		assert_eq!(body.vel, Vector(0.0, 1.0));
+	}
+}
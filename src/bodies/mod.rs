@@ -0,0 +1,12 @@
+//! Ready-made collision bodies
+//!
+//! The bodies in this module implement `Collable` so they can be used with
+//! `solve` directly, with `()` as state. Which tiles block movement is
+//! decided by the tile type through `TileKind`.
+pub use self::aabb::AabbCollider;
+pub use self::circle::{CircleCollider, CircleResponse};
+pub use self::kinematic::KinematicBody;
+
+mod aabb;
+mod circle;
+mod kinematic;
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use super::Rect;
+
+/// Spatial hash for finding bodies that may overlap one another
+///
+/// Tile collision handles bodies against the net; this handles bodies against
+/// each other. Bodies are inserted as boxes every frame into cells of a grid
+/// aligned with the `TileNet`, and only bodies sharing a cell are compared.
+/// Cells are one tile large by default; use a cell size around the size of
+/// a typical body.
+///
+/// ```
+/// use tile_net::{Rect, SpatialHash, Vector};
+/// let mut hash = SpatialHash::new();
+/// let player = hash.insert(Rect::new(Vector(1.0, 1.0), Vector(1.0, 2.0)));
+/// let enemy = hash.insert(Rect::new(Vector(1.5, 2.5), Vector(1.0, 1.0)));
+/// let far = hash.insert(Rect::new(Vector(9.0, 9.0), Vector(1.0, 1.0)));
+/// assert_eq!(hash.pairs(), vec![(player, enemy)]);
+/// assert_eq!(hash.query(Rect::new(Vector(8.0, 8.0), Vector(4.0, 4.0))), vec![far]);
+/// // Next frame
+/// hash.clear();
+/// ```
+#[derive(Clone, Debug)]
+pub struct SpatialHash {
+	cell_size: f32,
+	cells: HashMap<(i32, i32), Vec<usize>>,
+	rects: Vec<Rect>,
+}
+
+impl Default for SpatialHash {
+	fn default() -> SpatialHash {
+		SpatialHash::new()
+	}
+}
+
+impl SpatialHash {
+	/// Create an empty hash with cells of one tile
+	pub fn new() -> SpatialHash {
+		SpatialHash::with_cell_size(1.0)
+	}
+
+	/// Create an empty hash with square cells of `cell_size` tiles
+	///
+	/// # Panics #
+	/// Panics if `cell_size` is not positive.
+	pub fn with_cell_size(cell_size: f32) -> SpatialHash {
+		assert!(cell_size > 0.0, "cells must have a positive size");
+		SpatialHash {
+			cell_size,
+			cells: HashMap::new(),
+			rects: vec![],
+		}
+	}
+
+	/// Remove every body, keeping the allocated cells for reuse
+	pub fn clear(&mut self) {
+		for cell in self.cells.values_mut() {
+			cell.clear();
+		}
+		self.rects.clear();
+	}
+
+	/// Add a body, returning its id
+	///
+	/// Ids count up from zero in insertion order, so they can index a list
+	/// of bodies inserted in the same order.
+	pub fn insert(&mut self, rect: Rect) -> usize {
+		let id = self.rects.len();
+		self.rects.push(rect);
+		let (min, max) = self.cells_of(&rect);
+		for y in min.1..max.1 + 1 {
+			for x in min.0..max.0 + 1 {
+				self.cells.entry((x, y)).or_default().push(id);
+			}
+		}
+		id
+	}
+
+	/// Get the box of a body
+	pub fn get(&self, id: usize) -> Option<Rect> {
+		self.rects.get(id).cloned()
+	}
+
+	/// Get the number of bodies
+	pub fn len(&self) -> usize {
+		self.rects.len()
+	}
+
+	/// Check whether there are no bodies
+	pub fn is_empty(&self) -> bool {
+		self.rects.is_empty()
+	}
+
+	/// Get the ids of the bodies overlapping a box, in ascending order
+	///
+	/// Boxes that only touch along an edge do not overlap.
+	pub fn query(&self, rect: Rect) -> Vec<usize> {
+		let (min, max) = self.cells_of(&rect);
+		let mut found = vec![];
+		for y in min.1..max.1 + 1 {
+			for x in min.0..max.0 + 1 {
+				if let Some(cell) = self.cells.get(&(x, y)) {
+					found.extend(cell.iter().cloned().filter(|id| self.rects[*id].intersects(&rect)));
+				}
+			}
+		}
+		found.sort_unstable();
+		found.dedup();
+		found
+	}
+
+	/// Get every pair of overlapping bodies as (smaller id, larger id), sorted
+	pub fn pairs(&self) -> Vec<(usize, usize)> {
+		let mut pairs = vec![];
+		for cell in self.cells.values() {
+			for (index, &a) in cell.iter().enumerate() {
+				for &b in &cell[index + 1..] {
+					if self.rects[a].intersects(&self.rects[b]) {
+						pairs.push((a.min(b), a.max(b)));
+					}
+				}
+			}
+		}
+		pairs.sort_unstable();
+		pairs.dedup();
+		pairs
+	}
+
+	// First and last cell covered by a box. The far edges are exclusive, so
+	// a box ending exactly on a cell edge does not reach into the next cell.
+	fn cells_of(&self, rect: &Rect) -> ((i32, i32), (i32, i32)) {
+		let first = |start: f32| (start / self.cell_size).floor() as i32;
+		let last = |start: f32, size: f32| {
+			((start + size) / self.cell_size).ceil() as i32 - 1
+		};
+		let min = (first(rect.pos.0), first(rect.pos.1));
+		let max = (last(rect.pos.0, rect.size.0).max(min.0), last(rect.pos.1, rect.size.1).max(min.1));
+		(min, max)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::Vector;
+
+	fn square(x: f32, y: f32, size: f32) -> Rect {
+		Rect::new(Vector(x, y), Vector(size, size))
+	}
+
+	#[test]
+	fn edges_do_not_overlap() {
+		let mut hash = SpatialHash::with_cell_size(2.0);
+		hash.insert(square(0.0, 0.0, 1.0));
+		hash.insert(square(1.0, 0.0, 1.0));
+		hash.insert(square(-0.5, -0.5, 0.75));
+		assert_eq!(hash.pairs(), vec![(0, 2)]);
+		assert_eq!(hash.query(square(1.0, 1.0, 5.0)), Vec::<usize>::new());
+		assert_eq!(hash.len(), 3);
+	}
+
+	#[test]
+	fn large_bodies_span_cells() {
+		let mut hash = SpatialHash::new();
+		hash.insert(square(0.0, 0.0, 10.0));
+		hash.insert(square(3.0, 3.0, 1.0));
+		hash.insert(square(8.0, 2.0, 1.0));
+		hash.insert(square(8.5, 2.5, 1.0));
+		assert_eq!(hash.pairs(), vec![(0, 1), (0, 2), (0, 3), (2, 3)]);
+		hash.clear();
+		assert!(hash.is_empty());
+		assert_eq!(hash.pairs(), vec![]);
+		assert_eq!(hash.insert(square(0.0, 0.0, 1.0)), 0);
+	}
+}
@@ -0,0 +1,105 @@
+use super::{GridSpace, SignedCoord, TileNet, TileView, Vector};
+
+/// A viewport in world space, mapped onto the tiles of a net
+///
+/// World space is whatever the renderer works in, typically pixels. Where
+/// the tiles lie in it is described by a `GridSpace`, the one of the net
+/// when it already works in the units of the renderer. Tiles only partially
+/// on screen count as visible.
+///
+/// ```
+/// use tile_net::{Camera, GridSpace, SignedCoord, TileNet, Vector};
+/// let mut net: TileNet<usize> = TileNet::new(100, 100);
+/// net.set_space(GridSpace::new(Vector(0.0, 0.0), Vector(16.0, 16.0)));
+/// let camera = Camera::new(Vector(200.0, 120.0), Vector(320.0, 240.0), *net.get_space());
+/// assert_eq!(camera.visible_rect(), (2, 23, 0, 15));
+/// assert_eq!(camera.visible_view(&net).count(), 21 * 15);
+/// assert_eq!(camera.world_to_tile(Vector(33.0, -1.0)), SignedCoord::new(2, -1));
+/// assert_eq!(camera.tile_to_world((2, -1)), Vector(32.0, -16.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+	/// Center of the viewport, in world units
+	pub center: Vector,
+	/// Width and height of the viewport, in world units
+	pub size: Vector,
+	/// Where the tiles lie in world space
+	pub space: GridSpace,
+}
+
+impl Camera {
+	/// Create a camera from its center, size and where the tiles lie
+	pub fn new(center: Vector, size: Vector, space: GridSpace) -> Camera {
+		Camera {
+			center,
+			size,
+			space,
+		}
+	}
+
+	/// Get the tile containing a world position, see `GridSpace::world_to_tile`
+	pub fn world_to_tile(&self, position: Vector) -> SignedCoord {
+		self.space.world_to_tile(position)
+	}
+
+	/// Get the world position of the corner of a tile with the smallest coordinates
+	pub fn tile_to_world<P>(&self, tile: P) -> Vector
+		where P: Into<SignedCoord>
+	{
+		self.space.tile_to_world(tile.into()).pos
+	}
+
+	/// Get the visible tiles as (x_start, x_stop, y_start, y_stop)
+	///
+	/// The stops are exclusive, and a tile whose edge only touches the edge
+	/// of the viewport is not visible. The rectangle may extend outside of
+	/// any net.
+	pub fn visible_rect(&self) -> (i32, i32, i32, i32) {
+		let (start, stop) = (self.center - self.size * 0.5, self.center + self.size * 0.5);
+		let first = self.world_to_tile(start);
+		// The tile starting right at the far edge is not visible
+		let last = self.world_to_tile(stop);
+		let past = |tile: i32, edge: f32, corner: f32| if edge > corner { tile + 1 } else { tile };
+		let corner = self.tile_to_world(last);
+		(first.x, past(last.x, stop.0, corner.0), first.y, past(last.y, stop.1, corner.1))
+	}
+
+	/// Create a view over the visible tiles of a net
+	///
+	/// The view is clipped to the net like in `TileNet::view_box`.
+	pub fn visible_view<'a, T>(&self, net: &'a TileNet<T>) -> TileView<'a, T> {
+		let rect = self.visible_rect();
+		let clamp = |x: i32| x.max(0) as usize;
+		net.view_box((clamp(rect.0), clamp(rect.1), clamp(rect.2), clamp(rect.3)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn edges_are_half_open() {
+		let space = GridSpace::new(Vector(0.0, 0.0), Vector(16.0, 16.0));
+		let camera = Camera::new(Vector(32.0, 32.0), Vector(32.0, 32.0), space);
+		assert_eq!(camera.visible_rect(), (1, 3, 1, 3));
+		let camera = Camera::new(Vector(0.0, 0.0), Vector(33.0, 31.0), space);
+		assert_eq!(camera.visible_rect(), (-2, 2, -1, 1));
+		let net = TileNet::sample();
+		let coords: Vec<(usize, usize)> = camera.visible_view(&net).map(|x| (x.1, x.2)).collect();
+		assert_eq!(coords, vec![(0, 0), (1, 0)]);
+	}
+
+	#[test]
+	fn follows_the_space() {
+		// Tiles 8 wide and 4 high, with tile (0, 0) at (-8, 4)
+		let space = GridSpace::new(Vector(-8.0, 4.0), Vector(8.0, 4.0));
+		let camera = Camera::new(Vector(0.0, 8.0), Vector(16.0, 8.0), space);
+		assert_eq!(camera.world_to_tile(Vector(-0.5, 4.0)), SignedCoord::new(0, 0));
+		assert_eq!(camera.world_to_tile(Vector(-8.5, 3.5)), SignedCoord::new(-1, -1));
+		assert_eq!(camera.tile_to_world((1, 2)), Vector(0.0, 12.0));
+		assert_eq!(camera.visible_rect(), (0, 2, 0, 2));
+		let camera = Camera { center: Vector(1.0, 9.0), ..camera };
+		assert_eq!(camera.visible_rect(), (0, 3, 0, 3));
+	}
+}
@@ -0,0 +1,266 @@
+use std::cmp::min;
+use std::iter::FusedIterator;
+use std::sync::Arc;
+use super::TileNet;
+
+pub use self::sync::{SyncTileNet, SyncView};
+
+mod sync;
+
+/// Width and height of a chunk of a `ChunkedTileNet`, in tiles
+pub const CHUNK_SIZE: usize = 32;
+
+/// A net stored as shared square chunks, for cheap snapshots
+///
+/// `TileNet` keeps its tiles in one `Vec`, so handing the map to a render
+/// thread means cloning all of it every frame. This net splits the tiles
+/// into chunks of `CHUNK_SIZE` by `CHUNK_SIZE` behind an `Arc`. A snapshot
+/// shares every chunk, and writing to the net afterwards clones only the
+/// chunk being written, once per snapshot.
+///
+/// ```
+/// use tile_net::ChunkedTileNet;
+/// use std::thread;
+/// let mut net: ChunkedTileNet<usize> = ChunkedTileNet::new(256, 256);
+/// net.set(&1, (10, 10));
+/// let snapshot = net.cow_snapshot();
+/// net.set(&2, (10, 10));
+/// let render = thread::spawn(move || *snapshot.get((10, 10)).unwrap());
+/// assert_eq!(render.join().unwrap(), 1);
+/// assert_eq!(net.get((10, 10)), Some(&2));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChunkedTileNet<T> {
+	chunks: Vec<Arc<Vec<T>>>,
+	cols: usize,
+	rows: usize,
+}
+
+/// Read-only view of a `ChunkedTileNet` at the time of the snapshot
+///
+/// Created by `ChunkedTileNet::cow_snapshot`. Cloning it is cheap.
+///
+/// ```
+/// use tile_net::ChunkedTileNet;
+/// let mut net: ChunkedTileNet<usize> = ChunkedTileNet::new(64, 64);
+/// net.set(&1, (40, 3));
+/// let snapshot = net.cow_snapshot();
+/// net.set(&1, (41, 3));
+/// let drawn: Vec<_> = snapshot.view_box((30, 50, 0, 10)).filter(|x| *x.0 == 1).collect();
+/// assert_eq!(drawn, vec![(&1, 40, 3)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CowSnapshot<T> {
+	net: ChunkedTileNet<T>,
+}
+
+impl<T> ChunkedTileNet<T>
+	where T: Clone + Default
+{
+	/// Create a new net of the size (cols, rows) filled with Default
+	pub fn new(cols: usize, rows: usize) -> ChunkedTileNet<T> {
+		let count = cols.div_ceil(CHUNK_SIZE) * rows.div_ceil(CHUNK_SIZE);
+		let chunk = Arc::new(vec![T::default(); CHUNK_SIZE * CHUNK_SIZE]);
+		ChunkedTileNet {
+			chunks: vec![chunk; count],
+			cols,
+			rows,
+		}
+	}
+
+	/// Copy a net into chunks
+	pub fn from_net(net: &TileNet<T>) -> ChunkedTileNet<T> {
+		let (cols, rows) = net.get_size();
+		let mut chunked = ChunkedTileNet::new(cols, rows);
+		for (index, tile) in net.get_raw().iter().enumerate() {
+			chunked.set(tile, (index % cols, index / cols));
+		}
+		chunked
+	}
+}
+
+impl<T> ChunkedTileNet<T>
+	where T: Clone
+{
+	/// Get the size as (cols, rows)
+	pub fn get_size(&self) -> (usize, usize) {
+		(self.cols, self.rows)
+	}
+
+	/// Get a tile
+	pub fn get(&self, p: (usize, usize)) -> Option<&T> {
+		self.locate(p).map(|(chunk, index)| &self.chunks[chunk][index])
+	}
+
+	/// Get a tile for editing, cloning its chunk if a snapshot shares it
+	pub fn get_mut(&mut self, p: (usize, usize)) -> Option<&mut T> {
+		match self.locate(p) {
+			Some((chunk, index)) => Some(&mut Arc::make_mut(&mut self.chunks[chunk])[index]),
+			None => None,
+		}
+	}
+
+	/// Set a single grid point
+	///
+	/// Out-of-bounds coordinates are silently ignored.
+	pub fn set(&mut self, value: &T, p: (usize, usize)) {
+		if let Some(tile) = self.get_mut(p) {
+			tile.clone_from(value);
+		}
+	}
+
+	/// Set every tile in the box from `start` to `stop`, both corners included
+	///
+	/// Parts of the box outside of the net are ignored, see `TileNet::set_box_filled`.
+	pub fn set_box_filled(&mut self, value: &T, start: (usize, usize), stop: (usize, usize)) {
+		for y in start.1..min(stop.1.saturating_add(1), self.rows) {
+			for x in start.0..min(stop.0.saturating_add(1), self.cols) {
+				self.set(value, (x, y));
+			}
+		}
+	}
+
+	/// Create a view box that iterates over tiles within that box
+	///
+	/// The box is clipped to the net, see `TileNet::view_box`.
+	pub fn view_box<'a>(&'a self, rectangle: (usize, usize, usize, usize)) -> ChunkedView<'a, T> {
+		let rectangle = (rectangle.0, min(rectangle.1, self.cols), rectangle.2, min(rectangle.3, self.rows));
+		ChunkedView {
+			net: self,
+			rectangle,
+			current: (rectangle.0, rectangle.2),
+		}
+	}
+
+	/// Take a snapshot sharing every chunk with the net
+	pub fn cow_snapshot(&self) -> CowSnapshot<T> {
+		CowSnapshot { net: self.clone() }
+	}
+
+	/// Copy into a plain net
+	pub fn to_tile_net(&self) -> TileNet<T>
+		where T: Default
+	{
+		TileNet::from_fn((self.cols, self.rows), |x, y| self.get((x, y)).expect("inside the net").clone())
+	}
+
+	// Chunk and index inside the chunk of a coordinate
+	fn locate(&self, p: (usize, usize)) -> Option<(usize, usize)> {
+		if p.0 < self.cols && p.1 < self.rows {
+			let chunk = p.0 / CHUNK_SIZE + p.1 / CHUNK_SIZE * self.cols.div_ceil(CHUNK_SIZE);
+			Some((chunk, p.0 % CHUNK_SIZE + p.1 % CHUNK_SIZE * CHUNK_SIZE))
+		} else {
+			None
+		}
+	}
+}
+
+impl<T> CowSnapshot<T>
+	where T: Clone
+{
+	/// Get the size as (cols, rows)
+	pub fn get_size(&self) -> (usize, usize) {
+		self.net.get_size()
+	}
+
+	/// Get a tile
+	pub fn get(&self, p: (usize, usize)) -> Option<&T> {
+		self.net.get(p)
+	}
+
+	/// Create a view box that iterates over tiles within that box
+	///
+	/// See `ChunkedTileNet::view_box`.
+	pub fn view_box<'a>(&'a self, rectangle: (usize, usize, usize, usize)) -> ChunkedView<'a, T> {
+		self.net.view_box(rectangle)
+	}
+
+	/// Copy into a plain net
+	pub fn to_tile_net(&self) -> TileNet<T>
+		where T: Default
+	{
+		self.net.to_tile_net()
+	}
+}
+
+/// Tile iterator for a rectangular view of a `ChunkedTileNet` or `CowSnapshot`
+///
+/// Yields the tiles row by row with their column and row, like `TileView`.
+#[derive(Clone, Debug)]
+pub struct ChunkedView<'a, T>
+	where T: 'a
+{
+	net: &'a ChunkedTileNet<T>,
+	rectangle: (usize, usize, usize, usize),
+	current: (usize, usize),
+}
+
+impl<'a, T> Iterator for ChunkedView<'a, T>
+	where T: Clone
+{
+	type Item = (&'a T, usize, usize);
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.current.1 >= self.rectangle.3 || self.rectangle.0 >= self.rectangle.1 {
+			return None;
+		}
+		let (x, y) = self.current;
+		self.current.0 += 1;
+		if self.current.0 >= self.rectangle.1 {
+			self.current.1 += 1;
+			self.current.0 = self.rectangle.0;
+		}
+		self.net.get((x, y)).map(|tile| (tile, x, y))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let left = if self.current.1 >= self.rectangle.3 || self.rectangle.0 >= self.rectangle.1 {
+			0
+		} else {
+			let width = self.rectangle.1 - self.rectangle.0;
+			(self.rectangle.3 - self.current.1) * width - (self.current.0 - self.rectangle.0)
+		};
+		(left, Some(left))
+	}
+}
+
+impl<'a, T> ExactSizeIterator for ChunkedView<'a, T>
+	where T: Clone
+{
+}
+
+impl<'a, T> FusedIterator for ChunkedView<'a, T>
+	where T: Clone
+{
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn writes_clone_only_their_chunk() {
+		let mut net = ChunkedTileNet::from_net(&TileNet::from_iter(70, 0..70 * 40));
+		assert_eq!(net.chunks.len(), 6);
+		let snapshot = net.cow_snapshot();
+		net.set_box_filled(&0, (60, 35), (100, 100));
+		let shared = net.chunks.iter().zip(&snapshot.net.chunks).filter(|x| Arc::ptr_eq(x.0, x.1)).count();
+		assert_eq!(shared, 4);
+		assert_eq!(snapshot.get((69, 39)), Some(&(69 + 39 * 70)));
+		assert_eq!(net.get((69, 39)), Some(&0));
+		assert_eq!(snapshot.to_tile_net().get_raw(), TileNet::from_iter(70, 0..70 * 40).get_raw());
+		assert_eq!(net.get((70, 0)), None);
+	}
+
+	#[test]
+	fn view_across_chunks() {
+		let net = ChunkedTileNet::from_net(&TileNet::from_iter(70, 0..70 * 40));
+		let snapshot = net.cow_snapshot();
+		let view = snapshot.view_box((30, 34, 38, 100));
+		assert_eq!(view.len(), 8);
+		let tiles: Vec<_> = view.map(|x| (*x.0, x.1, x.2)).collect();
+		assert_eq!(tiles[..2], [(30 + 38 * 70, 30, 38), (31 + 38 * 70, 31, 38)]);
+		assert_eq!(tiles[7], (33 + 39 * 70, 33, 39));
+		assert_eq!(net.view_box((60, 100, 0, 1)).count(), 10);
+		assert_eq!(net.view_box((80, 100, 0, 1)).count(), 0);
+	}
+}
@@ -0,0 +1,278 @@
+use std::cmp::min;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard};
+use super::CHUNK_SIZE;
+use super::super::TileNet;
+
+/// A net shared between threads, locking one chunk at a time
+///
+/// Tiles are split into chunks of `CHUNK_SIZE` by `CHUNK_SIZE`, each behind
+/// its own `RwLock`, and every method takes `&self`. Put the net in an `Arc`
+/// and worker threads can run collision queries while the main thread edits
+/// it: a query or an edit only waits for the chunks it touches.
+///
+/// A query sees every tile as it was when it read its chunk, so an edit
+/// spanning several chunks may be seen half done. Tiles are returned by
+/// value, since a reference would have to hold on to the lock.
+///
+/// ```
+/// use tile_net::{Line, SyncTileNet, Vector};
+/// use std::sync::Arc;
+/// use std::thread;
+/// let net: Arc<SyncTileNet<usize>> = Arc::new(SyncTileNet::new(128, 128));
+/// net.set_box_filled(&1, (100, 0), (100, 127));
+/// let worker = {
+///     let net = net.clone();
+///     thread::spawn(move || {
+///         let ray = Line(Vector(0.5, 10.5), Vector(120.5, 10.5));
+///         net.any(ray.supercover(), |x| *x != 0)
+///     })
+/// };
+/// net.set(&2, (5, 100));
+/// assert!(worker.join().unwrap());
+/// assert_eq!(net.get((5, 100)), Some(2));
+/// ```
+#[derive(Debug)]
+pub struct SyncTileNet<T> {
+	chunks: Vec<RwLock<Vec<T>>>,
+	cols: usize,
+	rows: usize,
+}
+
+impl<T> SyncTileNet<T>
+	where T: Clone + Default
+{
+	/// Create a new net of the size (cols, rows) filled with Default
+	pub fn new(cols: usize, rows: usize) -> SyncTileNet<T> {
+		let count = cols.div_ceil(CHUNK_SIZE) * rows.div_ceil(CHUNK_SIZE);
+		SyncTileNet {
+			chunks: (0..count).map(|_| RwLock::new(vec![T::default(); CHUNK_SIZE * CHUNK_SIZE])).collect(),
+			cols,
+			rows,
+		}
+	}
+
+	/// Copy a net into chunks
+	pub fn from_net(net: &TileNet<T>) -> SyncTileNet<T> {
+		let (cols, rows) = net.get_size();
+		let mut shared = SyncTileNet::new(cols, rows);
+		for (index, tile) in net.get_raw().iter().enumerate() {
+			if let Some((chunk, at)) = shared.locate((index % cols, index / cols)) {
+				shared.chunks[chunk].get_mut().unwrap_or_else(PoisonError::into_inner)[at] = tile.clone();
+			}
+		}
+		shared
+	}
+
+	/// Copy into a plain net
+	pub fn to_tile_net(&self) -> TileNet<T> {
+		TileNet::from_fn((self.cols, self.rows), |x, y| self.get((x, y)).expect("inside the net"))
+	}
+}
+
+impl<T> SyncTileNet<T>
+	where T: Clone
+{
+	/// Get the size as (cols, rows)
+	pub fn get_size(&self) -> (usize, usize) {
+		(self.cols, self.rows)
+	}
+
+	/// Get a copy of a tile
+	pub fn get(&self, p: (usize, usize)) -> Option<T> {
+		self.locate(p).map(|(chunk, index)| self.read(chunk)[index].clone())
+	}
+
+	/// Set a single grid point
+	///
+	/// Out-of-bounds coordinates are silently ignored.
+	pub fn set(&self, value: &T, p: (usize, usize)) {
+		if let Some((chunk, index)) = self.locate(p) {
+			self.chunks[chunk].write().unwrap_or_else(PoisonError::into_inner)[index].clone_from(value);
+		}
+	}
+
+	/// Set every tile in the box from `start` to `stop`, both corners included
+	///
+	/// Every chunk is locked once, see `TileNet::set_box_filled`.
+	pub fn set_box_filled(&self, value: &T, start: (usize, usize), stop: (usize, usize)) {
+		let (x1, y1) = (min(stop.0.saturating_add(1), self.cols), min(stop.1.saturating_add(1), self.rows));
+		if start.0 >= x1 || start.1 >= y1 {
+			return;
+		}
+		let across = self.cols.div_ceil(CHUNK_SIZE);
+		for chunk_y in start.1 / CHUNK_SIZE..(y1 - 1) / CHUNK_SIZE + 1 {
+			for chunk_x in start.0 / CHUNK_SIZE..(x1 - 1) / CHUNK_SIZE + 1 {
+				let mut tiles = self.chunks[chunk_x + chunk_y * across].write().unwrap_or_else(PoisonError::into_inner);
+				let (ox, oy) = (chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE);
+				for y in start.1.max(oy)..y1.min(oy + CHUNK_SIZE) {
+					for x in start.0.max(ox)..x1.min(ox + CHUNK_SIZE) {
+						tiles[x - ox + (y - oy) * CHUNK_SIZE].clone_from(value);
+					}
+				}
+			}
+		}
+	}
+
+	/// Check whether any tile at the given points matches `pred`
+	///
+	/// Takes the points of a supercover or any other list. Points outside of
+	/// the net are skipped. The lock of a chunk is held while consecutive
+	/// points stay inside of it, so a line locks each chunk it crosses once.
+	pub fn any<I, F>(&self, points: I, mut pred: F) -> bool
+		where I: IntoIterator<Item = (i32, i32)>,
+		      F: FnMut(&T) -> bool
+	{
+		let mut current: Option<(usize, RwLockReadGuard<'_, Vec<T>>)> = None;
+		for (x, y) in points {
+			if x < 0 || y < 0 {
+				continue;
+			}
+			let (chunk, index) = match self.locate((x as usize, y as usize)) {
+				Some(found) => found,
+				None => continue,
+			};
+			if current.as_ref().map(|x| x.0) != Some(chunk) {
+				// Release the previous chunk first, so an editor never waits on two
+				drop(current.take());
+				current = Some((chunk, self.read(chunk)));
+			}
+			if pred(&current.as_ref().expect("just locked").1[index]) {
+				return true;
+			}
+		}
+		false
+	}
+
+	/// Read-lock the chunks under a box to look at its tiles
+	///
+	/// The box is given like in `TileNet::view_box` and clipped to the net.
+	/// Edits to the chunks under the box wait until the view is dropped, so
+	/// every tile in it is seen as it was at the same moment. Chunks are
+	/// locked in order, so views and edits can not deadlock each other.
+	///
+	/// ```
+	/// use tile_net::SyncTileNet;
+	/// let net: SyncTileNet<usize> = SyncTileNet::new(100, 100);
+	/// net.set_box_filled(&1, (30, 30), (33, 33));
+	/// let view = net.read_box((31, 40, 31, 40));
+	/// assert_eq!(view.iter().filter(|x| *x.0 == 1).count(), 9);
+	/// assert_eq!(view.get((33, 33)), Some(&1));
+	/// assert_eq!(view.get((30, 30)), None);
+	/// ```
+	pub fn read_box(&self, rectangle: (usize, usize, usize, usize)) -> SyncView<'_, T> {
+		let rectangle = (rectangle.0, min(rectangle.1, self.cols), rectangle.2, min(rectangle.3, self.rows));
+		let mut guards = vec![];
+		let (mut first, mut across) = ((0, 0), 0);
+		if rectangle.0 < rectangle.1 && rectangle.2 < rectangle.3 {
+			first = (rectangle.0 / CHUNK_SIZE, rectangle.2 / CHUNK_SIZE);
+			across = (rectangle.1 - 1) / CHUNK_SIZE + 1 - first.0;
+			let chunks = self.cols.div_ceil(CHUNK_SIZE);
+			for chunk_y in first.1..(rectangle.3 - 1) / CHUNK_SIZE + 1 {
+				for chunk_x in first.0..first.0 + across {
+					guards.push(self.read(chunk_x + chunk_y * chunks));
+				}
+			}
+		}
+		SyncView {
+			guards,
+			first,
+			across,
+			rectangle,
+		}
+	}
+
+	fn read(&self, chunk: usize) -> RwLockReadGuard<'_, Vec<T>> {
+		self.chunks[chunk].read().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	// Chunk and index inside the chunk of a coordinate
+	fn locate(&self, p: (usize, usize)) -> Option<(usize, usize)> {
+		if p.0 < self.cols && p.1 < self.rows {
+			let chunk = p.0 / CHUNK_SIZE + p.1 / CHUNK_SIZE * self.cols.div_ceil(CHUNK_SIZE);
+			Some((chunk, p.0 % CHUNK_SIZE + p.1 % CHUNK_SIZE * CHUNK_SIZE))
+		} else {
+			None
+		}
+	}
+}
+
+/// Tiles of a box of a `SyncTileNet`, read-locked until dropped
+///
+/// Created by `SyncTileNet::read_box`.
+pub struct SyncView<'a, T>
+	where T: 'a
+{
+	guards: Vec<RwLockReadGuard<'a, Vec<T>>>,
+	// Column and row of the first locked chunk, and the chunks locked per row
+	first: (usize, usize),
+	across: usize,
+	rectangle: (usize, usize, usize, usize),
+}
+
+impl<'a, T> SyncView<'a, T> {
+	/// Get a tile inside of the box
+	pub fn get(&self, p: (usize, usize)) -> Option<&T> {
+		let rectangle = self.rectangle;
+		if p.0 < rectangle.0 || p.0 >= rectangle.1 || p.1 < rectangle.2 || p.1 >= rectangle.3 {
+			return None;
+		}
+		let chunk = p.0 / CHUNK_SIZE - self.first.0 + (p.1 / CHUNK_SIZE - self.first.1) * self.across;
+		Some(&self.guards[chunk][p.0 % CHUNK_SIZE + p.1 % CHUNK_SIZE * CHUNK_SIZE])
+	}
+
+	/// Iterate over the tiles of the box row by row, with their column and row
+	pub fn iter(&self) -> impl Iterator<Item = (&T, usize, usize)> {
+		let (x0, x1, y0, y1) = self.rectangle;
+		(y0..y1).flat_map(move |y| (x0..x1).map(move |x| (x, y)))
+			.map(move |(x, y)| (self.get((x, y)).expect("inside the box"), x, y))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Arc;
+	use std::thread;
+
+	#[test]
+	fn edits_while_reading() {
+		let net = Arc::new(SyncTileNet::from_net(&TileNet::from_iter(70, 0..70 * 40)));
+		assert_eq!(net.chunks.len(), 6);
+		assert_eq!(net.to_tile_net().get_raw(), TileNet::from_iter(70, 0..70 * 40).get_raw());
+		let readers: Vec<_> = (0..4)
+			.map(|_| {
+				let net = net.clone();
+				thread::spawn(move || {
+					(0..200)
+						.map(|_| {
+							// Every chunk of the row is either cleared or untouched
+							let view = net.read_box((0, 70, 39, 40));
+							for &(start, stop) in &[(30, 32), (32, 64), (64, 70)] {
+								let cleared = (start..stop).filter(|x| view.get((*x, 39)) == Some(&0)).count();
+								assert!(cleared == 0 || cleared == stop - start);
+							}
+							drop(view);
+							net.any((0..70).map(|x| (x, 39)), |x| *x == 0)
+						})
+						.collect::<Vec<bool>>()
+				})
+			})
+			.collect();
+		net.set_box_filled(&0, (30, 35), (100, 100));
+		for reader in readers {
+			let seen = reader.join().unwrap();
+			// Once a cleared tile is seen, it stays cleared
+			assert!(seen.windows(2).all(|x| x[0] <= x[1]));
+		}
+		assert_eq!(net.get((69, 39)), Some(0));
+		assert_eq!(net.get((29, 39)), Some(29 + 39 * 70));
+		assert_eq!(net.get((70, 0)), None);
+		assert!(net.any(vec![(-1, 0), (30, 35)], |x| *x == 0));
+		assert!(!net.any(vec![(-1, 0), (29, 35), (99, 0)], |x| *x == 0));
+		let view = net.read_box((28, 100, 38, 100));
+		assert_eq!(view.iter().count(), 42 * 2);
+		assert_eq!(view.iter().next(), Some((&(28 + 38 * 70), 28, 38)));
+		assert_eq!(view.iter().filter(|x| *x.0 == 0).count(), 40 * 2);
+		assert_eq!(net.read_box((70, 80, 0, 10)).iter().count(), 0);
+	}
+}
@@ -1,15 +1,24 @@
-pub use super::{SuperCover, Line, Vector, TileNet, TileSet};
+pub use super::{SuperCover, Vector, TileNet, TileSet};
+use super::TileKind;
 
-pub use interleave::{IterList, MultiIter};
+pub use self::covers::{AttributedSupercovers, BatchSupercovers, PointSupercovers};
+pub use self::trace::{SolveStep, SolveTrace};
+
+mod covers;
+mod trace;
 
 /// A vertex iterator.
 ///
 /// Used internally by the collision engine. It combines static
 /// points with an offset. Every iteration returns the point + offset.
+/// The points may also be rotated and scaled around the offset first, see
+/// `Points::transformed`.
 pub struct Points<'a> {
 	index: usize,
 	offset: Vector,
 	points: &'a [(f32, f32)],
+	// Sine, cosine and scale, if the points are transformed
+	transform: Option<(f32, f32, f32)>,
 }
 
 impl<'a> Points<'a> {
@@ -32,6 +41,31 @@ impl<'a> Points<'a> {
 			index: 0,
 			offset: offset,
 			points: points,
+			transform: None,
+		}
+	}
+
+	/// Create a points iterator rotating and scaling the points around the offset
+	///
+	/// Every point is scaled by `scale`, then rotated by `angle` radians from
+	/// the positive x axis towards the positive y axis, like
+	/// `Vector::rotated`, and then moved by the offset. This traces a
+	/// rotating sprite from its fixed local vertices without storing a
+	/// rotated copy every frame.
+	///
+	/// ```
+	/// use tile_net::{Points, Vector};
+	/// use std::f32::consts::FRAC_PI_2;
+	/// let local = [(1.0, 0.0), (0.0, 0.5)];
+	/// let points: Vec<(f32, f32)> = Points::transformed(Vector(3.0, 3.0), FRAC_PI_2, 2.0, &local).collect();
+	/// assert!((points[0].0 - 3.0).abs() < 1e-6 && (points[0].1 - 5.0).abs() < 1e-6);
+	/// assert!((points[1].0 - 2.0).abs() < 1e-6 && (points[1].1 - 3.0).abs() < 1e-6);
+	/// ```
+	pub fn transformed(offset: Vector, angle: f32, scale: f32, points: &'a [(f32, f32)]) -> Points<'a> {
+		let (sin, cos) = angle.sin_cos();
+		Points {
+			transform: Some((sin, cos, scale)),
+			..Points::new(offset, points)
 		}
 	}
 }
@@ -39,18 +73,181 @@ impl<'a> Points<'a> {
 impl<'a> Iterator for Points<'a> {
 	type Item = (f32, f32);
 	fn next(&mut self) -> Option<Self::Item> {
+		let transform = self.transform;
 		let ret = self.points
 			.get(self.index)
 			.cloned()
-			.map(|x| Vector::from_tuple(x) + self.offset)
-			.map(|x| (x.0, x.1));
+			.map(|x| match transform {
+				Some((sin, cos, scale)) => (x.0 * scale * cos - x.1 * scale * sin, x.0 * scale * sin + x.1 * scale * cos),
+				None => x,
+			})
+			.map(|x| (Vector::from(x) + self.offset).into());
 		self.index += 1;
 		ret
 	}
 }
 
+/// What happened during a single resolve step, or during a whole `solve`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolution {
+	/// The move was accepted and the collable moved by the given vector
+	Moved {
+		/// The applied movement
+		by: Vector,
+	},
+	/// The move was refused because of a known contact
+	Blocked {
+		/// The tile that was hit first
+		first_hit: (i32, i32),
+		/// Normal of the surface that was hit, pointing away from the tile
+		normal: Vector,
+		/// Fraction of the queued move that was free, from 0 to 1
+		t: f32,
+	},
+	/// The move was refused without details, the collable will try again
+	Refused,
+	/// The collable can not move at all, no further attempts are made
+	Stuck,
+}
+
+impl Resolution {
+	/// Check whether this resolution ends the resolve loop successfully
+	pub fn is_moved(&self) -> bool {
+		matches!(*self, Resolution::Moved { .. })
+	}
+}
+
+/// Strategy used by `Collable::solve_with_resolver` to shorten a blocked move
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolver {
+	/// Multiply the move by the factor until it is free, at most 30 times
+	///
+	/// If no scaled move is free, the collable does not move.
+	Scale(f32),
+	/// Bisect between the longest known free and the shortest known blocked move
+	///
+	/// Stops after `max_iters` steps, or once the two differ by less than
+	/// `epsilon` tiles. The result is always a free move: a collable already
+	/// overlapping a solid tile is `Stuck`, see `Collable::solve_with_resolver`.
+	BinarySearch {
+		/// Highest number of bisection steps
+		max_iters: usize,
+		/// Precision of the resulting move, in tiles
+		epsilon: f32,
+	},
+}
+
+impl Default for Resolver {
+	fn default() -> Resolver {
+		Resolver::BinarySearch {
+			max_iters: 16,
+			epsilon: 1e-3,
+		}
+	}
+}
+
+/// Order in which `Collable::solve_axes` handles the axes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AxisOrder {
+	/// Move horizontally first
+	XThenY,
+	/// Move vertically first
+	YThenX,
+}
+
+/// How `Collable::solve_with` searches for a free move
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveStrategy {
+	/// Call `resolve` until it accepts a move, like `solve`
+	Resolve,
+	/// Solve each axis on its own in the given order, like `solve_axes`
+	Axes(AxisOrder),
+}
+
+/// Settings of `Collable::solve_with`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolveOptions {
+	/// Highest number of calls to `resolve`, per axis with `SolveStrategy::Axes`
+	pub max_iterations: usize,
+	/// How to search for a free move
+	pub strategy: SolveStrategy,
+	/// Give up once a refused move is shorter than this, in tiles
+	///
+	/// Zero keeps going until `max_iterations` is reached.
+	pub epsilon: f32,
+}
+
+impl Default for SolveOptions {
+	/// The settings of `solve`: 30 iterations of `resolve` and no epsilon
+	fn default() -> SolveOptions {
+		SolveOptions {
+			max_iterations: 30,
+			strategy: SolveStrategy::Resolve,
+			epsilon: 0.0,
+		}
+	}
+}
+
+/// Outcome of `Collable::solve_with`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolveReport {
+	/// Number of calls to `resolve_detailed`, over both axes with `SolveStrategy::Axes`
+	pub iterations: usize,
+	/// Whether a move was accepted, on both axes with `SolveStrategy::Axes`
+	pub resolved: bool,
+	/// Solid tiles read by `resolve_detailed` whenever it did not accept the
+	/// move, and tiles reported through `Resolution::Blocked`, in the order
+	/// they were hit, without repeats. A solid border counts as solid.
+	pub hits: Vec<(i32, i32)>,
+	/// Movement applied, adding up every accepted `Resolution::Moved`
+	pub moved: Vector,
+	/// Sensor tiles passed through, as given by `Collable::sensors` after every solve, without repeats
+	pub sensors: Vec<(i32, i32)>,
+}
+
+impl Default for SolveReport {
+	fn default() -> SolveReport {
+		SolveReport {
+			iterations: 0,
+			resolved: false,
+			hits: vec![],
+			moved: Vector(0.0, 0.0),
+			sensors: vec![],
+		}
+	}
+}
+
+/// Outcome of `Collable::solve_axes`, per axis
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisReport {
+	/// Result of solving the horizontal move
+	pub x: Resolution,
+	/// Result of solving the vertical move
+	pub y: Resolution,
+	/// The move queued before solving
+	pub requested: Vector,
+}
+
+impl AxisReport {
+	/// Check whether the horizontal move was cut short
+	pub fn blocked_x(&self) -> bool {
+		!matches!(self.x, Resolution::Moved { by } if by.0 == self.requested.0)
+	}
+
+	/// Check whether the vertical move was cut short
+	///
+	/// With a downward move in a y-down world, this means standing on the ground.
+	pub fn blocked_y(&self) -> bool {
+		!matches!(self.y, Resolution::Moved { by } if by.1 == self.requested.1)
+	}
+}
+
 /// Trait for dynamic objects so they can easily check collisions with the `TileMap`
-pub trait Collable<T, S> {
+///
+/// `T` is the tile type and `S` the state handed to the solve stages, see
+/// the crate documentation. Objects without state can leave `S` out, it
+/// defaults to `()`.
+pub trait Collable<T, S = ()> {
 	/// Returns the set of points associated with this object. These points are used to
 	/// draw lines to their respective next points. For a rectangle, the four courners
 	/// may be points. For a circle, a whole bunch of points may be defined.
@@ -60,6 +257,15 @@ pub trait Collable<T, S> {
 	/// but you can do whatever makes sense in your application.
 	fn queued(&self) -> Vector;
 
+	/// Replace the queued move
+	///
+	/// Used by `solve_axes` to move along one axis at a time, and by
+	/// `solve_with_resolver` to try shorter moves. A later `queued` should
+	/// return the given vector, also after `presolve`. Unlike the `enqueue`
+	/// of the 2.x examples, this replaces the move instead of adding to it,
+	/// see the migration notes of the crate.
+	fn enqueue(&mut self, vector: Vector);
+
 	/// Resolve the movement: you get a set of tiles and you decide what to do with them.
 	/// If you aren't satisfied, you can change the move vector and return false, this means
 	/// that we'll try again. Another set of tiles may then be given.
@@ -67,9 +273,28 @@ pub trait Collable<T, S> {
 	///
 	/// IMPORTANT: You should add the move from queued_move to your point set. The ray tracer
 	/// also adds to find the next points. This will prevent you from getting stuck in a wall.
+	///
+	/// The set skips indices covered by `OutOfBounds::Solid`, iterate over
+	/// `TileSet::with_border` to be stopped by a solid border.
 	fn resolve<I>(&mut self, set: TileSet<T, I>, state: &mut S) -> bool
 		where I: Iterator<Item = (i32, i32)>;
 
+	/// Resolve the movement and describe the outcome
+	///
+	/// The default implementation calls `resolve` and reports either `Moved` with
+	/// the queued move or `Refused`. Override it to report contact details, or
+	/// `Stuck` to end the resolve loop early.
+	fn resolve_detailed<I>(&mut self, set: TileSet<T, I>, state: &mut S) -> Resolution
+		where I: Iterator<Item = (i32, i32)>
+	{
+		let by = self.queued();
+		if self.resolve(set, state) {
+			Resolution::Moved { by }
+		} else {
+			Resolution::Refused
+		}
+	}
+
 	/// Called at the beginning of `solve`
 	///
 	/// This method is useful when resetting internal variables of state.
@@ -81,45 +306,494 @@ pub trait Collable<T, S> {
 	/// Used to process the result from the resolve loop.
 	fn postsolve(&mut self, _collided_once: bool, _resolved: bool, _state: &mut S) {}
 
+	/// Get the sensor tiles passed through during the last solve
+	///
+	/// Added to `SolveReport::sensors` by `solve_with`. Returns nothing by
+	/// default; the built-in bodies list the tiles for which
+	/// `TileKind::is_sensor` holds.
+	fn sensors(&self) -> &[(i32, i32)] {
+		&[]
+	}
+
 	/// Convenience function for the resolve loop
 	///
 	/// Calls presolve at the beginning and postsolve at the end.
 	/// Runs the resolve function in a loop of at max 30 iterations.
 	/// This is to avoid potential deadlock if the resolve function
 	/// is poorly coded and returns false all the time.
-	fn solve(&mut self, net: &TileNet<T>, state: &mut S) {
-		self.presolve(state);
-		static MAX_ITERATIONS: usize = 30;
-		let mut collided_once = false;
-		let mut resolved = false;
-		for _ in 0..MAX_ITERATIONS {
-			let tiles = net.collide_set(self.tiles());
-			if self.resolve(tiles, state) {
-				resolved = true;
-				break;
+	///
+	/// Returns the accepted `Moved` resolution, the last `Blocked` one if the
+	/// loop ran out of iterations, or `Stuck`.
+	fn solve(&mut self, net: &TileNet<T>, state: &mut S) -> Resolution {
+		solve_loop(self, net, state, None, SolveOptions::default(), None)
+	}
+
+	/// Solve with a custom budget and strategy, reporting what happened
+	///
+	/// `presolve` and `postsolve` are called around every solve, so twice
+	/// with `SolveStrategy::Axes`, which splits the move like `solve_axes`.
+	/// Tiles are solid according to `TileKind::is_solid`.
+	///
+	/// ```
+	/// use tile_net::*;
+	/// struct Box {
+	///     pos: Vector,
+	///     mov: Vector,
+	/// }
+	/// impl Collable<usize> for Box {
+	///     fn points(&self) -> Points {
+	///         Points::new(self.pos, &[(0.0, 0.0), (0.99, 0.0), (0.0, 0.99), (0.99, 0.99)])
+	///     }
+	///     fn queued(&self) -> Vector {
+	///         self.mov
+	///     }
+	///     fn enqueue(&mut self, mov: Vector) {
+	///         self.mov = mov;
+	///     }
+	///     fn resolve<I>(&mut self, _: TileSet<usize, I>, _: &mut ()) -> bool
+	///         where I: Iterator<Item = (i32, i32)>
+	///     {
+	///         unreachable!()
+	///     }
+	///     fn resolve_detailed<I>(&mut self, mut set: TileSet<usize, I>, _: &mut ()) -> Resolution
+	///         where I: Iterator<Item = (i32, i32)>
+	///     {
+	///         if set.by_ref().all(|x| *x == 0) {
+	///             self.pos = self.pos + self.mov;
+	///             return Resolution::Moved { by: self.mov };
+	///         }
+	///         let first_hit = set.get_coords();
+	///         self.mov.scale(0.5);
+	///         Resolution::Blocked { first_hit, normal: Vector(0.0, 0.0), t: 0.0 }
+	///     }
+	/// }
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set_col(&1, 5);
+	/// let mut body = Box { pos: Vector(2.0, 2.0), mov: Vector(4.0, 1.0) };
+	/// let options = SolveOptions { strategy: SolveStrategy::Axes(AxisOrder::XThenY), ..SolveOptions::default() };
+	/// let report = body.solve_with(&net, options, &mut ());
+	/// assert!(report.resolved);
+	/// assert_eq!(report.iterations, 3);
+	/// assert_eq!(report.hits, vec![(5, 2)]);
+	/// assert_eq!(report.moved, Vector(2.0, 1.0));
+	/// ```
+	fn solve_with(&mut self, net: &TileNet<T>, options: SolveOptions, state: &mut S) -> SolveReport
+		where T: TileKind
+	{
+		let mut report = SolveReport::default();
+		match options.strategy {
+			SolveStrategy::Resolve => {
+				let last = solve_loop(self, net, state, None, options, Some((&mut report, T::is_solid)));
+				report.resolved = last.is_moved();
+			}
+			SolveStrategy::Axes(order) => {
+				let axes = solve_split(self, net, order, state, options, Some((&mut report, T::is_solid)));
+				report.resolved = axes.x.is_moved() && axes.y.is_moved();
 			}
-			collided_once = true;
 		}
-		self.postsolve(collided_once, resolved, state);
+		report
+	}
+
+	/// Solve like `solve`, appending every iteration to a trace
+	///
+	/// Each call to `resolve_detailed` adds a `SolveStep` to `trace`, holding
+	/// the tiles the resolver took from its `TileSet`. Unread tiles are not
+	/// recorded, so a resolver stopping at the first solid tile shows exactly
+	/// what it based its decision on. Because the trace is appended to, one
+	/// trace can cover several frames.
+	fn solve_traced(&mut self, net: &TileNet<T>, state: &mut S, trace: &mut SolveTrace) -> Resolution {
+		solve_loop(self, net, state, Some(trace), SolveOptions::default(), None)
+	}
+
+	/// Solve by shortening the queued move until it is free
+	///
+	/// Instead of relying on `resolve` to shrink the move, the longest free
+	/// fraction of the queued move is searched with the given `Resolver`, where
+	/// a move is free if none of its tiles are solid according to `is_solid`,
+	/// and it does not reach into an `OutOfBounds::Solid` border.
+	/// Then `resolve` is called once with that move to apply it. `presolve`
+	/// and `postsolve` are called as with `solve`. If not even an empty move
+	/// is free, because the collable already overlaps a solid tile, `resolve`
+	/// is not called and `Stuck` is returned with the move left queued.
+	fn solve_with_resolver<F>(&mut self,
+	                          net: &TileNet<T>,
+	                          resolver: Resolver,
+	                          mut is_solid: F,
+	                          state: &mut S)
+	                          -> Resolution
+		where F: FnMut(&T) -> bool
+	{
+		self.presolve(state);
+		let full = self.queued();
+		let mut free = |this: &mut Self, fraction: f32| {
+			this.enqueue(full * fraction);
+			!net.collide_set(this.tiles()).with_border().any(|x| x.map_or(true, &mut is_solid))
+		};
+		let fraction = if free(self, 1.0) {
+			1.0
+		} else if !free(self, 0.0) {
+			self.enqueue(full);
+			self.postsolve(true, false, state);
+			return Resolution::Stuck;
+		} else {
+			match resolver {
+				Resolver::Scale(factor) => {
+					let mut fraction = 1.0;
+					let mut accepted = 0.0;
+					for _ in 0..30 {
+						fraction *= factor;
+						if free(self, fraction) {
+							accepted = fraction;
+							break;
+						}
+					}
+					accepted
+				}
+				Resolver::BinarySearch { max_iters, epsilon } => {
+					let length = full.norm2sq().sqrt();
+					let (mut low, mut high) = (0.0, 1.0);
+					for _ in 0..max_iters {
+						if (high - low) * length < epsilon {
+							break;
+						}
+						let middle = (low + high) / 2.0;
+						if free(self, middle) {
+							low = middle;
+						} else {
+							high = middle;
+						}
+					}
+					low
+				}
+			}
+		};
+		self.enqueue(full * fraction);
+		let tiles = net.collide_set(self.tiles());
+		let resolution = self.resolve_detailed(tiles, state);
+		self.postsolve(fraction < 1.0, resolution.is_moved(), state);
+		resolution
+	}
+
+	/// Solve the queued move one axis at a time
+	///
+	/// Splits the queued move into its horizontal and vertical part, then
+	/// calls `enqueue` and `solve` for each part in the given order. This
+	/// lets a platformer character slide along walls and tell a wall from a
+	/// floor without juggling state between two calls to `solve`.
+	fn solve_axes(&mut self, net: &TileNet<T>, order: AxisOrder, state: &mut S) -> AxisReport {
+		solve_split(self, net, order, state, SolveOptions::default(), None)
 	}
 
 	/// Gives us a list of points, sorted by proximity on the line.
 	///
 	/// The sortedness of the returned iterator means you can base your decision on the
 	/// first element(s), as they represent the first collision.
-	fn tiles(&self) -> MultiIter<(i32, i32)> {
-		let origin = self.points();
-		let mut destination = self.points();
-		destination.offset += self.queued();
-		let mut multi = interleave!((i32, i32););
-
-		for point1 in origin {
-			let point2 = destination.next().unwrap();
-			let point1 = Vector::from_tuple(point1);
-			let point2 = Vector::from_tuple(point2);
-			let line = Line(point1, point2);
-			multi.push(Box::new(line.supercover()));
-		}
-		multi
+	/// The tiles of different points are taken in turns, which only sorts them
+	/// per point; override this with `PointSupercovers::nearest_first` to have
+	/// the first tile be the earliest of the whole body.
+	fn tiles(&self) -> PointSupercovers {
+		PointSupercovers::new(self.points(), self.queued())
+	}
+
+	/// Like `tiles`, but every tile is tagged with the index of the point producing it
+	///
+	/// Feed the result to `TileNet::collide_set_attributed` to find out which
+	/// points touch something, for instance whether only the bottom corners of
+	/// a box hit a tile, meaning it stands on the ground.
+	///
+	/// ```
+	/// use tile_net::*;
+	/// struct Feet(Vec<(f32, f32)>);
+	/// impl Collable<usize, ()> for Feet {
+	///     fn points<'a>(&'a self) -> Points<'a> {
+	///         Points::new(Vector(2.5, 4.5), &self.0)
+	///     }
+	///     fn queued(&self) -> Vector {
+	///         Vector(0.0, 1.0)
+	///     }
+	///     fn enqueue(&mut self, _: Vector) {}
+	///     fn resolve<I>(&mut self, _: TileSet<usize, I>, _: &mut ()) -> bool
+	///         where I: Iterator<Item = (i32, i32)>
+	///     {
+	///         true
+	///     }
+	/// }
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set(&1, (2, 5));
+	/// // Head first, then the left and right foot
+	/// let body = Feet(vec![(0.0, -1.0), (0.0, 0.0), (2.0, 0.0)]);
+	/// let mut set = net.collide_set_attributed(body.tiles_attributed());
+	/// let mut grounded = vec![];
+	/// while let Some(tile) = set.next() {
+	///     if *tile != 0 {
+	///         grounded.push(set.get_point());
+	///     }
+	/// }
+	/// assert_eq!(grounded, vec![1]);
+	/// ```
+	fn tiles_attributed(&self) -> AttributedSupercovers {
+		self.tiles().attributed()
+	}
+}
+
+// A report to add up what happened to, with the tiles deemed solid for its hits
+type Reporting<'a, T> = Option<(&'a mut SolveReport, fn(&T) -> bool)>;
+
+// The move of `Collable::solve_axes`, split into two solves.
+fn solve_split<T, S, C>(this: &mut C,
+                        net: &TileNet<T>,
+                        order: AxisOrder,
+                        state: &mut S,
+                        options: SolveOptions,
+                        mut report: Reporting<T>)
+                        -> AxisReport
+	where C: Collable<T, S> + ?Sized
+{
+	let requested = this.queued();
+	let (x, y) = (Vector(requested.0, 0.0), Vector(0.0, requested.1));
+	let (first, second) = match order {
+		AxisOrder::XThenY => (x, y),
+		AxisOrder::YThenX => (y, x),
+	};
+	this.enqueue(first);
+	let first = solve_loop(this, net, state, None, options, report.as_mut().map(|x| (&mut *x.0, x.1)));
+	this.enqueue(second);
+	let second = solve_loop(this, net, state, None, options, report);
+	let (x, y) = match order {
+		AxisOrder::XThenY => (first, second),
+		AxisOrder::YThenX => (second, first),
+	};
+	AxisReport { x, y, requested }
+}
+
+// The resolve loop of `Collable::solve`, optionally recording every
+// iteration and adding up what happened to a report
+fn solve_loop<T, S, C>(this: &mut C,
+                       net: &TileNet<T>,
+                       state: &mut S,
+                       mut trace: Option<&mut SolveTrace>,
+                       options: SolveOptions,
+                       mut report: Reporting<T>)
+                       -> Resolution
+	where C: Collable<T, S> + ?Sized
+{
+	this.presolve(state);
+	let mut collided_once = false;
+	let mut last = Resolution::Stuck;
+	for _ in 0..options.max_iterations {
+		let queued = this.queued();
+		let mut tiles = vec![];
+		let outcome = if trace.is_some() || report.is_some() {
+			let recorder = trace::Recorder {
+				tiles: this.tiles(),
+				seen: &mut tiles,
+			};
+			this.resolve_detailed(net.collide_set(recorder), state)
+		} else {
+			this.resolve_detailed(net.collide_set(this.tiles()), state)
+		};
+		if let Some((ref mut report, is_solid)) = report {
+			report.iterations += 1;
+			if let Resolution::Moved { by } = outcome {
+				report.moved += by;
+			} else {
+				let solid = |coords: &(i32, i32)| {
+					net.collide_set(Some(*coords).into_iter()).with_border().any(|x| x.map_or(true, is_solid))
+				};
+				let first_hit = match outcome {
+					Resolution::Blocked { first_hit, .. } => Some(first_hit),
+					_ => None,
+				};
+				for coords in tiles.iter().filter(|x| solid(x)).chain(first_hit.as_ref()) {
+					if !report.hits.contains(coords) {
+						report.hits.push(*coords);
+					}
+				}
+			}
+		}
+		if let Some(ref mut trace) = trace {
+			trace.steps.push(SolveStep { queued, tiles, outcome });
+		}
+		match outcome {
+			moved @ Resolution::Moved { .. } => {
+				last = moved;
+				break;
+			}
+			Resolution::Stuck => {
+				collided_once = true;
+				last = Resolution::Stuck;
+				break;
+			}
+			Resolution::Refused => {}
+			blocked => last = blocked,
+		}
+		collided_once = true;
+		if this.queued().norm2sq() < options.epsilon * options.epsilon {
+			break;
+		}
+	}
+	this.postsolve(collided_once, last.is_moved(), state);
+	if let Some((report, _)) = report {
+		for coords in this.sensors() {
+			if !report.sensors.contains(coords) {
+				report.sensors.push(*coords);
+			}
+		}
+	}
+	last
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Refuses every move, halving it
+	struct Wall {
+		mov: Vector,
+	}
+
+	impl Collable<usize> for Wall {
+		fn points<'a>(&'a self) -> Points<'a> {
+			Points::new(Vector(0.5, 0.5), &[(0.0, 0.0)])
+		}
+
+		fn queued(&self) -> Vector {
+			self.mov
+		}
+
+		fn enqueue(&mut self, vector: Vector) {
+			self.mov = vector;
+		}
+
+		fn resolve<I>(&mut self, _: TileSet<usize, I>, _: &mut ()) -> bool
+			where I: Iterator<Item = (i32, i32)>
+		{
+			self.mov.scale(0.5);
+			false
+		}
+	}
+
+	// Moves wherever it is told to
+	struct Ghost {
+		pos: Vector,
+		mov: Vector,
+	}
+
+	impl Collable<usize> for Ghost {
+		fn points<'a>(&'a self) -> Points<'a> {
+			Points::new(self.pos, &[(0.0, 0.0)])
+		}
+
+		fn queued(&self) -> Vector {
+			self.mov
+		}
+
+		fn enqueue(&mut self, vector: Vector) {
+			self.mov = vector;
+		}
+
+		fn resolve<I>(&mut self, _: TileSet<usize, I>, _: &mut ()) -> bool
+			where I: Iterator<Item = (i32, i32)>
+		{
+			self.pos += self.mov;
+			true
+		}
+	}
+
+	// Moves only when every tile is free, halving the move otherwise
+	struct Bumper {
+		pos: Vector,
+		mov: Vector,
+	}
+
+	impl Collable<usize> for Bumper {
+		fn points<'a>(&'a self) -> Points<'a> {
+			Points::new(self.pos, &[(0.0, 0.0)])
+		}
+
+		fn queued(&self) -> Vector {
+			self.mov
+		}
+
+		fn enqueue(&mut self, vector: Vector) {
+			self.mov = vector;
+		}
+
+		fn resolve<I>(&mut self, mut set: TileSet<usize, I>, _: &mut ()) -> bool
+			where I: Iterator<Item = (i32, i32)>
+		{
+			if set.all(|x| *x == 0) {
+				self.pos += self.mov;
+				true
+			} else {
+				self.mov.scale(0.5);
+				false
+			}
+		}
+	}
+
+	#[test]
+	fn refused_steps_report_hits() {
+		let mut net: TileNet<usize> = TileNet::new(8, 8);
+		net.set(&1, (3, 1));
+		net.set(&1, (1, 4));
+		let mut bumper = Bumper {
+			pos: Vector(1.5, 1.5),
+			mov: Vector(4.0, 0.0),
+		};
+		let report = bumper.solve_with(&net, SolveOptions::default(), &mut ());
+		assert_eq!((report.resolved, report.hits), (true, vec![(3, 1)]));
+		assert_eq!(bumper.pos, Vector(2.5, 1.5));
+		// Split like `solve_axes`, hitting the other tile on the way down
+		let mut twin = Bumper {
+			pos: Vector(1.5, 1.5),
+			mov: Vector(-1.0, 4.0),
+		};
+		let axes = twin.solve_axes(&net, AxisOrder::YThenX, &mut ());
+		bumper.pos = Vector(1.5, 1.5);
+		bumper.mov = Vector(-1.0, 4.0);
+		let options = SolveOptions {
+			strategy: SolveStrategy::Axes(AxisOrder::YThenX),
+			..SolveOptions::default()
+		};
+		let report = bumper.solve_with(&net, options, &mut ());
+		assert_eq!(bumper.pos, twin.pos);
+		assert_eq!(report.resolved, axes.x.is_moved() && axes.y.is_moved());
+		assert_eq!(report.hits, vec![(1, 4)]);
+	}
+
+	#[test]
+	fn resolver_never_free() {
+		let net: TileNet<usize> = TileNet::new(4, 4);
+		let mut ghost = Ghost {
+			pos: Vector(1.5, 1.5),
+			mov: Vector(1.0, 1.0),
+		};
+		let resolution = ghost.solve_with_resolver(&net, Resolver::Scale(0.5), |_| true, &mut ());
+		assert_eq!(resolution, Resolution::Stuck);
+		assert_eq!((ghost.pos, ghost.mov), (Vector(1.5, 1.5), Vector(1.0, 1.0)));
+		let resolution = ghost.solve_with_resolver(&net, Resolver::default(), |_| true, &mut ());
+		assert_eq!(resolution, Resolution::Stuck);
+		assert_eq!(ghost.pos, Vector(1.5, 1.5));
+	}
+
+	#[test]
+	fn solve_with_budget() {
+		let net: TileNet<usize> = TileNet::new(4, 4);
+		let mut wall = Wall { mov: Vector(1.0, 0.0) };
+		assert_eq!(wall.solve_with(&net, SolveOptions::default(), &mut ()).iterations, 30);
+		let options = SolveOptions {
+			max_iterations: 5,
+			..SolveOptions::default()
+		};
+		let report = wall.solve_with(&net, options, &mut ());
+		assert_eq!((report.iterations, report.resolved, report.moved), (5, false, Vector(0.0, 0.0)));
+		wall.mov = Vector(1.0, 0.0);
+		let options = SolveOptions {
+			epsilon: 0.1,
+			..SolveOptions::default()
+		};
+		// 1/2, 1/4, 1/8 and then 1/16, which is below the epsilon
+		assert_eq!(wall.solve_with(&net, options, &mut ()).iterations, 4);
 	}
 }
@@ -0,0 +1,257 @@
+//! Recordings of the resolve loop of `Collable::solve`
+//!
+//! A trace holds one step per call to `resolve_detailed`: the move that was
+//! queued, the tiles the resolver read, and what it decided. Traces encode
+//! to a small binary message, so a player can attach the one where their
+//! character got stuck to a bug report, and it can be replayed against the
+//! same map later.
+use std::io;
+use super::{Resolution, Vector};
+use super::super::export::{invalid, read_varint, write_varint};
+
+const MAGIC: [u8; 4] = *b"TNST";
+const VERSION: u8 = 1;
+
+// Upper bound on the steps and tiles in a decoded trace, so a corrupt count
+// can not make the decoder allocate without bounds.
+const MAX_ITEMS: u64 = 1 << 24;
+
+/// One iteration of the resolve loop
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolveStep {
+	/// The move queued when `resolve_detailed` was called
+	pub queued: Vector,
+	/// The tiles taken from the `TileSet`, in the order the resolver read them
+	pub tiles: Vec<(i32, i32)>,
+	/// What `resolve_detailed` returned
+	pub outcome: Resolution,
+}
+
+/// Every iteration of one or more calls to `Collable::solve_traced`
+///
+/// ```
+/// use tile_net::{Collable, Points, Resolution, SolveTrace, TileNet, TileSet, Vector};
+/// struct Player(Vector, Vector);
+/// impl Collable<usize, ()> for Player {
+///     fn points(&self) -> Points {
+///         Points::new(self.0, &[(0.0, 0.0)])
+///     }
+///     fn queued(&self) -> Vector {
+///         self.1
+///     }
+///     fn enqueue(&mut self, mov: Vector) {
+///         self.1 = mov;
+///     }
+///     fn resolve<I>(&mut self, mut set: TileSet<usize, I>, _: &mut ()) -> bool
+///         where I: Iterator<Item = (i32, i32)>
+///     {
+///         if set.all(|x| *x == 0) {
+///             self.0 = self.0 + self.1;
+///             true
+///         } else {
+///             self.1 = self.1 * 0.5;
+///             false
+///         }
+///     }
+/// }
+/// let mut net: TileNet<usize> = TileNet::new(4, 1);
+/// net.set(&1, (3, 0));
+/// let mut player = Player(Vector(0.5, 0.5), Vector(3.0, 0.0));
+/// let mut trace = SolveTrace::new();
+/// player.solve_traced(&net, &mut (), &mut trace);
+///
+/// assert_eq!(trace.steps.len(), 2);
+/// assert_eq!(trace.steps[0].tiles, vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+/// assert_eq!(trace.steps[0].outcome, Resolution::Refused);
+/// assert_eq!(trace.steps[1].queued, Vector(1.5, 0.0));
+/// assert_eq!(SolveTrace::decode(&trace.encode()).unwrap(), trace);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolveTrace {
+	/// The recorded iterations, oldest first
+	pub steps: Vec<SolveStep>,
+}
+
+impl SolveTrace {
+	/// Create an empty trace
+	pub fn new() -> SolveTrace {
+		SolveTrace::default()
+	}
+
+	/// Encode the trace into a message
+	///
+	/// Counts and tile coordinates are LEB128 varints, like in
+	/// `Patch::encode`, with the coordinates zigzag-encoded so small negative
+	/// ones stay short. Floats are stored as the 4 little-endian bytes of their
+	/// bits, so decoding gives back exactly the same trace.
+	pub fn encode(&self) -> Vec<u8> {
+		let mut out = MAGIC.to_vec();
+		out.push(VERSION);
+		write_varint(self.steps.len() as u64, &mut out);
+		for step in &self.steps {
+			write_vector(step.queued, &mut out);
+			write_varint(step.tiles.len() as u64, &mut out);
+			for &tile in &step.tiles {
+				write_tile(tile, &mut out);
+			}
+			match step.outcome {
+				Resolution::Moved { by } => {
+					out.push(0);
+					write_vector(by, &mut out);
+				}
+				Resolution::Blocked { first_hit, normal, t } => {
+					out.push(1);
+					write_tile(first_hit, &mut out);
+					write_vector(normal, &mut out);
+					write_u32(t.to_bits(), &mut out);
+				}
+				Resolution::Refused => out.push(2),
+				Resolution::Stuck => out.push(3),
+			}
+		}
+		out
+	}
+
+	/// Decode a message made by `encode`
+	///
+	/// Messages with another magic or version, trailing bytes or more than
+	/// 2^24 steps or tiles per step are rejected.
+	pub fn decode(bytes: &[u8]) -> io::Result<SolveTrace> {
+		if bytes.len() < 5 || bytes[0..4] != MAGIC {
+			return Err(invalid("not a tile_net solve trace"));
+		}
+		if bytes[4] != VERSION {
+			return Err(invalid("unsupported solve trace version"));
+		}
+		let mut input = &bytes[5..];
+		let mut trace = SolveTrace::new();
+		for _ in 0..read_count(&mut input)? {
+			let queued = read_vector(&mut input)?;
+			let mut tiles = vec![];
+			for _ in 0..read_count(&mut input)? {
+				tiles.push(read_tile(&mut input)?);
+			}
+			let outcome = match read_bytes::<1>(&mut input)?[0] {
+				0 => Resolution::Moved { by: read_vector(&mut input)? },
+				1 => {
+					Resolution::Blocked {
+						first_hit: read_tile(&mut input)?,
+						normal: read_vector(&mut input)?,
+						t: f32::from_bits(read_u32(&mut input)?),
+					}
+				}
+				2 => Resolution::Refused,
+				3 => Resolution::Stuck,
+				_ => return Err(invalid("unknown resolution in solve trace")),
+			};
+			trace.steps.push(SolveStep { queued, tiles, outcome });
+		}
+		if !input.is_empty() {
+			return Err(invalid("solve trace has trailing bytes"));
+		}
+		Ok(trace)
+	}
+}
+
+/// Iterator recording every tile it hands out
+pub(crate) struct Recorder<'a, I> {
+	pub(crate) tiles: I,
+	pub(crate) seen: &'a mut Vec<(i32, i32)>,
+}
+
+impl<'a, I> Iterator for Recorder<'a, I>
+	where I: Iterator<Item = (i32, i32)>
+{
+	type Item = (i32, i32);
+	fn next(&mut self) -> Option<Self::Item> {
+		let tile = self.tiles.next();
+		self.seen.extend(tile);
+		tile
+	}
+}
+
+fn write_u32(value: u32, out: &mut Vec<u8>) {
+	out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_vector(vector: Vector, out: &mut Vec<u8>) {
+	write_u32(vector.0.to_bits(), out);
+	write_u32(vector.1.to_bits(), out);
+}
+
+fn write_tile(tile: (i32, i32), out: &mut Vec<u8>) {
+	let zigzag = |x: i32| ((x << 1) ^ (x >> 31)) as u32 as u64;
+	write_varint(zigzag(tile.0), out);
+	write_varint(zigzag(tile.1), out);
+}
+
+fn read_bytes<const N: usize>(input: &mut &[u8]) -> io::Result<[u8; N]> {
+	if input.len() < N {
+		return Err(invalid("solve trace ends early"));
+	}
+	let (bytes, rest) = input.split_at(N);
+	*input = rest;
+	let mut array = [0; N];
+	array.copy_from_slice(bytes);
+	Ok(array)
+}
+
+fn read_u32(input: &mut &[u8]) -> io::Result<u32> {
+	read_bytes(input).map(u32::from_le_bytes)
+}
+
+fn read_count(input: &mut &[u8]) -> io::Result<u64> {
+	let count = read_varint(input)?;
+	if count > MAX_ITEMS {
+		return Err(invalid("solve trace holds too many items"));
+	}
+	Ok(count)
+}
+
+fn read_vector(input: &mut &[u8]) -> io::Result<Vector> {
+	Ok(Vector(f32::from_bits(read_u32(input)?), f32::from_bits(read_u32(input)?)))
+}
+
+fn read_tile(input: &mut &[u8]) -> io::Result<(i32, i32)> {
+	let mut coordinate = || match read_varint(input)? {
+		value if value <= u64::from(u32::MAX) => Ok((value as u32 >> 1) as i32 ^ -((value & 1) as i32)),
+		_ => Err(invalid("tile in solve trace does not fit")),
+	};
+	Ok((coordinate()?, coordinate()?))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn roundtrip_every_outcome() {
+		let outcomes = [Resolution::Moved { by: Vector(0.25, -1.0) },
+		                Resolution::Blocked { first_hit: (-3, 7), normal: Vector(0.0, -1.0), t: 0.5 },
+		                Resolution::Refused,
+		                Resolution::Stuck];
+		let trace = SolveTrace {
+			steps: outcomes.iter()
+				.map(|&outcome| {
+					SolveStep {
+						queued: Vector(1.0, 2.0),
+						tiles: vec![(0, 0), (-1, i32::MAX), (i32::MIN, 5)],
+						outcome,
+					}
+				})
+				.collect(),
+		};
+		let bytes = trace.encode();
+		assert_eq!(SolveTrace::decode(&bytes).unwrap(), trace);
+		assert!(SolveTrace::decode(&bytes[..bytes.len() - 1]).is_err());
+		assert!(SolveTrace::decode(&bytes[1..]).is_err());
+		let mut huge = bytes[..5].to_vec();
+		write_varint(u64::from(u32::MAX), &mut huge);
+		assert!(SolveTrace::decode(&huge).is_err());
+		let mut wide = bytes[..5].to_vec();
+		wide.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0x80, 0x80, 0x80, 0x80, 0x10, 0, 2]);
+		assert!(SolveTrace::decode(&wide).is_err());
+		wide[19] = 0x0f;
+		assert!(SolveTrace::decode(&wide).is_ok());
+	}
+}
@@ -0,0 +1,247 @@
+//! Pictures of a map with collision traces drawn on top
+//!
+//! When a body tunnels through a wall or snags on a corner, seeing which
+//! tiles the supercover visited answers most questions at once. Collect the
+//! map, lines, tiles, points and hits in a `DebugDraw`, then render it as
+//! SVG to view in a browser, or as binary PPM for tools without SVG support.
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Write};
+use super::{Line, TileNet, Vector};
+
+// Colors as RGB, shared by both outputs
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const SOLID: [u8; 3] = [96, 96, 96];
+const VISITED: [u8; 3] = [128, 160, 255];
+const LINE: [u8; 3] = [220, 0, 0];
+const POINT: [u8; 3] = [0, 160, 0];
+const HIT: [u8; 3] = [0, 0, 0];
+
+/// A map with lines, visited tiles, points and hit markers to draw
+///
+/// Positions are in tiles. Everything is drawn in layers: solid tiles, then
+/// visited tiles, lines, points and finally hits, each layer in the order it
+/// was added.
+///
+/// ```
+/// use tile_net::{DebugDraw, Line, TileNet, Vector};
+/// let mut net: TileNet<usize> = TileNet::new(8, 4);
+/// net.set_col(&1, 5);
+/// let line = Line(Vector(0.5, 0.5), Vector(6.5, 3.5));
+/// let svg = DebugDraw::new(&net, |x| *x != 0)
+///     .tiles(line.supercover())
+///     .line(line)
+///     .hit(Vector(5.0, 2.75))
+///     .to_svg(16);
+/// assert!(svg.starts_with("<svg"));
+///
+/// let mut ppm = vec![];
+/// DebugDraw::new(&net, |x| *x != 0).write_ppm(&mut ppm, 2).unwrap();
+/// assert_eq!(ppm.len(), "P6\n16 8\n255\n".len() + 16 * 8 * 3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DebugDraw {
+	cols: usize,
+	rows: usize,
+	solid: Vec<bool>,
+	tiles: Vec<(i32, i32)>,
+	lines: Vec<Line>,
+	points: Vec<Vector>,
+	hits: Vec<Vector>,
+}
+
+impl DebugDraw {
+	/// Start a picture of the tiles of a net for which `is_solid` returns true
+	pub fn new<T, F>(net: &TileNet<T>, is_solid: F) -> DebugDraw
+		where F: FnMut(&T) -> bool
+	{
+		let (cols, rows) = net.get_size();
+		DebugDraw {
+			cols,
+			rows,
+			solid: net.get_raw().iter().map(is_solid).collect(),
+			tiles: vec![],
+			lines: vec![],
+			points: vec![],
+			hits: vec![],
+		}
+	}
+
+	/// Mark visited tiles, such as a supercover or the coordinates of a `TileSet`
+	pub fn tiles<I>(mut self, tiles: I) -> DebugDraw
+		where I: IntoIterator<Item = (i32, i32)>
+	{
+		self.tiles.extend(tiles);
+		self
+	}
+
+	/// Draw a line, such as the path of a point
+	pub fn line(mut self, line: Line) -> DebugDraw {
+		self.lines.push(line);
+		self
+	}
+
+	/// Draw a dot, such as a collision point of a body
+	pub fn point(mut self, point: Vector) -> DebugDraw {
+		self.points.push(point);
+		self
+	}
+
+	/// Draw a cross, such as where a cast hit a tile
+	pub fn hit(mut self, hit: Vector) -> DebugDraw {
+		self.hits.push(hit);
+		self
+	}
+
+	/// Render as SVG, with every tile `scale` pixels wide
+	pub fn to_svg(&self, scale: u32) -> String {
+		let s = scale as f32;
+		let mut svg = String::new();
+		let fill = |color: [u8; 3]| format!("rgb({},{},{})", color[0], color[1], color[2]);
+		// Writing to a String can not fail
+		let _ = writeln!(svg,
+		                 "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+		                 self.cols as u32 * scale,
+		                 self.rows as u32 * scale);
+		let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", fill(BACKGROUND));
+		for (index, _) in self.solid.iter().enumerate().filter(|x| *x.1) {
+			let (x, y) = (index % self.cols, index / self.cols);
+			let _ = writeln!(svg,
+			                 "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+			                 x as f32 * s,
+			                 y as f32 * s,
+			                 s,
+			                 s,
+			                 fill(SOLID));
+		}
+		for &(x, y) in &self.tiles {
+			let _ = writeln!(svg,
+			                 "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"0.5\"/>",
+			                 x as f32 * s,
+			                 y as f32 * s,
+			                 s,
+			                 s,
+			                 fill(VISITED));
+		}
+		for line in &self.lines {
+			let _ = writeln!(svg,
+			                 "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"/>",
+			                 line.0 .0 * s,
+			                 line.0 .1 * s,
+			                 line.1 .0 * s,
+			                 line.1 .1 * s,
+			                 fill(LINE));
+		}
+		for point in &self.points {
+			let _ = writeln!(svg,
+			                 "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+			                 point.0 * s,
+			                 point.1 * s,
+			                 s / 8.0,
+			                 fill(POINT));
+		}
+		for hit in &self.hits {
+			let (x, y, r) = (hit.0 * s, hit.1 * s, s / 4.0);
+			let _ = writeln!(svg,
+			                 "<path d=\"M{} {}L{} {}M{} {}L{} {}\" stroke=\"{}\"/>",
+			                 x - r,
+			                 y - r,
+			                 x + r,
+			                 y + r,
+			                 x - r,
+			                 y + r,
+			                 x + r,
+			                 y - r,
+			                 fill(HIT));
+		}
+		svg.push_str("</svg>\n");
+		svg
+	}
+
+	/// Write as binary PPM, with every tile `scale` pixels wide
+	///
+	/// Visited tiles are blended halfway with the tile below.
+	pub fn write_ppm<W>(&self, out: &mut W, scale: u32) -> io::Result<()>
+		where W: Write
+	{
+		let scale = scale as usize;
+		let (width, height) = (self.cols * scale, self.rows * scale);
+		let mut pixels = vec![BACKGROUND; width * height];
+		for (index, solid) in self.solid.iter().enumerate() {
+			if *solid {
+				fill_tile(&mut pixels, width, scale, (index % self.cols) as i32, (index / self.cols) as i32, |_| SOLID);
+			}
+		}
+		for &(x, y) in &self.tiles {
+			if x >= 0 && y >= 0 && (x as usize) < self.cols && (y as usize) < self.rows {
+				fill_tile(&mut pixels, width, scale, x, y, |below| {
+					let mut blended = [0; 3];
+					for channel in 0..3 {
+						blended[channel] = ((below[channel] as u16 + VISITED[channel] as u16) / 2) as u8;
+					}
+					blended
+				});
+			}
+		}
+		let mut plot = |point: Vector, color: [u8; 3]| {
+			let (x, y) = (point.0 * scale as f32, point.1 * scale as f32);
+			if x >= 0.0 && y >= 0.0 && (x as usize) < width && (y as usize) < height {
+				pixels[x as usize + y as usize * width] = color;
+			}
+		};
+		for line in &self.lines {
+			let delta = line.1 - line.0;
+			// Two samples per pixel leave no gaps
+			let steps = (delta.norm2sq().sqrt() * scale as f32 * 2.0).ceil().max(1.0) as usize;
+			for step in 0..steps + 1 {
+				plot(line.0 + delta * (step as f32 / steps as f32), LINE);
+			}
+		}
+		let pixel = 1.0 / scale as f32;
+		for &point in &self.points {
+			for &(dx, dy) in &[(0.0, 0.0), (1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)] {
+				plot(point + Vector(dx, dy) * pixel, POINT);
+			}
+		}
+		for &hit in &self.hits {
+			for step in -3..4 {
+				let offset = step as f32 * pixel;
+				plot(hit + Vector(offset, offset), HIT);
+				plot(hit + Vector(offset, -offset), HIT);
+			}
+		}
+		write!(out, "P6\n{} {}\n255\n", width, height)?;
+		for pixel in &pixels {
+			out.write_all(pixel)?;
+		}
+		Ok(())
+	}
+}
+
+fn fill_tile<F>(pixels: &mut [[u8; 3]], width: usize, scale: usize, x: i32, y: i32, mut color: F)
+	where F: FnMut([u8; 3]) -> [u8; 3]
+{
+	for row in y as usize * scale..(y as usize + 1) * scale {
+		for pixel in &mut pixels[row * width + x as usize * scale..row * width + (x as usize + 1) * scale] {
+			*pixel = color(*pixel);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ppm_layers() {
+		let mut net: TileNet<usize> = TileNet::new(2, 1);
+		net.set(&1, (0, 0));
+		let draw = DebugDraw::new(&net, |x| *x != 0).tiles(vec![(0, 0), (5, 5)]);
+		let mut ppm = vec![];
+		draw.write_ppm(&mut ppm, 1).unwrap();
+		let header = b"P6\n2 1\n255\n".len();
+		assert_eq!(&ppm[header..], &[112, 128, 175, 255, 255, 255]);
+		let svg = draw.point(Vector(1.5, 0.5)).to_svg(10);
+		assert_eq!(svg.matches("<rect").count(), 4);
+		assert!(svg.contains("<circle cx=\"15\" cy=\"5\""));
+	}
+}
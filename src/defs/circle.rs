@@ -0,0 +1,39 @@
+use std::f32::consts::PI;
+use super::Vector;
+
+/// Describe a circle by its center and radius
+///
+/// ```
+/// use tile_net::{Circle, Points, Vector};
+/// let circle = Circle::new(Vector(3.0, 2.0), 0.5);
+/// let outline = circle.collision_points(4);
+/// let points: Vec<(f32, f32)> = Points::new(circle.center, &outline).collect();
+/// assert_eq!(points[0], (3.5, 2.0));
+/// assert_eq!(points.len(), 4);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+	/// Center of the circle
+	pub center: Vector,
+	/// Radius of the circle
+	pub radius: f32,
+}
+
+impl Circle {
+	/// Create a circle from its center and radius
+	pub fn new(center: Vector, radius: f32) -> Circle {
+		Circle { center, radius }
+	}
+
+	/// Get `count` points spread evenly over the perimeter, relative to the center
+	///
+	/// Pass them to `Points::new` together with the center to trace a circle
+	/// with `Collable`. The first point lies towards positive x, and the
+	/// points go on towards positive y. Keep neighbouring points less than a
+	/// tile apart, or thin walls may slip between them.
+	pub fn collision_points(&self, count: usize) -> Vec<(f32, f32)> {
+		(0..count)
+			.map(|x| (Vector(1.0, 0.0).rotated(2.0 * PI * x as f32 / count as f32) * self.radius).to_tuple())
+			.collect()
+	}
+}
@@ -0,0 +1,122 @@
+use std::convert::TryFrom;
+
+/// Describe a tile of a net by its column and row
+///
+/// Methods taking a tile, like `TileNet::get` and `TileNet::set`, accept
+/// anything convertible into a `GridCoord`, so naming the fields guards
+/// against swapping them while plain `(x, y)` tuples keep working.
+///
+/// ```
+/// use tile_net::{GridCoord, TileNet};
+/// let mut net: TileNet<usize> = TileNet::new(4, 3);
+/// net.set(&1, GridCoord { x: 3, y: 0 });
+/// assert_eq!(net.get((3, 0)), Some(&1));
+/// assert_eq!(net.get(GridCoord::new(0, 3)), None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GridCoord {
+	/// Column, counted from the left
+	pub x: u32,
+	/// Row, counted from the top
+	pub y: u32,
+}
+
+/// Describe a tile by its column and row, which may lie outside of the net
+///
+/// This is what collision iterators like `SuperCover` and `TileSet` deal
+/// in, since a moving object can leave the net on every side.
+///
+/// ```
+/// use tile_net::{GridCoord, SignedCoord};
+/// let left = SignedCoord::from((-1, 4));
+/// assert_eq!(left.to_grid(), None);
+/// assert_eq!(SignedCoord::new(2, 4).to_grid(), Some(GridCoord::new(2, 4)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SignedCoord {
+	/// Column, counted from the left
+	pub x: i32,
+	/// Row, counted from the top
+	pub y: i32,
+}
+
+impl GridCoord {
+	/// Create a coordinate from a column and a row
+	pub fn new(x: u32, y: u32) -> GridCoord {
+		GridCoord { x, y }
+	}
+
+	/// Convert the coordinate into a (column, row) tuple used for indexing
+	pub fn to_tuple(&self) -> (usize, usize) {
+		(self.x as usize, self.y as usize)
+	}
+}
+
+impl SignedCoord {
+	/// Create a coordinate from a column and a row
+	pub fn new(x: i32, y: i32) -> SignedCoord {
+		SignedCoord { x, y }
+	}
+
+	/// Convert the coordinate into a (column, row) tuple
+	pub fn to_tuple(&self) -> (i32, i32) {
+		(self.x, self.y)
+	}
+
+	/// Get the tile, unless it lies left of or above the net
+	pub fn to_grid(&self) -> Option<GridCoord> {
+		if self.x >= 0 && self.y >= 0 { Some(GridCoord::new(self.x as u32, self.y as u32)) } else { None }
+	}
+}
+
+/// Columns and rows beyond `u32::MAX` saturate, so they stay outside of the net
+///
+/// A net with `OutOfBounds::Wrap` wraps the saturated coordinate, so any
+/// column beyond `u32::MAX` lands on the same tile as `u32::MAX` itself.
+impl From<(usize, usize)> for GridCoord {
+	fn from(tuple: (usize, usize)) -> GridCoord {
+		let clamp = |x: usize| u32::try_from(x).unwrap_or(u32::MAX);
+		GridCoord::new(clamp(tuple.0), clamp(tuple.1))
+	}
+}
+
+impl From<GridCoord> for (usize, usize) {
+	fn from(coord: GridCoord) -> (usize, usize) {
+		coord.to_tuple()
+	}
+}
+
+impl From<(i32, i32)> for SignedCoord {
+	fn from(tuple: (i32, i32)) -> SignedCoord {
+		SignedCoord::new(tuple.0, tuple.1)
+	}
+}
+
+impl From<SignedCoord> for (i32, i32) {
+	fn from(coord: SignedCoord) -> (i32, i32) {
+		coord.to_tuple()
+	}
+}
+
+/// Columns and rows beyond `i32::MAX` saturate
+impl From<GridCoord> for SignedCoord {
+	fn from(coord: GridCoord) -> SignedCoord {
+		let clamp = |x: u32| i32::try_from(x).unwrap_or(i32::MAX);
+		SignedCoord::new(clamp(coord.x), clamp(coord.y))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn conversions() {
+		assert_eq!(GridCoord::from((3usize, 7usize)), GridCoord { x: 3, y: 7 });
+		assert_eq!(<(usize, usize)>::from(GridCoord::new(3, 7)), (3, 7));
+		assert_eq!(GridCoord::from((usize::MAX, 1)).x, u32::MAX);
+		assert_eq!(SignedCoord::from(GridCoord::new(u32::MAX, 5)), SignedCoord::new(i32::MAX, 5));
+		assert_eq!(SignedCoord::new(0, -1).to_grid(), None);
+		assert_eq!(<(i32, i32)>::from(SignedCoord::from((-2, 9))), (-2, 9));
+	}
+}
@@ -0,0 +1,102 @@
+//! Conversions to and from the vocabulary types of other math crates
+//!
+//! `mint` is the common ground that glam, nalgebra, cgmath and most renderers
+//! accept, so bridging through it keeps this crate independent of all of them.
+//! `glam` is bridged directly since it is what most game projects use.
+#[cfg(feature = "glam")]
+use glam::Vec2;
+#[cfg(feature = "mint")]
+use mint::{Point2, Vector2};
+#[cfg(feature = "mint")]
+use super::Rect;
+use super::Vector;
+
+#[cfg(feature = "glam")]
+impl From<Vec2> for Vector {
+	fn from(vector: Vec2) -> Vector {
+		Vector(vector.x, vector.y)
+	}
+}
+
+#[cfg(feature = "glam")]
+impl From<Vector> for Vec2 {
+	fn from(vector: Vector) -> Vec2 {
+		Vec2::new(vector.0, vector.1)
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<Point2<f32>> for Vector {
+	fn from(point: Point2<f32>) -> Vector {
+		Vector(point.x, point.y)
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<Vector> for Point2<f32> {
+	fn from(vector: Vector) -> Point2<f32> {
+		Point2 {
+			x: vector.0,
+			y: vector.1,
+		}
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<Vector2<f32>> for Vector {
+	fn from(vector: Vector2<f32>) -> Vector {
+		Vector(vector.x, vector.y)
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<Vector> for Vector2<f32> {
+	fn from(vector: Vector) -> Vector2<f32> {
+		Vector2 {
+			x: vector.0,
+			y: vector.1,
+		}
+	}
+}
+
+/// A rectangle converts from and to its top-left corner and its size
+#[cfg(feature = "mint")]
+impl From<(Point2<f32>, Vector2<f32>)> for Rect {
+	fn from(rect: (Point2<f32>, Vector2<f32>)) -> Rect {
+		Rect::new(rect.0.into(), rect.1.into())
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<Rect> for (Point2<f32>, Vector2<f32>) {
+	fn from(rect: Rect) -> (Point2<f32>, Vector2<f32>) {
+		(rect.pos.into(), rect.size.into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(feature = "glam")]
+	#[test]
+	fn glam() {
+		let vec: Vec2 = Vector(1.0, 2.0).into();
+		assert_eq!(vec, Vec2::new(1.0, 2.0));
+		assert_eq!(Vector::from(vec), Vector(1.0, 2.0));
+	}
+
+	#[cfg(feature = "mint")]
+	#[test]
+	fn mint() {
+		let point: Point2<f32> = Vector(1.0, 2.0).into();
+		assert_eq!((point.x, point.y), (1.0, 2.0));
+		assert_eq!(Vector::from(point), Vector(1.0, 2.0));
+		let vector: Vector2<f32> = Vector(3.0, 4.0).into();
+		assert_eq!(Vector::from(vector), Vector(3.0, 4.0));
+
+		let rect = Rect::new(Vector(1.0, 2.0), Vector(3.0, 4.0));
+		let parts: (Point2<f32>, Vector2<f32>) = rect.into();
+		assert_eq!(Rect::from(parts), rect);
+	}
+}
@@ -1,3 +1,4 @@
+use std::iter::FusedIterator;
 pub use super::Vector;
 
 /// Describe a line by its start and end `Vector` respectively
@@ -16,6 +17,23 @@ pub use super::Vector;
 pub struct Line(pub Vector, pub Vector);
 
 impl Line {
+	/// Create a line from anything convertible to `Vector`
+	///
+	/// This accepts float tuples as handed out by `Points`, as well as
+	/// `glam::Vec2` and `mint` points with the respective features enabled.
+	///
+	/// ```
+	/// use tile_net::{Line, Vector};
+	/// let line = Line::new(Vector(0.5, 1.0), (1.2, -1.0));
+	/// assert_eq!(line, Line(Vector(0.5, 1.0), Vector(1.2, -1.0)));
+	/// ```
+	pub fn new<A, B>(start: A, stop: B) -> Line
+		where A: Into<Vector>,
+		      B: Into<Vector>
+	{
+		Line(start.into(), stop.into())
+	}
+
 	/// Create a line using its end-point, starting in (0, 0)
 	pub fn from_origin(p: Vector) -> Line {
 		Line(Vector(0.0, 0.0), p)
@@ -36,52 +54,63 @@ impl Line {
 	/// The intended use for this iterator is in finding a collision
 	/// between a tile and a moving object.
 	///
+	/// Movements within a single tile, the common case for bodies moving a
+	/// fraction of a tile per frame, and axis-aligned movements skip the
+	/// slope setup, as it is known in advance.
 	pub fn supercover(&self) -> SuperCover {
 		let (start, stop) = (self.0, self.1);
-		let new = stop - start;
-		let (vx, vy) = (new.0, new.1);
-		let slope_x = 1.0 + vy * vy / vx / vx;
-		let slope_y = 1.0 + vx * vx / vy / vy;
-		let (dx, dy) = (slope_x.sqrt(), slope_y.sqrt());
-
 		let (ix, iy) = (start.0.floor() as i32, start.1.floor() as i32);
+		let (dest_x, dest_y) = (stop.0.floor() as i32, stop.1.floor() as i32);
+		let steps = ((dest_x as i64 - ix as i64).unsigned_abs() as usize, (dest_y as i64 - iy as i64).unsigned_abs() as usize);
+		let len = steps.0 + steps.1;
+		let mut cover = SuperCover {
+			dx: 0.0,
+			dy: 0.0,
+			sx: 1,
+			sy: 1,
+			ex: 0.0,
+			ey: 0.0,
+			ix,
+			iy,
+			steps,
+			front: (0, 0),
+			back: steps,
+			left: len + 1,
+		};
+		// Only the destination is yielded, which is the starting tile
+		if len == 0 {
+			return cover;
+		}
 
-		let (sx, sy);
-		let (ex, ey);
+		let new = stop - start;
+		let (vx, vy) = (new.0, new.1);
+		let (dx, dy) = if vy == 0.0 {
+			(1.0, f32::INFINITY)
+		} else if vx == 0.0 {
+			(f32::INFINITY, 1.0)
+		} else {
+			let slope_x = 1.0 + vy * vy / vx / vx;
+			let slope_y = 1.0 + vx * vx / vy / vy;
+			(slope_x.sqrt(), slope_y.sqrt())
+		};
 
 		if vx < 0.0 {
-			sx = -1;
-			ex = start.0.fract() * dx;
+			cover.sx = -1;
+			cover.ex = start.0.fract() * dx;
 		} else {
-			sx = 1;
-			ex = (1.0 - start.0.fract()) * dx;
+			cover.ex = (1.0 - start.0.fract()) * dx;
 		}
 
 		if vy < 0.0 {
-			sy = -1;
-			ey = start.1.fract() * dy;
+			cover.sy = -1;
+			cover.ey = start.1.fract() * dy;
 		} else {
-			sy = 1;
-			ey = (1.0 - start.1.fract()) * dy;
+			cover.ey = (1.0 - start.1.fract()) * dy;
 		}
 
-		let len = (stop.0.floor() as i64 - start.0.floor() as i64).abs() as usize +
-		          (stop.1.floor() as i64 - start.1.floor() as i64).abs() as usize;
-
-		SuperCover {
-			it: 0,
-			len: len,
-			dx: dx,
-			dy: dy,
-			sx: sx,
-			sy: sy,
-			ex: ex,
-			ey: ey,
-			ix: ix,
-			iy: iy,
-			dest_x: stop.0.floor() as i32,
-			dest_y: stop.1.floor() as i32,
-		}
+		cover.dx = dx;
+		cover.dy = dy;
+		cover
 	}
 }
 
@@ -100,10 +129,19 @@ impl Line {
 /// 	println!("{:?}", tile);
 /// }
 /// ```
+///
+/// The number of tiles is known up front, and the line can be walked from
+/// the far end as well.
+///
+/// ```
+/// use tile_net::{Line, Vector};
+/// let line = Line(Vector(0.5, 0.5), Vector(2.5, 1.5));
+/// assert_eq!(line.supercover().len(), 4);
+/// let back: Vec<(i32, i32)> = line.supercover().rev().collect();
+/// assert_eq!(back, vec![(2, 1), (1, 1), (1, 0), (0, 0)]);
+/// ```
 #[derive(Clone)]
 pub struct SuperCover {
-	it: usize,
-	len: usize,
 	dx: f32,
 	dy: f32,
 	sx: i32,
@@ -112,47 +150,126 @@ pub struct SuperCover {
 	ey: f32,
 	ix: i32,
 	iy: i32,
-	dest_x: i32,
-	dest_y: i32,
+	// Steps along each axis from the start to the destination, and from the
+	// start to the tiles taken next from the front and the back
+	steps: (usize, usize),
+	front: (usize, usize),
+	back: (usize, usize),
+	left: usize,
 }
 
 impl SuperCover {
-	fn minimize_distance_from_zero(&mut self) {
-		// let minimal = self.ex.min(self.ey);
-		// self.ex -= minimal;
-		// self.ey -= minimal;
+	/// Trace from `start` along `movement`, relative to the tile of `start`
+	///
+	/// `Line::supercover` needs the end point, but above 2^24 adding a short
+	/// movement to a coordinate may round it away entirely. This moves the
+	/// start into tile (0, 0), runs the trace there where floats are
+	/// precise, and shifts the resulting tiles back. The collision engine
+	/// traces every point of a `Collable` this way.
+	///
+	/// ```
+	/// use tile_net::{Line, SuperCover, Vector};
+	/// let start = Vector(33554432.0, 0.5);
+	/// // The end point rounds back onto the start
+	/// assert_eq!(Line(start, start + Vector(1.5, 0.0)).supercover().count(), 1);
+	/// let tiles: Vec<(i32, i32)> = SuperCover::from_movement(start, Vector(1.5, 0.0)).collect();
+	/// assert_eq!(tiles, vec![(33554432, 0), (33554433, 0)]);
+	/// ```
+	pub fn from_movement(start: Vector, movement: Vector) -> SuperCover {
+		let origin = Vector(start.0.floor(), start.1.floor());
+		let local = start - origin;
+		let mut cover = Line(local, local + movement).supercover();
+		cover.ix += origin.0 as i32;
+		cover.iy += origin.1 as i32;
+		cover
 	}
 
-	fn step_to_next_tile(&mut self) {
-		// This algorithm gives top-bias
-		if self.ex < self.ey {
-			self.ex += self.dx;
-			self.ix += self.sx;
-		} else {
-			self.ey += self.dy;
-			self.iy += self.sy;
+	// Distance along the line at which the next tile is entered, until the
+	// line is done
+	pub(crate) fn peek_entry(&self) -> Option<f32> {
+		if self.left == 0 {
+			return None;
 		}
+		let (x, y) = self.front;
+		let entry = match (x, y) {
+			(0, 0) => 0.0,
+			(0, _) => self.edge_y(y - 1),
+			(_, 0) => self.edge_x(x - 1),
+			_ => self.edge_x(x - 1).max(self.edge_y(y - 1)),
+		};
+		Some(entry as f32)
+	}
+
+	// Distance along the line at which the given step along an axis is taken.
+	// Each is computed on its own rather than summed up, so the front and the
+	// back agree on the order of the steps.
+	fn edge_x(&self, step: usize) -> f64 {
+		edge(self.ex, self.dx, step)
+	}
+
+	fn edge_y(&self, step: usize) -> f64 {
+		edge(self.ey, self.dy, step)
+	}
+
+	fn tile(&self, steps: (usize, usize)) -> (i32, i32) {
+		(self.ix.wrapping_add((steps.0 as i32).wrapping_mul(self.sx)),
+		 self.iy.wrapping_add((steps.1 as i32).wrapping_mul(self.sy)))
+	}
+}
+
+fn edge(first: f32, each: f32, step: usize) -> f64 {
+	if step == 0 {
+		first as f64
+	} else {
+		first as f64 + step as f64 * each as f64
 	}
 }
 
 impl Iterator for SuperCover {
 	type Item = (i32, i32);
 	fn next(&mut self) -> Option<Self::Item> {
-		if self.it < self.len {
-			self.it += 1;
-			let old = Some((self.ix, self.iy));
-			self.step_to_next_tile();
-			self.minimize_distance_from_zero();
-			old
-		} else if self.it == self.len {
-			self.it += 1;
-			Some((self.dest_x, self.dest_y))
-		} else {
-			None
+		if self.left == 0 {
+			return None;
+		}
+		self.left -= 1;
+		let tile = self.tile(self.front);
+		let (x, y) = self.front;
+		// This algorithm gives top-bias
+		if x < self.steps.0 && (y == self.steps.1 || self.edge_x(x) < self.edge_y(y)) {
+			self.front.0 += 1;
+		} else if y < self.steps.1 {
+			self.front.1 += 1;
+		}
+		Some(tile)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.left, Some(self.left))
+	}
+}
+
+impl DoubleEndedIterator for SuperCover {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		if self.left == 0 {
+			return None;
 		}
+		self.left -= 1;
+		let tile = self.tile(self.back);
+		// Undo the later of the last steps along each axis
+		let (x, y) = self.back;
+		if x > 0 && (y == 0 || self.edge_x(x - 1) >= self.edge_y(y - 1)) {
+			self.back.0 -= 1;
+		} else if y > 0 {
+			self.back.1 -= 1;
+		}
+		Some(tile)
 	}
 }
 
+impl ExactSizeIterator for SuperCover {}
+
+impl FusedIterator for SuperCover {}
+
 #[cfg(test)]
 mod tests {
 	use super::{Line, Vector};
@@ -166,7 +283,7 @@ mod tests {
 	fn last(point: (i32, i32)) -> bool {
 		match Line::from_origin(Vector(point.0 as f32, point.1 as f32))
 			.supercover()
-			.last()
+			.next_back()
 			.map(|x| point.0 == x.0 && point.1 == x.1) {
 			Some(boolean) => boolean,
 			None => false,
@@ -196,4 +313,66 @@ mod tests {
 		assert!(last((1, 2)));
 	}
 
+	#[test]
+	fn fast_paths_match_the_full_setup() {
+		let lines = [Line(Vector(0.5, 0.5), Vector(5.25, 0.5)),
+		             Line(Vector(-0.5, 2.5), Vector(-0.5, -3.0)),
+		             Line(Vector(3.0, 1.0), Vector(-2.0, 1.0)),
+		             Line(Vector(0.1, 0.2), Vector(0.9, 0.3)),
+		             Line(Vector(-0.7, -0.2), Vector(-0.3, -0.9))];
+		for line in &lines {
+			let (v, start) = (line.1 - line.0, line.0);
+			let reference = super::SuperCover { dx: (1.0 + v.1 * v.1 / v.0 / v.0).sqrt(),
+			                                    dy: (1.0 + v.0 * v.0 / v.1 / v.1).sqrt(),
+			                                    ..line.supercover() };
+			let reference = super::SuperCover {
+				ex: if v.0 < 0.0 { start.0.fract() } else { 1.0 - start.0.fract() } * reference.dx,
+				ey: if v.1 < 0.0 { start.1.fract() } else { 1.0 - start.1.fract() } * reference.dy,
+				..reference
+			};
+			assert!(line.supercover().eq(reference), "{:?}", line);
+		}
+		assert_eq!(Line(Vector(0.1, 0.2), Vector(0.9, 0.3)).supercover().collect::<Vec<_>>(), vec![(0, 0)]);
+	}
+
+	#[test]
+	fn from_movement_rebases() {
+		let tiles = |start, movement| super::SuperCover::from_movement(start, movement).collect::<Vec<_>>();
+		assert_eq!(tiles(Vector(2.5, 3.5), Vector(2.0, 2.0)),
+		           Line(Vector(2.5, 3.5), Vector(4.5, 5.5)).supercover().collect::<Vec<_>>());
+		// Negative starts measure the distance to the tile sides from the floor
+		assert_eq!(tiles(Vector(-0.1, -0.5), Vector(1.0, 0.8)), vec![(-1, -1), (0, -1), (0, 0)]);
+		assert_eq!(tiles(Vector(-8388608.0, 0.5), Vector(-1.5, 0.0)), vec![(-8388608, 0), (-8388609, 0), (-8388610, 0)]);
+	}
+
+	#[test]
+	fn walks_from_both_ends() {
+		let line = Line(Vector(0.3, -0.8), Vector(-4.6, 3.1));
+		let forward: Vec<(i32, i32)> = line.supercover().collect();
+		let mut backward: Vec<(i32, i32)> = line.supercover().rev().collect();
+		backward.reverse();
+		assert_eq!(forward, backward);
+		let mut cover = line.supercover();
+		assert_eq!(cover.len(), forward.len());
+		assert_eq!(cover.next(), Some(forward[0]));
+		assert_eq!(cover.next_back(), forward.last().cloned());
+		assert_eq!(cover.len(), forward.len() - 2);
+		assert_eq!(cover.next(), Some(forward[1]));
+		let middle: Vec<(i32, i32)> = cover.by_ref().collect();
+		assert_eq!(middle, &forward[2..forward.len() - 1]);
+		assert_eq!((cover.len(), cover.next(), cover.next_back()), (0, None, None));
+		// The back end steps on its own rather than tracing the whole line
+		let long = Line(Vector(0.19359294, 0.7569318), Vector(9619.1, -825.0));
+		let forward: Vec<(i32, i32)> = long.supercover().collect();
+		let mut backward: Vec<(i32, i32)> = long.supercover().rev().collect();
+		backward.reverse();
+		assert_eq!(forward, backward);
+		let mut cover = Line(Vector(0.5, 0.5), Vector(3.0e6, 2.0e6)).supercover();
+		assert_eq!(cover.next_back(), Some((3000000, 2000000)));
+		assert_eq!(cover.next_back(), Some((2999999, 2000000)));
+		assert_eq!(cover.len(), 5000000 - 1);
+		let mut single = Line(Vector(0.1, 0.1), Vector(0.2, 0.2)).supercover();
+		assert_eq!((single.len(), single.next_back(), single.next()), (1, Some((0, 0)), None));
+	}
+
 }
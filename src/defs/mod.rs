@@ -1,5 +1,15 @@
+pub mod circle;
+pub mod coord;
 pub mod line;
+#[cfg(any(feature = "glam", feature = "mint"))]
+mod interop;
+pub mod rect;
+pub mod space;
 pub mod vector;
 
+pub use self::circle::Circle;
+pub use self::coord::{GridCoord, SignedCoord};
 pub use self::line::{Line, SuperCover};
+pub use self::rect::Rect;
+pub use self::space::{GridSpace, YAxis};
 pub use self::vector::Vector;
@@ -0,0 +1,170 @@
+pub use super::Vector;
+
+/// Describe an axis-aligned rectangle by its top-left corner and its size
+///
+/// ```
+/// use tile_net::{Rect, Vector};
+/// let mut rect = Rect::new(Vector(1.0, 2.0), Vector(3.0, 4.0));
+/// assert_eq!(rect.pos, Vector(1.0, 2.0));
+/// rect.set_place(Vector(5.0, 5.0));
+/// assert_eq!(rect.pos, Vector(5.0, 5.0));
+/// assert_eq!(rect.size, Vector(3.0, 4.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+	/// Top-left corner, the corner with the smallest coordinates
+	pub pos: Vector,
+	/// Width and height
+	pub size: Vector,
+}
+
+impl Rect {
+	/// Create a rectangle from its top-left corner and size
+	pub fn new(pos: Vector, size: Vector) -> Rect {
+		Rect { pos, size }
+	}
+
+	/// Create a rectangle spanning from its smallest to its largest coordinates
+	///
+	/// ```
+	/// use tile_net::{Rect, Vector};
+	/// let rect = Rect::from_min_max(Vector(1.0, 2.0), Vector(4.0, 3.0));
+	/// assert_eq!(rect, Rect::new(Vector(1.0, 2.0), Vector(3.0, 1.0)));
+	/// assert_eq!(rect.max(), Vector(4.0, 3.0));
+	/// ```
+	pub fn from_min_max(min: Vector, max: Vector) -> Rect {
+		Rect::new(min, max - min)
+	}
+
+	/// Move the rectangle so its top-left corner is at `pos`
+	pub fn set_place(&mut self, pos: Vector) {
+		self.pos = pos;
+	}
+
+	/// Get the corner with the largest coordinates
+	pub fn max(&self) -> Vector {
+		self.pos + self.size
+	}
+
+	/// Get the center
+	pub fn center(&self) -> Vector {
+		self.pos + self.size * 0.5
+	}
+
+	/// Get the corners, clockwise from the top-left one
+	pub fn corners(&self) -> [Vector; 4] {
+		let max = self.max();
+		[self.pos, Vector(max.0, self.pos.1), max, Vector(self.pos.0, max.1)]
+	}
+
+	/// Check whether a point lies inside
+	///
+	/// The smaller edges are part of the rectangle and the larger ones are
+	/// not, like tiles own their top-left edges, so touching rectangles
+	/// never share a point.
+	///
+	/// ```
+	/// use tile_net::{Rect, Vector};
+	/// let rect = Rect::new(Vector(0.0, 0.0), Vector(2.0, 1.0));
+	/// assert!(rect.contains(Vector(0.0, 0.5)));
+	/// assert!(!rect.contains(Vector(2.0, 0.5)));
+	/// ```
+	pub fn contains(&self, point: Vector) -> bool {
+		let max = self.max();
+		point.0 >= self.pos.0 && point.1 >= self.pos.1 && point.0 < max.0 && point.1 < max.1
+	}
+
+	/// Check whether two rectangles overlap
+	///
+	/// Rectangles that only touch along an edge do not overlap.
+	pub fn intersects(&self, other: &Rect) -> bool {
+		let (a, b) = (self.max(), other.max());
+		self.pos.0 < b.0 && other.pos.0 < a.0 && self.pos.1 < b.1 && other.pos.1 < a.1
+	}
+
+	/// Get the rectangle moved by an offset
+	pub fn translated(&self, offset: Vector) -> Rect {
+		Rect::new(self.pos + offset, self.size)
+	}
+
+	/// Get points along the border, relative to the top-left corner
+	///
+	/// Pass them to `Points::new` together with `pos` to trace the rectangle
+	/// with `Collable`. Points on the right and bottom edges are moved
+	/// `inset` inwards: those edges belong to the next tiles over, so a box
+	/// resting exactly on a tile edge would otherwise overlap the tile. The
+	/// points lie no further than a tile apart, so thin walls can not slip
+	/// between them, and the four corners come first.
+	///
+	/// ```
+	/// use tile_net::{Points, Rect, Vector};
+	/// let rect = Rect::new(Vector(2.0, 2.0), Vector(1.0, 1.0));
+	/// let outline = rect.collision_points(0.01);
+	/// assert_eq!(outline, vec![(0.0, 0.0), (0.99, 0.0), (0.0, 0.99), (0.99, 0.99)]);
+	/// assert_eq!(Points::new(rect.pos, &outline).last(), Some((2.99, 2.99)));
+	/// ```
+	pub fn collision_points(&self, inset: f32) -> Vec<(f32, f32)> {
+		let steps = |length: f32| {
+			let count = length.ceil().max(1.0) as usize;
+			(0..count + 1)
+				.map(move |x| if x == count {
+					length - inset
+				} else {
+					length * x as f32 / count as f32
+				})
+				.collect::<Vec<_>>()
+		};
+		let (xs, ys) = (steps(self.size.0), steps(self.size.1));
+		let (right, bottom) = (xs[xs.len() - 1], ys[ys.len() - 1]);
+		let mut points = vec![(0.0, 0.0), (right, 0.0), (0.0, bottom), (right, bottom)];
+		for &x in &xs[1..xs.len() - 1] {
+			points.push((x, 0.0));
+			points.push((x, bottom));
+		}
+		for &y in &ys[1..ys.len() - 1] {
+			points.push((0.0, y));
+			points.push((right, y));
+		}
+		points
+	}
+
+	/// Get the rectangle grown by `amount` on every side
+	///
+	/// Negative amounts shrink it, down to a size of zero around the
+	/// center.
+	pub fn expanded(&self, amount: f32) -> Rect {
+		let center = self.center();
+		let half = Vector((self.size.0 * 0.5 + amount).max(0.0), (self.size.1 * 0.5 + amount).max(0.0));
+		Rect::from_min_max(center - half, center + half)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn geometry() {
+		let rect = Rect::new(Vector(1.0, 2.0), Vector(2.0, 4.0));
+		assert_eq!(rect.center(), Vector(2.0, 4.0));
+		assert_eq!(rect.corners(), [Vector(1.0, 2.0), Vector(3.0, 2.0), Vector(3.0, 6.0), Vector(1.0, 6.0)]);
+		assert_eq!(rect.translated(Vector(-1.0, 1.0)).pos, Vector(0.0, 3.0));
+		assert_eq!(rect.expanded(0.5), Rect::new(Vector(0.5, 1.5), Vector(3.0, 5.0)));
+		assert_eq!(rect.expanded(-1.5), Rect::new(Vector(2.0, 3.5), Vector(0.0, 1.0)));
+		assert!(rect.contains(Vector(1.0, 5.9)) && !rect.contains(Vector(0.9, 3.0)));
+		assert!(rect.intersects(&Rect::new(Vector(2.9, 5.9), Vector(1.0, 1.0))));
+		assert!(!rect.intersects(&Rect::new(Vector(3.0, 2.0), Vector(1.0, 1.0))));
+		assert!(!rect.intersects(&rect.translated(Vector(0.0, -4.0))));
+	}
+
+	#[test]
+	fn collision_points_spacing() {
+		let points = Rect::new(Vector(0.0, 0.0), Vector(2.5, 1.0)).collision_points(1e-3);
+		assert_eq!(points.len(), 8);
+		assert!(points.iter().all(|x| x.0 < 2.5 && x.1 < 1.0));
+		assert_eq!(points[3], (2.499, 0.999));
+		for point in &points {
+			assert!(points.iter().any(|x| x != point && (x.0 - point.0).abs() + (x.1 - point.1).abs() <= 1.0));
+		}
+	}
+}
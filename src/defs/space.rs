@@ -0,0 +1,140 @@
+use super::{Rect, SignedCoord, Vector};
+
+/// Which way rows are counted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum YAxis {
+	/// Rows grow downwards like on a screen, with row 0 at the top
+	#[default]
+	Down,
+	/// Rows grow upwards like in physics, with row 0 at the bottom
+	Up,
+}
+
+impl YAxis {
+	/// Get the sign of y pointing down, towards the ground
+	///
+	/// Multiply gravity and ground probes by it instead of hardcoding a sign.
+	pub fn down(&self) -> f32 {
+		match *self {
+			YAxis::Down => 1.0,
+			YAxis::Up => -1.0,
+		}
+	}
+}
+
+/// Describe where the tiles of a net lie in world space
+///
+/// World space is whatever the game works in, like pixels or meters. Tile
+/// (0, 0) has its corner with the smallest coordinates at `origin`, and
+/// every tile is `tile_size` units wide and high. The default maps world
+/// units one to one onto tiles, which is what collision and tracing work in.
+///
+/// World and grid y always point the same way, so movements, supercovers
+/// and normals need no flipping in either `YAxis`. The axis decides which
+/// side counts as the ground in `TileNet::contact_flags` and
+/// `TileNet::contact_manifold`, and that views go through the rows from the
+/// top of the screen, which is the last row when y points up.
+///
+/// ```
+/// use tile_net::{GridSpace, SignedCoord, Vector};
+/// let space = GridSpace::new(Vector(-8.0, 0.0), Vector(16.0, 16.0));
+/// // Negative positions round down, not towards zero
+/// assert_eq!(space.world_to_tile(Vector(-8.5, 17.0)), SignedCoord::new(-1, 1));
+/// assert_eq!(space.tile_to_world(SignedCoord::new(-1, 1)).pos, Vector(-24.0, 16.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridSpace {
+	/// World position of the corner of tile (0, 0) with the smallest coordinates
+	pub origin: Vector,
+	/// Width and height of a tile, in world units
+	pub tile_size: Vector,
+	/// Which way rows are counted
+	pub y_axis: YAxis,
+}
+
+impl Default for GridSpace {
+	fn default() -> GridSpace {
+		GridSpace::new(Vector(0.0, 0.0), Vector(1.0, 1.0))
+	}
+}
+
+impl GridSpace {
+	/// Create a space from the corner of tile (0, 0) and the size of a tile
+	pub fn new(origin: Vector, tile_size: Vector) -> GridSpace {
+		GridSpace {
+			origin,
+			tile_size,
+			y_axis: YAxis::Down,
+		}
+	}
+
+	/// Change which way rows are counted
+	pub fn with_y_axis(mut self, y_axis: YAxis) -> GridSpace {
+		self.y_axis = y_axis;
+		self
+	}
+
+	/// Get the tile containing a world position
+	///
+	/// Positions on the edge between two tiles belong to the one with the
+	/// larger coordinates, as everywhere else in this crate.
+	pub fn world_to_tile(&self, position: Vector) -> SignedCoord {
+		let grid = self.to_grid_units(position);
+		SignedCoord::new(grid.0.floor() as i32, grid.1.floor() as i32)
+	}
+
+	/// Convert a world position to grid units, where tile (x, y) covers x..x + 1 and y..y + 1
+	pub fn to_grid_units(&self, position: Vector) -> Vector {
+		self.scale_to_grid_units(position - self.origin)
+	}
+
+	/// Convert a size or movement in world units to grid units
+	pub fn scale_to_grid_units(&self, size: Vector) -> Vector {
+		Vector(size.0 / self.tile_size.0, size.1 / self.tile_size.1)
+	}
+
+	/// Convert a rectangle in world coordinates to grid units
+	pub fn rect_to_grid_units(&self, rect: Rect) -> Rect {
+		Rect::new(self.to_grid_units(rect.pos), self.scale_to_grid_units(rect.size))
+	}
+
+	/// Get the area a tile covers in world space
+	pub fn tile_to_world(&self, tile: SignedCoord) -> Rect {
+		let corner = Vector(tile.x as f32 * self.tile_size.0, tile.y as f32 * self.tile_size.1);
+		Rect::new(self.origin + corner, self.tile_size)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trips() {
+		let space = GridSpace::new(Vector(3.0, -2.0), Vector(0.5, 2.0));
+		for &tile in &[(0, 0), (-3, 7), (5, -1)] {
+			let area = space.tile_to_world(tile.into());
+			assert_eq!(space.world_to_tile(area.pos).to_tuple(), tile);
+			assert_eq!(space.world_to_tile(area.pos + area.size * 0.99).to_tuple(), tile);
+			assert_eq!(space.world_to_tile(area.pos + area.size).to_tuple(), (tile.0 + 1, tile.1 + 1));
+		}
+		assert_eq!(GridSpace::default().world_to_tile(Vector(-0.25, 0.0)), SignedCoord::new(-1, 0));
+	}
+
+	#[test]
+	fn y_up_views_and_ground() {
+		use super::super::super::{Points, TileNet};
+		let mut net: TileNet<usize> = TileNet::from_iter(2, 0..6);
+		net.set_space(GridSpace::default().with_y_axis(YAxis::Up));
+		let rows: Vec<usize> = net.view_all().map(|x| x.2).collect();
+		assert_eq!(rows, vec![2, 2, 1, 1, 0, 0]);
+		assert_eq!(net.view_all().display_with(|x| (b'0' + *x as u8) as char).to_string(), "45\n23\n01");
+		assert_eq!(net.view_box((1, 2, 1, 3)).map(|x| *x.0).collect::<Vec<_>>(), vec![5, 3]);
+		// Standing on row 0, with row 1 above
+		let corners = [(0.5, 1.0)];
+		let flags = net.contact_flags(Points::new(Vector(0.0, 0.0), &corners), 0.01, |x| *x < 2);
+		assert!(flags.ground && !flags.ceiling);
+		let contacts = net.contact_manifold(Points::new(Vector(0.0, 0.0), &corners), 0.01, |x| *x < 2);
+		assert_eq!(contacts[0].normal, Vector(0.0, 1.0));
+	}
+}
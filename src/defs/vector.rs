@@ -1,4 +1,4 @@
-use std::ops::{Add, AddAssign, Sub};
+use std::ops::{Add, AddAssign, Mul, Sub};
 
 /// Describe a point in 2-space
 ///
@@ -21,6 +21,21 @@ impl Vector {
 		Vector(tuple.0, tuple.1)
 	}
 
+	/// Convert the vector into a float tuple
+	///
+	/// Points handed out by `Points` are tuples, positions and movements are
+	/// vectors. `From` is implemented both ways to move between the two.
+	///
+	/// ```
+	/// use tile_net::Vector;
+	/// let point: (f32, f32) = Vector(1.0, 2.0).into();
+	/// assert_eq!(point, Vector(1.0, 2.0).to_tuple());
+	/// assert_eq!(Vector::from(point), Vector(1.0, 2.0));
+	/// ```
+	pub fn to_tuple(&self) -> (f32, f32) {
+		(self.0, self.1)
+	}
+
 	/// Compute the 2-norm squared, used for checking the total speed
 	pub fn norm2sq(&self) -> f32 {
 		self.0 * self.0 + self.1 * self.1
@@ -31,6 +46,75 @@ impl Vector {
 		self.0 *= factor;
 		self.1 *= factor;
 	}
+
+	/// Compute the dot product
+	pub fn dot(&self, other: Vector) -> f32 {
+		self.0 * other.0 + self.1 * other.1
+	}
+
+	/// Rotate the vector by `angle` radians
+	///
+	/// The rotation goes from the positive x axis towards the positive y axis.
+	///
+	/// ```
+	/// use tile_net::Vector;
+	/// let rotated = Vector(1.0, 0.0).rotated(std::f32::consts::FRAC_PI_2);
+	/// assert!((rotated - Vector(0.0, 1.0)).norm2sq() < 1e-12);
+	/// ```
+	pub fn rotated(&self, angle: f32) -> Vector {
+		let (sin, cos) = angle.sin_cos();
+		Vector(self.0 * cos - self.1 * sin, self.0 * sin + self.1 * cos)
+	}
+
+	/// Reflect the vector about a surface with the given normal
+	///
+	/// The normal does not need to be normalized. A zero normal leaves the
+	/// vector unchanged. This is the response used for bouncing off a wall.
+	///
+	/// ```
+	/// use tile_net::Vector;
+	/// let bounced = Vector(1.0, 2.0).reflect(Vector(0.0, -1.0));
+	/// assert_eq!(bounced, Vector(1.0, -2.0));
+	/// ```
+	pub fn reflect(&self, normal: Vector) -> Vector {
+		let length = normal.norm2sq();
+		if length == 0.0 {
+			return *self;
+		}
+		let factor = 2.0 * self.dot(normal) / length;
+		Vector(self.0 - factor * normal.0, self.1 - factor * normal.1)
+	}
+
+	/// Project the vector onto another vector
+	///
+	/// Projecting onto a surface tangent gives the sliding response.
+	/// Projecting onto a zero vector yields a zero vector.
+	///
+	/// ```
+	/// use tile_net::Vector;
+	/// let slide = Vector(3.0, 4.0).project_onto(Vector(2.0, 0.0));
+	/// assert_eq!(slide, Vector(3.0, 0.0));
+	/// ```
+	pub fn project_onto(&self, other: Vector) -> Vector {
+		let length = other.norm2sq();
+		if length == 0.0 {
+			return Vector(0.0, 0.0);
+		}
+		let factor = self.dot(other) / length;
+		Vector(other.0 * factor, other.1 * factor)
+	}
+}
+
+impl From<(f32, f32)> for Vector {
+	fn from(tuple: (f32, f32)) -> Vector {
+		Vector::from_tuple(tuple)
+	}
+}
+
+impl From<Vector> for (f32, f32) {
+	fn from(vector: Vector) -> (f32, f32) {
+		vector.to_tuple()
+	}
 }
 
 impl Add for Vector {
@@ -48,6 +132,14 @@ impl AddAssign for Vector {
 	}
 }
 
+impl Mul<f32> for Vector {
+	type Output = Vector;
+
+	fn mul(self, factor: f32) -> Vector {
+		Vector(self.0 * factor, self.1 * factor)
+	}
+}
+
 impl Sub for Vector {
 	type Output = Vector;
 
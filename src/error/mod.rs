@@ -0,0 +1,104 @@
+use std::error::Error;
+use std::fmt;
+
+/// Errors reported by the `try_` family of `TileNet` operations and checked constructors
+///
+/// Every lossy operation in this crate (ignoring out-of-bounds writes,
+/// clamping negative coordinates, padding incomplete rows) has a `try_`
+/// counterpart that reports the problem as one of these variants instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TileNetError {
+	/// A coordinate lies outside the net
+	OutOfBounds {
+		/// The offending coordinate as (col, row)
+		coord: (usize, usize),
+		/// Size of the net as (cols, rows)
+		size: (usize, usize),
+	},
+	/// A box has its start corner past its stop corner on either axis
+	InvertedBox {
+		/// The start corner as (col, row)
+		start: (usize, usize),
+		/// The stop corner as (col, row)
+		stop: (usize, usize),
+	},
+	/// A position was negative, infinite or NaN where a grid index was expected
+	InvalidPosition {
+		/// The offending position
+		position: (f32, f32),
+	},
+	/// A net was requested with zero columns
+	ZeroColumns,
+	/// Tile data does not fill a whole number of rows
+	IncompleteRow {
+		/// Total number of tiles provided
+		len: usize,
+		/// The requested column count
+		cols: usize,
+	},
+	/// A row does not have as many tiles as the first row
+	RaggedRow {
+		/// Index of the offending row
+		row: usize,
+		/// Number of tiles in that row
+		len: usize,
+		/// Number of tiles in the first row
+		cols: usize,
+	},
+	/// A `PalettedTileNet` has no room for another distinct tile
+	PaletteFull {
+		/// Highest number of distinct tiles
+		capacity: usize,
+	},
+	/// A buffer can not hold all the tiles that are to be written to it
+	BufferTooSmall {
+		/// Length of the buffer
+		len: usize,
+		/// Number of tiles to be written
+		needed: usize,
+	},
+	/// A sample to learn from holds no tiles
+	EmptySample,
+}
+
+impl fmt::Display for TileNetError {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			TileNetError::OutOfBounds { coord, size } => {
+				write!(formatter,
+				       "coordinate ({}, {}) is outside a net of size ({}, {})",
+				       coord.0,
+				       coord.1,
+				       size.0,
+				       size.1)
+			}
+			TileNetError::InvertedBox { start, stop } => {
+				write!(formatter,
+				       "box from ({}, {}) to ({}, {}) is inverted",
+				       start.0,
+				       start.1,
+				       stop.0,
+				       stop.1)
+			}
+			TileNetError::InvalidPosition { position } => {
+				write!(formatter, "position ({}, {}) is not a valid grid position", position.0, position.1)
+			}
+			TileNetError::ZeroColumns => write!(formatter, "a net must have at least one column"),
+			TileNetError::IncompleteRow { len, cols } => {
+				write!(formatter, "{} tiles do not fill whole rows of {} columns", len, cols)
+			}
+			TileNetError::RaggedRow { row, len, cols } => {
+				write!(formatter, "row {} has {} tiles instead of {}", row, len, cols)
+			}
+			TileNetError::PaletteFull { capacity } => {
+				write!(formatter, "the palette already holds {} distinct tiles", capacity)
+			}
+			TileNetError::BufferTooSmall { len, needed } => {
+				write!(formatter, "a buffer of {} elements can not hold {} tiles", len, needed)
+			}
+			TileNetError::EmptySample => write!(formatter, "a sample must hold at least one tile"),
+		}
+	}
+}
+
+impl Error for TileNetError {}
@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A change in the tiles a body touches, see `CollisionTracker`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CollisionEvent<K> {
+	/// The body started touching the tile this frame
+	Enter(K, (i32, i32)),
+	/// The body touched the tile last frame and still does
+	Stay(K, (i32, i32)),
+	/// The body touched the tile last frame but no longer does
+	Exit(K, (i32, i32)),
+}
+
+/// Turns the tiles each body touches per frame into enter, stay and exit events
+///
+/// Bodies are identified by any ordered key, such as an index or entity id.
+/// Feed the coordinates of the tiles touched by each body with `update`,
+/// usually from `TileSet::with_coords`, then call `finish` once per frame.
+/// Bodies that are not updated in a frame touch nothing, so they exit every
+/// tile. Events are sorted by body, then tile, so they come out the same
+/// on every run.
+///
+/// ```
+/// use tile_net::{CollisionEvent, CollisionTracker};
+/// let mut tracker = CollisionTracker::new();
+/// tracker.update("player", vec![(1, 1), (2, 1)]);
+/// assert_eq!(tracker.finish(), vec![CollisionEvent::Enter("player", (1, 1)),
+///                                   CollisionEvent::Enter("player", (2, 1))]);
+/// tracker.update("player", vec![(2, 1), (3, 1)]);
+/// assert_eq!(tracker.finish(), vec![CollisionEvent::Exit("player", (1, 1)),
+///                                   CollisionEvent::Stay("player", (2, 1)),
+///                                   CollisionEvent::Enter("player", (3, 1))]);
+/// assert_eq!(tracker.finish().len(), 2);
+/// assert!(tracker.finish().is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct CollisionTracker<K> {
+	previous: BTreeMap<K, BTreeSet<(i32, i32)>>,
+	current: BTreeMap<K, BTreeSet<(i32, i32)>>,
+}
+
+impl<K> Default for CollisionTracker<K>
+	where K: Clone + Ord
+{
+	fn default() -> CollisionTracker<K> {
+		CollisionTracker::new()
+	}
+}
+
+impl<K> CollisionTracker<K>
+	where K: Clone + Ord
+{
+	/// Create a tracker where no body touches anything
+	pub fn new() -> CollisionTracker<K> {
+		CollisionTracker {
+			previous: BTreeMap::new(),
+			current: BTreeMap::new(),
+		}
+	}
+
+	/// Record tiles touched by a body this frame
+	///
+	/// Calling this several times for the same body in a frame adds up the
+	/// tiles, and touching the same tile twice counts once.
+	pub fn update<I>(&mut self, body: K, tiles: I)
+		where I: IntoIterator<Item = (i32, i32)>
+	{
+		self.current.entry(body).or_default().extend(tiles);
+	}
+
+	/// End the frame, returning the events since the last `finish`
+	pub fn finish(&mut self) -> Vec<CollisionEvent<K>> {
+		let current = std::mem::take(&mut self.current);
+		let previous = std::mem::replace(&mut self.previous, current);
+		let empty = BTreeSet::new();
+		let mut bodies: BTreeSet<&K> = previous.keys().collect();
+		bodies.extend(self.previous.keys());
+		let mut events = vec![];
+		for body in bodies {
+			let before = previous.get(body).unwrap_or(&empty);
+			let now = self.previous.get(body).unwrap_or(&empty);
+			for &tile in before.union(now) {
+				events.push(match (before.contains(&tile), now.contains(&tile)) {
+					(true, true) => CollisionEvent::Stay(body.clone(), tile),
+					(false, _) => CollisionEvent::Enter(body.clone(), tile),
+					(_, false) => CollisionEvent::Exit(body.clone(), tile),
+				});
+			}
+		}
+		self.previous.retain(|_, tiles| !tiles.is_empty());
+		events
+	}
+
+	/// Check whether a body touched a tile in the last finished frame
+	pub fn is_touching(&self, body: &K, tile: (i32, i32)) -> bool {
+		self.previous.get(body).is_some_and(|x| x.contains(&tile))
+	}
+
+	/// Forget every body, without emitting exit events
+	pub fn clear(&mut self) {
+		self.previous.clear();
+		self.current.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bodies_are_tracked_separately() {
+		let mut tracker = CollisionTracker::new();
+		tracker.update(1, vec![(0, 0)]);
+		tracker.update(0, vec![(0, 0)]);
+		tracker.update(1, vec![(0, 0), (5, 5)]);
+		assert_eq!(tracker.finish(),
+		           vec![CollisionEvent::Enter(0, (0, 0)),
+		                CollisionEvent::Enter(1, (0, 0)),
+		                CollisionEvent::Enter(1, (5, 5))]);
+		assert!(tracker.is_touching(&1, (5, 5)));
+		tracker.update(1, vec![(5, 5)]);
+		assert_eq!(tracker.finish(),
+		           vec![CollisionEvent::Exit(0, (0, 0)),
+		                CollisionEvent::Exit(1, (0, 0)),
+		                CollisionEvent::Stay(1, (5, 5))]);
+		assert!(!tracker.is_touching(&0, (0, 0)));
+		tracker.clear();
+		assert!(tracker.finish().is_empty());
+	}
+}
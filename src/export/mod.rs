@@ -0,0 +1,292 @@
+//! Streaming export of maps, one row at a time
+//!
+//! The stream starts with a short header holding the column count, followed
+//! by one length-prefixed record per row. Rows are encoded and written as
+//! they are handed over, so a generated or chunked world can be exported
+//! without ever holding more than a single row in memory.
+//!
+//! With the `flate2` feature the stream can be deflate-compressed on the fly.
+//!
+//! Edits made after the export can be sent as `Patch` lists, which have a
+//! compact encoding of their own: see `Patch::encode`.
+#[cfg(feature = "flate2")]
+use flate2::Compression;
+#[cfg(feature = "flate2")]
+use flate2::write::DeflateEncoder;
+use std::io::{self, Read, Write};
+use super::TileNet;
+
+mod patch;
+
+const MAGIC: [u8; 4] = *b"TNRW";
+const VERSION: u8 = 1;
+
+// Error for a stream or message that can not be decoded
+pub(crate) fn invalid(message: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Append a number as a LEB128 varint: 7 bits per byte, low bits first
+pub(crate) fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+	while value >= 0x80 {
+		out.push(value as u8 | 0x80);
+		value >>= 7;
+	}
+	out.push(value as u8);
+}
+
+// Take a varint written by `write_varint` off the front of the input
+pub(crate) fn read_varint(input: &mut &[u8]) -> io::Result<u64> {
+	let mut value = 0u64;
+	for shift in (0..64).step_by(7) {
+		let (&byte, rest) = input.split_first().ok_or_else(|| invalid("message ends early"))?;
+		*input = rest;
+		value |= u64::from(byte & 0x7f) << shift;
+		if byte & 0x80 == 0 {
+			return Ok(value);
+		}
+	}
+	Err(invalid("varint is too long"))
+}
+
+/// Writes rows of tiles to an `io::Write`
+///
+/// ```
+/// use tile_net::{RowWriter, TileNet};
+/// let mut writer = RowWriter::new(Vec::new(), 3).unwrap();
+/// // Rows can come from anywhere, here they are generated on the fly
+/// for row in 0..4u8 {
+///   writer.write_row(&[row, row, row], |x, out| out.push(*x)).unwrap();
+/// }
+/// let bytes = writer.finish().unwrap();
+///
+/// let net: TileNet<u8> = TileNet::import(&bytes[..], |x| {
+///   let mut byte = [0];
+///   std::io::Read::read_exact(x, &mut byte).map(|_| byte[0])
+/// }).unwrap();
+/// assert_eq!(net.get_size(), (3, 4));
+/// assert_eq!(net.get((1, 2)), Some(&2));
+/// ```
+pub struct RowWriter<W>
+	where W: Write
+{
+	writer: W,
+	cols: usize,
+	row: Vec<u8>,
+	rows: u64,
+}
+
+impl<W> RowWriter<W>
+	where W: Write
+{
+	/// Start a stream of rows with `cols` tiles each
+	///
+	/// The header is written immediately.
+	pub fn new(mut writer: W, cols: usize) -> io::Result<RowWriter<W>> {
+		let mut header = [0u8; 16];
+		header[0..4].copy_from_slice(&MAGIC);
+		header[4] = VERSION;
+		header[8..16].copy_from_slice(&(cols as u64).to_le_bytes());
+		writer.write_all(&header)?;
+		Ok(RowWriter {
+			writer,
+			cols,
+			row: vec![],
+			rows: 0,
+		})
+	}
+
+	/// Encode and write a single row
+	///
+	/// `encode` appends the bytes of one tile to the given buffer. The row must
+	/// hold exactly as many tiles as the stream has columns.
+	pub fn write_row<T, F>(&mut self, row: &[T], mut encode: F) -> io::Result<()>
+		where F: FnMut(&T, &mut Vec<u8>)
+	{
+		if row.len() != self.cols {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "row length differs from column count"));
+		}
+		self.row.clear();
+		for tile in row {
+			encode(tile, &mut self.row);
+		}
+		self.writer.write_all(&(self.row.len() as u32).to_le_bytes())?;
+		self.writer.write_all(&self.row)?;
+		self.rows += 1;
+		Ok(())
+	}
+
+	/// Get the amount of rows written so far
+	pub fn rows_written(&self) -> u64 {
+		self.rows
+	}
+
+	/// Flush the stream and give back the underlying writer
+	pub fn finish(mut self) -> io::Result<W> {
+		self.writer.flush()?;
+		Ok(self.writer)
+	}
+}
+
+#[cfg(feature = "flate2")]
+impl<W> RowWriter<DeflateEncoder<W>>
+	where W: Write
+{
+	/// Start a deflate-compressed stream of rows
+	///
+	/// `level` ranges from 0 (no compression) to 9 (best compression).
+	/// Wrap the reading side in a `flate2::read::DeflateDecoder`.
+	pub fn compressed(writer: W, cols: usize, level: u32) -> io::Result<RowWriter<DeflateEncoder<W>>> {
+		RowWriter::new(DeflateEncoder::new(writer, Compression::new(level)), cols)
+	}
+
+	/// Complete the compressed stream and give back the underlying writer
+	pub fn finish_compressed(self) -> io::Result<W> {
+		self.writer.finish()
+	}
+}
+
+/// Reads rows of tiles written by a `RowWriter`
+pub struct RowReader<R>
+	where R: Read
+{
+	reader: R,
+	cols: usize,
+	row: Vec<u8>,
+}
+
+impl<R> RowReader<R>
+	where R: Read
+{
+	/// Start reading a stream of rows, validating the header
+	pub fn new(mut reader: R) -> io::Result<RowReader<R>> {
+		let mut header = [0u8; 16];
+		reader.read_exact(&mut header)?;
+		if header[0..4] != MAGIC {
+			return Err(invalid("not a tile_net row stream"));
+		}
+		if header[4] != VERSION {
+			return Err(invalid("unsupported row stream version"));
+		}
+		let mut cols = [0u8; 8];
+		cols.copy_from_slice(&header[8..16]);
+		let cols = u64::from_le_bytes(cols) as usize;
+		if cols == 0 {
+			return Err(invalid("row stream has zero columns"));
+		}
+		Ok(RowReader {
+			reader,
+			cols,
+			row: vec![],
+		})
+	}
+
+	/// Get the column count of the stream
+	pub fn col_count(&self) -> usize {
+		self.cols
+	}
+
+	/// Read and decode the next row, or `None` at the end of the stream
+	///
+	/// `decode` is called once per tile with a cursor into the row's bytes.
+	pub fn read_row<T, F>(&mut self, mut decode: F) -> io::Result<Option<Vec<T>>>
+		where F: FnMut(&mut &[u8]) -> io::Result<T>
+	{
+		let mut length = [0u8; 4];
+		match self.reader.read(&mut length[..1])? {
+			0 => return Ok(None),
+			_ => self.reader.read_exact(&mut length[1..])?,
+		}
+		self.row.resize(u32::from_le_bytes(length) as usize, 0);
+		self.reader.read_exact(&mut self.row)?;
+		let mut cursor = &self.row[..];
+		let mut tiles = Vec::with_capacity(self.cols);
+		for _ in 0..self.cols {
+			tiles.push(decode(&mut cursor)?);
+		}
+		if !cursor.is_empty() {
+			return Err(invalid("row has trailing bytes"));
+		}
+		Ok(Some(tiles))
+	}
+}
+
+impl<T> TileNet<T> {
+	/// Export the net row by row
+	///
+	/// See `RowWriter` for the stream layout.
+	pub fn export<W, F>(&self, writer: W, mut encode: F) -> io::Result<W>
+		where W: Write,
+		      F: FnMut(&T, &mut Vec<u8>)
+	{
+		let mut writer = RowWriter::new(writer, self.col_count())?;
+		for row in self.get_raw().chunks(self.col_count()) {
+			writer.write_row(row, &mut encode)?;
+		}
+		writer.finish()
+	}
+
+	/// Import a net from a row stream
+	pub fn import<R, F>(reader: R, mut decode: F) -> io::Result<TileNet<T>>
+		where R: Read,
+		      F: FnMut(&mut &[u8]) -> io::Result<T>
+	{
+		let mut reader = RowReader::new(reader)?;
+		let mut map = vec![];
+		while let Some(row) = reader.read_row(&mut decode)? {
+			map.extend(row);
+		}
+		Ok(TileNet::from_raw(reader.col_count(), map))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn encode(tile: &u16, out: &mut Vec<u8>) {
+		out.extend_from_slice(&tile.to_le_bytes());
+	}
+
+	fn decode(input: &mut &[u8]) -> io::Result<u16> {
+		let mut bytes = [0u8; 2];
+		input.read_exact(&mut bytes)?;
+		Ok(u16::from_le_bytes(bytes))
+	}
+
+	#[test]
+	fn roundtrip() {
+		let net: TileNet<u16> = TileNet::from_iter(5, 0..35);
+		let bytes = net.export(vec![], encode).unwrap();
+		assert_eq!(bytes.len(), 16 + 7 * (4 + 10));
+		let loaded = TileNet::import(&bytes[..], decode).unwrap();
+		assert_eq!(loaded.get_size(), (5, 7));
+		assert_eq!(loaded.get_raw(), net.get_raw());
+	}
+
+	#[test]
+	fn rejects_bad_rows() {
+		let mut writer = RowWriter::new(vec![], 2).unwrap();
+		assert!(writer.write_row(&[1u16], encode).is_err());
+		writer.write_row(&[1u16, 2], encode).unwrap();
+		assert_eq!(writer.rows_written(), 1);
+		let bytes = writer.finish().unwrap();
+		assert!(TileNet::import(&bytes[..bytes.len() - 1], decode).is_err());
+		assert!(TileNet::import(&bytes[1..], decode).is_err());
+	}
+
+	#[cfg(feature = "flate2")]
+	#[test]
+	fn compressed() {
+		use flate2::read::DeflateDecoder;
+		let net: TileNet<u16> = TileNet::new(100, 100);
+		let mut writer = RowWriter::compressed(vec![], 100, 6).unwrap();
+		for row in net.get_raw().chunks(100) {
+			writer.write_row(row, encode).unwrap();
+		}
+		let bytes = writer.finish_compressed().unwrap();
+		assert!(bytes.len() < 100 * 100 * 2 / 10);
+		let loaded = TileNet::import(DeflateDecoder::new(&bytes[..]), decode).unwrap();
+		assert_eq!(loaded.get_raw(), net.get_raw());
+	}
+}
@@ -0,0 +1,164 @@
+//! Binary encoding of `Patch` lists
+//!
+//! A message starts with a magic and version byte, followed by the patch
+//! count. Each patch holds its start position and its tiles as runs of equal
+//! tiles, where every run is a length and a single encoded tile. All numbers
+//! are LEB128 varints, so small edits stay a handful of bytes.
+use std::io;
+use super::{invalid, read_varint, write_varint};
+use super::super::Patch;
+
+const MAGIC: [u8; 4] = *b"TNPT";
+const VERSION: u8 = 1;
+
+// Upper bound on the tiles in a decoded message, so a corrupt length can
+// not make the decoder allocate without bounds.
+const MAX_TILES: u64 = 1 << 24;
+
+impl<T> Patch<T>
+	where T: PartialEq
+{
+	/// Encode a list of patches into a message
+	///
+	/// `encode` appends the bytes of one tile to the given buffer, like in
+	/// `TileNet::export`. It is called once per run of equal tiles.
+	///
+	/// ```
+	/// use tile_net::{Patch, TileNet};
+	/// let mut net: TileNet<u8> = TileNet::new(64, 64);
+	/// let before = net.snapshot();
+	/// net.set_circle_filled(&1, (32, 32), 4);
+	/// let bytes = Patch::encode(&net.diff(&before), |x, out| out.push(*x));
+	/// assert!(bytes.len() < 64);
+	///
+	/// let patches = Patch::decode(&bytes, |x| {
+	///     let (&byte, rest) = x.split_first().ok_or(std::io::ErrorKind::UnexpectedEof)?;
+	///     *x = rest;
+	///     Ok(byte)
+	/// }).unwrap();
+	/// let mut copy: TileNet<u8> = TileNet::new(64, 64);
+	/// copy.apply_patches(&patches);
+	/// assert_eq!(copy.get_raw(), net.get_raw());
+	/// ```
+	pub fn encode<F>(patches: &[Patch<T>], mut encode: F) -> Vec<u8>
+		where F: FnMut(&T, &mut Vec<u8>)
+	{
+		let mut out = MAGIC.to_vec();
+		out.push(VERSION);
+		write_varint(patches.len() as u64, &mut out);
+		for patch in patches {
+			write_varint(patch.start.0 as u64, &mut out);
+			write_varint(patch.start.1 as u64, &mut out);
+			let mut runs = vec![];
+			for tile in &patch.tiles {
+				match runs.last_mut() {
+					Some(&mut (last, ref mut count)) if last == tile => *count += 1,
+					_ => runs.push((tile, 1u64)),
+				}
+			}
+			write_varint(runs.len() as u64, &mut out);
+			for (tile, count) in runs {
+				write_varint(count, &mut out);
+				encode(tile, &mut out);
+			}
+		}
+		out
+	}
+}
+
+impl<T> Patch<T>
+	where T: Clone
+{
+	/// Decode a message made by `encode`
+	///
+	/// `decode` reads one tile from a cursor into the message. Messages with
+	/// another magic or version, trailing bytes or more than 2^24 tiles are
+	/// rejected.
+	pub fn decode<F>(bytes: &[u8], mut decode: F) -> io::Result<Vec<Patch<T>>>
+		where F: FnMut(&mut &[u8]) -> io::Result<T>
+	{
+		if bytes.len() < 5 || bytes[0..4] != MAGIC {
+			return Err(invalid("not a tile_net patch message"));
+		}
+		if bytes[4] != VERSION {
+			return Err(invalid("unsupported patch message version"));
+		}
+		let mut input = &bytes[5..];
+		let count = read_varint(&mut input)?;
+		let mut total = 0u64;
+		let mut patches = vec![];
+		for _ in 0..count {
+			let x = read_varint(&mut input)? as usize;
+			let y = read_varint(&mut input)? as usize;
+			let runs = read_varint(&mut input)?;
+			let mut tiles = vec![];
+			for _ in 0..runs {
+				let length = read_varint(&mut input)?;
+				total = match total.checked_add(length) {
+					Some(total) if total <= MAX_TILES => total,
+					_ => return Err(invalid("patch message holds too many tiles")),
+				};
+				let tile = decode(&mut input)?;
+				tiles.extend((0..length).map(|_| tile.clone()));
+			}
+			patches.push(Patch {
+				start: (x, y),
+				tiles,
+			});
+		}
+		if !input.is_empty() {
+			return Err(invalid("patch message has trailing bytes"));
+		}
+		Ok(patches)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Read;
+
+	fn encode(tile: &u16, out: &mut Vec<u8>) {
+		out.extend_from_slice(&tile.to_le_bytes());
+	}
+
+	fn decode(input: &mut &[u8]) -> io::Result<u16> {
+		let mut bytes = [0u8; 2];
+		input.read_exact(&mut bytes)?;
+		Ok(u16::from_le_bytes(bytes))
+	}
+
+	#[test]
+	fn roundtrip() {
+		let patches = vec![Patch { start: (300, 1), tiles: vec![7, 7, 7, 1, 7] },
+		                   Patch { start: (0, 0), tiles: vec![] }];
+		let bytes = Patch::encode(&patches, encode);
+		assert_eq!(bytes.len(), 5 + 1 + (2 + 1 + 1 + 3 * 3) + 3);
+		assert_eq!(Patch::decode(&bytes, decode).unwrap(), patches);
+	}
+
+	#[test]
+	fn rejects_bad_messages() {
+		let bytes = Patch::encode(&[Patch { start: (1, 2), tiles: vec![3u16] }], encode);
+		assert!(Patch::decode(&bytes[..bytes.len() - 1], decode).is_err());
+		assert!(Patch::decode(&bytes[1..], decode).is_err());
+		let mut longer = bytes.clone();
+		longer.push(0);
+		assert!(Patch::decode(&longer, decode).is_err());
+		let mut huge = bytes[..5].to_vec();
+		huge.extend_from_slice(&[1, 0, 0, 1, 0x80, 0x80, 0x80, 0x10, 0, 0]);
+		assert!(Patch::decode(&huge, decode).is_err());
+	}
+
+	#[test]
+	fn rejects_overflowing_lengths() {
+		let mut bytes = MAGIC.to_vec();
+		bytes.push(VERSION);
+		// One patch at (0, 0) with two runs, the second one u64::MAX tiles long
+		bytes.extend_from_slice(&[1, 0, 0, 2, 1, 5, 0]);
+		write_varint(u64::MAX, &mut bytes);
+		bytes.extend_from_slice(&[5, 0]);
+		let error = Patch::decode(&bytes, decode).unwrap_err();
+		assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+	}
+}
@@ -0,0 +1,265 @@
+//! Deterministic fixed-point geometry
+//!
+//! Floating point results may differ between platforms and compilers, which
+//! breaks lockstep simulations. The types here mirror `Vector`, `Line` and
+//! `SuperCover` with 16.16 fixed-point numbers and integer-only traversal,
+//! so every machine visits the same tiles for the same input. Pick one set
+//! through a type alias, and the rest of the code stays the same:
+//!
+//! ```
+//! use tile_net::{Fixed, FixedLine as Line, FixedVector as Vector, TileNet};
+//! let net: TileNet<usize> = TileNet::sample();
+//! let line = Line(Vector::from_f32(0.5, 0.5), Vector(Fixed::from_int(3), Fixed::from_f32(1.5)));
+//! let tiles: Vec<(i32, i32)> = line.supercover().collect();
+//! assert_eq!(tiles, vec![(0, 0), (1, 0), (1, 1), (2, 1), (3, 1)]);
+//! assert_eq!(net.collide_set(line.supercover()).count(), 5);
+//! ```
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Number of fractional bits in a `Fixed`
+pub const FRAC_BITS: u32 = 16;
+
+const ONE: i32 = 1 << FRAC_BITS;
+
+/// A signed 16.16 fixed-point number
+///
+/// Multiplication rounds towards negative infinity, division towards zero.
+///
+/// ```
+/// use tile_net::Fixed;
+/// let half = Fixed::from_f32(0.5);
+/// assert_eq!(half * Fixed::from_int(3), Fixed::from_f32(1.5));
+/// assert_eq!(Fixed::from_f32(-1.25).floor(), -2);
+/// assert_eq!(Fixed::from_f32(-1.25).fract(), Fixed::from_f32(0.75));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(pub i32);
+
+impl Fixed {
+	/// Convert an integer
+	pub fn from_int(value: i32) -> Fixed {
+		Fixed(value << FRAC_BITS)
+	}
+
+	/// Convert a float, rounding to the nearest representable value
+	///
+	/// Converting is deterministic; do it once when loading data rather than
+	/// every frame.
+	pub fn from_f32(value: f32) -> Fixed {
+		Fixed((value * ONE as f32).round() as i32)
+	}
+
+	/// Convert to a float, for rendering
+	pub fn to_f32(self) -> f32 {
+		self.0 as f32 / ONE as f32
+	}
+
+	/// Get the largest integer less than or equal to the number
+	pub fn floor(self) -> i32 {
+		self.0 >> FRAC_BITS
+	}
+
+	/// Get the distance from `floor`, always between 0 and 1
+	pub fn fract(self) -> Fixed {
+		Fixed(self.0 & (ONE - 1))
+	}
+
+	/// Get the absolute value
+	pub fn abs(self) -> Fixed {
+		Fixed(self.0.abs())
+	}
+}
+
+impl Add for Fixed {
+	type Output = Fixed;
+	fn add(self, other: Fixed) -> Fixed {
+		Fixed(self.0 + other.0)
+	}
+}
+
+impl Sub for Fixed {
+	type Output = Fixed;
+	fn sub(self, other: Fixed) -> Fixed {
+		Fixed(self.0 - other.0)
+	}
+}
+
+impl Mul for Fixed {
+	type Output = Fixed;
+	fn mul(self, other: Fixed) -> Fixed {
+		Fixed(((self.0 as i64 * other.0 as i64) >> FRAC_BITS) as i32)
+	}
+}
+
+impl Div for Fixed {
+	type Output = Fixed;
+	// The dividend is scaled up to keep the fractional bits
+	#[allow(clippy::suspicious_arithmetic_impl)]
+	fn div(self, other: Fixed) -> Fixed {
+		Fixed((((self.0 as i64) << FRAC_BITS) / other.0 as i64) as i32)
+	}
+}
+
+impl Neg for Fixed {
+	type Output = Fixed;
+	fn neg(self) -> Fixed {
+		Fixed(-self.0)
+	}
+}
+
+/// Fixed-point counterpart of `Vector`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FixedVector(pub Fixed, pub Fixed);
+
+impl FixedVector {
+	/// Convert a pair of floats, see `Fixed::from_f32`
+	pub fn from_f32(x: f32, y: f32) -> FixedVector {
+		FixedVector(Fixed::from_f32(x), Fixed::from_f32(y))
+	}
+
+	/// Convert to a pair of floats, for rendering
+	pub fn to_tuple(&self) -> (f32, f32) {
+		(self.0.to_f32(), self.1.to_f32())
+	}
+}
+
+impl Add for FixedVector {
+	type Output = FixedVector;
+	fn add(self, other: FixedVector) -> FixedVector {
+		FixedVector(self.0 + other.0, self.1 + other.1)
+	}
+}
+
+impl Sub for FixedVector {
+	type Output = FixedVector;
+	fn sub(self, other: FixedVector) -> FixedVector {
+		FixedVector(self.0 - other.0, self.1 - other.1)
+	}
+}
+
+impl Mul<Fixed> for FixedVector {
+	type Output = FixedVector;
+	fn mul(self, factor: Fixed) -> FixedVector {
+		FixedVector(self.0 * factor, self.1 * factor)
+	}
+}
+
+/// Fixed-point counterpart of `Line`, from start to end
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FixedLine(pub FixedVector, pub FixedVector);
+
+impl FixedLine {
+	/// Create a supercover iterator, see `Line::supercover`
+	///
+	/// Ties between crossing a vertical and a horizontal tile edge go the
+	/// same way as in `Line::supercover`.
+	pub fn supercover(&self) -> FixedSuperCover {
+		let (start, stop) = (self.0, self.1);
+		let delta = stop - start;
+		let distance = |position: Fixed, velocity: Fixed| {
+			if velocity.0 < 0 {
+				position.fract().0 as i64
+			} else {
+				(ONE - position.fract().0) as i64
+			}
+		};
+		let len = (stop.0.floor() as i64 - start.0.floor() as i64).unsigned_abs() +
+		          (stop.1.floor() as i64 - start.1.floor() as i64).unsigned_abs();
+		FixedSuperCover {
+			remaining: len as usize,
+			done: false,
+			ex: distance(start.0, delta.0),
+			ey: distance(start.1, delta.1),
+			vx: delta.0.abs().0 as i64,
+			vy: delta.1.abs().0 as i64,
+			sx: if delta.0.0 < 0 { -1 } else { 1 },
+			sy: if delta.1.0 < 0 { -1 } else { 1 },
+			ix: start.0.floor(),
+			iy: start.1.floor(),
+			dest: (stop.0.floor(), stop.1.floor()),
+		}
+	}
+}
+
+/// Integer-only supercover traversal, see `FixedLine::supercover`
+///
+/// Instead of the floating-point times of `SuperCover`, the distances to the
+/// next edges are compared by cross-multiplying with the speeds.
+#[derive(Clone, Debug)]
+pub struct FixedSuperCover {
+	remaining: usize,
+	done: bool,
+	// Distance to the next vertical and horizontal edge, in fixed-point units
+	ex: i64,
+	ey: i64,
+	vx: i64,
+	vy: i64,
+	sx: i32,
+	sy: i32,
+	ix: i32,
+	iy: i32,
+	dest: (i32, i32),
+}
+
+impl Iterator for FixedSuperCover {
+	type Item = (i32, i32);
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.remaining > 0 {
+			self.remaining -= 1;
+			let current = (self.ix, self.iy);
+			// Top bias, like `SuperCover`: x goes first only if strictly earlier
+			if (self.ex as i128) * (self.vy as i128) < (self.ey as i128) * (self.vx as i128) {
+				self.ex += ONE as i64;
+				self.ix += self.sx;
+			} else {
+				self.ey += ONE as i64;
+				self.iy += self.sy;
+			}
+			Some(current)
+		} else if !self.done {
+			self.done = true;
+			Some(self.dest)
+		} else {
+			None
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let left = self.remaining + if self.done { 0 } else { 1 };
+		(left, Some(left))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{Line, Vector};
+
+	#[test]
+	fn arithmetic() {
+		let a = Fixed::from_f32(2.5);
+		let b = Fixed::from_int(-2);
+		assert_eq!((a + b).to_f32(), 0.5);
+		assert_eq!((a - b).to_f32(), 4.5);
+		assert_eq!((a * b).to_f32(), -5.0);
+		assert_eq!((a / b).to_f32(), -1.25);
+		assert_eq!((-a).floor(), -3);
+		assert_eq!((FixedVector(a, b) * b).to_tuple(), (-5.0, 4.0));
+	}
+
+	#[test]
+	fn matches_float_supercover() {
+		let lines = [((0.5, 0.5), (7.25, 3.5)),
+		             ((3.5, 2.5), (-4.25, -1.75)),
+		             ((0.5, 0.5), (0.5, 6.5)),
+		             ((2.75, 1.5), (-3.5, 1.5)),
+		             ((1.5, 1.5), (4.5, 4.5)),
+		             ((0.25, 0.75), (0.75, 0.25))];
+		for &(start, stop) in &lines {
+			let float = Line(Vector::from_tuple(start), Vector::from_tuple(stop)).supercover();
+			let fixed = FixedLine(FixedVector::from_f32(start.0, start.1), FixedVector::from_f32(stop.0, stop.1));
+			assert_eq!(fixed.supercover().size_hint(), (float.len(), Some(float.len())));
+			assert_eq!(float.collect::<Vec<_>>(), fixed.supercover().collect::<Vec<_>>());
+		}
+	}
+}
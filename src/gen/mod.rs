@@ -0,0 +1,315 @@
+//! Procedural generation of nets
+//!
+//! Generators take an `Rng`, so the same seed always gives the same map, on
+//! every platform. Maps from noise functions, as provided by noise crates,
+//! are built with `from_noise`:
+//!
+//! ```
+//! use tile_net::gen;
+//! // Any function of the position works; real maps use Perlin or simplex noise
+//! let wave = |x: f32, y: f32| (x * 0.4).sin() + (y * 0.3).cos();
+//! let map = gen::from_noise((40, 30), gen::island((40, 30), wave), gen::threshold(0.0, '~', '.'));
+//! assert_eq!(map.get((0, 0)), Some(&'~'));
+//! assert!(map.get_raw().contains(&'.'));
+//! ```
+use super::TileNet;
+
+pub use self::wfc::Model;
+
+pub mod wfc;
+
+/// Create a net of (cols, rows) tiles by classifying a noise value per tile
+///
+/// `sampler` is called with the column and row of every tile, in row-major
+/// order, so `sampler(x, y)` lands at `get((x, y))`. Scale the coordinates
+/// inside the sampler to change the feature size.
+///
+/// # Panics #
+/// Panics if the column count is zero.
+pub fn from_noise<T, S, C>(size: (usize, usize), sampler: S, classify: C) -> TileNet<T>
+	where S: Fn(f32, f32) -> f32,
+	      C: Fn(f32) -> T
+{
+	TileNet::from_fn(size, |x, y| classify(sampler(x as f32, y as f32)))
+}
+
+/// Classify values below `level` as `below`, and the rest as `above`
+pub fn threshold<T>(level: f32, below: T, above: T) -> impl Fn(f32) -> T
+	where T: Clone
+{
+	move |x| if x < level { below.clone() } else { above.clone() }
+}
+
+/// Lower a sampler towards the edges of a map of (cols, rows) tiles
+///
+/// The value drops by the square of the distance from the center, scaled so
+/// the middle of every edge is 1 away. Thresholding the result at 0 turns
+/// noise in -1..1 into land surrounded by water.
+pub fn island<S>(size: (usize, usize), sampler: S) -> impl Fn(f32, f32) -> f32
+	where S: Fn(f32, f32) -> f32
+{
+	let half = (size.0 as f32 / 2.0, size.1 as f32 / 2.0);
+	move |x, y| {
+		let (dx, dy) = ((x + 0.5 - half.0) / half.0, (y + 0.5 - half.1) / half.1);
+		sampler(x, y) - (dx * dx + dy * dy)
+	}
+}
+
+/// Generate caves with a cellular automaton, where `true` is wall
+///
+/// Starts with `fill` of the tiles as random walls, then runs `passes`
+/// rounds where a tile becomes wall when at least 5 of the 9 tiles around
+/// and including it are walls. Tiles outside of the map count as walls, so
+/// caves close off at the edges. A fill of about 0.45 and 4 passes gives
+/// open, connected caverns.
+///
+/// ```
+/// use tile_net::gen::{self, Rng};
+/// let caves = gen::caves((60, 40), 0.45, 4, &mut Rng::new(9));
+/// let walls = caves.get_raw().iter().filter(|x| **x).count();
+/// assert!(walls > 500 && walls < 2000);
+/// assert_eq!(caves.get_raw(), gen::caves((60, 40), 0.45, 4, &mut Rng::new(9)).get_raw());
+/// ```
+///
+/// # Panics #
+/// Panics if the column count is zero.
+pub fn caves(size: (usize, usize), fill: f32, passes: usize, rng: &mut Rng) -> TileNet<bool> {
+	let mut net = TileNet::from_fn(size, |_, _| rng.next_f32() < fill);
+	for _ in 0..passes {
+		let previous = net.clone();
+		let wall = |x: isize, y: isize| x < 0 || y < 0 || previous.get((x as usize, y as usize)).cloned().unwrap_or(true);
+		net = TileNet::from_fn(size, |x, y| {
+			let (x, y) = (x as isize, y as isize);
+			let count = (-1..2).flat_map(|dy| (-1..2).map(move |dx| (dx, dy))).filter(|&(dx, dy)| wall(x + dx, y + dy)).count();
+			count >= 5
+		});
+	}
+	net
+}
+
+/// Generate a perfect maze, where `true` is wall
+///
+/// Passages run through the tiles with odd coordinates, and exactly one path
+/// connects any two of them. The outer edge is always wall, as is the last
+/// row or column of an even sized map. Seeds work like in `Rng::new`.
+///
+/// ```
+/// use tile_net::gen;
+/// let maze = gen::maze((21, 11), 5);
+/// assert_eq!(maze.get((1, 1)), Some(&false));
+/// assert_eq!(maze.get((0, 5)), Some(&true));
+/// // 10 by 5 cells, joined by 49 openings
+/// assert_eq!(maze.get_raw().iter().filter(|x| !**x).count(), 50 + 49);
+/// ```
+///
+/// # Panics #
+/// Panics if the column count is zero.
+pub fn maze(size: (usize, usize), seed: u64) -> TileNet<bool> {
+	let mut rng = Rng::new(seed);
+	let mut net = TileNet::from_fn(size, |_, _| true);
+	let cells = (size.0.saturating_sub(1) / 2, size.1.saturating_sub(1) / 2);
+	if cells.0 == 0 || cells.1 == 0 {
+		return net;
+	}
+	// Depth-first backtracking over the cells, opening the wall to each new one
+	let tile = |x: usize, y: usize| (2 * x + 1, 2 * y + 1);
+	let mut visited = vec![false; cells.0 * cells.1];
+	let mut stack = vec![(0, 0)];
+	visited[0] = true;
+	net.set(&false, tile(0, 0));
+	while let Some(&(x, y)) = stack.last() {
+		let mut next = vec![];
+		if x > 0 && !visited[x - 1 + y * cells.0] {
+			next.push((x - 1, y));
+		}
+		if x + 1 < cells.0 && !visited[x + 1 + y * cells.0] {
+			next.push((x + 1, y));
+		}
+		if y > 0 && !visited[x + (y - 1) * cells.0] {
+			next.push((x, y - 1));
+		}
+		if y + 1 < cells.1 && !visited[x + (y + 1) * cells.0] {
+			next.push((x, y + 1));
+		}
+		if next.is_empty() {
+			stack.pop();
+			continue;
+		}
+		let (nx, ny) = next[rng.below(next.len())];
+		visited[nx + ny * cells.0] = true;
+		net.set(&false, (x + nx + 1, y + ny + 1));
+		net.set(&false, tile(nx, ny));
+		stack.push((nx, ny));
+	}
+	net
+}
+
+/// Carve rooms joined by corridors into a net, by binary space partitioning
+///
+/// The net is split in two at random, again and again, until the parts are
+/// too small to hold two rooms of `min_room` by `min_room` tiles. Every part
+/// gets one room, and sibling parts are joined by an L-shaped corridor, so
+/// every room can be reached from every other. Only `floor` is written, and
+/// rooms keep a wall of at least one tile towards the edge of the net, so
+/// fill the net with walls first.
+///
+/// Returns the rooms as (x_start, x_stop, y_start, y_stop), with exclusive
+/// stops.
+///
+/// ```
+/// use tile_net::TileNet;
+/// use tile_net::gen::{self, Rng};
+/// let mut net = TileNet::from_fn((48, 32), |_, _| '#');
+/// let rooms = gen::dungeon(&mut net, &'.', 4, &mut Rng::new(2));
+/// assert!(rooms.len() > 1);
+/// let room = rooms[0];
+/// assert_eq!(net.get((room.0, room.2)), Some(&'.'));
+/// assert_eq!(net.get((0, 0)), Some(&'#'));
+/// ```
+pub fn dungeon<T>(net: &mut TileNet<T>, floor: &T, min_room: usize, rng: &mut Rng) -> Vec<(usize, usize, usize, usize)>
+	where T: Clone
+{
+	let (cols, rows) = net.get_size();
+	let mut rooms = vec![];
+	partition(net, floor, (0, cols, 0, rows), min_room.max(1), rng, &mut rooms);
+	rooms
+}
+
+// Split a part of the net or fill it with a room, returning the center of a room in it
+fn partition<T>(net: &mut TileNet<T>,
+                floor: &T,
+                part: (usize, usize, usize, usize),
+                min_room: usize,
+                rng: &mut Rng,
+                rooms: &mut Vec<(usize, usize, usize, usize)>)
+                -> Option<(usize, usize)>
+	where T: Clone
+{
+	// A room and the wall around it
+	let least = min_room + 2;
+	let (width, height) = (part.1 - part.0, part.3 - part.2);
+	if width < least || height < least {
+		return None;
+	}
+	let split_x = width >= 2 * least && (width >= height || height < 2 * least);
+	let split_y = !split_x && height >= 2 * least;
+	let (first, second) = if split_x {
+		let at = part.0 + least + rng.below(width - 2 * least + 1);
+		((part.0, at, part.2, part.3), (at, part.1, part.2, part.3))
+	} else if split_y {
+		let at = part.2 + least + rng.below(height - 2 * least + 1);
+		((part.0, part.1, part.2, at), (part.0, part.1, at, part.3))
+	} else {
+		let (w, h) = (min_room + rng.below(width - least + 1), min_room + rng.below(height - least + 1));
+		let x = part.0 + 1 + rng.below(width - 1 - w);
+		let y = part.2 + 1 + rng.below(height - 1 - h);
+		net.set_box_filled(floor, (x, y), (x + w - 1, y + h - 1));
+		rooms.push((x, x + w, y, y + h));
+		return Some((x + w / 2, y + h / 2));
+	};
+	let a = partition(net, floor, first, min_room, rng, rooms);
+	let b = partition(net, floor, second, min_room, rng, rooms);
+	if let (Some(a), Some(b)) = (a, b) {
+		// Along the row of one room, then the column of the other
+		let corner = if rng.below(2) == 0 { (b.0, a.1) } else { (a.0, b.1) };
+		net.set_box_filled(floor, (a.0.min(corner.0), a.1.min(corner.1)), (a.0.max(corner.0), a.1.max(corner.1)));
+		net.set_box_filled(floor, (b.0.min(corner.0), b.1.min(corner.1)), (b.0.max(corner.0), b.1.max(corner.1)));
+	}
+	a.or(b)
+}
+
+/// Small seedable random number generator (SplitMix64)
+///
+/// Fast, and good enough for level generation, but not for cryptography.
+///
+/// ```
+/// use tile_net::gen::Rng;
+/// let mut a = Rng::new(7);
+/// let mut b = Rng::new(7);
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// assert!(a.below(6) < 6);
+/// assert!((0.0..1.0).contains(&a.next_f32()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+	state: u64,
+}
+
+impl Rng {
+	/// Create a generator from a seed
+	pub fn new(seed: u64) -> Rng {
+		Rng { state: seed }
+	}
+
+	/// Get the next 64 random bits
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	/// Get a number in `0..n`
+	///
+	/// # Panics #
+	/// Panics if `n` is 0.
+	pub fn below(&mut self, n: usize) -> usize {
+		assert!(n > 0, "can not pick a number below 0");
+		// Widening multiplication keeps the bias negligible without a loop
+		((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+	}
+
+	/// Get a number in `0.0..1.0`
+	pub fn next_f32(&mut self) -> f32 {
+		(self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn noise_is_row_major() {
+		let net = from_noise((3, 2), |x, y| x + 10.0 * y, |x| x as usize);
+		assert_eq!(net.get_raw(), &[0, 1, 2, 10, 11, 12]);
+		let net = from_noise((3, 2), |x, _| x, threshold(1.0, false, true));
+		assert_eq!(net.get_raw(), &[false, true, true, false, true, true]);
+		// The island falloff is 0 in the middle and 1 at the middle of an edge
+		let falloff = island((4, 2), |_, _| 0.0);
+		assert_eq!(falloff(1.5, 0.5), 0.0);
+		assert_eq!(falloff(-0.5, 0.5), -1.0);
+	}
+
+	// Number of tiles matching `open` reachable from `start`
+	fn reachable<T, F>(net: &TileNet<T>, start: (usize, usize), open: F) -> usize
+		where F: Fn(&T) -> bool
+	{
+		let map = super::super::path::dijkstra(net, vec![start], |x| if open(x) { Some(1) } else { None });
+		map.get_raw().iter().filter(|x| x.is_some()).count()
+	}
+
+	#[test]
+	fn mazes_and_dungeons_are_connected() {
+		for seed in 0..5 {
+			let maze = maze((16, 9), seed);
+			let open = maze.get_raw().iter().filter(|x| !**x).count();
+			assert_eq!(open, 2 * 7 * 4 - 1);
+			assert_eq!(reachable(&maze, (1, 1), |x| !*x), open);
+			assert!((0..9).all(|y| maze.get((15, y)) == Some(&true)));
+
+			let mut net = TileNet::from_fn((50, 40), |_, _| 0);
+			let rooms = dungeon(&mut net, &1, 3, &mut Rng::new(seed));
+			assert!(rooms.len() >= 4);
+			for (i, a) in rooms.iter().enumerate() {
+				assert!(a.0 > 0 && a.1 < 50 && a.2 > 0 && a.3 < 40);
+				assert!(a.1 - a.0 >= 3 && a.3 - a.2 >= 3);
+				assert!(rooms[i + 1..].iter().all(|b| a.1 <= b.0 || b.1 <= a.0 || a.3 <= b.2 || b.3 <= a.2));
+			}
+			let floor = net.get_raw().iter().filter(|x| **x == 1).count();
+			assert_eq!(reachable(&net, (rooms[0].0, rooms[0].2), |x| *x == 1), floor);
+		}
+		assert!(maze((2, 2), 0).get_raw().iter().all(|x| *x));
+	}
+}
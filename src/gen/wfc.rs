@@ -0,0 +1,233 @@
+//! Wave function collapse, learning which tiles may touch from a sample
+//!
+//! Every pair of neighbouring tiles in the sample, in each of the four
+//! directions, is allowed next to each other in the output, and nothing
+//! else is. Generation repeatedly fixes the undecided tile with the fewest
+//! options left, picking among them as often as they occur in the sample,
+//! and rules out whatever that makes impossible around it.
+//!
+//! ```
+//! use tile_net::TileNet;
+//! use tile_net::gen::{Model, Rng};
+//! let sample = TileNet::from_str_map("
+//! ~~~~~~
+//! ~~..~~
+//! ~.##.~
+//! ~.##.~
+//! ~~..~~
+//! ~~~~~~
+//! ", |x| x).unwrap();
+//! let model = Model::learn(&sample).unwrap();
+//! let map = model.generate((30, 20), &mut Rng::new(1), 10).unwrap();
+//! assert_eq!(map.get_size(), (30, 20));
+//! // Rock only ever borders sand or rock, never water
+//! for (tile, x, y) in map.view_all() {
+//!     if *tile == '#' {
+//!         for &(dx, dy) in &[(1, 0), (0, 1)] {
+//!             assert_ne!(map.get((x + dx, y + dy)), Some(&'~'));
+//!         }
+//!     }
+//! }
+//! ```
+use super::Rng;
+use super::super::{TileNet, TileNetError};
+
+// Offsets of the neighbours, with each direction next to its opposite
+const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Adjacency rules and frequencies of the tiles of a sample
+#[derive(Clone, Debug)]
+pub struct Model<T> {
+	tiles: Vec<T>,
+	weights: Vec<u32>,
+	// allowed[direction][a][b]: b may be the neighbour of a in that direction
+	allowed: [Vec<Vec<bool>>; 4],
+}
+
+impl<T> Model<T>
+	where T: Clone + PartialEq
+{
+	/// Learn the tiles of a sample and which of them may touch
+	///
+	/// Fails with `TileNetError::EmptySample` if the sample has no tiles, as
+	/// nothing could be generated from it.
+	pub fn learn(sample: &TileNet<T>) -> Result<Model<T>, TileNetError> {
+		if sample.get_raw().is_empty() {
+			return Err(TileNetError::EmptySample);
+		}
+		let mut tiles: Vec<T> = vec![];
+		let mut weights = vec![];
+		let ids: Vec<usize> = sample.get_raw()
+			.iter()
+			.map(|tile| match tiles.iter().position(|x| x == tile) {
+				Some(id) => {
+					weights[id] += 1;
+					id
+				}
+				None => {
+					tiles.push(tile.clone());
+					weights.push(1);
+					tiles.len() - 1
+				}
+			})
+			.collect();
+		let count = tiles.len();
+		let mut allowed = [vec![vec![false; count]; count],
+		                   vec![vec![false; count]; count],
+		                   vec![vec![false; count]; count],
+		                   vec![vec![false; count]; count]];
+		let (cols, rows) = sample.get_size();
+		for y in 0..rows {
+			for x in 0..cols {
+				let id = ids[x + y * cols];
+				for (direction, &(dx, dy)) in DIRECTIONS.iter().enumerate() {
+					let (nx, ny) = (x as isize + dx, y as isize + dy);
+					if nx >= 0 && ny >= 0 && (nx as usize) < cols && (ny as usize) < rows {
+						allowed[direction][id][ids[nx as usize + ny as usize * cols]] = true;
+					}
+				}
+			}
+		}
+		Ok(Model {
+			tiles,
+			weights,
+			allowed,
+		})
+	}
+
+	/// Get the different tiles of the sample, in order of first appearance
+	pub fn tiles(&self) -> &[T] {
+		&self.tiles
+	}
+
+	/// Generate a net of (cols, rows) tiles following the rules of the sample
+	///
+	/// Generation can run into a tile with no options left; it then starts
+	/// over, up to `attempts` times in total, and returns `None` if every
+	/// attempt failed. Samples with few rules rarely need more than one.
+	///
+	/// # Panics #
+	/// Panics if the column count is zero.
+	pub fn generate(&self, size: (usize, usize), rng: &mut Rng, attempts: usize) -> Option<TileNet<T>> {
+		assert!(size.0 > 0, "a TileNet needs at least one column");
+		for _ in 0..attempts {
+			if let Some(ids) = self.run(size, rng) {
+				return Some(TileNet::from_fn(size, |x, y| self.tiles[ids[x + y * size.0]].clone()));
+			}
+		}
+		None
+	}
+
+	// One attempt, giving the tile id of every cell
+	fn run(&self, (cols, rows): (usize, usize), rng: &mut Rng) -> Option<Vec<usize>> {
+		let count = self.tiles.len();
+		let mut wave = vec![vec![true; count]; cols * rows];
+		let mut options = vec![count; cols * rows];
+		// Every cell starts in the stack, so rules no tile can follow show up at once
+		let mut stack: Vec<usize> = (0..cols * rows).collect();
+		loop {
+			while let Some(cell) = stack.pop() {
+				let (x, y) = ((cell % cols) as isize, (cell / cols) as isize);
+				for (direction, &(dx, dy)) in DIRECTIONS.iter().enumerate() {
+					let (nx, ny) = (x + dx, y + dy);
+					if nx < 0 || ny < 0 || nx as usize >= cols || ny as usize >= rows {
+						continue;
+					}
+					let neighbour = nx as usize + ny as usize * cols;
+					let mut changed = false;
+					for id in 0..count {
+						if wave[neighbour][id] &&
+						   !(0..count).any(|from| wave[cell][from] && self.allowed[direction][from][id]) {
+							wave[neighbour][id] = false;
+							options[neighbour] -= 1;
+							changed = true;
+						}
+					}
+					if options[neighbour] == 0 {
+						return None;
+					}
+					if changed {
+						stack.push(neighbour);
+					}
+				}
+			}
+
+			// Undecided cell with the fewest options, ties broken at random
+			let mut chosen = None;
+			let mut ties = 0;
+			for (cell, &left) in options.iter().enumerate() {
+				if left < 2 {
+					continue;
+				}
+				match chosen {
+					Some((_, fewest)) if left > fewest => continue,
+					Some((_, fewest)) if left == fewest => {
+						ties += 1;
+						if rng.below(ties) == 0 {
+							chosen = Some((cell, left));
+						}
+					}
+					_ => {
+						ties = 1;
+						chosen = Some((cell, left));
+					}
+				}
+			}
+			let cell = match chosen {
+				Some((cell, _)) => cell,
+				None => return Some(wave.iter().map(|x| x.iter().position(|x| *x).expect("collapsed")).collect()),
+			};
+			// Pick one of its options, weighted by how common it is
+			let total: u32 = (0..count).filter(|x| wave[cell][*x]).map(|x| self.weights[x]).sum();
+			let mut pick = rng.below(total as usize) as u32;
+			let choice = (0..count)
+				.filter(|x| wave[cell][*x])
+				.find(|&id| {
+					if pick < self.weights[id] {
+						return true;
+					}
+					pick -= self.weights[id];
+					false
+				})
+				.expect("the pick is below the total weight");
+			for (id, option) in wave[cell].iter_mut().enumerate() {
+				*option = id == choice;
+			}
+			options[cell] = 1;
+			stack.push(cell);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn follows_the_rules_and_the_seed() {
+		// Stripes: every row is one colour, and colours cycle downwards
+		let sample = TileNet::from_rows(vec![vec![0, 0, 0], vec![1, 1, 1], vec![2, 2, 2], vec![0, 0, 0]]).unwrap();
+		let model = Model::learn(&sample).unwrap();
+		assert_eq!(model.tiles(), &[0, 1, 2]);
+		let map = model.generate((8, 9), &mut Rng::new(3), 1).unwrap();
+		for y in 0..9 {
+			let row = *map.get((0, y)).unwrap();
+			assert!((0..8).all(|x| map.get((x, y)) == Some(&row)));
+			if y > 0 {
+				assert_eq!((map.get((0, y - 1)).unwrap() + 1) % 3, row);
+			}
+		}
+		let again = model.generate((8, 9), &mut Rng::new(3), 1).unwrap();
+		assert_eq!(map.get_raw(), again.get_raw());
+		// A single tile with no neighbours allowed can only fill a single tile
+		let lonely = Model::learn(&TileNet::from_rows(vec![vec![5]]).unwrap()).unwrap();
+		assert!(lonely.generate((1, 1), &mut Rng::new(0), 1).is_some());
+		assert!(lonely.generate((2, 1), &mut Rng::new(0), 3).is_none());
+	}
+
+	#[test]
+	fn empty_samples_are_refused() {
+		let sample: TileNet<usize> = TileNet::new(4, 0);
+		assert_eq!(Model::learn(&sample).unwrap_err(), TileNetError::EmptySample);
+	}
+}
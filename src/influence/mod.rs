@@ -0,0 +1,189 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::cmp::min;
+use super::TileNet;
+
+/// A float per tile, for threat maps, scent trails and other spreading values
+///
+/// Sources add influence around a tile, `decay` fades everything a little
+/// every tick, and `blur` smears values into the neighbouring tiles. The
+/// values live in a plain `TileNet<f32>` of the same size as the map, so
+/// views, `get` and the rest work on them as usual.
+///
+/// ```
+/// use tile_net::InfluenceMap;
+/// let mut threat = InfluenceMap::new((20, 20));
+/// threat.add_source((10, 10), 8.0, 3);
+/// assert_eq!(threat.get((10, 10)), 8.0);
+/// assert_eq!(threat.get((12, 10)), 4.0);
+/// assert_eq!(threat.get((14, 10)), 0.0);
+/// threat.decay(0.5);
+/// threat.blur(1);
+/// assert!(threat.get((14, 10)) > 0.0);
+/// assert!(threat.get((10, 10)) < 4.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct InfluenceMap {
+	values: TileNet<f32>,
+}
+
+impl InfluenceMap {
+	/// Create a map of (cols, rows) tiles without any influence
+	pub fn new(size: (usize, usize)) -> InfluenceMap {
+		InfluenceMap { values: TileNet::new(size.0, size.1) }
+	}
+
+	/// Create a map covering the same tiles as a net
+	pub fn for_net<T>(net: &TileNet<T>) -> InfluenceMap {
+		InfluenceMap::new(net.get_size())
+	}
+
+	/// Get the influence on a tile, 0 outside of the map
+	pub fn get(&self, tile: (usize, usize)) -> f32 {
+		self.values.get(tile).cloned().unwrap_or(0.0)
+	}
+
+	/// Get the values of every tile
+	pub fn values(&self) -> &TileNet<f32> {
+		&self.values
+	}
+
+	/// Get the values of every tile for editing
+	pub fn values_mut(&mut self) -> &mut TileNet<f32> {
+		&mut self.values
+	}
+
+	/// Add influence around a tile, falling off linearly with the distance
+	///
+	/// The tile itself gets `strength`, and tiles up to `radius` tiles away
+	/// get less the further away they are, down to nothing just outside of
+	/// the radius. Negative strengths subtract influence.
+	pub fn add_source(&mut self, tile: (usize, usize), strength: f32, radius: usize) {
+		let (cols, rows) = self.values.get_size();
+		let reach = radius as f32 + 1.0;
+		for y in tile.1.saturating_sub(radius)..min(tile.1 + radius + 1, rows) {
+			for x in tile.0.saturating_sub(radius)..min(tile.0 + radius + 1, cols) {
+				let (dx, dy) = (x.abs_diff(tile.0) as f32, y.abs_diff(tile.1) as f32);
+				let falloff = 1.0 - (dx * dx + dy * dy).sqrt() / reach;
+				if falloff > 0.0 {
+					if let Some(value) = self.values.get_mut((x, y)) {
+						*value += strength * falloff;
+					}
+				}
+			}
+		}
+	}
+
+	/// Multiply every value by `factor`, once per tick
+	///
+	/// A factor of 0.9 per tick halves the influence about every 6.6 ticks.
+	pub fn decay(&mut self, factor: f32) {
+		for value in self.values.get_raw_mut() {
+			*value *= factor;
+		}
+	}
+
+	/// Replace every value by the average of the box of tiles up to `radius` away
+	///
+	/// Near the edges only the tiles inside of the map are averaged, so no
+	/// influence leaks out. A radius of 0 leaves the values unchanged.
+	pub fn blur(&mut self, radius: usize) {
+		let (cols, rows) = self.values.get_size();
+		if radius == 0 || cols == 0 {
+			return;
+		}
+		let mut rows_blurred = vec![0.0; cols * rows];
+		for (row, out) in self.values.get_raw().chunks(cols).zip(rows_blurred.chunks_mut(cols)) {
+			blur_row(row, out, radius);
+		}
+		let rows_blurred = &rows_blurred;
+		for (y, out) in self.values.get_raw_mut().chunks_mut(cols).enumerate() {
+			blur_column(rows_blurred, y, out, radius);
+		}
+	}
+
+	/// Blur like `blur`, on the rayon thread pool
+	///
+	/// The result is exactly the same as from `blur`.
+	#[cfg(feature = "rayon")]
+	pub fn par_blur(&mut self, radius: usize) {
+		let (cols, rows) = self.values.get_size();
+		if radius == 0 || cols == 0 {
+			return;
+		}
+		let mut rows_blurred = vec![0.0; cols * rows];
+		self.values
+			.get_raw()
+			.par_chunks(cols)
+			.zip(rows_blurred.par_chunks_mut(cols))
+			.for_each(|(row, out)| blur_row(row, out, radius));
+		let rows_blurred = &rows_blurred;
+		self.values
+			.get_raw_mut()
+			.par_chunks_mut(cols)
+			.enumerate()
+			.for_each(|(y, out)| blur_column(rows_blurred, y, out, radius));
+	}
+}
+
+// Average every value of a row with its neighbours up to `radius` away
+fn blur_row(row: &[f32], out: &mut [f32], radius: usize) {
+	for (x, out) in out.iter_mut().enumerate() {
+		let window = &row[x.saturating_sub(radius)..min(x + radius + 1, row.len())];
+		*out = window.iter().sum::<f32>() / window.len() as f32;
+	}
+}
+
+// Fill row `y` with the average of the rows up to `radius` above and below it
+fn blur_column(values: &[f32], y: usize, out: &mut [f32], radius: usize) {
+	let cols = out.len();
+	let rows = values.len() / cols;
+	let window = y.saturating_sub(radius)..min(y + radius + 1, rows);
+	let count = window.len() as f32;
+	for value in out.iter_mut() {
+		*value = 0.0;
+	}
+	for row in window {
+		for (value, add) in out.iter_mut().zip(&values[row * cols..(row + 1) * cols]) {
+			*value += *add;
+		}
+	}
+	for value in out.iter_mut() {
+		*value /= count;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn blur_keeps_the_total_away_from_edges() {
+		let mut map = InfluenceMap::new((9, 7));
+		map.add_source((4, 3), 9.0, 0);
+		map.add_source((20, 3), 9.0, 5);
+		assert_eq!(map.values().get_raw().iter().sum::<f32>(), 9.0);
+		map.blur(1);
+		assert_eq!(map.get((3, 2)), 1.0);
+		assert_eq!(map.get((6, 3)), 0.0);
+		assert!((map.values().get_raw().iter().sum::<f32>() - 9.0).abs() < 1e-4);
+		// The corner averages only the four tiles inside of the map
+		let mut map = InfluenceMap::new((3, 3));
+		map.values_mut().set(&4.0, (0, 0));
+		map.blur(1);
+		assert_eq!(map.get((0, 0)), 1.0);
+		assert_eq!(map.get((2, 2)), 0.0);
+	}
+
+	#[cfg(feature = "rayon")]
+	#[test]
+	fn par_blur() {
+		let mut serial = InfluenceMap::new((40, 30));
+		serial.add_source((10, 10), 3.0, 6);
+		serial.add_source((30, 25), -2.0, 9);
+		let mut parallel = serial.clone();
+		serial.blur(2);
+		parallel.par_blur(2);
+		assert_eq!(serial.values().get_raw(), parallel.values().get_raw());
+	}
+}
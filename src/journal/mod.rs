@@ -0,0 +1,239 @@
+use std::cmp::min;
+use std::ops::Deref;
+use super::TileNet;
+
+/// A `TileNet` recording its edits, so they can be undone and redone
+///
+/// Every edit logs the coordinate, old tile and new tile of each tile that
+/// changed. Edits between `begin` and `commit` form one transaction, which
+/// `undo` and `redo` revert and reapply as a whole; other edits are a
+/// transaction of their own. Reading goes through `Deref`, so queries and
+/// collision sets work on the journal directly.
+///
+/// ```
+/// use tile_net::{JournaledTileNet, TileNet};
+/// let mut net = JournaledTileNet::new(TileNet::<usize>::new(4, 4));
+/// net.begin();
+/// net.set(&1, (0, 0));
+/// net.edit((0, 4, 2, 3), |net| net.set_row(&2, 2));
+/// net.commit();
+/// net.set(&3, (3, 3));
+/// assert!(net.undo());
+/// assert_eq!(net.get((3, 3)), Some(&0));
+/// assert!(net.undo());
+/// assert_eq!(net.get_raw().iter().sum::<usize>(), 0);
+/// assert!(!net.undo());
+/// assert!(net.redo());
+/// assert_eq!(net.get((2, 2)), Some(&2));
+/// ```
+#[derive(Clone, Debug)]
+pub struct JournaledTileNet<T> {
+	net: TileNet<T>,
+	undo: Vec<Vec<Entry<T>>>,
+	redo: Vec<Vec<Entry<T>>>,
+	open: Option<Vec<Entry<T>>>,
+}
+
+#[derive(Clone, Debug)]
+struct Entry<T> {
+	coord: (usize, usize),
+	old: T,
+	new: T,
+}
+
+impl<T> JournaledTileNet<T>
+	where T: Clone + PartialEq
+{
+	/// Start recording edits to a net, with an empty history
+	pub fn new(net: TileNet<T>) -> JournaledTileNet<T> {
+		JournaledTileNet {
+			net,
+			undo: vec![],
+			redo: vec![],
+			open: None,
+		}
+	}
+
+	/// Stop recording, returning the net as it is now
+	pub fn into_inner(self) -> TileNet<T> {
+		self.net
+	}
+
+	/// Start a transaction, grouping the edits up to `commit`
+	///
+	/// Calling `begin` inside a transaction has no effect.
+	pub fn begin(&mut self) {
+		if self.open.is_none() {
+			self.open = Some(vec![]);
+		}
+	}
+
+	/// Finish the transaction started by `begin`
+	///
+	/// Transactions without changes are not recorded.
+	pub fn commit(&mut self) {
+		if let Some(entries) = self.open.take() {
+			self.push(entries);
+		}
+	}
+
+	/// Set a single grid point
+	pub fn set(&mut self, value: &T, p: (usize, usize)) {
+		if let Ok(tile) = self.net.try_get(p) {
+			if *tile != *value {
+				let entry = Entry {
+					coord: p,
+					old: tile.clone(),
+					new: value.clone(),
+				};
+				self.net.set(value, p);
+				self.record(vec![entry]);
+			}
+		}
+	}
+
+	/// Edit the net freely, recording the changes inside a box
+	///
+	/// The box is given as (x_start, x_stop, y_start, y_stop) with exclusive
+	/// stops, like in `TileNet::view_box`. Changes outside of the box are
+	/// kept but not recorded, so they can not be undone.
+	///
+	/// # Panics #
+	/// Panics if `f` resizes the net, since the history refers to tiles by their coordinates.
+	pub fn edit<F>(&mut self, rect: (usize, usize, usize, usize), f: F)
+		where F: FnOnce(&mut TileNet<T>)
+	{
+		let (cols, rows) = self.net.get_size();
+		let (x_stop, y_stop) = (min(rect.1, cols), min(rect.3, rows));
+		let mut before = vec![];
+		for y in rect.2..y_stop {
+			for x in rect.0..x_stop {
+				before.push(self.net.get_raw()[x + y * cols].clone());
+			}
+		}
+		f(&mut self.net);
+		assert!(self.net.get_size() == (cols, rows), "JournaledTileNet::edit can not resize the net");
+		let mut entries = vec![];
+		let mut before = before.into_iter();
+		for y in rect.2..y_stop {
+			for x in rect.0..x_stop {
+				let old = before.next().expect("the box has not changed size");
+				let new = &self.net.get_raw()[x + y * cols];
+				if old != *new {
+					entries.push(Entry {
+						coord: (x, y),
+						old,
+						new: new.clone(),
+					});
+				}
+			}
+		}
+		self.record(entries);
+	}
+
+	/// Revert the last transaction, returning whether there was one
+	pub fn undo(&mut self) -> bool {
+		self.commit();
+		match self.undo.pop() {
+			Some(entries) => {
+				self.net.batch(|net| {
+					for entry in entries.iter().rev() {
+						net.set(&entry.old, entry.coord);
+					}
+				});
+				self.redo.push(entries);
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Reapply the last undone transaction, returning whether there was one
+	pub fn redo(&mut self) -> bool {
+		self.commit();
+		match self.redo.pop() {
+			Some(entries) => {
+				self.net.batch(|net| {
+					for entry in &entries {
+						net.set(&entry.new, entry.coord);
+					}
+				});
+				self.undo.push(entries);
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Forget every recorded transaction
+	pub fn clear_history(&mut self) {
+		self.undo.clear();
+		self.redo.clear();
+		if let Some(ref mut entries) = self.open {
+			entries.clear();
+		}
+	}
+
+	fn record(&mut self, mut entries: Vec<Entry<T>>) {
+		match self.open {
+			Some(ref mut open) => open.append(&mut entries),
+			None => self.push(entries),
+		}
+	}
+
+	fn push(&mut self, entries: Vec<Entry<T>>) {
+		if !entries.is_empty() {
+			self.undo.push(entries);
+			self.redo.clear();
+		}
+	}
+}
+
+impl<T> Deref for JournaledTileNet<T> {
+	type Target = TileNet<T>;
+	fn deref(&self) -> &TileNet<T> {
+		&self.net
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn redo_is_cleared_by_new_edits() {
+		let mut net = JournaledTileNet::new(TileNet::<usize>::new(3, 3));
+		net.set(&1, (0, 0));
+		net.set(&1, (0, 0));
+		net.set(&2, (1, 0));
+		net.set(&2, (9, 9));
+		assert!(net.undo());
+		net.set(&3, (2, 0));
+		assert!(!net.redo());
+		assert_eq!(&net.get_raw()[..3], &[1, 0, 3]);
+		assert!(net.undo() && net.undo() && !net.undo());
+		assert_eq!(net.get_raw(), &[0; 9]);
+	}
+
+	#[test]
+	fn edit_records_only_the_box() {
+		let mut net = JournaledTileNet::new(TileNet::<usize>::new(3, 3));
+		net.edit((0, 2, 0, 2), |net| net.fill(&4));
+		assert!(net.undo());
+		assert_eq!(net.get_raw(), &[0, 0, 4, 0, 0, 4, 4, 4, 4]);
+		net.begin();
+		net.begin();
+		net.commit();
+		assert!(net.redo());
+		net.clear_history();
+		assert!(!net.undo());
+		assert_eq!(net.into_inner().get_raw(), &[4; 9]);
+	}
+
+	#[test]
+	#[should_panic(expected = "JournaledTileNet::edit can not resize the net")]
+	fn edit_can_not_resize() {
+		let mut net = JournaledTileNet::new(TileNet::<usize>::new(3, 3));
+		net.edit((0, 3, 0, 3), |net| net.resize((2, 2)));
+	}
+}
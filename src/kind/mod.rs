@@ -0,0 +1,283 @@
+use super::{Vector, YAxis};
+
+/// Decide how a tile takes part in collisions
+///
+/// The built-in bodies and helpers such as `Occupancy` ask the tile itself
+/// whether it blocks movement, so the decision lives in one place instead of
+/// in a closure at every call site. Integers are solid when non-zero and
+/// `bool` is solid when true.
+///
+/// ```
+/// use tile_net::TileKind;
+/// #[derive(Clone, Debug, Default)]
+/// enum Tile {
+///     #[default]
+///     Air,
+///     Stone,
+///     Water,
+/// }
+/// impl TileKind for Tile {
+///     fn is_solid(&self) -> bool {
+///         matches!(*self, Tile::Stone)
+///     }
+/// }
+/// assert!(Tile::Stone.is_solid());
+/// assert!(!Tile::Water.is_solid());
+/// assert!(3u8.is_solid() && !0i32.is_solid());
+/// ```
+pub trait TileKind {
+	/// Check whether the tile blocks movement
+	fn is_solid(&self) -> bool;
+
+	/// Make a solid tile directional, blocking only from one side
+	///
+	/// A directional tile only collides with bodies crossing the returned
+	/// edge from the outside, such as a platform that can be jumped through from
+	/// below but stood upon from above. Solvers not aware of directions treat
+	/// it as fully solid. Returns `None`, meaning solid from every side, by
+	/// default.
+	///
+	/// ```
+	/// use tile_net::{AabbCollider, Collable, Direction, Rect, TileKind, TileNet, Vector};
+	/// #[derive(Clone, Debug, Default)]
+	/// struct Platform(bool);
+	/// impl TileKind for Platform {
+	///     fn is_solid(&self) -> bool {
+	///         self.0
+	///     }
+	///     fn one_way(&self) -> Option<Direction> {
+	///         Some(Direction::Up)
+	///     }
+	/// }
+	/// let mut net: TileNet<Platform> = TileNet::new(10, 10);
+	/// net.set_row(&Platform(true), 5);
+	/// // Jump up through the platform...
+	/// let mut body = AabbCollider::new(Rect::new(Vector(2.0, 6.0), Vector(1.0, 1.0)));
+	/// body.vel = Vector(0.0, -3.0);
+	/// body.solve(&net, &mut ());
+	/// assert_eq!(body.rect().pos, Vector(2.0, 3.0));
+	/// // ...and land on top of it
+	/// body.vel = Vector(0.0, 3.0);
+	/// body.solve(&net, &mut ());
+	/// assert_eq!(body.rect().pos, Vector(2.0, 4.0));
+	/// ```
+	fn one_way(&self) -> Option<Direction> {
+		None
+	}
+
+	/// Check whether the tile is a sensor, such as a pickup or a checkpoint
+	///
+	/// Sensors are reported by the built-in bodies, see
+	/// `AabbCollider::sensors`, and in `SolveReport::sensors` by `solve_with`,
+	/// so a body can react to passing through them.
+	/// A sensor should not be solid; bodies move through it either way.
+	/// Returns `false` by default. When using closures instead of `TileKind`,
+	/// look for sensors in the tiles of `TileSet::with_coords`.
+	///
+	/// ```
+	/// use tile_net::{AabbCollider, Collable, Rect, TileKind, TileNet, Vector};
+	/// #[derive(Clone, Debug, Default, PartialEq)]
+	/// enum Tile {
+	///     #[default]
+	///     Air,
+	///     Coin,
+	/// }
+	/// impl TileKind for Tile {
+	///     fn is_solid(&self) -> bool {
+	///         false
+	///     }
+	///     fn is_sensor(&self) -> bool {
+	///         *self == Tile::Coin
+	///     }
+	/// }
+	/// let mut net: TileNet<Tile> = TileNet::new(10, 10);
+	/// net.set(&Tile::Coin, (4, 2));
+	/// let mut body = AabbCollider::new(Rect::new(Vector(1.0, 2.0), Vector(1.0, 1.0)));
+	/// body.vel = Vector(5.0, 0.0);
+	/// body.solve(&net, &mut ());
+	/// assert_eq!(body.rect().pos, Vector(6.0, 2.0));
+	/// assert_eq!(body.sensors(), &[(4, 2)]);
+	/// ```
+	fn is_sensor(&self) -> bool {
+		false
+	}
+
+	/// Get the solid part of a solid tile
+	///
+	/// Shapes other than `TileShape::Full` let bodies walk up slopes and
+	/// stand on partial tiles. Like `one_way`, solvers not aware of shapes
+	/// treat the tile as full. Returns `TileShape::Full` by default.
+	fn shape(&self) -> TileShape {
+		TileShape::Full
+	}
+}
+
+/// Solid part of a tile, as a floor rising from the bottom of the tile
+///
+/// Heights are fractions of a tile measured upwards from the bottom edge of
+/// the tile, at a horizontal position measured from its left edge. Names
+/// follow the compass with north being up: `SlopeNE` rises towards the
+/// north-east.
+///
+/// ```
+/// use tile_net::TileShape;
+/// assert_eq!(TileShape::SlopeNE.height(0.25), 0.25);
+/// assert_eq!(TileShape::SlopeNW.height(0.25), 0.75);
+/// assert_eq!(TileShape::Slope { left: 0.0, right: 0.5 }.max_height(0.0, 0.5), 0.25);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TileShape {
+	/// The whole tile is solid
+	Full,
+	/// A 45° slope from the bottom-left to the top-right corner
+	SlopeNE,
+	/// A 45° slope from the top-left to the bottom-right corner
+	SlopeNW,
+	/// The bottom half of the tile is solid
+	HalfBottom,
+	/// A straight slope of arbitrary steepness
+	Slope {
+		/// Height at the left edge, from 0 to 1
+		left: f32,
+		/// Height at the right edge, from 0 to 1
+		right: f32,
+	},
+	/// A heightmap of `TILE_SUBDIV` equally wide columns, from left to right
+	///
+	/// Each height is given in steps of `1 / TILE_SUBDIV` tiles, so
+	/// `TILE_SUBDIV` is a full column. Larger values are treated as full.
+	///
+	/// ```
+	/// use tile_net::{TileShape, TILE_SUBDIV};
+	/// let mut steps = [0; TILE_SUBDIV];
+	/// steps[TILE_SUBDIV / 2..].copy_from_slice(&[4, 4, 4, 4]);
+	/// let shape = TileShape::Heights(steps);
+	/// assert_eq!(shape.height(0.25), 0.0);
+	/// assert_eq!(shape.height(0.75), 0.5);
+	/// assert_eq!(shape.max_height(0.0, 0.5), 0.0);
+	/// assert_eq!(shape.max_height(0.0, 0.6), 0.5);
+	/// ```
+	Heights([u8; TILE_SUBDIV]),
+}
+
+/// Number of columns in a `TileShape::Heights` heightmap
+pub const TILE_SUBDIV: usize = 8;
+
+impl TileShape {
+	// Heights at the left and right edge, between which the surface is
+	// linear. `None` for heightmaps.
+	fn ends(&self) -> Option<(f32, f32)> {
+		match *self {
+			TileShape::Full => Some((1.0, 1.0)),
+			TileShape::SlopeNE => Some((0.0, 1.0)),
+			TileShape::SlopeNW => Some((1.0, 0.0)),
+			TileShape::HalfBottom => Some((0.5, 0.5)),
+			TileShape::Slope { left, right } => Some((left, right)),
+			TileShape::Heights(_) => None,
+		}
+	}
+
+	// Heightmap column containing a horizontal position, clamped to the tile.
+	fn column(x: f32) -> usize {
+		((x.clamp(0.0, 1.0) * TILE_SUBDIV as f32) as usize).min(TILE_SUBDIV - 1)
+	}
+
+	/// Get the height of the surface at a horizontal position in the tile
+	///
+	/// Positions outside of 0 to 1 are clamped to the tile.
+	pub fn height(&self, x: f32) -> f32 {
+		match *self {
+			TileShape::Heights(heights) => step(heights[TileShape::column(x)]),
+			_ => {
+				let (left, right) = self.ends().unwrap_or((0.0, 0.0));
+				left + (right - left) * x.clamp(0.0, 1.0)
+			}
+		}
+	}
+
+	/// Get the highest point of the surface between two horizontal positions
+	pub fn max_height(&self, from: f32, to: f32) -> f32 {
+		match *self {
+			TileShape::Heights(heights) => {
+				let first = TileShape::column(from);
+				let last = ((to.clamp(0.0, 1.0) * TILE_SUBDIV as f32).ceil() as usize).max(1) - 1;
+				heights[first..last.max(first) + 1].iter().cloned().map(step).fold(0.0, f32::max)
+			}
+			_ => self.height(from).max(self.height(to)),
+		}
+	}
+
+	/// Get the unit normal of the surface at a horizontal position, pointing up out of the tile
+	///
+	/// Up is negative y unless the net counts rows upwards, see `YAxis`.
+	/// Heightmaps are flat within each column, so their normal points straight up.
+	///
+	/// ```
+	/// use tile_net::{TileShape, Vector, YAxis};
+	/// assert_eq!(TileShape::Full.normal(0.5, YAxis::Down), Vector(0.0, -1.0));
+	/// assert_eq!(TileShape::Full.normal(0.5, YAxis::Up), Vector(0.0, 1.0));
+	/// let normal = TileShape::SlopeNE.normal(0.5, YAxis::Up);
+	/// assert!(normal.0 < 0.0 && normal.1 > 0.0);
+	/// ```
+	pub fn normal(&self, _x: f32, y_axis: YAxis) -> Vector {
+		let rise = match self.ends() {
+			Some((left, right)) => right - left,
+			None => 0.0,
+		};
+		let length = (rise * rise + 1.0).sqrt();
+		Vector(-rise / length, -y_axis.down() / length)
+	}
+}
+
+// Height of a heightmap column as a fraction of a tile.
+fn step(height: u8) -> f32 {
+	(height as f32 / TILE_SUBDIV as f32).min(1.0)
+}
+
+/// Side of a tile
+///
+/// The y axis points down, so `Up` is the top edge of a tile, the one a body
+/// falling onto the tile crosses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+	/// The top edge, towards negative y
+	Up,
+	/// The bottom edge, towards positive y
+	Down,
+	/// The left edge, towards negative x
+	Left,
+	/// The right edge, towards positive x
+	Right,
+}
+
+impl Direction {
+	/// Get the unit vector pointing out of a tile through this side
+	pub fn normal(&self) -> Vector {
+		match *self {
+			Direction::Up => Vector(0.0, -1.0),
+			Direction::Down => Vector(0.0, 1.0),
+			Direction::Left => Vector(-1.0, 0.0),
+			Direction::Right => Vector(1.0, 0.0),
+		}
+	}
+}
+
+macro_rules! integer_kind {
+	($($t:ty),*) => {
+		$(
+			impl TileKind for $t {
+				fn is_solid(&self) -> bool {
+					*self != 0
+				}
+			}
+		)*
+	};
+}
+
+integer_kind!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl TileKind for bool {
+	fn is_solid(&self) -> bool {
+		*self
+	}
+}
@@ -17,6 +17,9 @@
 //! a small increment to a floating point above 2^24 may not register at all. Precision
 //! becomes worse as you approach 2^24. The technical reason is that a 32-bit float
 //! has 24 bits in its mantissa.
+//! To limit the damage, every point of a `Collable` is traced relative to the tile it
+//! starts in, see `SuperCover::from_movement`, so short moves still register far from
+//! the origin. Positions themselves are still rounded to what a float can hold.
 //! You do not need to worry about floating point errors, as the library ensures consistency
 //! by checking end-points.
 //!
@@ -58,7 +61,7 @@
 //!   net.set_row(&1, 9);
 //!   net.set_col(&1, 0);
 //!   net.set_col(&1, 9);
-//!   net.set_box(&1, (3, 3), (5, 7));
+//!   net.set_box_filled(&1, (3, 3), (5, 7));
 //!   println!["{:?}", net];
 //! }
 //!
@@ -77,7 +80,7 @@
 //!   net.set_row(&Example(2), 9);
 //!   net.set_col(&Example(3), 0);
 //!   net.set_col(&Example(4), 9);
-//!   net.set_box(&Example(5), (3, 3), (5, 7));
+//!   net.set_box_filled(&Example(5), (3, 3), (5, 7));
 //!   println!["{:?}", net];  // Requires Debug trait
 //! }
 //! ```
@@ -101,7 +104,7 @@
 //!   net.set_row(&2, 9);
 //!   net.set_col(&3, 0);
 //!   net.set_col(&4, 9);
-//!   net.set_box(&5, (3, 3), (5, 7));
+//!   net.set_box_filled(&5, (3, 3), (5, 7));
 //!   println!["{:?}", net];
 //!
 //!   // We create a new object with speed (100, 100) and check where our collision points will be!
@@ -151,6 +154,11 @@
 //!     self.mov
 //!   }
 //!
+//!   // Replaces the queued move, used to solve one axis at a time
+//!   fn enqueue(&mut self, vector: Vector) {
+//!     self.mov = vector;
+//!   }
+//!
 //!   // Here is where your magic happens!
 //!   // You will be given a TileSet, which contains all tiles which your object
 //!   // collides between the current frame jump.
@@ -179,6 +187,7 @@
 //! sufficiently in `resolve`, you may end up with a `TileSet` that does not cause collision.
 //! This is how we can almost perfectly find the position.
 //! You may employ other methods inside resolve. Whatever suits your needs.
+//! `Collable::solve_with_resolver` searches the free move for you, by bisection or by scaling.
 //! Here is the example again but this time we resolve the collision using a loop
 //!
 //! ```
@@ -191,7 +200,7 @@
 //!   net.set_row(&2, 9);
 //!   net.set_col(&3, 0);
 //!   net.set_col(&4, 9);
-//!   net.set_box(&5, (3, 3), (5, 7));
+//!   net.set_box_filled(&5, (3, 3), (5, 7));
 //!   println!["{:?}", net];
 //!
 //!   // Movement vector is (100, 100), which is way outside the box
@@ -242,6 +251,11 @@
 //!     self.mov
 //!   }
 //!
+//!   // Replaces the queued move, used to solve one axis at a time
+//!   fn enqueue(&mut self, vector: Vector) {
+//!     self.mov = vector;
+//!   }
+//!
 //!   // Here is where your magic happens!
 //!   // You will be given a TileSet, which contains all tiles which your object
 //!   // collides between the current frame jump.
@@ -280,7 +294,7 @@
 //!   net.set_row(&2, 9);
 //!   net.set_col(&3, 0);
 //!   net.set_col(&4, 9);
-//!   net.set_box(&5, (3, 3), (5, 7));
+//!   net.set_box_filled(&5, (3, 3), (5, 7));
 //!   println!["{:?}", net];
 //!   // This creates a box with x from 0 to 4 and y from 3 to 6
 //!   // Note that the last elements are not included (so for x: 0, 1, 2, 3, but not 4)
@@ -316,6 +330,8 @@
 //! Instead of using a manual loop, you can use the built-in `solve`. Which calls `presolve`,
 //! runs a loop around `resolve`, and then calls `postsolve` with bools denoting whether a
 //! solution was found and at least a single collision was encountered.
+//! `solve` also returns a `Resolution` describing the outcome. Implement `resolve_detailed`
+//! to report the tile that was hit and the contact normal through it.
 //!
 //! ```
 //! extern crate tile_net;
@@ -327,7 +343,7 @@
 //!   net.set_row(&2, 9);
 //!   net.set_col(&3, 0);
 //!   net.set_col(&4, 9);
-//!   net.set_box(&5, (3, 3), (5, 7));
+//!   net.set_box_filled(&5, (3, 3), (5, 7));
 //!   println!["{:?}", net];
 //!
 //!   let mut collider = MyObject::new();
@@ -362,6 +378,10 @@
 //!     self.mov
 //!   }
 //!
+//!   fn enqueue(&mut self, vector: Vector) {
+//!     self.mov = vector;
+//!   }
+//!
 //!   fn postsolve(&mut self, _collided_once: bool, resolved: bool, _state: &mut ()) {
 //!     if resolved {
 //!       println!["Able to move"];
@@ -396,23 +416,249 @@
 //! you also avoid redundant information stored in your objects.
 //!
 //! See the examples directory for an example where we use presolve and postsolve
-//! to find out if our object can jump or not.
+//! to find out if our object can jump or not. In short:
+//!
+//! ```
+//! use tile_net::*;
+//!
+//! #[derive(Default)]
+//! struct JumpState {
+//!   downward: bool,
+//! }
+//!
+//! struct Player {
+//!   pts: Vec<(f32, f32)>,
+//!   pos: Vector,
+//!   mov: Vector,
+//!   can_jump: bool,
+//! }
+//!
+//! impl Collable<usize, JumpState> for Player {
+//!   fn points(&self) -> Points {
+//!     Points::new(self.pos, &self.pts)
+//!   }
+//!
+//!   fn queued(&self) -> Vector {
+//!     self.mov
+//!   }
+//!
+//!   fn enqueue(&mut self, vector: Vector) {
+//!     self.mov = vector;
+//!   }
+//!
+//!   fn presolve(&mut self, state: &mut JumpState) {
+//!     state.downward = self.mov.1 > 1e-6;
+//!   }
+//!
+//!   fn postsolve(&mut self, collided_once: bool, _resolved: bool, state: &mut JumpState) {
+//!     // Landing on something means standing on it
+//!     self.can_jump = collided_once && state.downward;
+//!   }
+//!
+//!   fn resolve<I>(&mut self, mut set: TileSet<usize, I>, _state: &mut JumpState) -> bool
+//!     where I: Iterator<Item = (i32, i32)>
+//!   {
+//!     if set.all(|x| *x == 0) {
+//!       self.pos = self.pos + self.mov;
+//!       true
+//!     } else if self.mov.norm2sq() > 1e-6 {
+//!       self.mov.scale(0.6);
+//!       false
+//!     } else {
+//!       true
+//!     }
+//!   }
+//! }
+//!
+//! let mut net: TileNet<usize> = TileNet::new(10, 10);
+//! net.set_row(&1, 5);
+//! let mut player = Player {
+//!   pts: vec![(0.0, 0.0), (0.99, 0.0), (0.0, 0.99), (0.99, 0.99)],
+//!   pos: Vector(2.0, 2.0),
+//!   mov: Vector(0.0, 3.0),
+//!   can_jump: false,
+//! };
+//! player.solve(&net, &mut JumpState::default());
+//! assert!(player.can_jump);
+//! ```
+//!
+//! A `Collable` without state can skip the parameter, as in `impl Collable<usize> for ...`,
+//! and be solved with `&mut ()`.
+//!
+//! The ready-made `AabbCollider` and `CircleCollider` work with any tile type
+//! implementing `TileKind`, which tells them which tiles are solid.
+//!
+//! # Errors and Panics #
+//! The convenience methods on `TileNet` never fail: out-of-bounds writes are ignored
+//! whatever the `OutOfBounds` policy, views are clipped to the net and `from_iter`
+//! pads the last row with `Default`. When silently losing data is not acceptable,
+//! use the `try_` counterparts (`try_set`, `try_get`, `try_from_iter`, `try_view_box`,
+//! `try_view_center`, ...), which return a `TileNetError`. The lossy behavior is also available under explicit
+//! names (`lossy_from_iter`, `saturating_view_center`, ...).
+//! Methods on `TileNet` panic on a net with zero columns, which the checked
+//! constructors refuse, and on arguments they can not work with: a `step` of 0 for
+//! `view_box_strided`, or a box that is not square for `rotate_region_90`,
+//! `rotate_region_270` and `transpose_region`. Every method that can panic, on
+//! `TileNet` or elsewhere in the crate, says when under its Panics section.
+//!
+//! # Migrating from 2.x #
+//! Version 3 changes the following:
+//!
+//! * `Collable::enqueue` is a required method replacing the queued move. Bodies
+//!   based on the 2.x jump example had an inherent `enqueue` adding to the move;
+//!   rename it, as the `jump` example does with `accelerate`, and implement the
+//!   trait method by assigning the move.
+//! * `Collable::solve` returns a `Resolution` instead of a `bool`; use
+//!   `Resolution::is_moved` for the old value. `solve_with` needs tiles
+//!   implementing `TileKind`.
+//! * Setters and getters take `Into<GridCoord>`, so tuples must hold `usize`,
+//!   and `set_if` and `set_box_if` need `T: PartialEq`.
+//! * `Model::learn` returns a `Result`, `Camera::new` takes a `GridSpace` and
+//!   `TileShape::normal` takes the `YAxis` of the net.
+//! * `set_box` is deprecated: `set_box_filled` sets the same tiles.
+//!
+//! # Features #
+//! * `bytemuck`: zero-copy snapshots of nets holding plain-old-data tiles, see `PodView`.
+//! * `derive`: `#[derive(Collable)]` for bodies with a position, a velocity and a hitbox.
+//! * `fixed`: deterministic 16.16 fixed-point geometry for lockstep games, see `FixedLine`.
+//! * `flate2`: deflate-compressed row streams, see `RowWriter::compressed`.
+//! * `glam`: `From` conversions between `Vector` and `glam::Vec2`.
+//! * `macroquad`: drawing views and bodies with `macroquad`, see `draw_view` and the `platformer` example.
+//! * `mesh`: vertex buffers of textured quads for drawing a view, see `Mesh`.
+//! * `mint`: `From` conversions between `Vector`/`Rect` and `mint` points and vectors.
+//! * `rapier`: static `rapier2d` colliders for the solid tiles, see `TileNet::to_rapier_colliders`.
+//! * `rayon`: solving many bodies and blurring influence maps in parallel, see `TileNet::par_solve_all`
+//!   and `InfluenceMap::par_blur`.
 
 
-#[macro_use(interleave)]
-extern crate interleave;
+#[cfg(feature = "bytemuck")]
+extern crate bytemuck;
+#[cfg(feature = "flate2")]
+extern crate flate2;
+#[cfg(feature = "glam")]
+extern crate glam;
+#[cfg(feature = "macroquad")]
+extern crate macroquad;
+#[cfg(feature = "mint")]
+extern crate mint;
+#[cfg(feature = "rapier")]
+extern crate rapier2d;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "derive")]
+extern crate tile_net_derive;
 
+#[macro_use]
+mod macros;
+mod anim;
+mod bits;
+mod bodies;
+mod broadphase;
+mod camera;
+mod chunked;
 mod collable;
+mod debug_draw;
 mod defs;
+mod error;
+mod events;
+mod export;
+#[cfg(feature = "fixed")]
+mod fixed;
+pub mod gen;
+mod influence;
+mod journal;
+mod kind;
+#[cfg(feature = "macroquad")]
+mod macroquad_draw;
+#[cfg(feature = "mesh")]
+mod mesh;
+mod meta;
+mod occupancy;
+mod palette;
+pub mod path;
+#[cfg(feature = "bytemuck")]
+mod pod;
+mod prefab;
+#[cfg(feature = "rapier")]
+mod rapier;
 mod tiles;
 
-pub use defs::{SuperCover, Line, Vector};
-pub use collable::{Collable, Points};
-pub use tiles::{TileNet, TileNetProxy, TileView, TileSet};
+pub use anim::{AnimatedView, TileAnimator};
+pub use bits::{BitSet, BitTileNet};
+pub use bodies::{AabbCollider, CircleCollider, CircleResponse, KinematicBody};
+pub use broadphase::SpatialHash;
+pub use camera::Camera;
+pub use chunked::{ChunkedTileNet, ChunkedView, CowSnapshot, SyncTileNet, SyncView, CHUNK_SIZE};
+pub use debug_draw::DebugDraw;
+#[cfg(feature = "derive")]
+pub use tile_net_derive::Collable;
+pub use defs::{Circle, GridCoord, GridSpace, SuperCover, Line, Rect, SignedCoord, Vector, YAxis};
+pub use collable::{AttributedSupercovers, AxisOrder, AxisReport, BatchSupercovers, Collable, PointSupercovers, Points, Resolution, Resolver, SolveOptions, SolveReport, SolveStep, SolveStrategy, SolveTrace};
+pub use error::TileNetError;
+pub use events::{CollisionEvent, CollisionTracker};
+pub use export::{RowReader, RowWriter};
+#[cfg(feature = "fixed")]
+pub use fixed::{Fixed, FixedLine, FixedSuperCover, FixedVector, FRAC_BITS};
+pub use influence::InfluenceMap;
+pub use journal::JournaledTileNet;
+pub use kind::{Direction, TileKind, TileShape, TILE_SUBDIV};
+#[cfg(feature = "macroquad")]
+pub use macroquad_draw::{draw_rect, draw_view};
+#[cfg(feature = "mesh")]
+pub use mesh::{Mesh, VERTEX_FLOATS};
+pub use meta::MetaTileNet;
+pub use occupancy::{GroupSweep, Occupancy};
+pub use palette::{PaletteIndex, PalettedTileNet};
+#[cfg(feature = "bytemuck")]
+pub use pod::{PodError, PodView};
+pub use prefab::{Orientation, Prefab};
+pub use tiles::{Anchor, AttributedTileSet, Brush, BrushShape, CastHit, Contact, ContactFlags, DisplayWith, MapSnapshot, Metric, OutOfBounds, Patch, RayHit, RaycastIter, ShapeCastHit, Span, StridedView, TileNet, TileNetProxy, TileView, TileSet, WithBorder, WithCoords};
 
 #[cfg(test)]
 mod tests {
-	use super::TileNet;
+	use super::{AxisOrder, Collable, Points, Resolution, Resolver, TileNet, TileNetError, TileSet, Vector};
+
+	struct Dot {
+		pts: Vec<(f32, f32)>,
+		pos: Vector,
+		mov: Vector,
+	}
+
+	impl Dot {
+		fn new(pos: Vector, mov: Vector) -> Dot {
+			Dot {
+				pts: vec![(0.0, 0.0)],
+				pos,
+				mov,
+			}
+		}
+	}
+
+	impl Collable<usize, ()> for Dot {
+		fn points<'a>(&'a self) -> Points<'a> {
+			Points::new(self.pos, &self.pts)
+		}
+
+		fn queued(&self) -> Vector {
+			self.mov
+		}
+
+		fn enqueue(&mut self, vector: Vector) {
+			self.mov = vector;
+		}
+
+		fn resolve<I>(&mut self, mut set: TileSet<usize, I>, _state: &mut ()) -> bool
+			where I: Iterator<Item = (i32, i32)>
+		{
+			if set.all(|x| *x == 0) {
+				self.pos += self.mov;
+				true
+			} else {
+				self.mov.scale(0.5);
+				false
+			}
+		}
+	}
 
 	#[test]
 	fn get() {
@@ -458,6 +704,14 @@ mod tests {
 			.count();
 	}
 
+	#[test]
+	fn zero_width_view_box() {
+		let map: TileNet<usize> = TileNet::from_iter(10, 1..101);
+		assert_eq!(map.view_box((4, 4, 0, 10)).next(), None);
+		assert_eq!(map.view_box((6, 4, 0, 10)).len(), 0);
+		assert_eq!(format!("{}", map.view_box((4, 4, 0, 10)).display_with(|_| 'x')), "");
+	}
+
 	#[test]
 	fn from_iter_with_remainder() {
 		let map: TileNet<usize> = TileNet::from_iter(10, (1..25));
@@ -473,6 +727,114 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn try_get_and_set() {
+		let mut map: TileNet<usize> = TileNet::new(4, 3);
+		assert_eq!(map.try_set(&5, (3, 2)), Ok(()));
+		assert_eq!(map.try_get((3, 2)), Ok(&5));
+		let error = TileNetError::OutOfBounds {
+			coord: (4, 0),
+			size: (4, 3),
+		};
+		assert_eq!(map.try_set(&5, (4, 0)), Err(error));
+		assert_eq!(map.try_get((4, 0)), Err(error));
+		assert!(map.try_get_mut((0, 3)).is_err());
+		let mut proxy = map.prepare();
+		assert!(proxy.try_set(&1, (0, 3)).is_err());
+		assert_eq!(proxy.try_set(&1, (1, 1)), Ok((1, 1, 1, 1)));
+	}
+
+	#[test]
+	fn try_set_regions() {
+		let mut map: TileNet<usize> = TileNet::new(4, 3);
+		assert_eq!(map.try_set_row(&1, 3),
+		           Err(TileNetError::OutOfBounds {
+			           coord: (0, 3),
+			           size: (4, 3),
+		           }));
+		assert!(map.try_set_col(&1, 4).is_err());
+		assert!(map.try_set_box_outline(&1, (0, 0), (4, 2)).is_err());
+		assert!(map.try_set_box_filled(&1, (0, 2), (3, 1)).is_err());
+		assert_eq!(map.take_dirty(), None);
+		assert_eq!(map.try_set_row(&1, 2), Ok(()));
+		assert_eq!(map.try_set_col(&2, 0), Ok(()));
+		assert_eq!(map.try_set_box_outline(&3, (1, 0), (3, 1)), Ok(()));
+		assert_eq!(map.get_raw(), &[2, 3, 3, 3,
+		                            2, 3, 3, 3,
+		                            2, 1, 1, 1]);
+	}
+
+	#[test]
+	fn try_from_iter() {
+		assert_eq!(TileNet::try_from_iter(0, 0..4).err(), Some(TileNetError::ZeroColumns));
+		let map = TileNet::try_from_iter(2, 0..4).unwrap();
+		assert_eq!(map.get_size(), (2, 2));
+		let lossy: TileNet<usize> = TileNet::lossy_from_iter(3, 0..4);
+		assert_eq!(lossy.get_raw(), &[0, 1, 2, 3, 0, 0]);
+	}
+
+	#[test]
+	fn try_views() {
+		let map = TileNet::sample();
+		assert_eq!(map.try_view_box((0, 10, 0, 10)).unwrap().count(), 100);
+		assert!(map.try_view_box((0, 11, 0, 10)).is_err());
+		assert_eq!(map.try_view_center((5, 5), (2, 3)).unwrap().count(), 24);
+		assert!(map.try_view_center((1, 5), (2, 3)).is_err());
+		assert!(map.try_view_center((9, 5), (2, 3)).is_err());
+		assert_eq!(map.saturating_view_center((1, 5), (2, 3)).count(), 18);
+		assert!(map.try_view_center_f32((-0.5, 5.0), (0, 0)).is_err());
+		assert!(map.try_view_center_f32((f32::NAN, 5.0), (0, 0)).is_err());
+		assert_eq!(map.try_view_center_f32((5.5, 5.0), (1, 1)).unwrap().count(), 4);
+		let far = u32::MAX as usize;
+		assert!(map.try_view_center((far, 5), (far, 0)).is_err());
+		assert_eq!(map.saturating_view_center((5, 5), (usize::MAX, 1)).count(), 20);
+	}
+
+	#[test]
+	fn solve_resolution() {
+		let map = TileNet::sample();
+		let mut dot = Dot::new(Vector(1.5, 1.5), Vector(0.0, 6.0));
+		assert_eq!(dot.solve(&map, &mut ()),
+		           Resolution::Moved { by: Vector(0.0, 3.0) });
+		assert_eq!(dot.pos, Vector(1.5, 4.5));
+		let mut dot = Dot::new(Vector(1.5, 5.5), Vector(0.0, 1.0));
+		assert_eq!(dot.solve(&map, &mut ()), Resolution::Stuck);
+	}
+
+	#[test]
+	fn solve_axes() {
+		let map = TileNet::sample();
+		let mut dot = Dot::new(Vector(1.5, 3.5), Vector(2.0, 4.0));
+		let report = dot.solve_axes(&map, AxisOrder::XThenY, &mut ());
+		assert_eq!(report.x, Resolution::Moved { by: Vector(2.0, 0.0) });
+		assert!(!report.blocked_x());
+		assert!(report.blocked_y());
+		assert_eq!(dot.pos, Vector(3.5, 4.5));
+	}
+
+	#[test]
+	fn solve_with_resolver() {
+		let map = TileNet::sample();
+		let solid = |x: &usize| *x != 0;
+		let resolver = Resolver::BinarySearch {
+			max_iters: 30,
+			epsilon: 1e-4,
+		};
+		let mut dot = Dot::new(Vector(1.5, 1.5), Vector(0.0, 6.0));
+		assert!(dot.solve_with_resolver(&map, resolver, solid, &mut ()).is_moved());
+		assert!(dot.pos.1 < 5.0 && dot.pos.1 > 5.0 - 1e-3);
+		let mut dot = Dot::new(Vector(1.5, 1.5), Vector(0.0, 6.0));
+		assert!(dot.solve_with_resolver(&map, Resolver::Scale(0.5), solid, &mut ()).is_moved());
+		assert_eq!(dot.pos, Vector(1.5, 4.5));
+		let mut dot = Dot::new(Vector(1.5, 1.5), Vector(0.0, 2.0));
+		dot.solve_with_resolver(&map, Resolver::default(), solid, &mut ());
+		assert_eq!(dot.pos, Vector(1.5, 3.5));
+		// Starting inside a solid tile, not even the empty move is free
+		let mut dot = Dot::new(Vector(1.5, 6.5), Vector(0.0, -4.0));
+		assert_eq!(dot.solve_with_resolver(&map, resolver, solid, &mut ()), Resolution::Stuck);
+		assert_eq!(dot.pos, Vector(1.5, 6.5));
+	}
+
 	#[test]
 	fn collide_set() {
 		let map: TileNet<usize> = TileNet::from_iter(10,
@@ -0,0 +1,30 @@
+//! Drawing nets and bodies with `macroquad`
+//!
+//! Positions are in tiles and scaled by `tile_size` pixels, with tile (0, 0)
+//! at the origin. Combine with a `macroquad::camera::Camera2D` to scroll,
+//! and with `tile_net::Camera::visible_view` to only draw what is on screen.
+use macroquad::color::Color;
+use macroquad::shapes::draw_rectangle;
+use super::{Rect, TileView};
+
+/// Draw every tile of a view as a filled square
+///
+/// `color` picks the color of a tile, or `None` to leave it out.
+pub fn draw_view<T, F>(view: TileView<T>, tile_size: f32, mut color: F)
+	where F: FnMut(&T) -> Option<Color>
+{
+	for (tile, x, y) in view {
+		if let Some(color) = color(tile) {
+			draw_rectangle(x as f32 * tile_size, y as f32 * tile_size, tile_size, tile_size, color);
+		}
+	}
+}
+
+/// Draw a rectangle given in tiles, such as the `rect` of an `AabbCollider`
+pub fn draw_rect(rect: Rect, tile_size: f32, color: Color) {
+	draw_rectangle(rect.pos.0 * tile_size,
+	               rect.pos.1 * tile_size,
+	               rect.size.0 * tile_size,
+	               rect.size.1 * tile_size,
+	               color);
+}
@@ -0,0 +1,43 @@
+/// Create a `TileNet` from a grid of tiles, with rows separated by `;`
+///
+/// Every tile is a literal, like `1`, `-2.5`, `'#'` or `true`. For named tiles,
+/// see `TileNet::from_str_map`. The rows are checked like in
+/// `TileNet::from_rows`.
+///
+/// # Panics #
+/// Panics if the rows are not equally long.
+///
+/// ```
+/// #[macro_use]
+/// extern crate tile_net;
+/// fn main() {
+///     let net = tile_net![1 1 1;
+///                         1 0 1;
+///                         1 1 1];
+///     assert_eq!(net.get_size(), (3, 3));
+///     assert_eq!(net.get((1, 1)), Some(&0));
+/// }
+/// ```
+#[macro_export]
+macro_rules! tile_net {
+	($($($tile:literal)+);+ $(;)*) => {
+		$crate::TileNet::from_rows(vec![$(vec![$($tile),+]),+]).expect("tile_net! rows must be equally long")
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn literals() {
+		let net = tile_net![false true;
+		                    true false;];
+		assert_eq!(net.get_raw(), &[false, true, true, false]);
+		assert_eq!(tile_net![-1 2].get_raw(), &[-1, 2]);
+	}
+
+	#[test]
+	#[should_panic(expected = "tile_net! rows must be equally long")]
+	fn ragged_rows() {
+		tile_net![1 2; 3];
+	}
+}
@@ -0,0 +1,123 @@
+//! Vertex buffers for drawing a view of a net as textured quads
+//!
+//! Every vertex is four floats, `x, y, u, v`, with positions in tiles, so
+//! the buffers can be uploaded as they are and scaled by the tile size in
+//! the vertex shader. Quads are two triangles indexed counterclockwise in a
+//! y-down world, starting at the top-left corner.
+use super::TileView;
+use super::tiles::greedy_rects;
+
+/// Number of floats per vertex: position followed by texture coordinates
+pub const VERTEX_FLOATS: usize = 4;
+
+/// Interleaved vertices and triangle indices of a tile view
+///
+/// ```
+/// use tile_net::{Mesh, TileNet};
+/// let mut net: TileNet<usize> = TileNet::new(4, 4);
+/// net.set_box_filled(&1, (0, 0), (3, 1));
+/// net.set(&2, (2, 3));
+/// // Tile 1 is the left half of the texture, tile 2 the right half, 0 is empty
+/// let uv = |x: &usize| match *x {
+///     1 => Some([0.0, 0.0, 0.5, 1.0]),
+///     2 => Some([0.5, 0.0, 1.0, 1.0]),
+///     _ => None,
+/// };
+/// assert_eq!(Mesh::from_view(net.view_all(), uv).quad_count(), 9);
+/// let mesh = Mesh::greedy_from_view(net.view_all(), uv);
+/// assert_eq!(mesh.quad_count(), 2);
+/// assert_eq!(mesh.indices.len(), 2 * 6);
+/// // The first quad spans the 4 by 2 block of tile 1
+/// assert_eq!(&mesh.vertices[8..12], &[4.0, 2.0, 2.0, 2.0]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+	/// `VERTEX_FLOATS` floats per vertex, four vertices per quad
+	pub vertices: Vec<f32>,
+	/// Three indices into the vertices per triangle, two triangles per quad
+	pub indices: Vec<u32>,
+}
+
+impl Mesh {
+	/// Create one quad per tile that `uv` maps to a texture rectangle
+	///
+	/// `uv` gives the texture rectangle of a tile as `[u0, v0, u1, v1]`, where
+	/// (u0, v0) maps to the top-left corner of the tile, or `None` to leave
+	/// the tile out.
+	pub fn from_view<T, F>(view: TileView<T>, mut uv: F) -> Mesh
+		where F: FnMut(&T) -> Option<[f32; 4]>
+	{
+		let mut mesh = Mesh::default();
+		for (tile, x, y) in view {
+			if let Some(rect) = uv(tile) {
+				mesh.push_quad((x as f32, y as f32), (1, 1), rect);
+			}
+		}
+		mesh
+	}
+
+	/// Create quads covering the tiles that `uv` maps, merging equal neighbours
+	///
+	/// Adjacent tiles with the same texture rectangle are merged into as few
+	/// rectangular quads as the greedy algorithm finds, which cuts the vertex
+	/// count of large floors and walls dramatically. The texture rectangle
+	/// repeats once per tile of a merged quad, so its coordinates run past
+	/// (u1, v1): sample with a repeating texture, or wrap the coordinates into
+	/// the rectangle in the fragment shader when drawing from an atlas.
+	pub fn greedy_from_view<T, F>(view: TileView<T>, mut uv: F) -> Mesh
+		where F: FnMut(&T) -> Option<[f32; 4]>
+	{
+		let mut cells = vec![];
+		let (mut origin, mut width) = (None, 0);
+		for (tile, x, y) in view {
+			let origin = *origin.get_or_insert((x, y));
+			if y == origin.1 {
+				width += 1;
+			}
+			cells.push(uv(tile));
+		}
+		let mut mesh = Mesh::default();
+		if let Some(origin) = origin {
+			for (x, y, w, h, rect) in greedy_rects(cells, width) {
+				mesh.push_quad(((origin.0 + x) as f32, (origin.1 + y) as f32), (w, h), rect);
+			}
+		}
+		mesh
+	}
+
+	/// Get the number of quads
+	pub fn quad_count(&self) -> usize {
+		self.indices.len() / 6
+	}
+
+	// Add a quad of `size` tiles with the texture rectangle repeating per tile
+	fn push_quad(&mut self, position: (f32, f32), size: (usize, usize), rect: [f32; 4]) {
+		let first = (self.vertices.len() / VERTEX_FLOATS) as u32;
+		let (w, h) = (size.0 as f32, size.1 as f32);
+		let (u, v) = ((rect[2] - rect[0]) * w, (rect[3] - rect[1]) * h);
+		for &(dx, dy) in &[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)] {
+			self.vertices.extend_from_slice(&[position.0 + dx * w, position.1 + dy * h, rect[0] + dx * u, rect[1] + dy * v]);
+		}
+		self.indices.extend_from_slice(&[first, first + 1, first + 2, first + 2, first + 3, first]);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::TileNet;
+
+	#[test]
+	fn greedy_merges_clipped_views() {
+		let net: TileNet<usize> = TileNet::from_iter(10, (0..100).map(|x| x / 50));
+		let uv = |x: &usize| Some([*x as f32, 0.0, *x as f32 + 1.0, 1.0]);
+		let mesh = Mesh::greedy_from_view(net.view_box((8, 20, 3, 7)), uv);
+		assert_eq!(mesh.quad_count(), 2);
+		assert_eq!(&mesh.vertices[..4], &[8.0, 3.0, 0.0, 0.0]);
+		assert_eq!(&mesh.vertices[16..20], &[8.0, 5.0, 1.0, 0.0]);
+		assert_eq!(&mesh.vertices[24..28], &[10.0, 7.0, 3.0, 2.0]);
+		assert_eq!(&mesh.indices[6..], &[4, 5, 6, 6, 7, 4]);
+		assert_eq!(Mesh::greedy_from_view(net.view_box((3, 3, 0, 10)), uv), Mesh::default());
+		assert_eq!(Mesh::from_view(net.view_box((8, 20, 3, 7)), uv).quad_count(), 8);
+	}
+}
@@ -0,0 +1,120 @@
+use std::ops::Deref;
+use super::{Anchor, TileNet};
+
+/// A `TileNet` with a second value per tile, such as health or ownership
+///
+/// The metadata lives in its own net of the same size, so tiles stay small
+/// and collision code keeps working on plain tiles. Every method changing the
+/// size changes both, keeping a tile and its metadata at the same coordinate.
+/// Reading tiles goes through `Deref`, like with `JournaledTileNet`.
+///
+/// ```
+/// use tile_net::{Anchor, TileNet};
+/// let mut net = TileNet::<usize>::new(4, 4).with_metadata::<u8>();
+/// net.set(&1, (1, 1));
+/// *net.get_meta_mut((1, 1)).unwrap() = 100;
+/// net.resize_with((6, 6), Anchor::Center, 0, 0);
+/// assert_eq!(net.get((2, 2)), Some(&1));
+/// assert_eq!(net.get_meta((2, 2)), Some(&100));
+/// assert_eq!(net.meta().get_size(), (6, 6));
+/// ```
+#[derive(Clone, Debug)]
+pub struct MetaTileNet<T, M> {
+	tiles: TileNet<T>,
+	meta: TileNet<M>,
+}
+
+impl<T> TileNet<T> {
+	/// Pair every tile with Default-created metadata, see `MetaTileNet`
+	pub fn with_metadata<M>(self) -> MetaTileNet<T, M>
+		where M: Clone + Default
+	{
+		let meta = TileNet::new(self.get_size().0, self.get_size().1);
+		MetaTileNet { tiles: self, meta }
+	}
+}
+
+impl<T, M> MetaTileNet<T, M> {
+	/// Get the metadata of a tile
+	pub fn get_meta(&self, p: (usize, usize)) -> Option<&M> {
+		self.meta.get(p)
+	}
+
+	/// Get the metadata of a tile for editing
+	pub fn get_meta_mut(&mut self, p: (usize, usize)) -> Option<&mut M> {
+		self.meta.get_mut(p)
+	}
+
+	/// Get the net of metadata
+	pub fn meta(&self) -> &TileNet<M> {
+		&self.meta
+	}
+
+	/// Edit the tiles freely, see `edit_meta` for the metadata
+	///
+	/// # Panics #
+	/// Panics if `f` changes the size of the net; use `resize_with` instead.
+	pub fn edit<F>(&mut self, f: F)
+		where F: FnOnce(&mut TileNet<T>)
+	{
+		let size = self.tiles.get_size();
+		f(&mut self.tiles);
+		assert_eq!(size, self.tiles.get_size(), "resize a MetaTileNet as a whole");
+	}
+
+	/// Edit the metadata freely
+	///
+	/// # Panics #
+	/// Panics if `f` changes the size of the net; use `resize_with` instead.
+	pub fn edit_meta<F>(&mut self, f: F)
+		where F: FnOnce(&mut TileNet<M>)
+	{
+		let size = self.meta.get_size();
+		f(&mut self.meta);
+		assert_eq!(size, self.meta.get_size(), "resize a MetaTileNet as a whole");
+	}
+
+	/// Split into the tiles and the metadata
+	pub fn into_parts(self) -> (TileNet<T>, TileNet<M>) {
+		(self.tiles, self.meta)
+	}
+}
+
+impl<T, M> MetaTileNet<T, M>
+	where T: Clone,
+	      M: Clone
+{
+	/// Set a single grid point, keeping its metadata
+	pub fn set(&mut self, value: &T, p: (usize, usize)) {
+		self.tiles.set(value, p);
+	}
+
+	/// Resize both nets around an anchor, see `TileNet::resize_with`
+	pub fn resize_with(&mut self, size: (usize, usize), anchor: Anchor, fill: T, meta_fill: M) -> (isize, isize) {
+		self.meta.resize_with(size, anchor, meta_fill);
+		self.tiles.resize_with(size, anchor, fill)
+	}
+}
+
+impl<T, M> Deref for MetaTileNet<T, M> {
+	type Target = TileNet<T>;
+	fn deref(&self) -> &TileNet<T> {
+		&self.tiles
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[should_panic(expected = "resize a MetaTileNet as a whole")]
+	fn edit_keeps_the_size() {
+		let mut net = TileNet::<usize>::new(3, 2).with_metadata::<bool>();
+		net.edit(|net| net.fill(&2));
+		net.edit_meta(|meta| meta.set(&true, (2, 1)));
+		assert_eq!(net.get_raw(), &[2; 6]);
+		assert_eq!(net.meta().get_raw(), &[false, false, false, false, false, true]);
+		net.edit(|net| net.resize((4, 4)));
+	}
+}
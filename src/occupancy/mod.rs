@@ -0,0 +1,196 @@
+use super::{OutOfBounds, Rect, TileKind, TileNet, Vector};
+
+/// Summed-area table over the solid tiles of a `TileNet`
+///
+/// Answers "how many solid tiles are inside this box" in constant time, which
+/// makes it a cheap first pass before running any supercover. The index is a
+/// copy: rebuild it after editing the net or its out-of-bounds policy.
+///
+/// ```
+/// use tile_net::{GroupSweep, Occupancy, Rect, TileNet, Vector};
+/// let mut net: TileNet<usize> = TileNet::new(20, 10);
+/// net.set_col(&1, 15);
+/// let index = Occupancy::new(&net);
+/// let squad = [Rect::new(Vector(1.0, 1.0), Vector(1.0, 1.0)),
+///              Rect::new(Vector(3.0, 4.0), Vector(1.0, 1.0))];
+/// // Open space: no member needs a supercover
+/// assert_eq!(index.group_sweep(&squad, Vector(5.0, 0.0)), GroupSweep::Clear);
+/// // Towards the wall: every member's own corridor hits it
+/// assert_eq!(index.group_sweep(&squad, Vector(13.5, 0.0)), GroupSweep::Blocked(vec![0, 1]));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Occupancy {
+	cols: usize,
+	rows: usize,
+	sums: Vec<u32>,
+	// Whether anything outside of the net may be solid
+	border: bool,
+}
+
+/// Outcome of a group sweep
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GroupSweep {
+	/// No member can touch a solid tile during the movement
+	Clear,
+	/// Indices of the members whose own swept box contains solid tiles
+	///
+	/// Only these need a precise supercover test.
+	Blocked(Vec<usize>),
+}
+
+impl Occupancy {
+	/// Build the index from every solid tile
+	pub fn new<T>(net: &TileNet<T>) -> Occupancy
+		where T: TileKind
+	{
+		Occupancy::with_predicate(net, TileKind::is_solid)
+	}
+
+	/// Build the index from every tile for which `is_solid` returns true
+	pub fn with_predicate<T, F>(net: &TileNet<T>, is_solid: F) -> Occupancy
+		where F: Fn(&T) -> bool
+	{
+		let (cols, rows) = net.get_size();
+		let stride = cols + 1;
+		let mut sums = vec![0u32; stride * (rows + 1)];
+		let tiles = net.get_raw();
+		for y in 0..rows {
+			let mut row = 0;
+			for x in 0..cols {
+				if is_solid(&tiles[x + y * cols]) {
+					row += 1;
+				}
+				sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+			}
+		}
+		let border = match *net.get_out_of_bounds() {
+			OutOfBounds::Empty => false,
+			OutOfBounds::Solid => true,
+			OutOfBounds::Custom(ref tile) => is_solid(tile),
+			OutOfBounds::Wrap => sums[sums.len() - 1] > 0,
+		};
+		Occupancy { cols, rows, sums, border }
+	}
+
+	/// Count the solid tiles inside a box
+	///
+	/// The box is given as (left, right, top, bottom) with exclusive right and
+	/// bottom, like `TileNet::view_box`. Parts outside the net count as empty.
+	pub fn count(&self, rectangle: (usize, usize, usize, usize)) -> u32 {
+		let right = rectangle.1.min(self.cols);
+		let bottom = rectangle.3.min(self.rows);
+		if rectangle.0 >= right || rectangle.2 >= bottom {
+			return 0;
+		}
+		let stride = self.cols + 1;
+		let at = |x: usize, y: usize| self.sums[y * stride + x];
+		at(right, bottom) + at(rectangle.0, rectangle.2) - at(rectangle.0, bottom) -
+		at(right, rectangle.2)
+	}
+
+	/// Check whether any solid tile overlaps a world-space rectangle
+	///
+	/// Edges are treated inclusively, so a rectangle ending exactly on a tile
+	/// boundary also tests the tile beyond it. This errs on the side of caution.
+	/// Parts outside of the net follow the out-of-bounds policy of the net:
+	/// they are solid under `Solid`, under `Custom` with a solid tile, and
+	/// under `Wrap` as soon as the net has any solid tile.
+	pub fn any_in(&self, rect: Rect) -> bool {
+		let min = rect.pos;
+		let max = rect.pos + rect.size;
+		if self.border &&
+		   (min.0 < 0.0 || min.1 < 0.0 || max.0 >= self.cols as f32 || max.1 >= self.rows as f32) {
+			return true;
+		}
+		if max.0 < 0.0 || max.1 < 0.0 {
+			return false;
+		}
+		let clamp = |x: f32, len: usize| x.floor().clamp(0.0, len as f32) as usize;
+		let (left, right) = (clamp(min.0, self.cols), clamp(max.0, self.cols) + 1);
+		let (top, bottom) = (clamp(min.1, self.rows), clamp(max.1, self.rows) + 1);
+		self.count((left, right, top, bottom)) > 0
+	}
+
+	/// Test a group of boxes moving by the same vector
+	///
+	/// First the box covering the whole group over the whole movement is
+	/// tested. Only if that corridor contains solid tiles are the members
+	/// tested one by one.
+	pub fn group_sweep(&self, aabbs: &[Rect], movement: Vector) -> GroupSweep {
+		let swept: Vec<Rect> = aabbs.iter().map(|x| sweep(*x, movement)).collect();
+		let group = match swept.split_first() {
+			Some((first, rest)) => rest.iter().fold(*first, |acc, x| union(acc, *x)),
+			None => return GroupSweep::Clear,
+		};
+		if !self.any_in(group) {
+			return GroupSweep::Clear;
+		}
+		let blocked: Vec<usize> = swept.iter()
+			.enumerate()
+			.filter(|x| self.any_in(*x.1))
+			.map(|x| x.0)
+			.collect();
+		if blocked.is_empty() {
+			GroupSweep::Clear
+		} else {
+			GroupSweep::Blocked(blocked)
+		}
+	}
+}
+
+fn union(a: Rect, b: Rect) -> Rect {
+	let min = Vector(a.pos.0.min(b.pos.0), a.pos.1.min(b.pos.1));
+	let max = Vector((a.pos.0 + a.size.0).max(b.pos.0 + b.size.0),
+	                 (a.pos.1 + a.size.1).max(b.pos.1 + b.size.1));
+	Rect::new(min, max - min)
+}
+
+fn sweep(rect: Rect, movement: Vector) -> Rect {
+	union(rect, Rect::new(rect.pos + movement, rect.size))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn count() {
+		let net = TileNet::sample();
+		let index = Occupancy::new(&net);
+		assert_eq!(index.count((0, 10, 0, 10)), 50);
+		assert_eq!(index.count((0, 10, 0, 5)), 0);
+		assert_eq!(index.count((2, 4, 4, 7)), 4);
+		assert_eq!(index.count((8, 30, 9, 30)), 2);
+		assert_eq!(index.count((5, 5, 0, 10)), 0);
+	}
+
+	#[test]
+	fn group_sweep() {
+		let net = TileNet::sample();
+		let index = Occupancy::new(&net);
+		let group = [Rect::new(Vector(0.2, 0.2), Vector(0.5, 0.5)),
+		             Rect::new(Vector(6.2, 0.2), Vector(0.5, 0.5)),
+		             Rect::new(Vector(3.0, 3.0), Vector(0.5, 0.5))];
+		assert_eq!(index.group_sweep(&group, Vector(1.0, 1.0)), GroupSweep::Clear);
+		assert_eq!(index.group_sweep(&group, Vector(0.0, 1.5)), GroupSweep::Blocked(vec![2]));
+		assert_eq!(index.group_sweep(&[], Vector(0.0, 100.0)), GroupSweep::Clear);
+		assert!(!index.any_in(Rect::new(Vector(-5.0, 6.0), Vector(1.0, 1.0))));
+		assert!(index.any_in(Rect::new(Vector(-5.0, 6.0), Vector(5.0, 1.0))));
+		let far = [Rect::new(Vector(0.2, 0.2), Vector(0.5, 0.5))];
+		assert_eq!(index.group_sweep(&far, Vector(1e30, 0.0)), GroupSweep::Clear);
+	}
+
+	#[test]
+	fn border() {
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		let squad = [Rect::new(Vector(1.0, 1.0), Vector(1.0, 1.0))];
+		assert_eq!(Occupancy::new(&net).group_sweep(&squad, Vector(-2.0, 0.0)), GroupSweep::Clear);
+		net.set_out_of_bounds(OutOfBounds::Solid);
+		let index = Occupancy::new(&net);
+		assert_eq!(index.group_sweep(&squad, Vector(-2.0, 0.0)), GroupSweep::Blocked(vec![0]));
+		assert_eq!(index.group_sweep(&squad, Vector(1.5, 1.5)), GroupSweep::Clear);
+		assert_eq!(index.group_sweep(&squad, Vector(1e30, 0.0)), GroupSweep::Blocked(vec![0]));
+		net.set_out_of_bounds(OutOfBounds::Custom(0));
+		assert_eq!(Occupancy::new(&net).group_sweep(&squad, Vector(-2.0, 0.0)), GroupSweep::Clear);
+	}
+}
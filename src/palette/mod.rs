@@ -0,0 +1,255 @@
+use std::ops::Deref;
+use super::{TileNet, TileNetError};
+
+/// Index type of a `PalettedTileNet`, implemented for `u8` and `u16`
+pub trait PaletteIndex: Copy + Default {
+	/// Highest number of distinct tiles
+	const CAPACITY: usize;
+
+	/// Convert a palette position, which is below `CAPACITY`
+	fn from_usize(index: usize) -> Self;
+
+	/// Get the palette position
+	fn to_usize(self) -> usize;
+}
+
+macro_rules! palette_index {
+	($($t:ty),*) => {
+		$(
+			impl PaletteIndex for $t {
+				const CAPACITY: usize = <$t>::MAX as usize + 1;
+
+				fn from_usize(index: usize) -> $t {
+					index as $t
+				}
+
+				fn to_usize(self) -> usize {
+					self as usize
+				}
+			}
+		)*
+	};
+}
+
+palette_index!(u8, u16);
+
+/// A net storing every distinct tile once, with a small index per tile
+///
+/// Large tile structs with few distinct values take up the size of an index
+/// per tile, plus the size of the distinct tiles. Reading looks the index up
+/// in the palette, and setting adds unseen tiles to it. The palette is
+/// searched linearly, so keep it small.
+///
+/// The indices form a `TileNet<I>` available through `Deref`, so collision
+/// sets and other queries work on them directly. Compare against an index
+/// from `index_of`, or look tiles up with `resolve`.
+///
+/// ```
+/// use tile_net::{PalettedTileNet, TileNet};
+/// #[derive(Clone, Debug, Default, PartialEq)]
+/// struct Tile {
+///     name: String,
+///     friction: f32,
+/// }
+/// let ice = Tile { name: "ice".into(), friction: 0.1 };
+/// let mut net: PalettedTileNet<Tile, u8> = PalettedTileNet::new(100, 100);
+/// net.set_row(&ice, 50);
+/// assert_eq!(net.get((3, 50)), Some(&ice));
+/// assert_eq!(net.palette().len(), 2);
+/// let solid = net.index_of(&ice).unwrap();
+/// assert_eq!(net.collide_set((0..100).map(|y| (3, y))).filter(|x| **x == solid).count(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PalettedTileNet<T, I = u16> {
+	indices: TileNet<I>,
+	palette: Vec<T>,
+}
+
+impl<T, I> PalettedTileNet<T, I>
+	where T: Clone + Default + PartialEq,
+	      I: PaletteIndex
+{
+	/// Create a new net of the size (cols, rows) filled with Default
+	pub fn new(cols: usize, rows: usize) -> PalettedTileNet<T, I> {
+		PalettedTileNet {
+			indices: TileNet::new(cols, rows),
+			palette: vec![T::default()],
+		}
+	}
+}
+
+impl<T, I> PalettedTileNet<T, I>
+	where T: Clone + PartialEq,
+	      I: PaletteIndex
+{
+	/// Convert a net, failing with `PaletteFull` if it has too many distinct tiles
+	pub fn from_net(net: &TileNet<T>) -> Result<PalettedTileNet<T, I>, TileNetError> {
+		let mut palette = vec![];
+		let mut indices = Vec::with_capacity(net.get_raw().len());
+		for tile in net.get_raw() {
+			indices.push(find_or_add(&mut palette, tile)?);
+		}
+		Ok(PalettedTileNet {
+			indices: TileNet::from_vec(net.get_size().0, indices)?,
+			palette,
+		})
+	}
+
+	/// Convert into a plain net
+	pub fn to_tile_net(&self) -> TileNet<T> {
+		self.indices.map_ref(|x| self.palette[x.to_usize()].clone())
+	}
+
+	/// Get a tile
+	pub fn get(&self, p: (usize, usize)) -> Option<&T> {
+		self.indices.get(p).map(|x| self.resolve(*x))
+	}
+
+	/// Get the tile of an index
+	///
+	/// # Panics #
+	/// Panics if the index was not produced by this net.
+	pub fn resolve(&self, index: I) -> &T {
+		&self.palette[index.to_usize()]
+	}
+
+	/// Get the index of a tile, if it is in the palette
+	pub fn index_of(&self, tile: &T) -> Option<I> {
+		self.palette.iter().position(|x| x == tile).map(I::from_usize)
+	}
+
+	/// Get the distinct tiles, in order of their indices
+	pub fn palette(&self) -> &[T] {
+		&self.palette
+	}
+
+	/// Set a single grid point, reporting out-of-bounds coordinates and a full palette
+	pub fn try_set(&mut self, value: &T, p: (usize, usize)) -> Result<(), TileNetError> {
+		self.indices.try_get(p)?;
+		let index = find_or_add(&mut self.palette, value)?;
+		self.indices.set(&index, p);
+		Ok(())
+	}
+
+	/// Set a single grid point
+	///
+	/// Out-of-bounds coordinates are silently ignored, see `try_set`.
+	///
+	/// # Panics #
+	/// Panics if the palette is full.
+	pub fn set(&mut self, value: &T, p: (usize, usize)) {
+		if let Err(TileNetError::PaletteFull { .. }) = self.try_set(value, p) {
+			panic!("the palette of the net is full");
+		}
+	}
+
+	/// Set a row
+	///
+	/// # Panics #
+	/// Panics if the palette is full.
+	pub fn set_row(&mut self, value: &T, row: usize) {
+		if let Some(index) = self.add(value) {
+			self.indices.set_row(&index, row);
+		}
+	}
+
+	/// Set a column
+	///
+	/// # Panics #
+	/// Panics if the palette is full.
+	pub fn set_col(&mut self, value: &T, col: usize) {
+		if let Some(index) = self.add(value) {
+			self.indices.set_col(&index, col);
+		}
+	}
+
+	/// Set every tile, leaving a palette of just that tile
+	pub fn fill(&mut self, value: &T) {
+		self.palette = vec![value.clone()];
+		self.indices.fill(&I::from_usize(0));
+	}
+
+	/// Drop tiles no longer used from the palette, renumbering the rest
+	pub fn compact(&mut self) {
+		let mut used = vec![false; self.palette.len()];
+		for index in self.indices.get_raw() {
+			used[index.to_usize()] = true;
+		}
+		let mut remap = vec![I::default(); self.palette.len()];
+		let mut palette = vec![];
+		for (old, tile) in self.palette.drain(..).enumerate() {
+			if used[old] {
+				remap[old] = I::from_usize(palette.len());
+				palette.push(tile);
+			}
+		}
+		self.palette = palette;
+		for index in self.indices.get_raw_mut() {
+			*index = remap[index.to_usize()];
+		}
+	}
+
+	// Add a tile to the palette for a bulk edit. Returns `None` for an empty
+	// net, which has nothing to set.
+	fn add(&mut self, value: &T) -> Option<I> {
+		if self.indices.get_raw().is_empty() {
+			return None;
+		}
+		match find_or_add(&mut self.palette, value) {
+			Ok(index) => Some(index),
+			Err(_) => panic!("the palette of the net is full"),
+		}
+	}
+}
+
+impl<T, I> Deref for PalettedTileNet<T, I> {
+	type Target = TileNet<I>;
+	fn deref(&self) -> &TileNet<I> {
+		&self.indices
+	}
+}
+
+fn find_or_add<T, I>(palette: &mut Vec<T>, tile: &T) -> Result<I, TileNetError>
+	where T: Clone + PartialEq,
+	      I: PaletteIndex
+{
+	if let Some(index) = palette.iter().position(|x| x == tile) {
+		return Ok(I::from_usize(index));
+	}
+	if palette.len() == I::CAPACITY {
+		return Err(TileNetError::PaletteFull { capacity: I::CAPACITY });
+	}
+	palette.push(tile.clone());
+	Ok(I::from_usize(palette.len() - 1))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn palette_fills_up() {
+		let mut net: PalettedTileNet<u32, u8> = PalettedTileNet::new(20, 20);
+		for value in 1..256 {
+			net.set(&value, (value as usize % 20, value as usize / 20));
+		}
+		assert_eq!(net.try_set(&300, (0, 0)), Err(TileNetError::PaletteFull { capacity: 256 }));
+		assert_eq!(net.try_set(&300, (30, 0)), Err(TileNetError::OutOfBounds { coord: (30, 0), size: (20, 20) }));
+		net.set_row(&7, 0);
+		net.compact();
+		assert_eq!(net.palette().len(), 256 - 18);
+		net.set(&300, (0, 0));
+		assert_eq!(net.get((0, 0)), Some(&300));
+		assert_eq!(net.to_tile_net().get((5, 3)), Some(&65));
+	}
+
+	#[test]
+	fn from_net() {
+		let net = TileNet::sample();
+		let paletted: PalettedTileNet<usize, u8> = PalettedTileNet::from_net(&net).unwrap();
+		assert_eq!(paletted.palette().len(), 51);
+		assert_eq!(paletted.to_tile_net().get_raw(), net.get_raw());
+		assert_eq!(PalettedTileNet::<usize, u8>::from_net(&TileNet::from_iter(300, 0..300)).err(),
+		           Some(TileNetError::PaletteFull { capacity: 256 }));
+	}
+}
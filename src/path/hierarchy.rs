@@ -0,0 +1,410 @@
+use std::cmp::{min, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+use super::Path;
+use super::super::{Span, TileNet};
+
+// Abstract nodes standing for the start and goal of a query
+const START: usize = usize::MAX;
+const GOAL: usize = usize::MAX - 1;
+
+/// Precomputed entrance graph for fast, near-optimal paths on large maps
+///
+/// The net is split into square clusters. Where two neighbouring clusters
+/// share a run of passable border tiles, the middle of the run becomes an
+/// entrance on both sides, and the distances between the entrances of every
+/// cluster are computed once. A query then searches this small graph and
+/// only walks the tiles of the clusters along the way, so its cost depends
+/// on the length of the path instead of the size of the map.
+///
+/// Moves are orthogonal and every passable tile costs 1, like `astar` with
+/// a uniform cost. Paths go through the middle of border runs, so they can be
+/// a few steps longer than the shortest; pass them through `smooth` to
+/// straighten them. After editing the net, hand the span from
+/// `TileNet::take_dirty` to `update`, which rebuilds only the clusters near
+/// the change. The same `passable` function must be used for every call.
+///
+/// ```
+/// use tile_net::TileNet;
+/// use tile_net::path::Hierarchy;
+/// let mut net: TileNet<usize> = TileNet::new(64, 64);
+/// net.set_col(&1, 20);
+/// net.set(&0, (20, 50));
+/// let passable = |x: &usize| *x == 0;
+/// let mut hierarchy = Hierarchy::new(&net, 16, passable);
+/// let route = hierarchy.find_path(&net, (2, 2), (60, 2), passable).unwrap();
+/// assert_eq!(route.tiles.first(), Some(&(2, 2)));
+/// assert_eq!(route.tiles.last(), Some(&(60, 2)));
+/// assert!(route.tiles.contains(&(20, 50)));
+///
+/// // Close the gap and open another one
+/// net.take_dirty();
+/// net.set(&1, (20, 50));
+/// net.set(&0, (20, 10));
+/// hierarchy.update(&net, net.dirty().unwrap(), passable);
+/// let route = hierarchy.find_path(&net, (2, 2), (60, 2), passable).unwrap();
+/// assert!(route.tiles.contains(&(20, 10)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Hierarchy {
+	cluster_size: usize,
+	size: (usize, usize),
+	clusters: Vec<Cluster>,
+}
+
+// Entrances of a cluster as tile indices, the tiles they lead to in
+// neighbouring clusters, and the distances between every two entrances.
+#[derive(Clone, Debug, Default)]
+struct Cluster {
+	entrances: Vec<usize>,
+	links: Vec<Vec<usize>>,
+	distances: Vec<u32>,
+}
+
+// Distances and predecessors from a search limited to one cluster
+struct Local {
+	cols: usize,
+	rect: (usize, usize, usize, usize),
+	distances: Vec<u32>,
+	came_from: Vec<usize>,
+}
+
+impl Hierarchy {
+	/// Build the entrance graph with clusters of `cluster_size` by `cluster_size` tiles
+	///
+	/// # Panics #
+	/// Panics if `cluster_size` is 0.
+	pub fn new<T, F>(net: &TileNet<T>, cluster_size: usize, mut passable: F) -> Hierarchy
+		where F: FnMut(&T) -> bool
+	{
+		assert!(cluster_size > 0, "clusters must be at least one tile wide");
+		let size = net.get_size();
+		let count = size.0.div_ceil(cluster_size) * size.1.div_ceil(cluster_size);
+		let mut hierarchy = Hierarchy {
+			cluster_size,
+			size,
+			clusters: vec![Cluster::default(); count],
+		};
+		for cluster in 0..count {
+			hierarchy.rebuild(net, cluster, &mut passable);
+		}
+		hierarchy
+	}
+
+	/// Rebuild the clusters affected by a change to the tiles in `span`
+	///
+	/// If the net was resized since, everything is rebuilt.
+	pub fn update<T, F>(&mut self, net: &TileNet<T>, span: Span, mut passable: F)
+		where F: FnMut(&T) -> bool
+	{
+		if net.get_size() != self.size {
+			*self = Hierarchy::new(net, self.cluster_size, passable);
+			return;
+		}
+		// Entrances depend on the tiles just across the border too
+		let (columns, rows) = self.clusters_across();
+		let first = (span.0.saturating_sub(1) / self.cluster_size, span.1.saturating_sub(1) / self.cluster_size);
+		let last = (min((span.2 + 1) / self.cluster_size, columns - 1),
+		            min((span.3 + 1) / self.cluster_size, rows - 1));
+		for y in first.1..last.1 + 1 {
+			for x in first.0..last.0 + 1 {
+				self.rebuild(net, x + y * columns, &mut passable);
+			}
+		}
+	}
+
+	/// Get the number of entrances over all clusters
+	pub fn entrance_count(&self) -> usize {
+		self.clusters.iter().map(|x| x.entrances.len()).sum()
+	}
+
+	/// Find a path from `start` to `goal`
+	///
+	/// Like `astar`, the start may be blocked. Returns `None` if the goal can
+	/// not be reached, either end is outside the net, or the net was resized
+	/// without calling `update`.
+	pub fn find_path<T, F>(&self,
+	                       net: &TileNet<T>,
+	                       start: (usize, usize),
+	                       goal: (usize, usize),
+	                       mut passable: F)
+	                       -> Option<Path>
+		where F: FnMut(&T) -> bool
+	{
+		let cols = self.size.0;
+		if net.get_size() != self.size || start.0 >= cols || goal.0 >= cols || start.1 >= self.size.1 ||
+		   goal.1 >= self.size.1 || !passable(net.get(goal)?) {
+			return None;
+		}
+		let (start, goal) = (start.0 + start.1 * cols, goal.0 + goal.1 * cols);
+		let (start_cluster, goal_cluster) = (self.cluster_of(start), self.cluster_of(goal));
+		let from_start = self.local(net, start_cluster, start, &mut passable);
+		if start_cluster == goal_cluster && from_start.distance(goal).is_some() {
+			return Some(self.path(vec![from_start.walk(goal)]));
+		}
+		// Moves are symmetric, so a search from the goal gives the distances to it
+		let to_goal = self.local(net, goal_cluster, goal, &mut passable);
+
+		let mut best: BTreeMap<usize, (u32, usize)> = BTreeMap::new();
+		let mut open = BinaryHeap::new();
+		best.insert(START, (0, START));
+		open.push(Reverse((self.estimate(start, goal), 0, START)));
+		while let Some(Reverse((_, spent, node))) = open.pop() {
+			if node == GOAL {
+				break;
+			}
+			if best.get(&node).is_some_and(|x| x.0 < spent) {
+				continue;
+			}
+			let mut edges = vec![];
+			if node == START {
+				let cluster = &self.clusters[start_cluster];
+				edges.extend(cluster.entrances.iter().filter_map(|&x| from_start.distance(x).map(|d| (x, d))));
+			} else {
+				let cluster_index = self.cluster_of(node);
+				let cluster = &self.clusters[cluster_index];
+				let entrance = cluster.entrances.iter().position(|x| *x == node).expect("nodes are entrances");
+				let count = cluster.entrances.len();
+				for (other, &distance) in cluster.entrances.iter().zip(&cluster.distances[entrance * count..]) {
+					if distance != u32::MAX && *other != node {
+						edges.push((*other, distance));
+					}
+				}
+				edges.extend(cluster.links[entrance].iter().map(|&x| (x, 1)));
+				if cluster_index == goal_cluster {
+					edges.extend(to_goal.distance(node).map(|x| (GOAL, x)));
+				}
+			}
+			for (next, distance) in edges {
+				let total = spent + distance;
+				if best.get(&next).map_or(true, |x| total < x.0) {
+					best.insert(next, (total, node));
+					let estimate = if next == GOAL { 0 } else { self.estimate(next, goal) };
+					open.push(Reverse((total + estimate, total, next)));
+				}
+			}
+		}
+
+		// Walk back over the entrances, then fill in the tiles between them
+		let mut nodes = vec![GOAL];
+		while *nodes.last().expect("starts with the goal") != START {
+			let node = *nodes.last().expect("starts with the goal");
+			nodes.push(best.get(&node)?.1);
+		}
+		nodes.reverse();
+		let mut legs = vec![from_start.walk(nodes[1])];
+		for pair in nodes[1..nodes.len() - 1].windows(2) {
+			let (from, to) = (pair[0], pair[1]);
+			if self.cluster_of(from) == self.cluster_of(to) {
+				legs.push(self.local(net, self.cluster_of(from), from, &mut passable).walk(to));
+			} else {
+				legs.push(vec![from, to]);
+			}
+		}
+		let mut last = to_goal.walk(nodes[nodes.len() - 2]);
+		last.reverse();
+		legs.push(last);
+		Some(self.path(legs))
+	}
+
+	// Number of clusters per row and per column
+	fn clusters_across(&self) -> (usize, usize) {
+		(self.size.0.div_ceil(self.cluster_size), self.size.1.div_ceil(self.cluster_size))
+	}
+
+	fn cluster_of(&self, index: usize) -> usize {
+		let (x, y) = (index % self.size.0, index / self.size.0);
+		x / self.cluster_size + y / self.cluster_size * self.clusters_across().0
+	}
+
+	// Tiles of a cluster as (x_start, x_stop, y_start, y_stop)
+	fn rect(&self, cluster: usize) -> (usize, usize, usize, usize) {
+		let columns = self.clusters_across().0;
+		let (x, y) = (cluster % columns * self.cluster_size, cluster / columns * self.cluster_size);
+		(x, min(x + self.cluster_size, self.size.0), y, min(y + self.cluster_size, self.size.1))
+	}
+
+	fn estimate(&self, from: usize, to: usize) -> u32 {
+		let cols = self.size.0;
+		((from % cols).abs_diff(to % cols) + (from / cols).abs_diff(to / cols)) as u32
+	}
+
+	// Join legs sharing their end tiles into one path
+	fn path(&self, legs: Vec<Vec<usize>>) -> Path {
+		let mut tiles: Vec<(usize, usize)> = vec![];
+		for leg in legs {
+			for index in leg {
+				let tile = (index % self.size.0, index / self.size.0);
+				if tiles.last() != Some(&tile) {
+					tiles.push(tile);
+				}
+			}
+		}
+		Path {
+			cost: tiles.len() as u32 - 1,
+			tiles,
+		}
+	}
+
+	// Breadth-first search from a tile, without leaving its cluster
+	fn local<T, F>(&self, net: &TileNet<T>, cluster: usize, source: usize, passable: &mut F) -> Local
+		where F: FnMut(&T) -> bool
+	{
+		let rect = self.rect(cluster);
+		let area = (rect.1 - rect.0) * (rect.3 - rect.2);
+		let cols = self.size.0;
+		let tiles = net.get_raw();
+		let mut search = Local {
+			cols,
+			rect,
+			distances: vec![u32::MAX; area],
+			came_from: vec![source; area],
+		};
+		let start = search.index(source);
+		search.distances[start] = 0;
+		let mut queue = VecDeque::new();
+		queue.push_back(source);
+		while let Some(index) = queue.pop_front() {
+			let (x, y) = (index % cols, index / cols);
+			let distance = search.distances[search.index(index)] + 1;
+			let neighbours = [(x > rect.0, index.wrapping_sub(1)),
+			                  (x + 1 < rect.1, index + 1),
+			                  (y > rect.2, index.wrapping_sub(cols)),
+			                  (y + 1 < rect.3, index + cols)];
+			for &(inside, next) in &neighbours {
+				if !inside {
+					continue;
+				}
+				let local = search.index(next);
+				if search.distances[local] == u32::MAX && passable(&tiles[next]) {
+					search.distances[local] = distance;
+					search.came_from[local] = index;
+					queue.push_back(next);
+				}
+			}
+		}
+		search
+	}
+
+	// Find the entrances of a cluster, their links and their distances
+	fn rebuild<T, F>(&mut self, net: &TileNet<T>, cluster: usize, passable: &mut F)
+		where F: FnMut(&T) -> bool
+	{
+		let (x0, x1, y0, y1) = self.rect(cluster);
+		let (cols, rows) = self.size;
+		let tiles = net.get_raw();
+		let mut open = |index: usize| passable(&tiles[index]);
+		let at = |x: usize, y: usize| x + y * cols;
+		// Pairs of (inside, outside) tiles along every border with a neighbour
+		let mut borders: Vec<Vec<(usize, usize)>> = vec![];
+		if x0 > 0 {
+			borders.push((y0..y1).map(|y| (at(x0, y), at(x0 - 1, y))).collect());
+		}
+		if x1 < cols {
+			borders.push((y0..y1).map(|y| (at(x1 - 1, y), at(x1, y))).collect());
+		}
+		if y0 > 0 {
+			borders.push((x0..x1).map(|x| (at(x, y0), at(x, y0 - 1))).collect());
+		}
+		if y1 < rows {
+			borders.push((x0..x1).map(|x| (at(x, y1 - 1), at(x, y1))).collect());
+		}
+		let mut links: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+		for border in borders {
+			let mut run = vec![];
+			for pair in border.into_iter().map(Some).chain(Some(None)) {
+				match pair {
+					Some(pair) if open(pair.0) && open(pair.1) => run.push(pair),
+					_ => {
+						if !run.is_empty() {
+							let (inside, outside) = run[run.len() / 2];
+							links.entry(inside).or_default().push(outside);
+							run.clear();
+						}
+					}
+				}
+			}
+		}
+		let entrances: Vec<usize> = links.keys().cloned().collect();
+		let mut distances = Vec::with_capacity(entrances.len() * entrances.len());
+		for &entrance in &entrances {
+			let search = self.local(net, cluster, entrance, passable);
+			distances.extend(entrances.iter().map(|&x| search.distance(x).unwrap_or(u32::MAX)));
+		}
+		self.clusters[cluster] = Cluster {
+			entrances,
+			links: links.into_iter().map(|x| x.1).collect(),
+			distances,
+		};
+	}
+}
+
+impl Local {
+	// Position of a tile of the cluster in the buffers
+	fn index(&self, index: usize) -> usize {
+		(index % self.cols - self.rect.0) + (index / self.cols - self.rect.2) * (self.rect.1 - self.rect.0)
+	}
+
+	fn distance(&self, index: usize) -> Option<u32> {
+		Some(self.distances[self.index(index)]).filter(|x| *x != u32::MAX)
+	}
+
+	// Tiles from the source to a reached tile, both included
+	fn walk(&self, mut index: usize) -> Vec<usize> {
+		let mut tiles = vec![index];
+		while self.distances[self.index(index)] != 0 {
+			index = self.came_from[self.index(index)];
+			tiles.push(index);
+		}
+		tiles.reverse();
+		tiles
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::astar;
+
+	fn passable(x: &usize) -> bool {
+		*x == 0
+	}
+
+	#[test]
+	fn matches_astar_on_open_ground() {
+		// Uneven clusters along the right and bottom edges
+		let mut net: TileNet<usize> = TileNet::new(23, 17);
+		net.set_box_filled(&1, (5, 0), (5, 12));
+		net.set_box_filled(&1, (12, 4), (12, 16));
+		let hierarchy = Hierarchy::new(&net, 5, passable);
+		let exact = |start, goal| astar(&net, start, goal, |x| if passable(x) { Some(1) } else { None });
+		for &(start, goal) in &[((0, 0), (22, 16)), ((22, 0), (0, 16)), ((1, 1), (3, 3)), ((4, 4), (6, 4))] {
+			let route = hierarchy.find_path(&net, start, goal, passable).unwrap();
+			assert_eq!(route.tiles.first(), Some(&start));
+			assert_eq!(route.tiles.last(), Some(&goal));
+			assert!(route.tiles.windows(2).all(|x| x[0].0.abs_diff(x[1].0) + x[0].1.abs_diff(x[1].1) == 1));
+			assert!(route.tiles.iter().all(|x| passable(net.get(*x).unwrap())));
+			assert_eq!(route.cost as usize, route.tiles.len() - 1);
+			assert!(route.cost >= exact(start, goal).unwrap().cost);
+		}
+		assert_eq!(hierarchy.find_path(&net, (0, 0), (5, 0), passable), None);
+		assert_eq!(hierarchy.find_path(&net, (0, 0), (23, 0), passable), None);
+	}
+
+	#[test]
+	fn updates_and_resizes() {
+		let mut net: TileNet<usize> = TileNet::new(12, 4);
+		net.set_col(&1, 6);
+		let mut hierarchy = Hierarchy::new(&net, 4, passable);
+		assert_eq!(hierarchy.find_path(&net, (0, 0), (11, 0), passable), None);
+		net.take_dirty();
+		net.set(&0, (6, 3));
+		let span = net.take_dirty().unwrap();
+		hierarchy.update(&net, span, passable);
+		assert_eq!(hierarchy.find_path(&net, (0, 0), (11, 0), passable).map(|x| x.cost), Some(17));
+		let count = hierarchy.entrance_count();
+		net.resize((16, 4));
+		assert_eq!(hierarchy.find_path(&net, (0, 0), (11, 0), passable), None);
+		hierarchy.update(&net, (0, 0, 0, 0), passable);
+		assert!(hierarchy.entrance_count() > count);
+	}
+}
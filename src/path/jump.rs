@@ -0,0 +1,289 @@
+use std::cmp::{max, min, Reverse};
+use std::collections::BinaryHeap;
+use super::Path;
+use super::super::TileNet;
+
+const STRAIGHT: u32 = 10;
+const DIAGONAL: u32 = 14;
+
+/// Find the shortest path from `start` to `goal` with jump point search
+///
+/// Unlike `astar`, movement includes the diagonals, and every tile is either
+/// `passable` or not. Jump point search skips over the many equally short
+/// paths of open ground and only queues the tiles where the direction has to
+/// change around a wall, which is typically an order of magnitude faster
+/// than A* on open maps. A diagonal move needs both tiles beside it to be
+/// passable, so paths never cut corners.
+///
+/// The cost of the path is in tenths of a tile: 10 per orthogonal step and
+/// 14 per diagonal one. Every tile of the path is listed, not only the jump
+/// points. As in `astar`, the start may be blocked, and `None` is returned if
+/// the goal can not be reached or either end is outside the net.
+///
+/// ```
+/// use tile_net::TileNet;
+/// use tile_net::path;
+/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+/// net.set_box_filled(&1, (5, 0), (5, 7));
+/// let route = path::jps(&net, (0, 0), (9, 0), |x| *x == 0).unwrap();
+/// assert_eq!(route.tiles.first(), Some(&(0, 0)));
+/// assert_eq!(route.tiles.last(), Some(&(9, 0)));
+/// // Diagonally down to the end of the wall, around it, and back up
+/// assert_eq!(route.tiles.len(), 19);
+/// assert_eq!(route.cost, 14 * 7 + 10 * 11);
+/// ```
+pub fn jps<T, F>(net: &TileNet<T>, start: (usize, usize), goal: (usize, usize), passable: F) -> Option<Path>
+	where F: FnMut(&T) -> bool
+{
+	let (cols, rows) = net.get_size();
+	if start.0 >= cols || start.1 >= rows || goal.0 >= cols || goal.1 >= rows {
+		return None;
+	}
+	let mut grid = Grid {
+		net,
+		passable,
+		goal: (goal.0 as isize, goal.1 as isize),
+	};
+	let mut costs = vec![u32::MAX; cols * rows];
+	let mut came_from: Vec<usize> = (0..cols * rows).collect();
+	let index = |x: (isize, isize)| x.0 as usize + x.1 as usize * cols;
+	let start = (start.0 as isize, start.1 as isize);
+	costs[index(start)] = 0;
+	let mut open = BinaryHeap::new();
+	open.push(Reverse((octile(start, grid.goal), 0, index(start))));
+	while let Some(Reverse((_, spent, at))) = open.pop() {
+		if spent > costs[at] {
+			continue;
+		}
+		let node = ((at % cols) as isize, (at / cols) as isize);
+		if node == grid.goal {
+			break;
+		}
+		let parent = came_from[at];
+		let parent = if parent == at { None } else { Some(((parent % cols) as isize, (parent / cols) as isize)) };
+		for direction in grid.directions(node, parent) {
+			if let Some(point) = grid.jump(node, direction) {
+				let total = spent + octile(node, point);
+				if total < costs[index(point)] {
+					costs[index(point)] = total;
+					came_from[index(point)] = at;
+					open.push(Reverse((total + octile(point, grid.goal), total, index(point))));
+				}
+			}
+		}
+	}
+
+	// Walk back over the jump points, filling in the straight runs between them
+	let mut at = index(grid.goal);
+	let cost = *costs.get(at).filter(|x| **x != u32::MAX)?;
+	let mut tiles = vec![goal];
+	while came_from[at] != at {
+		let (from, to) = (came_from[at], at);
+		let (mut x, mut y) = ((to % cols) as isize, (to / cols) as isize);
+		let (fx, fy) = ((from % cols) as isize, (from / cols) as isize);
+		while (x, y) != (fx, fy) {
+			x += (fx - x).signum();
+			y += (fy - y).signum();
+			tiles.push((x as usize, y as usize));
+		}
+		at = from;
+	}
+	tiles.reverse();
+	Some(Path { tiles, cost })
+}
+
+// Cost of the shortest move between two tiles on open ground
+fn octile(from: (isize, isize), to: (isize, isize)) -> u32 {
+	let (dx, dy) = (from.0.abs_diff(to.0) as u32, from.1.abs_diff(to.1) as u32);
+	STRAIGHT * max(dx, dy) + (DIAGONAL - STRAIGHT) * min(dx, dy)
+}
+
+struct Grid<'a, T, F>
+	where T: 'a
+{
+	net: &'a TileNet<T>,
+	passable: F,
+	goal: (isize, isize),
+}
+
+impl<'a, T, F> Grid<'a, T, F>
+	where F: FnMut(&T) -> bool
+{
+	fn open(&mut self, x: isize, y: isize) -> bool {
+		if x < 0 || y < 0 {
+			return false;
+		}
+		match self.net.get((x as usize, y as usize)) {
+			Some(tile) => (self.passable)(tile),
+			None => false,
+		}
+	}
+
+	// Directions worth searching from a node, given where it was reached from
+	fn directions(&mut self, (x, y): (isize, isize), parent: Option<(isize, isize)>) -> Vec<(isize, isize)> {
+		let mut directions = vec![];
+		let (dx, dy) = match parent {
+			Some(parent) => ((x - parent.0).signum(), (y - parent.1).signum()),
+			None => {
+				for &(dx, dy) in &[(1, 0), (-1, 0), (0, 1), (0, -1)] {
+					if self.open(x + dx, y + dy) {
+						directions.push((dx, dy));
+					}
+				}
+				for &(dx, dy) in &[(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+					if self.open(x + dx, y) && self.open(x, y + dy) && self.open(x + dx, y + dy) {
+						directions.push((dx, dy));
+					}
+				}
+				return directions;
+			}
+		};
+		if dx != 0 && dy != 0 {
+			let (across, down) = (self.open(x + dx, y), self.open(x, y + dy));
+			if down {
+				directions.push((0, dy));
+			}
+			if across {
+				directions.push((dx, 0));
+			}
+			if across && down && self.open(x + dx, y + dy) {
+				directions.push((dx, dy));
+			}
+		} else {
+			// Sideways relative to the movement, in either axis
+			let (sx, sy) = (dy.abs(), dx.abs());
+			let ahead = self.open(x + dx, y + dy);
+			for &side in &[1, -1] {
+				let (ox, oy) = (sx * side, sy * side);
+				if self.open(x + ox, y + oy) {
+					directions.push((ox, oy));
+					if ahead && self.open(x + dx + ox, y + dy + oy) {
+						directions.push((dx + ox, dy + oy));
+					}
+				}
+			}
+			if ahead {
+				directions.push((dx, dy));
+			}
+		}
+		directions
+	}
+
+	// Move in a straight line until the goal, a wall, or a tile with a forced neighbour
+	fn jump_straight(&mut self, (mut x, mut y): (isize, isize), (dx, dy): (isize, isize)) -> Option<(isize, isize)> {
+		let (sx, sy) = (dy.abs(), dx.abs());
+		loop {
+			x += dx;
+			y += dy;
+			if !self.open(x, y) {
+				return None;
+			}
+			if (x, y) == self.goal {
+				return Some((x, y));
+			}
+			// A wall behind an open side means that side is only reachable from here
+			for &side in &[1, -1] {
+				if self.open(x + sx * side, y + sy * side) && !self.open(x - dx + sx * side, y - dy + sy * side) {
+					return Some((x, y));
+				}
+			}
+		}
+	}
+
+	// Move from a node until reaching a jump point, or return `None` at a wall
+	fn jump(&mut self, (mut x, mut y): (isize, isize), (dx, dy): (isize, isize)) -> Option<(isize, isize)> {
+		if dx == 0 || dy == 0 {
+			return self.jump_straight((x, y), (dx, dy));
+		}
+		loop {
+			if !(self.open(x + dx, y) && self.open(x, y + dy)) {
+				return None;
+			}
+			x += dx;
+			y += dy;
+			if !self.open(x, y) {
+				return None;
+			}
+			if (x, y) == self.goal || self.jump_straight((x, y), (dx, 0)).is_some() ||
+			   self.jump_straight((x, y), (0, dy)).is_some() {
+				return Some((x, y));
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Plain Dijkstra over the same moves
+	fn shortest(net: &TileNet<char>, start: (usize, usize), goal: (usize, usize)) -> Option<u32> {
+		let (cols, rows) = net.get_size();
+		let free = |x: isize, y: isize| x >= 0 && y >= 0 && net.get((x as usize, y as usize)) == Some(&'.');
+		let mut costs = vec![u32::MAX; cols * rows];
+		let mut open = BinaryHeap::new();
+		costs[start.0 + start.1 * cols] = 0;
+		open.push(Reverse((0, start.0 as isize, start.1 as isize)));
+		while let Some(Reverse((spent, x, y))) = open.pop() {
+			for dx in -1..2 {
+				for dy in -1..2 {
+					let (nx, ny) = (x + dx, y + dy);
+					if !free(nx, ny) || !free(nx, y) || !free(x, ny) {
+						continue;
+					}
+					let total = spent + if dx != 0 && dy != 0 { DIAGONAL } else { STRAIGHT };
+					if total < costs[nx as usize + ny as usize * cols] {
+						costs[nx as usize + ny as usize * cols] = total;
+						open.push(Reverse((total, nx, ny)));
+					}
+				}
+			}
+		}
+		Some(costs[goal.0 + goal.1 * cols]).filter(|x| *x != u32::MAX)
+	}
+
+	#[test]
+	fn shortest_without_cutting_corners() {
+		let net = TileNet::from_str_map("
+..#.......#...
+..#.####..#.#.
+....#..#....#.
+.####..####.#.
+......#.....#.
+.####.#.###...
+....#...#.....
+", |x| x).unwrap();
+		let free = |x: &char| *x == '.';
+		let ends = [(0, 0), (13, 0), (5, 2), (3, 0), (11, 5), (0, 6), (13, 6), (7, 4)];
+		for &start in &ends {
+			for &goal in &ends {
+				let route = jps(&net, start, goal, free).unwrap();
+				assert_eq!(Some(route.cost), shortest(&net, start, goal));
+				assert_eq!(route.tiles.first(), Some(&start));
+				assert_eq!(route.tiles.last(), Some(&goal));
+				let mut cost = 0;
+				for pair in route.tiles.windows(2) {
+					let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+					assert!(x0.abs_diff(x1) <= 1 && y0.abs_diff(y1) <= 1 && pair[0] != pair[1]);
+					assert!(free(net.get(pair[1]).unwrap()));
+					assert!(free(net.get((x1, y0)).unwrap()) && free(net.get((x0, y1)).unwrap()));
+					cost += if x0 != x1 && y0 != y1 { DIAGONAL } else { STRAIGHT };
+				}
+				assert_eq!(route.cost, cost);
+			}
+		}
+		assert_eq!(jps(&net, (0, 0), (2, 0), free), None);
+		assert_eq!(jps(&net, (0, 0), (14, 0), free), None);
+		// Leaving a blocked start is allowed
+		assert!(jps(&net, (2, 0), (3, 0), free).is_some());
+	}
+
+	#[test]
+	fn open_ground() {
+		let net: TileNet<usize> = TileNet::new(10, 10);
+		let route = jps(&net, (0, 0), (9, 9), |_| true).unwrap();
+		assert_eq!((route.tiles.len(), route.cost), (10, 9 * DIAGONAL));
+		let route = jps(&net, (2, 7), (9, 3), |_| true).unwrap();
+		assert_eq!(route.cost, 4 * DIAGONAL + 3 * STRAIGHT);
+	}
+}
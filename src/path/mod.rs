@@ -0,0 +1,255 @@
+//! Shortest paths over the tiles of a net
+//!
+//! Movement goes between orthogonal neighbours, except in `jps`. A cost
+//! function gives the cost of entering each tile, or `None` for tiles that can
+//! not be entered, so roads, mud and walls can share one map:
+//!
+//! ```
+//! use tile_net::TileNet;
+//! use tile_net::path;
+//! let net = TileNet::from_str_map("
+//! ..~~~..
+//! .#~~~#.
+//! .......
+//! ", |x| x).unwrap();
+//! let cost = |x: &char| match *x {
+//!     '.' => Some(1),
+//!     '~' => Some(5),
+//!     _ => None,
+//! };
+//! // Wading straight through the swamp costs more than walking around it
+//! let route = path::astar(&net, (0, 0), (6, 0), cost).unwrap();
+//! assert_eq!(route.cost, 10);
+//! assert_eq!(route.tiles.len(), 11);
+//! ```
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use super::{Line, TileNet, Vector};
+
+pub use self::hierarchy::Hierarchy;
+pub use self::jump::jps;
+
+mod hierarchy;
+mod jump;
+
+/// A route through a net, see `astar`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path {
+	/// Tiles from the start to the goal, both included
+	pub tiles: Vec<(usize, usize)>,
+	/// Sum of the costs of entering every tile after the start
+	pub cost: u32,
+}
+
+/// Find the cheapest path from `start` to `goal` with A*
+///
+/// `cost` returns the cost of entering a tile, or `None` if it blocks. Costs
+/// below 1 count as 1, which keeps the distance estimate exact enough to
+/// guarantee the cheapest path. The start tile may be blocked, letting a unit
+/// standing in a doorway leave it. Returns `None` if the goal can not be
+/// reached or either end is outside the net.
+pub fn astar<T, F>(net: &TileNet<T>, start: (usize, usize), goal: (usize, usize), cost: F) -> Option<Path>
+	where F: FnMut(&T) -> Option<u32>
+{
+	search(net, &[start], Some(goal), cost).path(net, goal)
+}
+
+/// Compute the cost of the cheapest path from any of the sources to every tile
+///
+/// Tiles that can not be reached, or are outside of the net, are `None`. The
+/// sources themselves cost 0. Walking downhill from any tile leads to the
+/// closest source, which lets many units share one search, since a flow
+/// field toward a common goal is just the map from that goal.
+///
+/// ```
+/// use tile_net::TileNet;
+/// use tile_net::path;
+/// let mut net: TileNet<u32> = TileNet::from_iter(4, vec![1; 8].into_iter());
+/// net.set(&3, (1, 0));
+/// let map = path::dijkstra(&net, vec![(0, 0)], |x| Some(*x));
+/// assert_eq!(map.get((1, 0)), Some(&Some(3)));
+/// assert_eq!(map.get((2, 0)), Some(&Some(4)));
+/// ```
+pub fn dijkstra<T, F, I>(net: &TileNet<T>, sources: I, cost: F) -> TileNet<Option<u32>>
+	where F: FnMut(&T) -> Option<u32>,
+	      I: IntoIterator<Item = (usize, usize)>
+{
+	let sources: Vec<(usize, usize)> = sources.into_iter().collect();
+	let search = search(net, &sources, None, cost);
+	TileNet::from_iter(net.get_size().0, search.costs.into_iter().map(|x| if x == u32::MAX { None } else { Some(x) }))
+}
+
+/// Remove waypoints that a straight line can skip
+///
+/// Grid paths zigzag along the axes. Starting at the first tile, this keeps
+/// only the tiles where the straight line from the previous kept tile, center
+/// to center, would pass through a tile that is not `passable` or outside of
+/// the net, as traced by `Line::supercover`. The first and last tiles are
+/// always kept. A line crossing exactly through a corner touches only one of
+/// the two tiles beside it, so thick movers should still keep a margin.
+///
+/// ```
+/// use tile_net::TileNet;
+/// use tile_net::path;
+/// let mut net: TileNet<usize> = TileNet::new(8, 8);
+/// net.set_box_filled(&1, (3, 0), (3, 5));
+/// let route = path::astar(&net, (0, 0), (7, 0), |x| if *x == 0 { Some(1) } else { None }).unwrap();
+/// let waypoints = path::smooth(&net, &route.tiles, |x| *x == 0);
+/// // Down to the end of the wall, past it, and straight up to the goal
+/// assert_eq!(waypoints, vec![(0, 0), (2, 6), (4, 6), (7, 0)]);
+/// assert_eq!(route.tiles.len(), 20);
+/// ```
+pub fn smooth<T, F>(net: &TileNet<T>, path: &[(usize, usize)], mut passable: F) -> Vec<(usize, usize)>
+	where F: FnMut(&T) -> bool
+{
+	let mut clear = |from: (usize, usize), to: (usize, usize)| {
+		let center = |x: (usize, usize)| Vector(x.0 as f32 + 0.5, x.1 as f32 + 0.5);
+		Line(center(from), center(to))
+			.supercover()
+			.all(|x| x.0 >= 0 && x.1 >= 0 && net.get((x.0 as usize, x.1 as usize)).is_some_and(&mut passable))
+	};
+	let mut waypoints: Vec<(usize, usize)> = path.iter().take(1).cloned().collect();
+	let mut index = 0;
+	while index + 1 < path.len() {
+		let anchor = path[index];
+		// The next tile on the path is always reachable from the previous one
+		let mut next = index + 1;
+		for candidate in (index + 2..path.len()).rev() {
+			if clear(anchor, path[candidate]) {
+				next = candidate;
+				break;
+			}
+		}
+		waypoints.push(path[next]);
+		index = next;
+	}
+	waypoints
+}
+
+// Costs and predecessors of a finished search, in row-major order
+struct Search {
+	costs: Vec<u32>,
+	came_from: Vec<usize>,
+}
+
+impl Search {
+	// Walk back from the goal along the predecessors
+	fn path<T>(&self, net: &TileNet<T>, goal: (usize, usize)) -> Option<Path> {
+		let cols = net.get_size().0;
+		let mut index = index_of(net, goal)?;
+		let cost = *self.costs.get(index).filter(|x| **x != u32::MAX)?;
+		let mut tiles = vec![goal];
+		while self.came_from[index] != index {
+			index = self.came_from[index];
+			tiles.push((index % cols, index / cols));
+		}
+		tiles.reverse();
+		Some(Path { tiles, cost })
+	}
+}
+
+fn index_of<T>(net: &TileNet<T>, p: (usize, usize)) -> Option<usize> {
+	let (cols, rows) = net.get_size();
+	if p.0 < cols && p.1 < rows { Some(p.0 + p.1 * cols) } else { None }
+}
+
+// Run A* towards `goal`, or Dijkstra over the whole net without one
+fn search<T, F>(net: &TileNet<T>, sources: &[(usize, usize)], goal: Option<(usize, usize)>, mut cost: F) -> Search
+	where F: FnMut(&T) -> Option<u32>
+{
+	let (cols, rows) = net.get_size();
+	let tiles = net.get_raw();
+	let mut search = Search {
+		costs: vec![u32::MAX; tiles.len()],
+		came_from: (0..tiles.len()).collect(),
+	};
+	let estimate = |index: usize| {
+		goal.map_or(0, |goal| {
+			let (x, y) = (index % cols, index / cols);
+			(x.abs_diff(goal.0) + y.abs_diff(goal.1)) as u32
+		})
+	};
+	let goal = goal.and_then(|x| index_of(net, x));
+	let mut open = BinaryHeap::new();
+	for &source in sources {
+		if let Some(index) = index_of(net, source) {
+			search.costs[index] = 0;
+			open.push(Reverse((estimate(index), 0, index)));
+		}
+	}
+	while let Some(Reverse((_, spent, index))) = open.pop() {
+		if spent > search.costs[index] {
+			continue;
+		}
+		if Some(index) == goal {
+			break;
+		}
+		let (x, y) = (index % cols, index / cols);
+		let neighbours = [(x > 0, index.wrapping_sub(1)),
+		                  (x + 1 < cols, index + 1),
+		                  (y > 0, index.wrapping_sub(cols)),
+		                  (y + 1 < rows, index + cols)];
+		for &(inside, next) in &neighbours {
+			if !inside {
+				continue;
+			}
+			let step = match cost(&tiles[next]) {
+				Some(step) => step.max(1),
+				None => continue,
+			};
+			let total = spent.saturating_add(step);
+			if total < search.costs[next] {
+				search.costs[next] = total;
+				search.came_from[next] = index;
+				open.push(Reverse((total.saturating_add(estimate(next)), total, next)));
+			}
+		}
+	}
+	search
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn passable(x: &usize) -> Option<u32> {
+		if *x == 0 { Some(1) } else { None }
+	}
+
+	#[test]
+	fn astar_walls_and_bounds() {
+		let net = TileNet::sample();
+		let route = astar(&net, (0, 0), (9, 4), passable).unwrap();
+		assert_eq!(route.cost, 13);
+		assert_eq!(route.tiles.first(), Some(&(0, 0)));
+		assert_eq!(route.tiles.last(), Some(&(9, 4)));
+		assert!(route.tiles.windows(2).all(|x| x[0].0.abs_diff(x[1].0) + x[0].1.abs_diff(x[1].1) == 1));
+		assert_eq!(astar(&net, (0, 0), (0, 0), passable), Some(Path { tiles: vec![(0, 0)], cost: 0 }));
+		assert_eq!(astar(&net, (0, 0), (0, 5), passable), None);
+		assert_eq!(astar(&net, (0, 0), (10, 0), passable), None);
+		// Leaving a blocked start is allowed
+		assert_eq!(astar(&net, (0, 5), (0, 3), passable).map(|x| x.cost), Some(2));
+	}
+
+	#[test]
+	fn smooth_open_and_blocked() {
+		let net: TileNet<usize> = TileNet::new(6, 6);
+		let stairs = [(0, 0), (1, 0), (1, 1), (2, 1), (2, 2), (3, 2), (3, 3)];
+		assert_eq!(smooth(&net, &stairs, |_| true), vec![(0, 0), (3, 3)]);
+		assert_eq!(smooth(&net, &stairs[..1], |_| true), vec![(0, 0)]);
+		assert!(smooth(&net, &[], |_| true).is_empty());
+		// Only the corner tile of the L is free
+		let l = [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)];
+		let free = |x: &usize| *x == 0;
+		let mut net = net;
+		net.set_box_filled(&1, (0, 1), (1, 5));
+		assert_eq!(smooth(&net, &l, free), vec![(0, 0), (2, 0), (2, 2)]);
+	}
+
+	#[test]
+	fn dijkstra_many_sources() {
+		let net: TileNet<usize> = TileNet::new(5, 1);
+		let map = dijkstra(&net, vec![(0, 0), (4, 0), (9, 9)], |_| Some(0));
+		assert_eq!(map.get_raw(), &[Some(0), Some(1), Some(2), Some(1), Some(0)]);
+	}
+}
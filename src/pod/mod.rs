@@ -0,0 +1,316 @@
+//! Zero-copy snapshots for plain-old-data tiles
+//!
+//! Enabled by the `bytemuck` feature. A snapshot is a small header followed
+//! by the raw row-major tile buffer, so loading a huge map is a single bounds
+//! and alignment check instead of element-by-element decoding.
+//!
+//! The header is always little-endian. The tile payload is stored in the
+//! native byte order of the writer, which is recorded in the header. Reading
+//! a snapshot from a host with a different byte order is refused by the
+//! zero-copy path; use `TileNet::from_pod_bytes_with` to swap each element.
+use bytemuck::{self, Pod};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::mem::{size_of, size_of_val};
+use super::TileNet;
+
+const MAGIC: [u8; 4] = *b"TNPD";
+const VERSION: u8 = 1;
+const LITTLE: u8 = 0;
+const BIG: u8 = 1;
+
+// The header is padded to 32 bytes so the payload stays aligned for any
+// tile type with an alignment of 32 or less, as long as the whole buffer is.
+const HEADER_LEN: usize = 32;
+
+fn native_order() -> u8 {
+	if cfg!(target_endian = "big") { BIG } else { LITTLE }
+}
+
+/// Reasons a byte buffer can not be read as a snapshot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PodError {
+	/// The buffer does not start with the snapshot magic
+	BadMagic,
+	/// The snapshot was written by an unknown format version
+	UnsupportedVersion(u8),
+	/// The payload byte order differs from the host's byte order
+	ByteOrder,
+	/// The stored tile size differs from `size_of::<T>()`
+	TileSize {
+		/// Size recorded in the header
+		stored: usize,
+		/// Size of the requested tile type
+		expected: usize,
+	},
+	/// The header describes a grid with zero columns or a size that overflows
+	Dimensions,
+	/// The buffer is shorter than the header says it should be
+	Truncated,
+	/// The payload is not suitably aligned for `T`
+	Misaligned,
+}
+
+impl fmt::Display for PodError {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			PodError::BadMagic => write!(formatter, "not a tile_net snapshot"),
+			PodError::UnsupportedVersion(v) => write!(formatter, "unsupported snapshot version {}", v),
+			PodError::ByteOrder => write!(formatter, "snapshot byte order differs from host"),
+			PodError::TileSize { stored, expected } => {
+				write!(formatter, "snapshot tile size is {}, expected {}", stored, expected)
+			}
+			PodError::Dimensions => write!(formatter, "invalid snapshot dimensions"),
+			PodError::Truncated => write!(formatter, "snapshot is truncated"),
+			PodError::Misaligned => write!(formatter, "snapshot payload is misaligned"),
+		}
+	}
+}
+
+impl Error for PodError {}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+	let mut word = [0u8; 8];
+	word.copy_from_slice(&bytes[..8]);
+	u64::from_le_bytes(word)
+}
+
+struct Header {
+	order: u8,
+	cols: usize,
+	rows: usize,
+}
+
+impl Header {
+	fn encode(&self, tile_size: usize) -> [u8; HEADER_LEN] {
+		let mut header = [0u8; HEADER_LEN];
+		header[0..4].copy_from_slice(&MAGIC);
+		header[4] = VERSION;
+		header[5] = self.order;
+		header[8..16].copy_from_slice(&(tile_size as u64).to_le_bytes());
+		header[16..24].copy_from_slice(&(self.cols as u64).to_le_bytes());
+		header[24..32].copy_from_slice(&(self.rows as u64).to_le_bytes());
+		header
+	}
+
+	fn decode(bytes: &[u8], tile_size: usize) -> Result<Header, PodError> {
+		if bytes.len() < HEADER_LEN {
+			return Err(PodError::Truncated);
+		}
+		if bytes[0..4] != MAGIC {
+			return Err(PodError::BadMagic);
+		}
+		if bytes[4] != VERSION {
+			return Err(PodError::UnsupportedVersion(bytes[4]));
+		}
+		let stored = read_u64(&bytes[8..]) as usize;
+		if stored != tile_size {
+			return Err(PodError::TileSize {
+				stored,
+				expected: tile_size,
+			});
+		}
+		let cols = read_u64(&bytes[16..]) as usize;
+		let rows = read_u64(&bytes[24..]) as usize;
+		if cols == 0 {
+			return Err(PodError::Dimensions);
+		}
+		Ok(Header {
+			order: bytes[5],
+			cols,
+			rows,
+		})
+	}
+
+	fn payload<'a>(&self, bytes: &'a [u8], tile_size: usize) -> Result<&'a [u8], PodError> {
+		let len = self.cols
+			.checked_mul(self.rows)
+			.and_then(|x| x.checked_mul(tile_size))
+			.ok_or(PodError::Dimensions)?;
+		bytes[HEADER_LEN..].get(..len).ok_or(PodError::Truncated)
+	}
+}
+
+/// Borrowed, zero-copy view of a snapshot
+///
+/// ```
+/// use tile_net::{PodView, TileNet};
+/// let net: TileNet<u8> = TileNet::from_iter(4, 0..16);
+/// let bytes = net.to_pod_bytes();
+/// // Bytes are always aligned for u8, wider tiles need an aligned buffer
+/// let view: PodView<u8> = PodView::parse(&bytes).unwrap();
+/// assert_eq!(view.get_size(), (4, 4));
+/// assert_eq!(view.get((1, 2)), Some(&9));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PodView<'a, T>
+	where T: 'a
+{
+	tiles: &'a [T],
+	cols: usize,
+}
+
+impl<'a, T> PodView<'a, T>
+	where T: 'a + Pod
+{
+	/// Interpret a byte buffer as a snapshot without copying the tiles
+	///
+	/// The buffer must be aligned for `T` and written on a host with the
+	/// same byte order.
+	pub fn parse(bytes: &'a [u8]) -> Result<PodView<'a, T>, PodError> {
+		let header = Header::decode(bytes, size_of::<T>())?;
+		if header.order != native_order() {
+			return Err(PodError::ByteOrder);
+		}
+		let payload = header.payload(bytes, size_of::<T>())?;
+		let tiles = bytemuck::try_cast_slice(payload).map_err(|_| PodError::Misaligned)?;
+		Ok(PodView {
+			tiles,
+			cols: header.cols,
+		})
+	}
+
+	/// Get a tuple that describes the size as (cols, rows)
+	pub fn get_size(&self) -> (usize, usize) {
+		(self.cols, self.tiles.len() / self.cols)
+	}
+
+	/// Get a reference to a 2D index
+	pub fn get(&self, p: (usize, usize)) -> Option<&'a T> {
+		if p.0 >= self.cols {
+			None
+		} else {
+			self.tiles.get(p.0 + p.1 * self.cols)
+		}
+	}
+
+	/// Get the raw row-major tiles behind the snapshot
+	pub fn get_raw(&self) -> &'a [T] {
+		self.tiles
+	}
+
+	/// Copy the snapshot into an owned `TileNet`
+	pub fn to_tilenet(&self) -> TileNet<T> {
+		TileNet::from_raw(self.cols, self.tiles.to_vec())
+	}
+}
+
+impl<T> TileNet<T>
+	where T: Pod
+{
+	/// Write a snapshot of this net
+	///
+	/// The tiles are written in native byte order, which is recorded in the header.
+	pub fn write_pod<W>(&self, writer: &mut W) -> io::Result<()>
+		where W: Write
+	{
+		let (cols, rows) = self.get_size();
+		let header = Header {
+			order: native_order(),
+			cols,
+			rows,
+		};
+		writer.write_all(&header.encode(size_of::<T>()))?;
+		writer.write_all(bytemuck::cast_slice(self.get_raw()))
+	}
+
+	/// Produce a snapshot of this net as an owned byte buffer
+	pub fn to_pod_bytes(&self) -> Vec<u8> {
+		let mut bytes = Vec::with_capacity(HEADER_LEN + size_of_val(self.get_raw()));
+		self.write_pod(&mut bytes).expect("writing to a Vec can not fail");
+		bytes
+	}
+
+	/// Load a snapshot by copying its payload in bulk
+	///
+	/// Unlike `PodView::parse` this accepts unaligned buffers. Snapshots with a
+	/// foreign byte order are refused.
+	pub fn from_pod_bytes(bytes: &[u8]) -> Result<TileNet<T>, PodError> {
+		if bytes.len() > 5 && bytes[5] != native_order() {
+			return Err(PodError::ByteOrder);
+		}
+		TileNet::from_pod_bytes_with(bytes, |x| x)
+	}
+
+	/// Load a snapshot, swapping elements written with a foreign byte order
+	///
+	/// `swap` is only called when the snapshot's byte order differs from the
+	/// host's; it should reverse the bytes of every field of the tile.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let net: TileNet<u16> = TileNet::from_iter(2, 0..4);
+	/// let bytes = net.to_pod_bytes();
+	/// let loaded: TileNet<u16> = TileNet::from_pod_bytes_with(&bytes, u16::swap_bytes).unwrap();
+	/// assert_eq!(loaded.get_raw(), net.get_raw());
+	/// ```
+	pub fn from_pod_bytes_with<F>(bytes: &[u8], swap: F) -> Result<TileNet<T>, PodError>
+		where F: Fn(T) -> T
+	{
+		let header = Header::decode(bytes, size_of::<T>())?;
+		if header.order != LITTLE && header.order != BIG {
+			return Err(PodError::ByteOrder);
+		}
+		let payload = header.payload(bytes, size_of::<T>())?;
+		let mut map = vec![T::zeroed(); header.cols * header.rows];
+		bytemuck::cast_slice_mut::<T, u8>(&mut map).copy_from_slice(payload);
+		if header.order != native_order() {
+			for tile in &mut map {
+				*tile = swap(*tile);
+			}
+		}
+		Ok(TileNet::from_raw(header.cols, map))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn aligned(bytes: &[u8]) -> Vec<u64> {
+		let mut storage = vec![0u64; bytes.len() / 8 + 1];
+		bytemuck::cast_slice_mut::<u64, u8>(&mut storage)[..bytes.len()].copy_from_slice(bytes);
+		storage
+	}
+
+	#[test]
+	fn roundtrip() {
+		let net = TileNet::<u32>::from_iter(7, 0..63);
+		let bytes = net.to_pod_bytes();
+		assert_eq!(bytes.len(), HEADER_LEN + 63 * 4);
+		let loaded = TileNet::<u32>::from_pod_bytes(&bytes).unwrap();
+		assert_eq!(loaded.get_size(), (7, 9));
+		assert_eq!(loaded.get_raw(), net.get_raw());
+
+		let storage = aligned(&bytes);
+		let view = PodView::<u32>::parse(&bytemuck::cast_slice(&storage)[..bytes.len()]).unwrap();
+		assert_eq!(view.get_raw(), net.get_raw());
+		assert_eq!(view.get((6, 8)), Some(&62));
+		assert_eq!(view.get((7, 0)), None);
+	}
+
+	#[test]
+	fn rejects_bad_input() {
+		let net = TileNet::<u32>::from_iter(2, 0..4);
+		let bytes = net.to_pod_bytes();
+		assert_eq!(TileNet::<u16>::from_pod_bytes(&bytes).err(),
+		           Some(PodError::TileSize {
+			           stored: 4,
+			           expected: 2,
+		           }));
+		assert_eq!(TileNet::<u32>::from_pod_bytes(&bytes[..bytes.len() - 1]).err(),
+		           Some(PodError::Truncated));
+		assert_eq!(TileNet::<u32>::from_pod_bytes(&bytes[1..]).err(),
+		           Some(PodError::BadMagic));
+
+		let mut foreign = bytes.clone();
+		foreign[5] = if native_order() == LITTLE { BIG } else { LITTLE };
+		let storage = aligned(&foreign);
+		assert_eq!(PodView::<u32>::parse(&bytemuck::cast_slice(&storage)[..foreign.len()]).err(),
+		           Some(PodError::ByteOrder));
+		assert_eq!(TileNet::<u32>::from_pod_bytes(&foreign).err(), Some(PodError::ByteOrder));
+		let swapped = TileNet::<u32>::from_pod_bytes_with(&foreign, u32::swap_bytes).unwrap();
+		assert_eq!(swapped.get((1, 0)), Some(&1u32.swap_bytes()));
+	}
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use super::{GridCoord, Span, TileNet};
+use super::tiles::union;
+
+/// A hand-authored piece of map, such as a room, with named points in it
+///
+/// Anchors mark tiles of interest, like doors or spawn points, so generated
+/// levels can line prefabs up with corridors and find where to put things
+/// after stamping them with `TileNet::stamp`.
+///
+/// ```
+/// use tile_net::{Orientation, Prefab, TileNet};
+/// let room = TileNet::from_str_map("
+/// www
+/// w.+
+/// www
+/// ", |x| x).unwrap();
+/// let room = Prefab::new(room).with_anchor("door", (2, 1));
+/// let mut level: TileNet<usize> = TileNet::new(6, 6);
+/// // Turn the door downwards, and put it at (3, 5)
+/// let turned = Orientation::rotated(1);
+/// let door = room.anchor_in("door", turned).unwrap();
+/// assert_eq!(door, (1, 2));
+/// level.stamp(&room, (2, 3), turned, |x| match *x {
+///     'w' => Some(1),
+///     '+' => Some(2),
+///     _ => None,
+/// });
+/// assert_eq!(level.get((3, 5)), Some(&2));
+/// assert_eq!(level.get((3, 4)), Some(&0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Prefab<T> {
+	/// Tiles of the prefab, with (0, 0) at its top-left corner
+	pub tiles: TileNet<T>,
+	anchors: BTreeMap<String, (usize, usize)>,
+}
+
+/// How to turn a prefab when stamping it
+///
+/// The prefab is first mirrored left to right if `flip_x` is set, then
+/// rotated clockwise, as seen with y pointing down, by `turns` quarter turns.
+/// Together these give all eight ways to lay a rectangle on a grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Orientation {
+	/// Mirror left to right before rotating
+	pub flip_x: bool,
+	/// Number of clockwise quarter turns, counted modulo 4
+	pub turns: u8,
+}
+
+impl Orientation {
+	/// Leave the prefab as it is
+	pub const IDENTITY: Orientation = Orientation {
+		flip_x: false,
+		turns: 0,
+	};
+
+	/// Rotate clockwise by a number of quarter turns
+	pub fn rotated(turns: u8) -> Orientation {
+		Orientation {
+			flip_x: false,
+			turns,
+		}
+	}
+
+	/// Mirror left to right, then rotate clockwise by a number of quarter turns
+	pub fn flipped(turns: u8) -> Orientation {
+		Orientation {
+			flip_x: true,
+			turns,
+		}
+	}
+
+	/// Get the size of a (cols, rows) box after turning it
+	pub fn size(&self, size: (usize, usize)) -> (usize, usize) {
+		if self.turns % 2 == 1 { (size.1, size.0) } else { size }
+	}
+
+	/// Get where a tile of a box of (cols, rows) tiles ends up after turning it
+	pub fn apply(&self, tile: (usize, usize), size: (usize, usize)) -> (usize, usize) {
+		let (mut x, mut y) = tile;
+		let (mut cols, mut rows) = size;
+		if self.flip_x {
+			x = cols - 1 - x;
+		}
+		for _ in 0..self.turns % 4 {
+			let turned = (rows - 1 - y, x);
+			x = turned.0;
+			y = turned.1;
+			::std::mem::swap(&mut cols, &mut rows);
+		}
+		(x, y)
+	}
+}
+
+impl<T> Prefab<T> {
+	/// Create a prefab without anchors
+	pub fn new(tiles: TileNet<T>) -> Prefab<T> {
+		Prefab {
+			tiles,
+			anchors: BTreeMap::new(),
+		}
+	}
+
+	/// Add or move a named anchor
+	///
+	/// # Panics #
+	/// Panics if the tile lies outside of the prefab.
+	pub fn with_anchor(mut self, name: &str, tile: (usize, usize)) -> Prefab<T> {
+		self.set_anchor(name, tile);
+		self
+	}
+
+	/// Add or move a named anchor
+	///
+	/// # Panics #
+	/// Panics if the tile lies outside of the prefab.
+	pub fn set_anchor(&mut self, name: &str, tile: (usize, usize)) {
+		let size = self.tiles.get_size();
+		assert!(tile.0 < size.0 && tile.1 < size.1,
+		        "anchor ({}, {}) lies outside a prefab of size ({}, {})",
+		        tile.0,
+		        tile.1,
+		        size.0,
+		        size.1);
+		self.anchors.insert(name.to_string(), tile);
+	}
+
+	/// Get a named anchor as it is in the prefab
+	pub fn anchor(&self, name: &str) -> Option<(usize, usize)> {
+		self.anchors.get(name).cloned()
+	}
+
+	/// Get a named anchor relative to the top-left corner of the turned prefab
+	///
+	/// Subtract it from a target tile to get the position to stamp at, so the
+	/// anchor lands on the target.
+	pub fn anchor_in(&self, name: &str, orientation: Orientation) -> Option<(usize, usize)> {
+		self.anchor(name).map(|x| orientation.apply(x, self.tiles.get_size()))
+	}
+
+	/// Iterate over the anchors in the order of their names
+	pub fn anchors(&self) -> impl Iterator<Item = (&str, (usize, usize))> {
+		self.anchors.iter().map(|(name, tile)| (name.as_str(), *tile))
+	}
+}
+
+impl<T> TileNet<T> {
+	/// Copy a turned prefab into the net, with its top-left corner at `position`
+	///
+	/// `remap` converts every tile of the prefab into a tile of the net, or
+	/// `None` to leave the tile of the net as it is, which makes empty parts of
+	/// the prefab transparent. Whatever falls outside of the net is clipped
+	/// away. Returns the tiles written to, or `None` if nothing was.
+	pub fn stamp<U, P, F>(&mut self, prefab: &Prefab<U>, position: P, orientation: Orientation, mut remap: F)
+	                      -> Option<Span>
+		where P: Into<GridCoord>,
+		      F: FnMut(&U) -> Option<T>
+	{
+		let position = position.into().to_tuple();
+		let size = prefab.tiles.get_size();
+		let (cols, rows) = self.get_size();
+		let mut span: Option<Span> = None;
+		for (tile, x, y) in prefab.tiles.view_all() {
+			let (dx, dy) = orientation.apply((x, y), size);
+			let (x, y) = (position.0 + dx, position.1 + dy);
+			if x >= cols || y >= rows {
+				continue;
+			}
+			if let Some(value) = remap(tile) {
+				self.raw_untracked()[x + y * cols] = value;
+				span = Some(union(span, (x, y, x, y)));
+			}
+		}
+		if let Some(span) = span {
+			self.touch(span);
+		}
+		span
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn all_eight_orientations() {
+		let tiles: TileNet<usize> = TileNet::from_iter(3, 1..7);
+		let prefab = Prefab::new(tiles.clone()).with_anchor("corner", (2, 0));
+		let stamped = |orientation: Orientation| {
+			let size = orientation.size((3, 2));
+			let mut net: TileNet<usize> = TileNet::new(size.0, size.1);
+			net.stamp(&prefab, (0, 0), orientation, |x| Some(*x));
+			net
+		};
+		assert_eq!(stamped(Orientation::IDENTITY).get_raw(), tiles.get_raw());
+		assert_eq!(stamped(Orientation::rotated(1)).get_raw(), tiles.rotated_90().get_raw());
+		assert_eq!(stamped(Orientation::rotated(3)).get_raw(), tiles.rotated_270().get_raw());
+		assert_eq!(stamped(Orientation::rotated(2)).get_raw(), &[6, 5, 4, 3, 2, 1]);
+		assert_eq!(stamped(Orientation::flipped(0)).get_raw(), &[3, 2, 1, 6, 5, 4]);
+		assert_eq!(stamped(Orientation::flipped(3)).get_raw(), tiles.transposed().get_raw());
+		assert_eq!(stamped(Orientation::rotated(4)).get_raw(), tiles.get_raw());
+		for turns in 0..4 {
+			for &orientation in &[Orientation::rotated(turns), Orientation::flipped(turns)] {
+				let corner = prefab.anchor_in("corner", orientation).unwrap();
+				assert_eq!(stamped(orientation).get(corner), Some(&3));
+			}
+		}
+		assert_eq!(prefab.anchor_in("door", Orientation::IDENTITY), None);
+	}
+
+	#[test]
+	fn clips_and_skips() {
+		let prefab = Prefab::new(TileNet::from_iter(2, 0..4usize));
+		let mut net: TileNet<usize> = TileNet::new(3, 3);
+		net.fill(&9);
+		net.take_dirty();
+		let span = net.stamp(&prefab, (2, 1), Orientation::IDENTITY, |x| if *x == 2 { None } else { Some(*x) });
+		assert_eq!(span, Some((2, 1, 2, 1)));
+		assert_eq!(net.take_dirty(), span);
+		assert_eq!(net.get_raw(), &[9, 9, 9, 9, 9, 0, 9, 9, 9]);
+		assert_eq!(net.stamp(&prefab, (5, 5), Orientation::IDENTITY, |x| Some(*x)), None);
+	}
+
+	#[test]
+	#[should_panic(expected = "anchor (1, 2) lies outside a prefab of size (3, 2)")]
+	fn anchors_stay_inside() {
+		Prefab::new(TileNet::<usize>::new(3, 2)).with_anchor("door", (1, 2));
+	}
+}
@@ -0,0 +1,62 @@
+//! Static colliders for `rapier2d`
+//!
+//! Uses `TileNet::decompose_rects`, so a floor becomes a single cuboid
+//! instead of one per tile, which keeps the broad phase of rapier small and
+//! avoids bodies catching on the seams between tiles.
+use rapier2d::geometry::ColliderBuilder;
+use rapier2d::math::Vector;
+use super::TileNet;
+
+impl<T> TileNet<T> {
+	/// Create cuboid collider builders covering the solid tiles
+	///
+	/// Every tile is `tile_size` units wide, and tile (0, 0) has its top-left
+	/// corner at the origin. The y axis points the same way as the rows of
+	/// the net, so flip it when the physics world has y pointing up.
+	///
+	/// ```
+	/// extern crate rapier2d;
+	/// extern crate tile_net;
+	/// use rapier2d::geometry::ColliderSet;
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set_row(&1, 9);
+	/// let mut colliders = ColliderSet::new();
+	/// for builder in net.to_rapier_colliders(|x| *x != 0, 0.5) {
+	///     colliders.insert(builder);
+	/// }
+	/// assert_eq!(colliders.len(), 1);
+	/// ```
+	pub fn to_rapier_colliders<F>(&self, is_solid: F, tile_size: f32) -> Vec<ColliderBuilder>
+		where F: FnMut(&T) -> bool
+	{
+		self.decompose_rects(is_solid)
+			.into_iter()
+			.map(|rect| {
+				let half = rect.size * (tile_size / 2.0);
+				let center = rect.pos * tile_size + half;
+				ColliderBuilder::cuboid(half.0, half.1).translation(Vector::new(center.0, center.1))
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn cuboids_are_centered() {
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		net.set_box_filled(&1, (1, 2), (2, 3));
+		let colliders = net.to_rapier_colliders(|x| *x != 0, 2.0);
+		assert_eq!(colliders.len(), 1);
+		let collider = colliders[0].build();
+		// Compare the components, which reads the same whether rapier returns
+		// the translation by value or by reference
+		let translation = collider.translation();
+		assert_eq!((translation.x, translation.y), (4.0, 6.0));
+		let cuboid = collider.shape().as_cuboid().unwrap();
+		assert_eq!(cuboid.half_extents, Vector::new(2.0, 2.0));
+	}
+}
@@ -0,0 +1,83 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use super::TileNet;
+use super::super::{Collable, Resolution};
+
+impl<T> TileNet<T> {
+	/// Solve every body against the net, in order
+	///
+	/// Returns the resolution of each body. Bodies do not collide with one
+	/// another, only with the net.
+	///
+	/// ```
+	/// use tile_net::{AabbCollider, Rect, TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set_row(&1, 5);
+	/// let mut bodies: Vec<_> = (0..4).map(|x| {
+	///     let mut body = AabbCollider::new(Rect::new(Vector(x as f32 * 2.0, 1.0), Vector(1.0, 1.0)));
+	///     body.vel = Vector(0.0, 8.0);
+	///     body
+	/// }).collect();
+	/// let results = net.solve_all(&mut bodies, &mut ());
+	/// assert_eq!(results.len(), 4);
+	/// assert!(bodies.iter().all(|x| x.rect().pos.1 == 4.0));
+	/// ```
+	pub fn solve_all<C, S>(&self, bodies: &mut [C], state: &mut S) -> Vec<Resolution>
+		where C: Collable<T, S>
+	{
+		bodies.iter_mut().map(|x| x.solve(self, state)).collect()
+	}
+
+	/// Solve every body against the net on the rayon thread pool
+	///
+	/// The net is only read while solving, so the bodies are independent.
+	/// Each body gets its own clone of `state`; changes to those clones are
+	/// dropped. The built-in bodies use `()` as their state.
+	#[cfg(feature = "rayon")]
+	pub fn par_solve_all<C, S>(&self, bodies: &mut [C], state: &S) -> Vec<Resolution>
+		where T: Sync,
+		      C: Collable<T, S> + Send,
+		      S: Clone + Sync
+	{
+		bodies.par_iter_mut().map(|x| x.solve(self, &mut state.clone())).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::super::{AabbCollider, Rect, Vector};
+
+	fn bodies() -> Vec<AabbCollider> {
+		(0..100)
+			.map(|x| {
+				let pos = Vector((x % 10) as f32 * 3.0, (x / 10) as f32);
+				let mut body = AabbCollider::new(Rect::new(pos, Vector(1.0, 1.0)));
+				body.vel = Vector(1.5, 0.0);
+				body
+			})
+			.collect()
+	}
+
+	#[test]
+	fn solve_all() {
+		let mut net: TileNet<usize> = TileNet::new(40, 10);
+		net.set_col(&1, 14);
+		let mut bodies = bodies();
+		let results = net.solve_all(&mut bodies, &mut ());
+		assert_eq!(results.len(), 100);
+		assert_eq!(bodies[4].rect().pos, Vector(13.0, 0.0));
+		assert_eq!(bodies[5].rect().pos, Vector(16.5, 0.0));
+	}
+
+	#[cfg(feature = "rayon")]
+	#[test]
+	fn par_solve_all() {
+		let mut net: TileNet<usize> = TileNet::new(40, 10);
+		net.set_col(&1, 14);
+		let mut serial = bodies();
+		let mut parallel = bodies();
+		assert_eq!(net.solve_all(&mut serial, &mut ()), net.par_solve_all(&mut parallel, &()));
+		assert!(serial.iter().zip(&parallel).all(|(a, b)| a.rect() == b.rect()));
+	}
+}
@@ -0,0 +1,126 @@
+use super::TileNet;
+use super::super::GridCoord;
+
+/// Footprint of a `Brush` around its center tile
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BrushShape {
+	/// Tiles up to `radius` away in both axes
+	Square,
+	/// Tiles whose center lies within `radius + 0.5` tiles, like `TileNet::set_circle_filled`
+	Circle,
+	/// Tiles up to `radius` moves away between orthogonal neighbours
+	Diamond,
+}
+
+/// A value painted over an area, blended with what is already there
+///
+/// `blend` receives the tile in the net and the value of the brush, and
+/// returns the new tile. This allows conditional writes, like digging a
+/// crater out of anything but bedrock, and mixing, like adding wetness.
+///
+/// ```
+/// use tile_net::{Brush, BrushShape, TileNet};
+/// const AIR: usize = 0;
+/// const DIRT: usize = 1;
+/// const BEDROCK: usize = 2;
+/// let mut net: TileNet<usize> = TileNet::new(7, 4);
+/// net.fill(&DIRT);
+/// net.set_row(&BEDROCK, 3);
+/// let crater = Brush::new(BrushShape::Circle, 2, AIR, |old: &usize, new: &usize| {
+///     if *old == BEDROCK { *old } else { *new }
+/// });
+/// net.paint(&crater, (3, 2));
+/// assert_eq!(net.get((3, 1)), Some(&AIR));
+/// assert_eq!(net.get((3, 3)), Some(&BEDROCK));
+/// assert_eq!(net.get((0, 0)), Some(&DIRT));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Brush<T, F> {
+	/// Footprint of the brush
+	pub shape: BrushShape,
+	/// Size of the footprint, 0 being a single tile
+	pub radius: usize,
+	/// Value painted by the brush
+	pub value: T,
+	/// Computes a painted tile from the tile in the net and `value`
+	pub blend: F,
+}
+
+impl<T, F> Brush<T, F>
+	where F: Fn(&T, &T) -> T
+{
+	/// Create a brush
+	pub fn new(shape: BrushShape, radius: usize, value: T, blend: F) -> Brush<T, F> {
+		Brush {
+			shape,
+			radius,
+			value,
+			blend,
+		}
+	}
+
+	/// Check whether the tile at an offset from the center is under the brush
+	pub fn covers(&self, offset: (isize, isize)) -> bool {
+		let radius = self.radius as isize;
+		let (dx, dy) = (offset.0.abs(), offset.1.abs());
+		match self.shape {
+			BrushShape::Square => dx <= radius && dy <= radius,
+			BrushShape::Circle => {
+				let reach = radius as f32 + 0.5;
+				(dx * dx + dy * dy) as f32 <= reach * reach
+			}
+			BrushShape::Diamond => dx + dy <= radius,
+		}
+	}
+}
+
+impl<T> TileNet<T> {
+	/// Blend the value of a brush into the tiles under it
+	///
+	/// Parts of the brush outside of the net are clipped away.
+	pub fn paint<P, F>(&mut self, brush: &Brush<T, F>, center: P)
+		where P: Into<GridCoord>,
+		      F: Fn(&T, &T) -> T
+	{
+		let center = center.into().to_tuple();
+		let (cols, rows) = self.get_size();
+		let radius = brush.radius;
+		if center.0 >= cols + radius || center.1 >= rows + radius || rows == 0 {
+			return;
+		}
+		let (x0, y0) = (center.0.saturating_sub(radius), center.1.saturating_sub(radius));
+		let (x1, y1) = ((center.0 + radius).min(cols - 1), (center.1 + radius).min(rows - 1));
+		let raw = self.raw_untracked();
+		for y in y0..y1 + 1 {
+			for x in x0..x1 + 1 {
+				if brush.covers((x as isize - center.0 as isize, y as isize - center.1 as isize)) {
+					let tile = &mut raw[x + y * cols];
+					*tile = (brush.blend)(tile, &brush.value);
+				}
+			}
+		}
+		self.touch((x0, y0, x1, y1));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn shapes_and_clipping() {
+		let count = |shape: BrushShape, radius: usize, center: (usize, usize)| {
+			let mut net: TileNet<usize> = TileNet::new(9, 9);
+			net.paint(&Brush::new(shape, radius, 1, |old: &usize, new: &usize| old + new), center);
+			net.get_raw().iter().sum::<usize>()
+		};
+		assert_eq!(count(BrushShape::Square, 1, (4, 4)), 9);
+		assert_eq!(count(BrushShape::Diamond, 2, (4, 4)), 13);
+		assert_eq!(count(BrushShape::Circle, 1, (4, 4)), 9);
+		assert_eq!(count(BrushShape::Circle, 2, (4, 4)), 21);
+		assert_eq!(count(BrushShape::Square, 0, (4, 4)), 1);
+		assert_eq!(count(BrushShape::Square, 1, (0, 8)), 4);
+		assert_eq!(count(BrushShape::Square, 2, (10, 10)), 1);
+		assert_eq!(count(BrushShape::Square, 2, (20, 0)), 0);
+	}
+}
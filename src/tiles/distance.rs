@@ -0,0 +1,189 @@
+use std::cmp::min;
+use super::TileNet;
+
+/// How to measure the distance between two tiles, see `TileNet::distance_transform`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+	/// Number of moves when diagonal moves are allowed
+	Chebyshev,
+	/// Number of moves between orthogonal neighbours
+	Manhattan,
+	/// Straight line distance between the tile centers, rounded
+	Euclidean,
+}
+
+// Larger than any distance on a net that fits in memory
+const FAR: u32 = u32::MAX / 2;
+
+impl<T> TileNet<T> {
+	/// Compute the distance from every tile to the nearest tile matching `pred`
+	///
+	/// Matching tiles are 0. Distances that do not fit, including all of them
+	/// when no tile matches, saturate at `u16::MAX`. Chebyshev and Manhattan
+	/// distances use the classic forward and backward raster passes, while
+	/// Euclidean distances are exact, computed one axis at a time.
+	///
+	/// Distances to the walls steer units away from them, and distances to
+	/// open ground score spawn points and decide where decoration goes.
+	///
+	/// ```
+	/// use tile_net::{Metric, TileNet};
+	/// let mut net: TileNet<usize> = TileNet::new(5, 3);
+	/// net.set(&1, (0, 0));
+	/// let manhattan = net.distance_transform(|x| *x == 1, Metric::Manhattan);
+	/// assert_eq!(manhattan.get((4, 2)), Some(&6));
+	/// let chebyshev = net.distance_transform(|x| *x == 1, Metric::Chebyshev);
+	/// assert_eq!(chebyshev.get((4, 2)), Some(&4));
+	/// let euclidean = net.distance_transform(|x| *x == 1, Metric::Euclidean);
+	/// assert_eq!(euclidean.get((4, 2)), Some(&4));
+	/// ```
+	pub fn distance_transform<F>(&self, mut pred: F, metric: Metric) -> TileNet<u16>
+		where F: FnMut(&T) -> bool
+	{
+		let (cols, rows) = self.get_size();
+		let mut distances: Vec<u32> = self.get_raw().iter().map(|x| if pred(x) { 0 } else { FAR }).collect();
+		match metric {
+			Metric::Chebyshev => raster(&mut distances, cols, rows, true),
+			Metric::Manhattan => raster(&mut distances, cols, rows, false),
+			Metric::Euclidean => euclidean(&mut distances, cols, rows),
+		}
+		TileNet::from_iter(cols, distances.into_iter().map(|x| min(x, u32::from(u16::MAX)) as u16))
+	}
+}
+
+// Two raster passes over neighbours one step away, optionally with the diagonals
+fn raster(distances: &mut [u32], cols: usize, rows: usize, diagonal: bool) {
+	let relax = |distances: &mut [u32], at: usize, x: usize, y: usize, dy: isize| {
+		let row = y as isize + dy;
+		if row < 0 || row >= rows as isize {
+			return;
+		}
+		let row = row as usize * cols;
+		let from = if diagonal { x.saturating_sub(1) } else { x };
+		let to = if diagonal { min(x + 2, cols) } else { x + 1 };
+		for neighbour in row + from..row + to {
+			distances[at] = min(distances[at], distances[neighbour] + 1);
+		}
+	};
+	for y in 0..rows {
+		for x in 0..cols {
+			let at = x + y * cols;
+			relax(distances, at, x, y, -1);
+			if x > 0 {
+				distances[at] = min(distances[at], distances[at - 1] + 1);
+			}
+		}
+	}
+	for y in (0..rows).rev() {
+		for x in (0..cols).rev() {
+			let at = x + y * cols;
+			relax(distances, at, x, y, 1);
+			if x + 1 < cols {
+				distances[at] = min(distances[at], distances[at + 1] + 1);
+			}
+		}
+	}
+}
+
+// Exact squared distances along the columns, then the rows, then the roots
+fn euclidean(distances: &mut [u32], cols: usize, rows: usize) {
+	let mut squared: Vec<f64> = distances.iter().map(|x| if *x == 0 { 0.0 } else { f64::INFINITY }).collect();
+	let mut line = vec![];
+	for x in 0..cols {
+		line.clear();
+		line.extend((0..rows).map(|y| squared[x + y * cols]));
+		for (y, value) in lower_envelope(&line).into_iter().enumerate() {
+			squared[x + y * cols] = value;
+		}
+	}
+	for y in 0..rows {
+		let values = lower_envelope(&squared[y * cols..(y + 1) * cols]);
+		squared[y * cols..(y + 1) * cols].copy_from_slice(&values);
+	}
+	for (distance, squared) in distances.iter_mut().zip(squared) {
+		*distance = if squared.is_finite() { squared.sqrt().round() as u32 } else { FAR };
+	}
+}
+
+// One dimensional squared distance transform by Felzenszwalb and Huttenlocher:
+// min over q of (p - q)² + f(q), the lower envelope of parabolas rooted at f
+fn lower_envelope(f: &[f64]) -> Vec<f64> {
+	let roots: Vec<usize> = (0..f.len()).filter(|q| f[*q].is_finite()).collect();
+	if roots.is_empty() {
+		return vec![f64::INFINITY; f.len()];
+	}
+	let intersection = |q: usize, v: usize| {
+		let (q2, v2) = ((q * q) as f64, (v * v) as f64);
+		((f[q] + q2) - (f[v] + v2)) / (2.0 * (q as f64 - v as f64))
+	};
+	// Parabolas of the envelope, and from where each one is the lowest
+	let mut parabolas: Vec<usize> = vec![roots[0]];
+	let mut starts: Vec<f64> = vec![f64::NEG_INFINITY];
+	for &q in &roots[1..] {
+		let mut s = intersection(q, *parabolas.last().expect("never empty"));
+		while s <= *starts.last().expect("never empty") {
+			parabolas.pop();
+			starts.pop();
+			s = intersection(q, *parabolas.last().expect("the first parabola is never removed"));
+		}
+		parabolas.push(q);
+		starts.push(s);
+	}
+	let mut k = 0;
+	(0..f.len())
+		.map(|p| {
+			while k + 1 < starts.len() && starts[k + 1] < p as f64 {
+				k += 1;
+			}
+			let q = parabolas[k];
+			(p as f64 - q as f64).powi(2) + f[q]
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Distance to the closest matching tile, by trying all of them
+	fn brute(net: &TileNet<char>, metric: Metric) -> Vec<u16> {
+		let (cols, _) = net.get_size();
+		let walls: Vec<(isize, isize)> = net.view_all().filter(|x| *x.0 == '#').map(|x| (x.1 as isize, x.2 as isize)).collect();
+		(0..net.get_raw().len())
+			.map(|at| {
+				let (x, y) = ((at % cols) as isize, (at / cols) as isize);
+				walls.iter()
+					.map(|w| {
+						let (dx, dy) = ((w.0 - x).abs(), (w.1 - y).abs());
+						match metric {
+							Metric::Chebyshev => dx.max(dy) as f64,
+							Metric::Manhattan => (dx + dy) as f64,
+							Metric::Euclidean => ((dx * dx + dy * dy) as f64).sqrt(),
+						}
+					})
+					.fold(f64::INFINITY, f64::min)
+					.round()
+					.min(f64::from(u16::MAX)) as u16
+			})
+			.collect()
+	}
+
+	#[test]
+	fn matches_brute_force() {
+		let net = TileNet::from_str_map("
+...........#......
+..#...............
+..................
+.........#........
+..................
+.................#
+#.................
+", |x| x).unwrap();
+		for &metric in &[Metric::Chebyshev, Metric::Manhattan, Metric::Euclidean] {
+			let distances = net.distance_transform(|x| *x == '#', metric);
+			assert_eq!(distances.get_raw(), &brute(&net, metric)[..], "{:?}", metric);
+			let none = net.distance_transform(|_| false, metric);
+			assert!(none.get_raw().iter().all(|x| *x == u16::MAX));
+		}
+	}
+}
@@ -1,8 +1,29 @@
 use std::fmt;
 use std::cmp::min;
-pub use self::tilenet::{TileNet, TileNetProxy};
+use std::iter::FusedIterator;
+use super::{SignedCoord, YAxis};
+pub use self::brush::{Brush, BrushShape};
+pub use self::distance::Metric;
+pub use self::query::{CastHit, Contact, ContactFlags, ShapeCastHit};
+pub use self::ray::{RayHit, RaycastIter};
+pub use self::snapshot::{MapSnapshot, Patch};
+pub use self::text::DisplayWith;
+pub use self::tilenet::{Span, TileNet, TileNetProxy};
+pub(crate) use self::tilenet::union;
+pub use self::views::StridedView;
+#[cfg(feature = "mesh")]
+pub(crate) use self::query::greedy_rects;
 
+mod batch;
+mod brush;
+mod distance;
+mod paint;
+mod query;
+mod ray;
+mod snapshot;
+mod text;
 mod tilenet;
+mod views;
 
 /// Tile iterator returning tiles from the `tile_net::TileNet`.
 ///
@@ -51,6 +72,112 @@ pub struct TileSet<'a, T, I>
 	tilenet: &'a TileNet<T>,
 	points: I,
 	last_coord: (i32, i32),
+	border: &'a OutOfBounds<T>,
+}
+
+/// What lies outside of a `TileNet`
+///
+/// See `TileNet::set_out_of_bounds` and `TileNet::collide_set_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutOfBounds<T> {
+	/// Nothing: indices outside of the net are skipped
+	Empty,
+	/// Solid tiles, so the border of the net acts as a wall
+	///
+	/// Respected by the queries on `TileNet`, by the built-in bodies and by
+	/// `#[derive(Collable)]`. A plain `TileSet` has no tile to yield, so it
+	/// skips these indices; iterate over `TileSet::with_border` to see them,
+	/// or use `Custom` with a solid tile where a tile is needed.
+	Solid,
+	/// Copies of the given tile
+	Custom(T),
+	/// The net itself, repeated: indices are taken modulo the size of the net
+	///
+	/// Makes worlds loop around, like in asteroids-style games. Affects
+	/// `TileNet::get`, `collide_set`, views reaching past the right or bottom
+	/// edge and the queries on `TileNet`. Writes never wrap: setters and
+	/// `TileNet::get_mut` ignore indices outside of the net, and checked
+	/// `try_` methods still report them.
+	///
+	/// ```
+	/// use tile_net::{OutOfBounds, TileNet};
+	/// let mut net: TileNet<usize> = TileNet::from_iter(3, 0..9);
+	/// net.set_out_of_bounds(OutOfBounds::Wrap);
+	/// assert_eq!(net.get((4, 3)), Some(&1));
+	/// let set = net.collide_set(vec![(-1, 0), (0, -1)].into_iter());
+	/// assert_eq!(set.cloned().collect::<Vec<_>>(), vec![2, 6]);
+	/// assert_eq!(net.view_box((2, 4, 0, 1)).map(|x| *x.0).collect::<Vec<_>>(), vec![2, 0]);
+	/// ```
+	Wrap,
+}
+
+impl<T> OutOfBounds<T> {
+	/// Convert the tile of `Custom`, keeping the other policies
+	pub fn map<U, F>(self, f: F) -> OutOfBounds<U>
+		where F: FnOnce(T) -> U
+	{
+		match self {
+			OutOfBounds::Empty => OutOfBounds::Empty,
+			OutOfBounds::Solid => OutOfBounds::Solid,
+			OutOfBounds::Custom(tile) => OutOfBounds::Custom(f(tile)),
+			OutOfBounds::Wrap => OutOfBounds::Wrap,
+		}
+	}
+
+	/// Borrow the tile of `Custom`
+	pub fn as_ref(&self) -> OutOfBounds<&T> {
+		match *self {
+			OutOfBounds::Empty => OutOfBounds::Empty,
+			OutOfBounds::Solid => OutOfBounds::Solid,
+			OutOfBounds::Custom(ref tile) => OutOfBounds::Custom(tile),
+			OutOfBounds::Wrap => OutOfBounds::Wrap,
+		}
+	}
+}
+
+/// Point of a `TileNet` that stays in place when resizing
+///
+/// See `TileNet::resize_with`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Anchor {
+	/// Keep the top-left corner, growing and shrinking to the right and bottom
+	TopLeft,
+	/// Keep the middle of the top edge
+	Top,
+	/// Keep the top-right corner
+	TopRight,
+	/// Keep the middle of the left edge
+	Left,
+	/// Keep the center, growing and shrinking on all sides
+	Center,
+	/// Keep the middle of the right edge
+	Right,
+	/// Keep the bottom-left corner
+	BottomLeft,
+	/// Keep the middle of the bottom edge
+	Bottom,
+	/// Keep the bottom-right corner
+	BottomRight,
+}
+
+impl Anchor {
+	// Offset of old content in a grid resized from `old` to `new` tiles,
+	// with 0 keeping the start, 1 the middle and 2 the end of an axis.
+	fn offset(&self, old: (usize, usize), new: (usize, usize)) -> (isize, isize) {
+		let (x, y) = match *self {
+			Anchor::TopLeft => (0, 0),
+			Anchor::Top => (1, 0),
+			Anchor::TopRight => (2, 0),
+			Anchor::Left => (0, 1),
+			Anchor::Center => (1, 1),
+			Anchor::Right => (2, 1),
+			Anchor::BottomLeft => (0, 2),
+			Anchor::Bottom => (1, 2),
+			Anchor::BottomRight => (2, 2),
+		};
+		let axis = |side: isize, old: usize, new: usize| (new as isize - old as isize) * side / 2;
+		(axis(x, old.0, new.0), axis(y, old.1, new.1))
+	}
 }
 
 impl<'a, T, I> TileSet<'a, T, I>
@@ -64,6 +191,59 @@ impl<'a, T, I> TileSet<'a, T, I>
 	pub fn get_coords(&self) -> (i32, i32) {
 		self.last_coord
 	}
+
+	/// Get the coordinate of the last tile, see `get_coords`
+	pub fn get_coord(&self) -> SignedCoord {
+		self.last_coord.into()
+	}
+
+	// Which way the rows of the net are counted.
+	pub(crate) fn y_axis(&self) -> YAxis {
+		self.tilenet.get_space().y_axis
+	}
+
+	// Like `next`, but yields `Some(None)` for a solid border instead of skipping it.
+	pub(crate) fn next_or_border(&mut self) -> Option<Option<&'a T>> {
+		for point in self.points.by_ref() {
+			self.last_coord = point;
+			if let Some(tile) = lookup(self.tilenet, self.border, point) {
+				return Some(tile);
+			}
+		}
+		None
+	}
+
+	/// Iterate over the tiles together with their coordinates
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let net = TileNet::sample();
+	/// let set = net.collide_set((3..7).map(|x| (4, x)));
+	/// let solid: Vec<(i32, i32)> = set.with_coords().filter(|x| *x.1 != 0).map(|x| x.0).collect();
+	/// assert_eq!(solid, vec![(4, 5), (4, 6)]);
+	/// ```
+	pub fn with_coords(self) -> WithCoords<'a, T, I> {
+		WithCoords { set: self }
+	}
+
+	/// Iterate over the tiles, yielding `None` for a solid border
+	///
+	/// Unlike the `TileSet` itself, this does not skip indices covered by
+	/// `OutOfBounds::Solid`, so a resolver checking for free tiles also
+	/// stops at the edge of the net.
+	///
+	/// ```
+	/// use tile_net::{OutOfBounds, TileNet};
+	/// let mut net: TileNet<usize> = TileNet::new(4, 4);
+	/// net.set_out_of_bounds(OutOfBounds::Solid);
+	/// let list = vec![(2, 0), (3, 0), (4, 0)];
+	/// assert!(net.collide_set(list.clone().into_iter()).all(|x| *x == 0));
+	/// let tiles: Vec<Option<&usize>> = net.collide_set(list.into_iter()).with_border().collect();
+	/// assert_eq!(tiles, vec![Some(&0), Some(&0), None]);
+	/// ```
+	pub fn with_border(self) -> WithBorder<'a, T, I> {
+		WithBorder { set: self }
+	}
 }
 
 impl<'a, T, I> Iterator for TileSet<'a, T, I>
@@ -73,19 +253,29 @@ impl<'a, T, I> Iterator for TileSet<'a, T, I>
 	type Item = &'a T;
 	fn next(&mut self) -> Option<Self::Item> {
 		loop {
-			if let Some(point) = self.points.next() {
-				self.last_coord = point;
-				if point.0 >= 0 && point.1 >= 0 && point.0 < self.tilenet.get_size().0 as i32 &&
-				   point.1 < self.tilenet.get_size().1 as i32 {
-					return self.tilenet.get((point.0 as usize, point.1 as usize));
-				} else {
-					continue;
-				}
-			} else {
-				return None;
+			match self.next_or_border() {
+				Some(Some(tile)) => return Some(tile),
+				Some(None) => continue,
+				None => return None,
 			}
 		}
 	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		// Every point gives a tile only if those outside of the net do too
+		let (lower, upper) = self.points.size_hint();
+		match *self.border {
+			OutOfBounds::Custom(_) => (lower, upper),
+			_ => (0, upper),
+		}
+	}
+}
+
+// Points outside of the net may be skipped, so only the end is certain
+impl<'a, T, I> FusedIterator for TileSet<'a, T, I>
+	where T: 'a,
+	      I: FusedIterator<Item = (i32, i32)>
+{
 }
 
 impl<'a, T, I> fmt::Debug for TileSet<'a, T, I>
@@ -95,17 +285,169 @@ impl<'a, T, I> fmt::Debug for TileSet<'a, T, I>
 	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
 		let viewer = self.clone();
 		for tile in viewer {
-			try!(write!(formatter, "{:?} ", tile));
+			write!(formatter, "{:?} ", tile)?;
 		}
 		Ok(())
 	}
 }
 
+// Find the tile at an index, following `border` outside of the net. Gives
+// `Some(None)` for a solid border and `None` for an index without a tile.
+fn lookup<'a, T>(tilenet: &'a TileNet<T>,
+                 border: &'a OutOfBounds<T>,
+                 point: (i32, i32))
+                 -> Option<Option<&'a T>> {
+	let (cols, rows) = tilenet.get_size();
+	if point.0 >= 0 && point.1 >= 0 && point.0 < cols as i32 && point.1 < rows as i32 {
+		return tilenet.get((point.0 as usize, point.1 as usize)).map(Some);
+	}
+	match *border {
+		OutOfBounds::Empty => None,
+		OutOfBounds::Solid => Some(None),
+		OutOfBounds::Custom(ref tile) => Some(Some(tile)),
+		OutOfBounds::Wrap => {
+			tilenet.wrap_around((point.0 as i64, point.1 as i64)).and_then(|x| tilenet.get(x)).map(Some)
+		}
+	}
+}
+
+/// Tile iterator yielding the coordinates of each tile of a `TileSet`
+///
+/// Created by `TileSet::with_coords`.
+#[derive(Clone)]
+pub struct WithCoords<'a, T, I>
+	where T: 'a
+{
+	set: TileSet<'a, T, I>,
+}
+
+impl<'a, T, I> Iterator for WithCoords<'a, T, I>
+	where T: 'a,
+	      I: Iterator<Item = (i32, i32)>
+{
+	type Item = ((i32, i32), &'a T);
+	fn next(&mut self) -> Option<Self::Item> {
+		self.set.next().map(|x| (self.set.get_coords(), x))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.set.size_hint()
+	}
+}
+
+/// Tile iterator yielding `None` for indices covered by a solid border
+///
+/// Created by `TileSet::with_border`.
+#[derive(Clone)]
+pub struct WithBorder<'a, T, I>
+	where T: 'a
+{
+	set: TileSet<'a, T, I>,
+}
+
+impl<'a, T, I> WithBorder<'a, T, I>
+	where T: 'a,
+	      I: Iterator<Item = (i32, i32)>
+{
+	/// Get the coordinate of the last tile, see `TileSet::get_coords`
+	pub fn get_coords(&self) -> (i32, i32) {
+		self.set.get_coords()
+	}
+}
+
+impl<'a, T, I> Iterator for WithBorder<'a, T, I>
+	where T: 'a,
+	      I: Iterator<Item = (i32, i32)>
+{
+	type Item = Option<&'a T>;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.set.next_or_border()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let (lower, upper) = self.set.points.size_hint();
+		match *self.set.border {
+			OutOfBounds::Custom(_) | OutOfBounds::Solid => (lower, upper),
+			_ => (0, upper),
+		}
+	}
+}
+
+/// Tile iterator that remembers which collision point produced each tile
+///
+/// Created by `TileNet::collide_set_attributed`, usually from
+/// `Collable::tiles_attributed`. Works like `TileSet`, but `get_point`
+/// additionally reports the index of the point whose line produced the last
+/// tile. This tells the bottom corners hitting the floor apart from the top
+/// corners hitting the ceiling. Indices outside of the net follow its
+/// out-of-bounds policy, like in `TileNet::collide_set`.
+///
+/// ```
+/// use tile_net::TileNet;
+/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+/// net.set(&1, (4, 6));
+/// let list = vec![((2, 6), 0), ((4, 6), 1)];
+/// let mut set = net.collide_set_attributed(list.into_iter());
+/// let mut hit_by = vec![];
+/// while let Some(tile) = set.next() {
+///     if *tile != 0 {
+///         hit_by.push((set.get_coords(), set.get_point()));
+///     }
+/// }
+/// assert_eq!(hit_by, vec![((4, 6), 1)]);
+/// ```
+#[derive(Clone)]
+pub struct AttributedTileSet<'a, T, I>
+	where T: 'a
+{
+	tilenet: &'a TileNet<T>,
+	points: I,
+	last_coord: (i32, i32),
+	last_point: usize,
+}
+
+impl<'a, T, I> AttributedTileSet<'a, T, I>
+	where T: 'a,
+	      I: Iterator<Item = ((i32, i32), usize)>
+{
+	/// Get the coordinate of the last tile
+	pub fn get_coords(&self) -> (i32, i32) {
+		self.last_coord
+	}
+
+	/// Get the index of the point that produced the last tile
+	///
+	/// The index refers to the order of the points given by `Collable::points`.
+	pub fn get_point(&self) -> usize {
+		self.last_point
+	}
+}
+
+impl<'a, T, I> Iterator for AttributedTileSet<'a, T, I>
+	where T: 'a,
+	      I: Iterator<Item = ((i32, i32), usize)>
+{
+	type Item = &'a T;
+	fn next(&mut self) -> Option<Self::Item> {
+		for (point, source) in &mut self.points {
+			self.last_coord = point;
+			self.last_point = source;
+			if let Some(Some(tile)) = lookup(self.tilenet, self.tilenet.get_out_of_bounds(), point) {
+				return Some(tile);
+			}
+		}
+		None
+	}
+}
+
 /// Tile iterator for a rectangular view of the `tile_net::TileNet`.
 ///
 /// Used to cull the amount of tiles to draw. You provide it with a desired
 /// rectangle, and the tileview will be your iterator iterating over only
 /// the desired tiles.
+///
+/// Rows are visited from the top of the screen down, which starts at the
+/// last row if the net counts rows upwards, see `YAxis`.
 #[derive(Clone)]
 pub struct TileView<'a, T>
 	where T: 'a
@@ -121,8 +463,10 @@ impl<'a, T> TileView<'a, T>
 	fn new(tilenet: &'a TileNet<T>,
 	       mut rectangle: (usize, usize, usize, usize))
 	       -> TileView<'a, T> {
-		rectangle.1 = min(rectangle.1, tilenet.get_size().0);
-		rectangle.3 = min(rectangle.3, tilenet.get_size().1);
+		if !matches!(*tilenet.get_out_of_bounds(), OutOfBounds::Wrap) {
+			rectangle.1 = min(rectangle.1, tilenet.get_size().0);
+			rectangle.3 = min(rectangle.3, tilenet.get_size().1);
+		}
 		TileView {
 			tilenet: tilenet,
 			rectangle: rectangle,
@@ -136,10 +480,15 @@ impl<'a, T> Iterator for TileView<'a, T>
 {
 	type Item = (&'a T, usize, usize);
 	fn next(&mut self) -> Option<Self::Item> {
-		if self.current.1 >= self.rectangle.3 {
+		if self.current.1 >= self.rectangle.3 || self.rectangle.0 >= self.rectangle.1 {
 			return None;
 		}
-		let tile = self.tilenet.get(self.current).map(|x| (x, self.current.0, self.current.1));
+		// Rows are counted from the top of the screen
+		let y = match self.tilenet.get_space().y_axis {
+			YAxis::Down => self.current.1,
+			YAxis::Up => self.rectangle.2 + self.rectangle.3 - 1 - self.current.1,
+		};
+		let tile = self.tilenet.get((self.current.0, y)).map(|x| (x, self.current.0, y));
 
 		self.current.0 += 1;
 		if self.current.0 >= self.rectangle.1 {
@@ -148,6 +497,26 @@ impl<'a, T> Iterator for TileView<'a, T>
 		}
 		tile
 	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let left = if self.current.1 >= self.rectangle.3 || self.rectangle.0 >= self.rectangle.1 {
+			0
+		} else {
+			let width = self.rectangle.1 - self.rectangle.0;
+			(self.rectangle.3 - self.current.1) * width - (self.current.0 - self.rectangle.0)
+		};
+		(left, Some(left))
+	}
+}
+
+impl<'a, T> ExactSizeIterator for TileView<'a, T>
+    where T: 'a
+{
+}
+
+impl<'a, T> FusedIterator for TileView<'a, T>
+    where T: 'a
+{
 }
 
 impl<'a, T> fmt::Debug for TileView<'a, T>
@@ -159,14 +528,14 @@ impl<'a, T> fmt::Debug for TileView<'a, T>
 		let width = viewer.rectangle.1 - viewer.rectangle.0;
 		for (index, tile) in viewer.enumerate() {
 			if index % width == 0 && index != 0 {
-				try!(formatter.write_str("\n"));
+				formatter.write_str("\n")?;
 			}
 			let mut current = format!("{:?}", tile);
 			let length = current.len();
 			if let Some(biggest) = biggest {
 				(0..biggest - length).map(|_| current.push(' ')).count();
 			}
-			try!(write!(formatter, "{} ", current));
+			write!(formatter, "{} ", current)?;
 		}
 		Ok(())
 	}
@@ -0,0 +1,420 @@
+use std::cmp::min;
+use super::{TileNet, TileNetProxy};
+use super::tilenet::Span;
+use super::super::{GridCoord, Line, Vector};
+
+impl<T> TileNet<T>
+    where T: Clone
+{
+	/// Copy a box of tiles from another net, with its top-left corner at `dest`
+	///
+	/// `src_rect` is given as (x_start, x_stop, y_start, y_stop) with exclusive
+	/// stops, like in `view_box`. Whatever falls outside of either net is
+	/// clipped away.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let room: TileNet<usize> = TileNet::from_iter(2, 1..5);
+	/// let mut world: TileNet<usize> = TileNet::new(3, 3);
+	/// world.blit(&room, (0, 2, 0, 2), (2, 1));
+	/// assert_eq!(world.get_raw(), &[0, 0, 0,
+	///                               0, 0, 1,
+	///                               0, 0, 3]);
+	/// ```
+	pub fn blit<P>(&mut self, src: &TileNet<T>, src_rect: (usize, usize, usize, usize), dest: P)
+		where P: Into<GridCoord>
+	{
+		let dest = dest.into().to_tuple();
+		let (src_cols, src_rows) = src.get_size();
+		let (cols, rows) = self.get_size();
+		let width = min(src_rect.1, src_cols).saturating_sub(src_rect.0).min(cols.saturating_sub(dest.0));
+		let height = min(src_rect.3, src_rows).saturating_sub(src_rect.2).min(rows.saturating_sub(dest.1));
+		if width == 0 {
+			return;
+		}
+		for row in 0..height {
+			let from = src_rect.0 + (src_rect.2 + row) * src_cols;
+			let to = dest.0 + (dest.1 + row) * cols;
+			self.raw_untracked()[to..to + width].clone_from_slice(&src.get_raw()[from..from + width]);
+		}
+		if height > 0 {
+			self.touch((dest.0, dest.1, dest.0 + width - 1, dest.1 + height - 1));
+		}
+	}
+
+	/// Set every tile touched by a line between the centers of two tiles
+	///
+	/// The tiles are found by `Line::supercover`, so the line is gapless: each
+	/// tile shares an edge with the next one. Both end points are set.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(4, 3);
+	/// net.set_line(&1, (0, 0), (3, 2));
+	/// assert_eq!(net.get_raw(), &[1, 1, 0, 0,
+	///                             0, 1, 1, 0,
+	///                             0, 0, 1, 1]);
+	/// ```
+	pub fn set_line<P>(&mut self, value: &T, from: P, to: P)
+		where P: Into<GridCoord>
+	{
+		let points = line_tiles(from.into().to_tuple(), to.into().to_tuple());
+		self.batch(|net| {
+			for point in points {
+				net.set(value, point);
+			}
+		});
+	}
+
+	/// Set a disc of tiles around a center tile
+	///
+	/// A tile is inside when its center lies within `radius + 0.5` tiles of
+	/// the center of `center`, so a radius of 0 sets a single tile.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(5, 5);
+	/// net.set_circle_filled(&1, (2, 2), 1);
+	/// assert_eq!(net.get_raw(), &[0, 0, 0, 0, 0,
+	///                             0, 1, 1, 1, 0,
+	///                             0, 1, 1, 1, 0,
+	///                             0, 1, 1, 1, 0,
+	///                             0, 0, 0, 0, 0]);
+	/// net.set_circle_outline(&2, (2, 2), 2);
+	/// assert_eq!(&net.get_raw()[..10], &[0, 2, 2, 2, 0,
+	///                                    2, 2, 1, 2, 2]);
+	/// ```
+	pub fn set_circle_filled<P>(&mut self, value: &T, center: P, radius: usize)
+		where P: Into<GridCoord>
+	{
+		self.set_ellipse_filled(value, center, (radius, radius));
+	}
+
+	/// Set the edge of a disc of tiles, see `set_circle_filled`
+	///
+	/// The edge consists of the tiles in the disc with a side or corner next
+	/// to a tile outside of it, so it has no diagonal gaps.
+	pub fn set_circle_outline<P>(&mut self, value: &T, center: P, radius: usize)
+		where P: Into<GridCoord>
+	{
+		self.set_ellipse_outline(value, center, (radius, radius));
+	}
+
+	/// Set an ellipse of tiles with radii (horizontal, vertical), see `set_circle_filled`
+	pub fn set_ellipse_filled<P>(&mut self, value: &T, center: P, radii: (usize, usize))
+		where P: Into<GridCoord>
+	{
+		let points = ellipse_tiles(center.into().to_tuple(), radii, false);
+		self.batch(|net| {
+			for point in points {
+				net.set(value, point);
+			}
+		});
+	}
+
+	/// Set the edge of an ellipse of tiles, see `set_circle_outline`
+	pub fn set_ellipse_outline<P>(&mut self, value: &T, center: P, radii: (usize, usize))
+		where P: Into<GridCoord>
+	{
+		let points = ellipse_tiles(center.into().to_tuple(), radii, true);
+		self.batch(|net| {
+			for point in points {
+				net.set(value, point);
+			}
+		});
+	}
+
+	/// Mirror a box of tiles left to right
+	///
+	/// Boxes are given as (x_start, x_stop, y_start, y_stop) with exclusive
+	/// stops, like in `view_box`, and are clipped to the net.
+	pub fn flip_region_x(&mut self, rect: (usize, usize, usize, usize)) {
+		self.remap_region(rect, |x, y, w, _| (w - 1 - x, y));
+	}
+
+	/// Mirror a box of tiles top to bottom
+	pub fn flip_region_y(&mut self, rect: (usize, usize, usize, usize)) {
+		self.remap_region(rect, |x, y, _, h| (x, h - 1 - y));
+	}
+
+	/// Rotate a box of tiles by half a turn
+	pub fn rotate_region_180(&mut self, rect: (usize, usize, usize, usize)) {
+		self.remap_region(rect, |x, y, w, h| (w - 1 - x, h - 1 - y));
+	}
+
+	/// Rotate a square box of tiles a quarter turn clockwise, as seen with y pointing down
+	///
+	/// Use `rotated_90` for boxes that are not square, after copying them
+	/// into a net of their own.
+	///
+	/// # Panics #
+	/// Panics if the box, after clipping, is not square.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::from_iter(3, 1..10);
+	/// net.rotate_region_90((1, 3, 1, 3));
+	/// assert_eq!(net.get_raw(), &[1, 2, 3,
+	///                             4, 8, 5,
+	///                             7, 9, 6]);
+	/// ```
+	pub fn rotate_region_90(&mut self, rect: (usize, usize, usize, usize)) {
+		self.remap_square(rect, |x, y, n| (y, n - 1 - x));
+	}
+
+	/// Rotate a square box of tiles a quarter turn counter-clockwise, see `rotate_region_90`
+	///
+	/// # Panics #
+	/// Panics if the box, after clipping, is not square.
+	pub fn rotate_region_270(&mut self, rect: (usize, usize, usize, usize)) {
+		self.remap_square(rect, |x, y, n| (n - 1 - y, x));
+	}
+
+	/// Mirror a square box of tiles along its top-left to bottom-right diagonal
+	///
+	/// # Panics #
+	/// Panics if the box, after clipping, is not square.
+	pub fn transpose_region(&mut self, rect: (usize, usize, usize, usize)) {
+		self.remap_square(rect, |x, y, _| (y, x));
+	}
+
+	/// Create a copy of the net rotated a quarter turn clockwise
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let net: TileNet<usize> = TileNet::from_iter(3, 1..7);
+	/// let rotated = net.rotated_90();
+	/// assert_eq!(rotated.get_size(), (2, 3));
+	/// assert_eq!(rotated.get_raw(), &[4, 1,
+	///                                 5, 2,
+	///                                 6, 3]);
+	/// ```
+	pub fn rotated_90(&self) -> TileNet<T> {
+		let (_, rows) = self.get_size();
+		self.remapped(|x, y| (y, rows - 1 - x))
+	}
+
+	/// Create a copy of the net rotated a quarter turn counter-clockwise
+	pub fn rotated_270(&self) -> TileNet<T> {
+		let (cols, _) = self.get_size();
+		self.remapped(|x, y| (cols - 1 - y, x))
+	}
+
+	/// Create a copy of the net mirrored along its top-left to bottom-right diagonal
+	pub fn transposed(&self) -> TileNet<T> {
+		self.remapped(|x, y| (y, x))
+	}
+
+	// Rearrange a clipped box, with `source` mapping a position in the box to
+	// the position whose tile ends up there, given the box size.
+	fn remap_region<F>(&mut self, rect: (usize, usize, usize, usize), source: F)
+		where F: Fn(usize, usize, usize, usize) -> (usize, usize)
+	{
+		let (cols, rows) = self.get_size();
+		let (x0, y0) = (rect.0, rect.2);
+		let w = min(rect.1, cols).saturating_sub(x0);
+		let h = min(rect.3, rows).saturating_sub(y0);
+		let mut old = Vec::with_capacity(w * h);
+		for y in 0..h {
+			let start = x0 + (y0 + y) * cols;
+			old.extend_from_slice(&self.get_raw()[start..start + w]);
+		}
+		let raw = self.raw_untracked();
+		for y in 0..h {
+			for x in 0..w {
+				let from = source(x, y, w, h);
+				raw[x0 + x + (y0 + y) * cols].clone_from(&old[from.0 + from.1 * w]);
+			}
+		}
+		if w > 0 && h > 0 {
+			self.touch((x0, y0, x0 + w - 1, y0 + h - 1));
+		}
+	}
+
+	// Like `remap_region`, for boxes that must be square.
+	fn remap_square<F>(&mut self, rect: (usize, usize, usize, usize), source: F)
+		where F: Fn(usize, usize, usize) -> (usize, usize)
+	{
+		let (cols, rows) = self.get_size();
+		let w = min(rect.1, cols).saturating_sub(rect.0);
+		let h = min(rect.3, rows).saturating_sub(rect.2);
+		assert_eq!(w, h, "rotating a region requires a square region");
+		self.remap_region(rect, |x, y, n, _| source(x, y, n));
+	}
+
+	// Build a net with the size swapped, with `source` mapping each new
+	// position to the position of its tile in this net.
+	fn remapped<F>(&self, source: F) -> TileNet<T>
+		where F: Fn(usize, usize) -> (usize, usize)
+	{
+		let (cols, rows) = self.get_size();
+		let mut map = Vec::with_capacity(cols * rows);
+		for y in 0..cols {
+			for x in 0..rows {
+				let from = source(x, y);
+				map.push(self.get_raw()[from.0 + from.1 * cols].clone());
+			}
+		}
+		// A net without rows still needs a column
+		let mut net = TileNet::from_raw(rows.max(1), map);
+		net.set_out_of_bounds(self.get_out_of_bounds().clone());
+		net
+	}
+}
+
+impl<'a, T> TileNetProxy<'a, T>
+    where T: Clone
+{
+	/// Set a disc of tiles, see `TileNet::set_circle_filled`
+	pub fn set_circle_filled<P>(&mut self, value: &T, center: P, radius: usize) -> Span
+		where P: Into<GridCoord>
+	{
+		self.set_ellipse_filled(value, center, (radius, radius))
+	}
+
+	/// Set the edge of a disc of tiles, see `TileNet::set_circle_outline`
+	pub fn set_circle_outline<P>(&mut self, value: &T, center: P, radius: usize) -> Span
+		where P: Into<GridCoord>
+	{
+		self.set_ellipse_outline(value, center, (radius, radius))
+	}
+
+	/// Set an ellipse of tiles, see `TileNet::set_ellipse_filled`
+	pub fn set_ellipse_filled<P>(&mut self, value: &T, center: P, radii: (usize, usize)) -> Span
+		where P: Into<GridCoord>
+	{
+		let center = center.into().to_tuple();
+		self.tilenet.set_ellipse_filled(value, center, radii);
+		for point in ellipse_tiles(center, radii, false) {
+			self.track(point);
+		}
+		self.get_span()
+	}
+
+	/// Set the edge of an ellipse of tiles, see `TileNet::set_ellipse_outline`
+	pub fn set_ellipse_outline<P>(&mut self, value: &T, center: P, radii: (usize, usize)) -> Span
+		where P: Into<GridCoord>
+	{
+		let center = center.into().to_tuple();
+		self.tilenet.set_ellipse_outline(value, center, radii);
+		for point in ellipse_tiles(center, radii, true) {
+			self.track(point);
+		}
+		self.get_span()
+	}
+}
+
+// Tiles of an ellipse, or only those touching a tile outside of it, skipping
+// negative coordinates.
+fn ellipse_tiles(center: (usize, usize), radii: (usize, usize), outline: bool) -> Vec<(usize, usize)> {
+	let (rx, ry) = (radii.0 as f32 + 0.5, radii.1 as f32 + 0.5);
+	let inside = |dx: i64, dy: i64| {
+		let (x, y) = (dx as f32 / rx, dy as f32 / ry);
+		x * x + y * y <= 1.0
+	};
+	let (cx, cy) = (center.0 as i64, center.1 as i64);
+	let (wx, wy) = (radii.0 as i64, radii.1 as i64);
+	let mut tiles = vec![];
+	for dy in -wy..wy + 1 {
+		for dx in -wx..wx + 1 {
+			if cx + dx < 0 || cy + dy < 0 || !inside(dx, dy) {
+				continue;
+			}
+			let surrounded = (-1..2).all(|y| (-1..2).all(|x| inside(dx + x, dy + y)));
+			if outline && surrounded {
+				continue;
+			}
+			tiles.push(((cx + dx) as usize, (cy + dy) as usize));
+		}
+	}
+	tiles
+}
+
+// Tiles covered by the line between the centers of two tiles.
+fn line_tiles(from: (usize, usize), to: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+	let center = |p: (usize, usize)| Vector(p.0 as f32 + 0.5, p.1 as f32 + 0.5);
+	Line(center(from), center(to)).supercover().map(|x| (x.0 as usize, x.1 as usize))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn blit_clips() {
+		let src: TileNet<usize> = TileNet::from_iter(3, 1..10);
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		net.blit(&src, (1, 10, 1, 10), (3, 3));
+		assert_eq!(net.get_raw()[15], 5);
+		assert_eq!(net.get_raw().iter().filter(|x| **x != 0).count(), 1);
+		net.blit(&src, (0, 3, 0, 3), (0, 0));
+		assert_eq!(&net.get_raw()[..4], &[1, 2, 3, 0]);
+		assert_eq!(&net.get_raw()[8..12], &[7, 8, 9, 0]);
+		net.blit(&src, (0, 3, 0, 3), (9, 0));
+		net.blit(&src, (3, 3, 0, 3), (0, 0));
+		assert_eq!(net.get_raw()[3], 0);
+	}
+
+	#[test]
+	fn set_line() {
+		let mut net: TileNet<usize> = TileNet::new(5, 5);
+		net.set_line(&1, (4, 1), (0, 1));
+		net.set_line(&2, (2, 0), (2, 9));
+		net.set_line(&3, (3, 3), (3, 3));
+		assert_eq!(net.get_raw(), &[0, 0, 2, 0, 0,
+		                            1, 1, 2, 1, 1,
+		                            0, 0, 2, 0, 0,
+		                            0, 0, 2, 3, 0,
+		                            0, 0, 2, 0, 0]);
+	}
+
+	#[test]
+	fn ellipse() {
+		let mut net: TileNet<usize> = TileNet::new(7, 5);
+		let span = net.prepare().set_ellipse_outline(&1, (3, 2), (3, 2));
+		assert_eq!(span, (0, 0, 6, 4));
+		assert_eq!(net.get_raw(), &[0, 1, 1, 1, 1, 1, 0,
+		                            1, 1, 0, 0, 0, 1, 1,
+		                            1, 0, 0, 0, 0, 0, 1,
+		                            1, 1, 0, 0, 0, 1, 1,
+		                            0, 1, 1, 1, 1, 1, 0]);
+		net.set_ellipse_filled(&2, (3, 2), (3, 2));
+		assert_eq!(net.get_raw().iter().filter(|x| **x == 2).count(), 31);
+		let span = net.prepare().set_circle_filled(&3, (0, 0), 1);
+		assert_eq!(span, (0, 0, 1, 1));
+		assert_eq!(&net.get_raw()[..2], &[3, 3]);
+	}
+
+	#[test]
+	fn region_transforms() {
+		let mut net: TileNet<usize> = TileNet::from_iter(3, 1..7);
+		net.flip_region_x((0, 3, 0, 2));
+		assert_eq!(net.get_raw(), &[3, 2, 1, 6, 5, 4]);
+		net.flip_region_y((1, 9, 0, 9));
+		assert_eq!(net.get_raw(), &[3, 5, 4, 6, 2, 1]);
+		net.rotate_region_180((0, 2, 0, 2));
+		assert_eq!(net.get_raw(), &[2, 6, 4, 5, 3, 1]);
+		net.rotate_region_90((0, 2, 0, 2));
+		net.rotate_region_270((0, 2, 0, 2));
+		assert_eq!(net.get_raw(), &[2, 6, 4, 5, 3, 1]);
+		net.transpose_region((1, 3, 0, 2));
+		assert_eq!(net.get_raw(), &[2, 6, 3, 5, 4, 1]);
+	}
+
+	#[test]
+	#[should_panic]
+	fn rotate_region_not_square() {
+		let mut net: TileNet<usize> = TileNet::new(3, 2);
+		net.rotate_region_90((0, 3, 0, 2));
+	}
+
+	#[test]
+	fn rotated() {
+		let net: TileNet<usize> = TileNet::from_iter(3, 1..7);
+		assert_eq!(net.rotated_270().get_raw(), &[3, 6, 2, 5, 1, 4]);
+		assert_eq!(net.transposed().get_raw(), &[1, 4, 2, 5, 3, 6]);
+		assert_eq!(net.rotated_90().rotated_90().rotated_90().get_raw(), net.rotated_270().get_raw());
+		let empty: TileNet<usize> = TileNet::new(3, 0);
+		assert_eq!(empty.rotated_90().get_raw().len(), 0);
+	}
+}
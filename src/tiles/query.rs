@@ -0,0 +1,654 @@
+use super::{OutOfBounds, RaycastIter, TileNet};
+use super::super::{GridSpace, Rect, SignedCoord, Vector};
+
+// Extra distance pushed past a tile edge, so a point pushed out towards
+// negative coordinates ends up in the neighbouring tile instead of on the edge.
+const EPSILON: f32 = 1e-4;
+
+/// Solid tiles found next to a set of points, see `TileNet::contact_flags`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContactFlags {
+	/// A solid tile is directly below a point, towards positive y unless y points up
+	pub ground: bool,
+	/// A solid tile is directly above a point
+	pub ceiling: bool,
+	/// A solid tile is directly left of a point
+	pub left: bool,
+	/// A solid tile is directly right of a point
+	pub right: bool,
+}
+
+impl ContactFlags {
+	/// Check whether any side touches a solid tile
+	pub fn any(&self) -> bool {
+		self.ground || self.ceiling || self.left || self.right
+	}
+}
+
+/// A point of a body touching a solid tile, see `TileNet::contact_manifold`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+	/// Index of the point touching the tile
+	pub point: usize,
+	/// The tile that is touched
+	pub tile: (i32, i32),
+	/// Normal of the tile side that is touched, pointing away from the tile
+	pub normal: Vector,
+	/// Gap between the point and the side of the tile
+	pub distance: f32,
+}
+
+/// First solid tile hit by a set of moving points, see `TileNet::cast_points`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CastHit {
+	/// Fraction of the movement that can be made before touching the tile, from 0 to 1
+	pub t: f32,
+	/// The tile that was hit
+	pub tile: (i32, i32),
+	/// Normal of the tile side that was hit, pointing away from the tile
+	///
+	/// Zero if the point started inside the tile.
+	pub normal: Vector,
+	/// Index of the point that hit the tile
+	pub point: usize,
+}
+
+/// First solid tile hit by a moving rectangle, see `TileNet::rect_cast`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapeCastHit {
+	/// Fraction of the movement that can be made before touching the tile, from 0 to 1
+	pub t: f32,
+	/// The tile that was hit
+	pub tile: (i32, i32),
+	/// Normal of the tile side that was hit, pointing away from the tile
+	///
+	/// Zero if the rectangle started overlapping the tile.
+	pub normal: Vector,
+}
+
+impl<T> TileNet<T> {
+	// Check whether a world position lies in a solid tile. Positions outside
+	// of the net follow the out-of-bounds policy.
+	pub(crate) fn solid_at<F>(&self, point: (f32, f32), is_solid: &mut F) -> bool
+		where F: FnMut(&T) -> bool
+	{
+		let (cols, rows) = self.get_size();
+		if point.0 >= 0.0 && point.1 >= 0.0 && point.0 < cols as f32 && point.1 < rows as f32 {
+			if let Some(tile) = self.get((point.0 as usize, point.1 as usize)) {
+				return is_solid(tile);
+			}
+		}
+		match *self.get_out_of_bounds() {
+			OutOfBounds::Empty => false,
+			OutOfBounds::Solid => true,
+			OutOfBounds::Custom(ref tile) => is_solid(tile),
+			OutOfBounds::Wrap => {
+				let wrapped = self.wrap((point.0.floor() as i64, point.1.floor() as i64));
+				wrapped.and_then(|x| self.get(x)).is_some_and(is_solid)
+			}
+		}
+	}
+
+	/// Iterate over the tiles overlapping a rectangle in world coordinates
+	///
+	/// The rectangle is mapped onto the net through its `GridSpace`. Tiles
+	/// are yielded row by row with their (col, row). A tile only touched
+	/// along its edge does not overlap, but a rectangle without area still
+	/// covers the tile it lies in. Parts outside of the net are skipped.
+	///
+	/// ```
+	/// use tile_net::{Rect, TileNet, Vector};
+	/// let net: TileNet<usize> = TileNet::from_iter(4, 0..16);
+	/// let hitbox = Rect::new(Vector(0.5, 1.0), Vector(1.0, 0.5));
+	/// let tiles: Vec<_> = net.tiles_in_rect(hitbox).collect();
+	/// assert_eq!(tiles, vec![((0, 1), &4), ((1, 1), &5)]);
+	/// ```
+	pub fn tiles_in_rect<'a>(&'a self, rect: Rect)
+	                         -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
+		let (cols, rows) = self.get_size();
+		let rect = self.get_space().rect_to_grid_units(rect);
+		let span = |start: f32, size: f32, len: usize| {
+			let first = start.floor();
+			let last = (start + size).ceil().max(first + 1.0);
+			let clip = |x: f32| x.clamp(0.0, len as f32) as usize;
+			(clip(first), clip(last))
+		};
+		let (x0, x1) = span(rect.pos.0, rect.size.0, cols);
+		let (y0, y1) = span(rect.pos.1, rect.size.1, rows);
+		let raw = self.get_raw();
+		(y0..y1).flat_map(move |y| (x0..x1).map(move |x| ((x, y), &raw[x + y * cols])))
+	}
+
+	/// Iterate over the tiles whose cell intersects a circle in world coordinates
+	///
+	/// Tiles are yielded row by row with their (col, row), like in
+	/// `tiles_in_rect`. See `tiles_in_radius_sorted` for the nearest tiles first.
+	///
+	/// ```
+	/// use tile_net::{TileNet, Vector};
+	/// let net: TileNet<usize> = TileNet::new(10, 10);
+	/// let crater: Vec<_> = net.tiles_in_radius(Vector(5.0, 5.0), 1.0).map(|x| x.0).collect();
+	/// assert_eq!(crater, vec![(4, 4), (5, 4), (4, 5), (5, 5)]);
+	/// assert_eq!(net.tiles_in_radius(Vector(5.5, 5.5), 0.6).count(), 5);
+	/// ```
+	pub fn tiles_in_radius<'a>(&'a self,
+	                           center: Vector,
+	                           radius: f32)
+	                           -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
+		let bounds = Rect::new(center - Vector(radius, radius), Vector(radius, radius) * 2.0);
+		let space = *self.get_space();
+		self.tiles_in_rect(bounds).filter(move |x| cell_distance(&space, x.0, center) < radius * radius)
+	}
+
+	/// Get the tiles whose cell intersects a circle, nearest to the center first
+	///
+	/// The distance of a tile is measured from the center to the closest point
+	/// of its cell, so the tile containing the center comes first. Ties are
+	/// broken row by row.
+	pub fn tiles_in_radius_sorted(&self, center: Vector, radius: f32) -> Vec<((usize, usize), &T)> {
+		let mut tiles: Vec<_> = self.tiles_in_radius(center, radius).collect();
+		let distance = |tile| cell_distance(self.get_space(), tile, center);
+		tiles.sort_by(|a, b| distance(a.0).total_cmp(&distance(b.0)));
+		tiles
+	}
+
+	/// Probe for solid tiles a short distance around each point
+	///
+	/// Every point is moved by `epsilon` in each of the four directions, and a
+	/// flag is set if any point lands in a solid tile. Use points on the outline
+	/// of a body, and an `epsilon` larger than the distance the points are
+	/// kept from surfaces. This tells whether a body stands on the ground or
+	/// touches a wall without running a solve. Which side is the ground
+	/// follows the `YAxis` of the net.
+	///
+	/// ```
+	/// use tile_net::{Points, TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set_row(&1, 5);
+	/// let corners = [(0.0, 0.0), (0.999, 0.0), (0.0, 0.999), (0.999, 0.999)];
+	/// let flags = net.contact_flags(Points::new(Vector(2.0, 4.0), &corners), 0.01, |x| *x != 0);
+	/// assert!(flags.ground && !flags.ceiling && !flags.left && !flags.right);
+	/// ```
+	pub fn contact_flags<I, F>(&self, points: I, epsilon: f32, mut is_solid: F) -> ContactFlags
+		where I: IntoIterator<Item = (f32, f32)>,
+		      F: FnMut(&T) -> bool
+	{
+		let mut flags = ContactFlags::default();
+		let down = self.get_space().y_axis.down() * epsilon;
+		for point in points {
+			let mut probe = |x: f32, y: f32| self.solid_at((point.0 + x, point.1 + y), &mut is_solid);
+			flags.ground = flags.ground || probe(0.0, down);
+			flags.ceiling = flags.ceiling || probe(0.0, -down);
+			flags.left = flags.left || probe(-epsilon, 0.0);
+			flags.right = flags.right || probe(epsilon, 0.0);
+		}
+		flags
+	}
+
+	/// Find every solid tile touched by a set of points
+	///
+	/// Like `contact_flags`, every point is probed by `epsilon` in each of
+	/// the four directions, but every contact is returned on its own, with
+	/// the touched tile, its normal and the gap to it. Ground contacts come
+	/// first for each point, followed by ceiling, left and right. Points that
+	/// are inside a solid tile are skipped; see `depenetrate` for those.
+	///
+	/// ```
+	/// use tile_net::{Points, TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set_row(&1, 5);
+	/// net.set_col(&1, 1);
+	/// // Wedged in the corner between the floor and the wall to the left
+	/// let corners = [(0.0, 0.0), (0.99, 0.0), (0.0, 0.99), (0.99, 0.99)];
+	/// let contacts = net.contact_manifold(Points::new(Vector(2.0, 4.0), &corners), 0.02, |x| *x != 0);
+	/// assert_eq!(contacts.len(), 4);
+	/// assert!(contacts.iter().any(|x| x.normal == Vector(0.0, -1.0)));
+	/// assert!(contacts.iter().any(|x| x.normal == Vector(1.0, 0.0)));
+	/// ```
+	pub fn contact_manifold<I, F>(&self, points: I, epsilon: f32, mut is_solid: F) -> Vec<Contact>
+		where I: IntoIterator<Item = (f32, f32)>,
+		      F: FnMut(&T) -> bool
+	{
+		let down = self.get_space().y_axis.down();
+		let directions = [Vector(0.0, down), Vector(0.0, -down), Vector(-1.0, 0.0), Vector(1.0, 0.0)];
+		let mut contacts = vec![];
+		for (index, point) in points.into_iter().enumerate() {
+			if self.solid_at(point, &mut is_solid) {
+				continue;
+			}
+			for &direction in &directions {
+				let probe = Vector::from(point) + direction * epsilon;
+				if !self.solid_at(probe.into(), &mut is_solid) {
+					continue;
+				}
+				let tile = (probe.0.floor() as i32, probe.1.floor() as i32);
+				// Gap from the point to the near side of the tile
+				let distance = match (direction.0 as i32, direction.1 as i32) {
+					(1, _) => tile.0 as f32 - point.0,
+					(-1, _) => point.0 - (tile.0 + 1) as f32,
+					(_, 1) => tile.1 as f32 - point.1,
+					_ => point.1 - (tile.1 + 1) as f32,
+				};
+				contacts.push(Contact {
+					point: index,
+					tile,
+					normal: direction * -1.0,
+					distance,
+				});
+			}
+		}
+		contacts
+	}
+
+	/// Find the earliest solid tile hit by points moving together
+	///
+	/// Traces every point along `movement` and returns the hit with the
+	/// smallest `t`. Moving all points by `movement * t` puts the first
+	/// point exactly on the side of the tile, so a body can be moved flush
+	/// against a wall in one step instead of by trial and error. Back off by a
+	/// small distance if the points must not touch the tile. A point starting
+	/// in a solid tile is a hit at `t = 0`. Points and movement are in world
+	/// coordinates, mapped onto the net through its `GridSpace`.
+	///
+	/// ```
+	/// use tile_net::{Points, TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set_col(&1, 6);
+	/// let corners = [(0.0, 0.0), (0.5, 0.0), (0.0, 0.5), (0.5, 0.5)];
+	/// let points = Points::new(Vector(2.0, 2.0), &corners);
+	/// let hit = net.cast_points(points, Vector(7.0, 0.0), |x| *x != 0).unwrap();
+	/// assert_eq!(hit.tile.0, 6);
+	/// assert_eq!(hit.normal, Vector(-1.0, 0.0));
+	/// assert!((hit.t - 0.5).abs() < 1e-6);
+	/// ```
+	pub fn cast_points<I, F>(&self, points: I, movement: Vector, mut is_solid: F) -> Option<CastHit>
+		where I: IntoIterator<Item = (f32, f32)>,
+		      F: FnMut(&T) -> bool
+	{
+		let space = self.get_space();
+		let movement = space.scale_to_grid_units(movement);
+		let mut earliest: Option<CastHit> = None;
+		for (index, point) in points.into_iter().enumerate() {
+			let point = space.to_grid_units(point.into()).into();
+			if let Some((t, tile, normal)) = self.cast(point, movement, &mut is_solid) {
+				if earliest.map_or(true, |x| t < x.t) {
+					earliest = Some(CastHit {
+						t,
+						tile,
+						normal,
+						point: index,
+					});
+				}
+			}
+		}
+		earliest
+	}
+
+	/// Sweep a rectangle and find the first solid tile it touches
+	///
+	/// Unlike `cast_points`, this is exact for a rectangle of any size: thin
+	/// pillars between sample points are found too. Moving the rectangle by
+	/// `movement * t` puts it flush against the tile. A rectangle resting
+	/// against a tile and sliding along it does not hit it, but one moving
+	/// into it hits at `t = 0`. The cost grows with the area swept.
+	/// Tiles outside of the net follow the out-of-bounds policy. The
+	/// rectangle and movement are in world coordinates, like in `cast_points`.
+	///
+	/// ```
+	/// use tile_net::{Rect, TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set(&1, (5, 3));
+	/// let body = Rect::new(Vector(1.0, 2.5), Vector(2.0, 1.0));
+	/// let hit = net.rect_cast(body, Vector(4.0, 0.0), |x| *x != 0).unwrap();
+	/// assert_eq!((hit.t, hit.tile, hit.normal), (0.5, (5, 3), Vector(-1.0, 0.0)));
+	/// assert_eq!(net.rect_cast(body, Vector(0.0, -2.0), |x| *x != 0), None);
+	/// ```
+	pub fn rect_cast<F>(&self, rect: Rect, movement: Vector, mut is_solid: F) -> Option<ShapeCastHit>
+		where F: FnMut(&T) -> bool
+	{
+		let rect = self.get_space().rect_to_grid_units(rect);
+		let movement = self.get_space().scale_to_grid_units(movement);
+		let range = |pos: f32, size: f32, delta: f32| {
+			let low = pos.min(pos + delta).floor() as i32;
+			let high = ((pos.max(pos + delta) + size).ceil() as i32).max(low + 1);
+			low..high
+		};
+		let mut earliest: Option<ShapeCastHit> = None;
+		for y in range(rect.pos.1, rect.size.1, movement.1) {
+			for x in range(rect.pos.0, rect.size.0, movement.0) {
+				if !self.solid_at((x as f32, y as f32), &mut is_solid) {
+					continue;
+				}
+				if let Some((t, normal)) = sweep(rect, movement, (x, y)) {
+					if earliest.map_or(true, |e| t < e.t) {
+						earliest = Some(ShapeCastHit {
+							t,
+							tile: (x, y),
+							normal,
+						});
+					}
+				}
+			}
+		}
+		earliest
+	}
+
+	// Walk the tiles crossed by a single moving point in order, returning the
+	// first solid one.
+	fn cast<F>(&self, point: (f32, f32), movement: Vector, is_solid: &mut F) -> Option<(f32, (i32, i32), Vector)>
+		where F: FnMut(&T) -> bool
+	{
+		let length = movement.norm2sq().sqrt();
+		RaycastIter::new(self, Vector::from(point), movement, length, is_solid, true).next_crossing()
+	}
+
+	/// Compute the shortest push moving every point out of solid tiles
+	///
+	/// Bodies that start inside a solid tile, because they were spawned there or
+	/// pushed by something else, get `Stuck` from `solve`. Move them by the
+	/// returned vector to recover. Only pushes along the axes are considered,
+	/// and a zero vector is returned if no point is inside a solid tile or no
+	/// push frees all points.
+	///
+	/// ```
+	/// use tile_net::{Points, TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set_row(&1, 5);
+	/// // A box whose bottom is 0.25 tiles into the floor
+	/// let corners = [(0.0, 0.0), (0.9, 0.0), (0.0, 0.9), (0.9, 0.9)];
+	/// let push = net.depenetrate(Points::new(Vector(2.0, 4.35), &corners), |x| *x != 0);
+	/// assert!((push.1 + 0.25).abs() < 1e-3 && push.0 == 0.0);
+	/// ```
+	pub fn depenetrate<I, F>(&self, points: I, mut is_solid: F) -> Vector
+		where I: IntoIterator<Item = (f32, f32)>,
+		      F: FnMut(&T) -> bool
+	{
+		let points: Vec<(f32, f32)> = points.into_iter().collect();
+		if !points.iter().any(|x| self.solid_at(*x, &mut is_solid)) {
+			return Vector(0.0, 0.0);
+		}
+		let limit = self.get_size().0 + self.get_size().1 + 1;
+		let directions = [Vector(1.0, 0.0), Vector(-1.0, 0.0), Vector(0.0, 1.0), Vector(0.0, -1.0)];
+		let mut best: Option<Vector> = None;
+		for &direction in &directions {
+			let mut distance = 0.0f32;
+			let mut free = false;
+			for _ in 0..limit {
+				let mut needed = None;
+				for &point in &points {
+					let moved = Vector::from(point) + direction * distance;
+					if !self.solid_at(moved.into(), &mut is_solid) {
+						continue;
+					}
+					// Distance from the original point to just past the edge of this tile
+					let along = if direction.0 != 0.0 { moved.0 } else { moved.1 };
+					let exit = if direction.0 + direction.1 > 0.0 {
+						along.floor() + 1.0 - along
+					} else {
+						along - along.floor() + EPSILON
+					};
+					needed = Some(needed.map_or(distance + exit, |x: f32| x.max(distance + exit)));
+				}
+				match needed {
+					Some(next) => distance = next,
+					None => {
+						free = true;
+						break;
+					}
+				}
+			}
+			let push = direction * distance;
+			if free && best.map_or(true, |x| push.norm2sq() < x.norm2sq()) {
+				best = Some(push);
+			}
+		}
+		best.unwrap_or(Vector(0.0, 0.0))
+	}
+
+	/// Cover the solid tiles with few axis-aligned rectangles
+	///
+	/// Greedy meshing: the first uncovered solid tile in row-major order
+	/// grows to the right as far as possible, then downwards as long as the
+	/// whole row below is solid and uncovered. The result is not always the
+	/// smallest possible set, but close to it for level geometry, and every
+	/// solid tile is covered by exactly one rectangle. Rectangles are in tile
+	/// units, ready to be scaled into static colliders of a physics engine.
+	///
+	/// ```
+	/// use tile_net::{Rect, TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(8, 8);
+	/// net.set_row(&1, 7);
+	/// net.set_box_filled(&1, (0, 3), (1, 6));
+	/// let rects = net.decompose_rects(|x| *x != 0);
+	/// // The wall grows down into the floor, the rest of the floor follows
+	/// assert_eq!(rects, vec![Rect::new(Vector(0.0, 3.0), Vector(2.0, 5.0)),
+	///                        Rect::new(Vector(2.0, 7.0), Vector(6.0, 1.0))]);
+	/// ```
+	pub fn decompose_rects<F>(&self, mut is_solid: F) -> Vec<Rect>
+		where F: FnMut(&T) -> bool
+	{
+		let cells: Vec<Option<()>> = self.get_raw().iter().map(|x| if is_solid(x) { Some(()) } else { None }).collect();
+		greedy_rects(cells, self.get_size().0)
+			.into_iter()
+			.map(|(x, y, w, h, _)| Rect::new(Vector(x as f32, y as f32), Vector(w as f32, h as f32)))
+			.collect()
+	}
+}
+
+// Greedy meshing over rows of `width` cells: the first remaining cell grows
+// to the right over equal cells, then downwards as long as the whole row
+// below is equal too. Returns the position, size and value of every
+// rectangle, covering every `Some` cell exactly once.
+pub(crate) fn greedy_rects<K>(mut cells: Vec<Option<K>>, width: usize) -> Vec<(usize, usize, usize, usize, K)>
+	where K: Copy + PartialEq
+{
+	let height = cells.len().checked_div(width).unwrap_or(0);
+	let mut rects = vec![];
+	for y in 0..height {
+		for x in 0..width {
+			let value = match cells[x + y * width] {
+				Some(value) => value,
+				None => continue,
+			};
+			let same = |cell: &Option<K>| *cell == Some(value);
+			let mut w = 1;
+			while x + w < width && same(&cells[x + w + y * width]) {
+				w += 1;
+			}
+			let mut h = 1;
+			while y + h < height && cells[x + (y + h) * width..x + w + (y + h) * width].iter().all(same) {
+				h += 1;
+			}
+			for row in y..y + h {
+				for cell in &mut cells[x + row * width..x + w + row * width] {
+					*cell = None;
+				}
+			}
+			rects.push((x, y, w, h, value));
+		}
+	}
+	rects
+}
+
+// Time of impact and normal of a rectangle moving into a single tile, by
+// intersecting the entry and exit times of both axes.
+fn sweep(rect: Rect, movement: Vector, tile: (i32, i32)) -> Option<(f32, Vector)> {
+	// Entry and exit time along one axis, with the normal when entering
+	let axis = |pos: f32, size: f32, delta: f32, start: f32| {
+		let (near, far) = (start - size, start + 1.0);
+		if delta > 0.0 {
+			Some(((near - pos) / delta, (far - pos) / delta, -1.0))
+		} else if delta < 0.0 {
+			Some(((far - pos) / delta, (near - pos) / delta, 1.0))
+		} else if pos > near && pos < far {
+			Some((f32::NEG_INFINITY, f32::INFINITY, 0.0))
+		} else {
+			None
+		}
+	};
+	let (enter_x, exit_x, normal_x) = axis(rect.pos.0, rect.size.0, movement.0, tile.0 as f32)?;
+	let (enter_y, exit_y, normal_y) = axis(rect.pos.1, rect.size.1, movement.1, tile.1 as f32)?;
+	let (enter, exit) = (enter_x.max(enter_y), exit_x.min(exit_y));
+	if enter >= exit || exit <= 0.0 || enter > 1.0 {
+		return None;
+	}
+	if enter < 0.0 {
+		// Already overlapping, unless only touching and moving away
+		return if enter_x < 0.0 && enter_y < 0.0 { Some((0.0, Vector(0.0, 0.0))) } else { None };
+	}
+	if enter_y >= enter_x {
+		Some((enter, Vector(0.0, normal_y)))
+	} else {
+		Some((enter, Vector(normal_x, 0.0)))
+	}
+}
+
+// Squared distance from a world position to the closest point of a tile's cell.
+fn cell_distance(space: &GridSpace, tile: (usize, usize), point: Vector) -> f32 {
+	let cell = space.tile_to_world(SignedCoord::new(tile.0 as i32, tile.1 as i32));
+	let axis = |start: f32, size: f32, x: f32| x - x.clamp(start, start + size);
+	let (dx, dy) = (axis(cell.pos.0, cell.size.0, point.0), axis(cell.pos.1, cell.size.1, point.1));
+	dx * dx + dy * dy
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn solid(x: &usize) -> bool {
+		*x != 0
+	}
+
+	#[test]
+	fn tiles_in_rect() {
+		let net: TileNet<usize> = TileNet::from_iter(4, 0..12);
+		let rect = |x, y, w, h| Rect::new(Vector(x, y), Vector(w, h));
+		let coords = |r| net.tiles_in_rect(r).map(|x| x.0).collect::<Vec<_>>();
+		assert_eq!(coords(rect(-5.0, -5.0, 5.5, 5.0)), vec![]);
+		assert_eq!(coords(rect(-5.0, -5.0, 5.5, 5.5)), vec![(0, 0)]);
+		assert_eq!(coords(rect(2.0, 2.0, 0.0, 0.0)), vec![(2, 2)]);
+		assert_eq!(coords(rect(2.5, 1.5, 9.0, 9.0)), vec![(2, 1), (3, 1), (2, 2), (3, 2)]);
+		assert_eq!(net.tiles_in_rect(rect(0.0, 0.0, 100.0, 100.0)).count(), 12);
+	}
+
+	#[test]
+	fn world_space() {
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		net.set_space(GridSpace::new(Vector(-32.0, 0.0), Vector(16.0, 16.0)));
+		let coords = |r| net.tiles_in_rect(r).map(|x| x.0).collect::<Vec<_>>();
+		assert_eq!(coords(Rect::new(Vector(-16.0, 16.0), Vector(16.0, 16.0))), vec![(1, 1)]);
+		let crater: Vec<_> = net.tiles_in_radius(Vector(0.0, 32.0), 8.0).map(|x| x.0).collect();
+		assert_eq!(crater, vec![(1, 1), (2, 1), (1, 2), (2, 2)]);
+		net.set(&1, (3, 2));
+		let hit = net.cast_points(vec![(-24.0, 40.0)], Vector(64.0, 0.0), solid).unwrap();
+		assert_eq!((hit.t, hit.tile, hit.normal), (0.625, (3, 2), Vector(-1.0, 0.0)));
+		let body = Rect::new(Vector(-32.0, 36.0), Vector(16.0, 8.0));
+		let hit = net.rect_cast(body, Vector(64.0, 0.0), solid).unwrap();
+		assert_eq!((hit.t, hit.tile), (0.5, (3, 2)));
+	}
+
+	#[test]
+	fn tiles_in_radius() {
+		let net: TileNet<usize> = TileNet::new(10, 10);
+		assert_eq!(net.tiles_in_radius(Vector(5.5, 5.5), 1.6).count(), 21);
+		assert_eq!(net.tiles_in_radius(Vector(-1.0, 5.5), 0.5).count(), 0);
+		let sorted = net.tiles_in_radius_sorted(Vector(2.2, 2.5), 1.0);
+		let coords: Vec<_> = sorted.iter().map(|x| x.0).collect();
+		assert_eq!(coords, vec![(2, 2), (1, 2), (2, 1), (2, 3), (1, 1), (1, 3), (3, 2), (3, 1), (3, 3)]);
+	}
+
+	#[test]
+	fn rect_cast() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_row(&1, 6);
+		net.set(&1, (7, 2));
+		let body = Rect::new(Vector(1.0, 5.0), Vector(0.5, 1.0));
+		// Sliding along the floor touches nothing
+		assert_eq!(net.rect_cast(body, Vector(3.0, 0.0), solid), None);
+		let hit = net.rect_cast(body, Vector(1.0, 1.0), solid).unwrap();
+		assert_eq!((hit.t, hit.tile, hit.normal), (0.0, (1, 6), Vector(0.0, -1.0)));
+		// A thin box passing between corner samples still hits the pillar
+		let thin = Rect::new(Vector(4.0, 1.5), Vector(8.0, 0.2));
+		let hit = net.rect_cast(thin, Vector(0.0, 2.0), solid).unwrap();
+		assert_eq!((hit.tile, hit.normal), ((7, 2), Vector(0.0, -1.0)));
+		assert!((hit.t - 0.15).abs() < 1e-6);
+		let inside = Rect::new(Vector(6.5, 2.5), Vector(1.0, 1.0));
+		assert_eq!(net.rect_cast(inside, Vector(1.0, 0.0), solid).unwrap().normal, Vector(0.0, 0.0));
+		let left = Rect::new(Vector(3.0, 2.0), Vector(1.0, 1.0));
+		let hit = net.rect_cast(left, Vector(10.0, 0.0), solid).unwrap();
+		assert_eq!((hit.t, hit.tile, hit.normal), (0.3, (7, 2), Vector(-1.0, 0.0)));
+	}
+
+	#[test]
+	fn contact_manifold() {
+		let mut net: TileNet<usize> = TileNet::new(10, 10);
+		net.set_row(&1, 5);
+		let points = [(2.5, 4.99), (3.0, 5.5), (9.99, 2.0)];
+		let contacts = net.contact_manifold(points.iter().cloned(), 0.05, solid);
+		assert_eq!(contacts.len(), 1);
+		assert_eq!((contacts[0].point, contacts[0].tile, contacts[0].normal), (0, (2, 5), Vector(0.0, -1.0)));
+		assert!((contacts[0].distance - 0.01).abs() < 1e-4);
+		net.set_out_of_bounds(OutOfBounds::Solid);
+		let contacts = net.contact_manifold(points.iter().cloned(), 0.05, solid);
+		assert_eq!(contacts.len(), 2);
+		assert_eq!((contacts[1].point, contacts[1].tile, contacts[1].normal), (2, (10, 2), Vector(-1.0, 0.0)));
+	}
+
+	#[test]
+	fn contact_flags() {
+		let mut net: TileNet<usize> = TileNet::new(5, 5);
+		net.set_col(&1, 0);
+		net.set_row(&1, 0);
+		let flags = net.contact_flags(vec![(1.0, 1.0), (2.5, 2.5)], 0.01, solid);
+		assert_eq!(flags,
+		           ContactFlags {
+		               ground: false,
+		               ceiling: true,
+		               left: true,
+		               right: false,
+		           });
+		assert!(!net.contact_flags(vec![(2.5, 2.5)], 0.01, solid).any());
+	}
+
+	#[test]
+	fn cast_points() {
+		let net = TileNet::sample();
+		let hit = net.cast_points(vec![(1.5, 1.5), (3.5, 2.0)], Vector(1.0, 6.0), solid).unwrap();
+		assert_eq!((hit.tile, hit.point, hit.normal), ((3, 5), 1, Vector(0.0, -1.0)));
+		assert!((hit.t - 0.5).abs() < 1e-6);
+		assert_eq!(net.cast_points(vec![(1.5, 1.5)], Vector(8.0, 3.0), solid), None);
+		let inside = net.cast_points(vec![(1.5, 1.5), (1.5, 7.0)], Vector(1.0, 0.0), solid).unwrap();
+		assert_eq!((inside.t, inside.point), (0.0, 1));
+		// Exactly through a corner, with only one of the side neighbours solid
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		net.set(&1, (2, 1));
+		let hit = net.cast_points(vec![(1.5, 1.5)], Vector(1.0, -1.0), solid).unwrap();
+		assert_eq!((hit.tile, hit.normal), ((2, 1), Vector(-1.0, 0.0)));
+	}
+
+	#[test]
+	fn depenetrate() {
+		let net = TileNet::sample();
+		assert_eq!(net.depenetrate(vec![(1.5, 2.5)], solid), Vector(0.0, 0.0));
+		let push = net.depenetrate(vec![(1.5, 5.25), (2.5, 6.5)], solid);
+		assert!((push - Vector(0.0, -1.5001)).norm2sq() < 1e-6);
+		let mut net: TileNet<usize> = TileNet::new(3, 3);
+		net.set_box_filled(&1, (0, 0), (1, 2));
+		assert_eq!(net.depenetrate(vec![(1.5, 1.5)], solid), Vector(0.5, 0.0));
+		// Space outside of the net is empty
+		let full: TileNet<usize> = TileNet::from_iter(2, vec![1, 1, 1, 1].into_iter());
+		assert_eq!(full.depenetrate(vec![(0.5, 0.5)], solid), Vector(-0.5001, 0.0));
+	}
+
+	#[test]
+	fn decompose_rects() {
+		let net = TileNet::sample();
+		assert_eq!(net.decompose_rects(solid), vec![Rect::new(Vector(0.0, 5.0), Vector(10.0, 5.0))]);
+		let net: TileNet<usize> = TileNet::from_rows(vec![vec![1, 1, 0], vec![1, 1, 1], vec![0, 1, 1]]).unwrap();
+		let rects = net.decompose_rects(solid);
+		assert_eq!(rects.len(), 3);
+		let area: f32 = rects.iter().map(|x| x.size.0 * x.size.1).sum();
+		assert_eq!(area, 7.0);
+		assert!(TileNet::<usize>::new(4, 4).decompose_rects(solid).is_empty());
+	}
+}
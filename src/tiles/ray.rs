@@ -0,0 +1,268 @@
+use super::{OutOfBounds, TileNet};
+use super::super::Vector;
+
+/// A solid tile crossed by a ray, see `TileNet::raycast_iter`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+	/// The tile that was hit
+	pub tile: (i32, i32),
+	/// Where the ray enters the tile
+	pub point: Vector,
+	/// Distance from the origin to `point`
+	pub distance: f32,
+	/// Normal of the tile side the ray enters through, pointing away from the tile
+	///
+	/// Zero for the tile containing the origin, and diagonal when the ray
+	/// enters exactly through a corner.
+	pub normal: Vector,
+}
+
+/// Iterator over the solid tiles along a ray, nearest first
+///
+/// Created by `TileNet::raycast_iter`.
+pub struct RaycastIter<'a, T, F>
+	where T: 'a
+{
+	net: &'a TileNet<T>,
+	is_solid: F,
+	origin: Vector,
+	dir: Vector,
+	length: f32,
+	max_t: f32,
+	tile: (i32, i32),
+	step: (i32, i32),
+	next: (f32, f32),
+	delta: (f32, f32),
+	started: bool,
+	corners: bool,
+	// Tiles entered at the last crossing still to be checked, the next one last
+	pending: [((i32, i32), Vector); 3],
+	queued: usize,
+	at: f32,
+}
+
+impl<T> TileNet<T> {
+	/// Iterate over every solid tile a ray passes through, nearest first
+	///
+	/// The ray starts at `origin` and runs along `dir` for `max_dist`; `dir`
+	/// does not need to be normalized. Each hit carries the point where the
+	/// ray enters the tile and its distance, so piercing projectiles can stop
+	/// after a number of hits and lasers can be drawn segment by segment.
+	/// Passing exactly through a corner only enters the diagonal tile.
+	/// Tiles outside of the net follow the out-of-bounds policy. The ray ends
+	/// once it leaves the net through a border that is not solid, so an
+	/// infinite `max_dist` is fine, except with an `OutOfBounds::Wrap` border
+	/// where a ray missing every solid tile never ends. A zero `dir` only
+	/// checks the tile containing `origin`.
+	///
+	/// ```
+	/// use tile_net::{TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(10, 3);
+	/// net.set_col(&1, 3);
+	/// net.set_col(&1, 4);
+	/// net.set_col(&1, 8);
+	/// let hits: Vec<_> = net.raycast_iter(Vector(0.5, 1.5), Vector(1.0, 0.0), 8.0, |x| *x != 0).collect();
+	/// assert_eq!(hits.iter().map(|x| x.tile).collect::<Vec<_>>(), vec![(3, 1), (4, 1), (8, 1)]);
+	/// assert_eq!(hits[0].point, Vector(3.0, 1.5));
+	/// assert_eq!(hits[2].distance, 7.5);
+	/// assert_eq!(hits[2].normal, Vector(-1.0, 0.0));
+	/// ```
+	pub fn raycast_iter<'a, F>(&'a self,
+	                           origin: Vector,
+	                           dir: Vector,
+	                           max_dist: f32,
+	                           is_solid: F)
+	                           -> RaycastIter<'a, T, F>
+		where F: FnMut(&T) -> bool
+	{
+		RaycastIter::new(self, origin, dir, max_dist, is_solid, false)
+	}
+}
+
+impl<'a, T, F> RaycastIter<'a, T, F>
+	where F: FnMut(&T) -> bool
+{
+	// Walk from `origin` along `dir`. With `corners`, passing a corner exactly
+	// also enters both side neighbours, before the diagonal tile, and every
+	// normal is along an axis.
+	pub(crate) fn new(net: &'a TileNet<T>,
+	                  origin: Vector,
+	                  dir: Vector,
+	                  max_dist: f32,
+	                  is_solid: F,
+	                  corners: bool)
+	                  -> RaycastIter<'a, T, F> {
+		let length = dir.norm2sq().sqrt();
+		// Time, in multiples of `dir`, to the first side crossing and between
+		// crossings, per axis
+		let axis = |start: f32, delta: f32| {
+			if delta > 0.0 {
+				(1, (start.floor() + 1.0 - start) / delta, 1.0 / delta)
+			} else if delta < 0.0 {
+				(-1, (start - start.floor()) / -delta, -1.0 / delta)
+			} else {
+				(0, f32::INFINITY, f32::INFINITY)
+			}
+		};
+		let (step_x, next_x, delta_x) = axis(origin.0, dir.0);
+		let (step_y, next_y, delta_y) = axis(origin.1, dir.1);
+		let tile = (origin.0.floor() as i32, origin.1.floor() as i32);
+		RaycastIter {
+			net,
+			is_solid,
+			origin,
+			dir,
+			length,
+			max_t: if length > 0.0 { max_dist / length } else { 0.0 },
+			tile,
+			step: (step_x, step_y),
+			next: (next_x, next_y),
+			delta: (delta_x, delta_y),
+			started: false,
+			corners,
+			pending: [(tile, Vector(0.0, 0.0)); 3],
+			queued: 0,
+			at: 0.0,
+		}
+	}
+
+	// Find the next solid tile, returning the time in multiples of `dir` at
+	// which the ray enters it.
+	pub(crate) fn next_crossing(&mut self) -> Option<(f32, (i32, i32), Vector)> {
+		if !self.started {
+			self.started = true;
+			if self.solid(self.tile) {
+				return Some((0.0, self.tile, Vector(0.0, 0.0)));
+			}
+		}
+		loop {
+			while self.queued > 0 {
+				self.queued -= 1;
+				let (tile, normal) = self.pending[self.queued];
+				if self.solid(tile) {
+					return Some((self.at, tile, normal));
+				}
+			}
+			if self.step == (0, 0) || self.escaped() {
+				return None;
+			}
+			let t = self.next.0.min(self.next.1);
+			if t > self.max_t {
+				return None;
+			}
+			let (step_x, step_y) = self.step;
+			if self.next.0 == self.next.1 && self.corners {
+				let tile = self.tile;
+				self.pending = [((tile.0 + step_x, tile.1 + step_y), Vector(-step_x as f32, 0.0)),
+				                ((tile.0, tile.1 + step_y), Vector(0.0, -step_y as f32)),
+				                ((tile.0 + step_x, tile.1), Vector(-step_x as f32, 0.0))];
+				self.queued = 3;
+				self.tile = self.pending[0].0;
+				self.next = (self.next.0 + self.delta.0, self.next.1 + self.delta.1);
+			} else {
+				let mut normal = Vector(0.0, 0.0);
+				if self.next.0 <= self.next.1 {
+					self.tile.0 += step_x;
+					self.next.0 += self.delta.0;
+					normal.0 = -step_x as f32;
+				}
+				if self.next.1 <= t {
+					self.tile.1 += step_y;
+					self.next.1 += self.delta.1;
+					normal = Vector(normal.0, -step_y as f32);
+				}
+				self.pending[0] = (self.tile, normal);
+				self.queued = 1;
+			}
+			self.at = t;
+		}
+	}
+
+	fn solid(&mut self, tile: (i32, i32)) -> bool {
+		self.net.solid_at((tile.0 as f32, tile.1 as f32), &mut self.is_solid)
+	}
+
+	// Whether the ray left the net for good: it lies outside on an axis it
+	// does not move back along, and the border is neither solid nor wrapping.
+	fn escaped(&mut self) -> bool {
+		let (cols, rows) = self.net.get_size();
+		let away = |tile: i32, step: i32, size: usize| {
+			(tile < 0 && step <= 0) || (tile as i64 >= size as i64 && step >= 0)
+		};
+		let outside = away(self.tile.0, self.step.0, cols) || away(self.tile.1, self.step.1, rows);
+		let wraps = match *self.net.get_out_of_bounds() {
+			OutOfBounds::Wrap => cols > 0 && rows > 0,
+			_ => false,
+		};
+		outside && !wraps && !self.solid(self.tile)
+	}
+}
+
+impl<'a, T, F> Iterator for RaycastIter<'a, T, F>
+	where F: FnMut(&T) -> bool
+{
+	type Item = RayHit;
+	fn next(&mut self) -> Option<RayHit> {
+		self.next_crossing().map(|(t, tile, normal)| {
+			RayHit {
+				tile,
+				point: self.origin + self.dir * t,
+				distance: t * self.length,
+				normal,
+			}
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn diagonal_and_start() {
+		let mut net: TileNet<usize> = TileNet::new(6, 6);
+		net.set(&1, (0, 0));
+		net.set(&1, (2, 2));
+		net.set(&1, (3, 2));
+		net.set(&1, (4, 4));
+		let hits: Vec<_> = net.raycast_iter(Vector(0.5, 0.5), Vector(2.0, 2.0), 10.0, |x| *x != 0).collect();
+		assert_eq!(hits.iter().map(|x| x.tile).collect::<Vec<_>>(), vec![(0, 0), (2, 2), (4, 4)]);
+		assert_eq!(hits[0].distance, 0.0);
+		assert!((hits[1].point - Vector(2.0, 2.0)).norm2sq() < 1e-9);
+		assert_eq!(hits[1].normal, Vector(-1.0, -1.0));
+		let short = net.raycast_iter(Vector(0.5, 0.5), Vector(1.0, 1.0), 2.0, |x| *x != 0).count();
+		assert_eq!(short, 1);
+	}
+
+	#[test]
+	fn backwards_into_solid_border() {
+		use super::super::OutOfBounds;
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		net.set_out_of_bounds(OutOfBounds::Solid);
+		let mut hits = net.raycast_iter(Vector(2.5, 1.25), Vector(-1.0, 0.0), 100.0, |x| *x != 0);
+		let hit = hits.next().unwrap();
+		assert_eq!((hit.tile, hit.point, hit.normal), ((-1, 1), Vector(0.0, 1.25), Vector(1.0, 0.0)));
+		assert_eq!(hits.nth(9).unwrap().tile, (-11, 1));
+	}
+
+	#[test]
+	fn ends_outside_of_the_net() {
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		net.set(&1, (2, 1));
+		let hits: Vec<_> = net.raycast_iter(Vector(0.5, 1.5), Vector(1.0, 0.1), f32::INFINITY, |x| *x != 0).collect();
+		assert_eq!(hits.iter().map(|x| x.tile).collect::<Vec<_>>(), vec![(2, 1)]);
+		assert_eq!(net.raycast_iter(Vector(-3.5, 1.5), Vector(-1.0, 0.0), f32::INFINITY, |x| *x != 0).count(), 0);
+		// Starting outside and heading into the net still finds the tile
+		assert_eq!(net.raycast_iter(Vector(-3.5, 1.5), Vector(1.0, 0.0), f32::INFINITY, |x| *x != 0).count(), 1);
+		net.set_out_of_bounds(OutOfBounds::Custom(0));
+		assert_eq!(net.raycast_iter(Vector(3.5, 3.5), Vector(1.0, 1.0), f32::INFINITY, |x| *x != 0).count(), 0);
+	}
+
+	#[test]
+	fn zero_direction() {
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		assert_eq!(net.raycast_iter(Vector(1.5, 1.5), Vector(0.0, 0.0), f32::INFINITY, |x| *x != 0).next(), None);
+		net.set(&1, (1, 1));
+		assert_eq!(net.raycast_iter(Vector(1.5, 1.5), Vector(0.0, 0.0), f32::INFINITY, |x| *x != 0).count(), 1);
+	}
+}
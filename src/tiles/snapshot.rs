@@ -0,0 +1,146 @@
+use std::cmp::min;
+use super::TileNet;
+
+/// Copy of the tiles of a `TileNet` at one point in time, see `TileNet::snapshot`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapSnapshot<T> {
+	cols: usize,
+	map: Vec<T>,
+}
+
+impl<T> MapSnapshot<T> {
+	/// Get the size of the net when the snapshot was taken, as (cols, rows)
+	pub fn get_size(&self) -> (usize, usize) {
+		(self.cols, self.map.len() / self.cols)
+	}
+}
+
+/// A run of changed tiles in a single row, see `TileNet::diff`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patch<T> {
+	/// Position of the first tile of the run, as (col, row)
+	pub start: (usize, usize),
+	/// The new tiles, from left to right
+	pub tiles: Vec<T>,
+}
+
+impl<T> TileNet<T>
+	where T: Clone
+{
+	/// Copy every tile, to compare the net against later on
+	pub fn snapshot(&self) -> MapSnapshot<T> {
+		MapSnapshot {
+			cols: self.get_size().0,
+			map: self.get_raw().to_vec(),
+		}
+	}
+
+	/// Write patches from `diff` into the net
+	///
+	/// Tiles falling outside of the net are ignored.
+	pub fn apply_patches(&mut self, patches: &[Patch<T>]) {
+		let (cols, rows) = self.get_size();
+		self.batch(|net| {
+			for patch in patches {
+				let (x, y) = patch.start;
+				if x >= cols || y >= rows || patch.tiles.is_empty() {
+					continue;
+				}
+				let len = min(patch.tiles.len(), cols - x);
+				let start = x + y * cols;
+				net.raw_untracked()[start..start + len].clone_from_slice(&patch.tiles[..len]);
+				net.touch((x, y, x + len - 1, y));
+			}
+		});
+	}
+}
+
+impl<T> TileNet<T>
+	where T: Clone + PartialEq
+{
+	/// Compute the patches turning a snapshot into the current net
+	///
+	/// Changed tiles are grouped into runs along each row, so applying the
+	/// patches to a net equal to the snapshot makes it equal to this net.
+	/// If the size changed, every row is sent: resize the other net first.
+	///
+	/// ```
+	/// use tile_net::{Patch, TileNet};
+	/// let mut server: TileNet<usize> = TileNet::new(5, 3);
+	/// let mut client = server.clone();
+	/// let before = server.snapshot();
+	/// server.set_row(&1, 1);
+	/// server.set(&2, (4, 2));
+	/// let patches = server.diff(&before);
+	/// assert_eq!(patches,
+	///            vec![Patch { start: (0, 1), tiles: vec![1; 5] },
+	///                 Patch { start: (4, 2), tiles: vec![2] }]);
+	/// client.apply_patches(&patches);
+	/// assert_eq!(client.get_raw(), server.get_raw());
+	/// ```
+	pub fn diff(&self, snapshot: &MapSnapshot<T>) -> Vec<Patch<T>> {
+		let (cols, rows) = self.get_size();
+		let raw = self.get_raw();
+		if snapshot.get_size() != (cols, rows) {
+			return raw.chunks(cols)
+				.enumerate()
+				.map(|(y, row)| {
+					Patch {
+						start: (0, y),
+						tiles: row.to_vec(),
+					}
+				})
+				.collect();
+		}
+		let mut patches = vec![];
+		for y in 0..rows {
+			let row = &raw[y * cols..(y + 1) * cols];
+			let old = &snapshot.map[y * cols..(y + 1) * cols];
+			let mut x = 0;
+			while x < cols {
+				if row[x] == old[x] {
+					x += 1;
+					continue;
+				}
+				let start = x;
+				while x < cols && row[x] != old[x] {
+					x += 1;
+				}
+				patches.push(Patch {
+					start: (start, y),
+					tiles: row[start..x].to_vec(),
+				});
+			}
+		}
+		patches
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn diff_and_apply() {
+		let mut net: TileNet<usize> = TileNet::from_iter(4, 0..12);
+		let snapshot = net.snapshot();
+		assert_eq!(net.diff(&snapshot), vec![]);
+		net.set(&9, (0, 0));
+		net.set(&9, (2, 0));
+		net.set(&9, (3, 0));
+		let patches = net.diff(&snapshot);
+		assert_eq!(patches.len(), 2);
+		let mut copy: TileNet<usize> = TileNet::from_iter(4, 0..12);
+		copy.take_dirty();
+		copy.apply_patches(&patches);
+		assert_eq!(copy.get_raw(), net.get_raw());
+		assert_eq!(copy.take_dirty(), Some((0, 0, 3, 0)));
+
+		net.resize((2, 2));
+		let patches = net.diff(&snapshot);
+		assert_eq!(patches.len(), 2);
+		copy.apply_patches(&[Patch { start: (3, 2), tiles: vec![7, 7] },
+		                     Patch { start: (0, 5), tiles: vec![7] }]);
+		assert_eq!(copy.get_raw()[11], 7);
+	}
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use super::{TileNet, TileView};
+use super::super::TileNetError;
+
+/// One character per tile rendering of a net or view
+///
+/// Created by `TileNet::display_with` and `TileView::display_with`. Rows
+/// are separated by newlines, without one after the last row.
+pub struct DisplayWith<'a, T, F>
+	where T: 'a
+{
+	view: TileView<'a, T>,
+	mapper: F,
+}
+
+impl<T> TileNet<T> {
+	/// Render the net with one character per tile
+	///
+	/// The `Debug` output prints every tile in full, which gets unreadable
+	/// for anything but numbers.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(4, 2);
+	/// net.set_row(&1, 1);
+	/// let text = net.display_with(|x| if *x == 0 { '.' } else { '#' }).to_string();
+	/// assert_eq!(text, "....\n####");
+	/// ```
+	pub fn display_with<F>(&self, mapper: F) -> DisplayWith<'_, T, F>
+		where F: Fn(&T) -> char
+	{
+		self.view_all().display_with(mapper)
+	}
+}
+
+impl<T> TileNet<T> {
+	/// Parse a map with one character per tile, one row per line
+	///
+	/// Blank lines before the first and after the last row are skipped, so
+	/// the map can start on the line after the opening quote. Every row must
+	/// have as many characters as the first, or `RaggedRow` is returned; the
+	/// row index counts from the first row. Characters are not trimmed, so
+	/// spaces can be tiles too.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let net = TileNet::from_str_map("
+	///     XXXXX
+	///     X...X
+	///     XXXXX
+	/// ", |x| x == 'X');
+	/// // Indentation counts as tiles
+	/// assert_eq!(net.unwrap().get_size(), (9, 3));
+	/// let net = TileNet::from_str_map("
+	/// XXXXX
+	/// X...X
+	/// XXXXX
+	/// ", |x| x == 'X').unwrap();
+	/// assert_eq!(net.get_size(), (5, 3));
+	/// assert_eq!(net.get((1, 1)), Some(&false));
+	/// assert_eq!(net.display_with(|x| if *x { '#' } else { '.' }).to_string(), "#####\n#...#\n#####");
+	/// ```
+	pub fn from_str_map<F>(map: &str, mapper: F) -> Result<TileNet<T>, TileNetError>
+		where F: Fn(char) -> T
+	{
+		let lines: Vec<&str> = map.lines().collect();
+		let blank = |x: &&str| x.trim().is_empty();
+		let start = lines.iter().position(|x| !blank(x)).unwrap_or(lines.len());
+		let stop = lines.iter().rposition(|x| !blank(x)).map_or(start, |x| x + 1);
+		let rows = lines[start..stop].iter().map(|line| line.chars().map(&mapper).collect()).collect();
+		TileNet::from_rows(rows)
+	}
+}
+
+impl<'a, T> TileView<'a, T>
+	where T: 'a
+{
+	/// Render the view with one character per tile, see `TileNet::display_with`
+	pub fn display_with<F>(self, mapper: F) -> DisplayWith<'a, T, F>
+		where F: Fn(&T) -> char
+	{
+		DisplayWith { view: self, mapper }
+	}
+}
+
+impl<'a, T, F> fmt::Display for DisplayWith<'a, T, F>
+	where T: 'a,
+	      F: Fn(&T) -> char
+{
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		// Iterate over a copy, without requiring `T: Clone` as `TileView::clone` does
+		let view = TileView {
+			tilenet: self.view.tilenet,
+			rectangle: self.view.rectangle,
+			current: self.view.current,
+		};
+		let mut row = None;
+		for (tile, _, y) in view {
+			if row.is_some_and(|x| x != y) {
+				formatter.write_str("\n")?;
+			}
+			row = Some(y);
+			write!(formatter, "{}", (self.mapper)(tile))?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_errors() {
+		let digit = |x: char| x.to_digit(10).unwrap_or(0);
+		assert_eq!(TileNet::from_str_map("\n  \n", digit).err(), Some(TileNetError::ZeroColumns));
+		assert_eq!(TileNet::from_str_map("\n123\n12\n", digit).err(),
+		           Some(TileNetError::RaggedRow { row: 1, len: 2, cols: 3 }));
+		let net = TileNet::from_str_map("12\r\n  \n34", digit).unwrap();
+		assert_eq!(net.get_raw(), &[1, 2, 0, 0, 3, 4]);
+	}
+
+	#[test]
+	fn display_view() {
+		let net = TileNet::sample();
+		let view = net.view_box((3, 6, 4, 7));
+		let text = view.display_with(|x| if *x > 50 { '#' } else { ' ' }).to_string();
+		assert_eq!(text, "   \n###\n###");
+		assert_eq!(net.view_box((0, 0, 0, 3)).display_with(|_| 'x').to_string(), "");
+	}
+}
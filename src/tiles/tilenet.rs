@@ -1,7 +1,7 @@
-extern crate interleave;
-
+use std::cmp::min;
 use std::fmt;
-use super::{TileView, TileSet};
+use super::{Anchor, AttributedTileSet, OutOfBounds, TileView, TileSet};
+use super::super::{GridCoord, GridSpace, Rect, TileNetError, Vector};
 
 /// `TileNet` is the main class in this library
 ///
@@ -16,10 +16,30 @@ use super::{TileView, TileSet};
 /// let my_net = TileNet::<Example>::new(10, 10);
 /// println!("{:?}", my_net);
 /// ```
-#[derive(Clone)]
 pub struct TileNet<T> {
 	map: Vec<T>,
 	cols: usize,
+	border: OutOfBounds<T>,
+	space: GridSpace,
+	dirty: Option<Span>,
+	listeners: Vec<Box<dyn FnMut(Span) + Send + Sync>>,
+	// While a batch of changes runs, the span changed so far
+	pending: Option<Option<Span>>,
+}
+
+// Listeners belong to the original net, so clones start without them.
+impl<T: Clone> Clone for TileNet<T> {
+	fn clone(&self) -> TileNet<T> {
+		TileNet {
+			map: self.map.clone(),
+			cols: self.cols,
+			border: self.border.clone(),
+			space: self.space,
+			dirty: self.dirty,
+			listeners: vec![],
+			pending: None,
+		}
+	}
 }
 
 impl<T: fmt::Debug> fmt::Debug for TileNet<T> {
@@ -27,14 +47,14 @@ impl<T: fmt::Debug> fmt::Debug for TileNet<T> {
 		let biggest = self.map.iter().map(|x| format!("{:?}", x).len()).max();
 		for (index, tile) in self.map.iter().enumerate() {
 			if index % self.cols == 0 && index != 0 {
-				try!(formatter.write_str("\n"));
+				formatter.write_str("\n")?;
 			}
 			let mut current = format!("{:?}", tile);
 			let length = current.len();
 			if let Some(biggest) = biggest {
 				(0..biggest - length).map(|_| current.push(' ')).count();
 			}
-			try!(write!(formatter, "{} ", current));
+			write!(formatter, "{} ", current)?;
 		}
 		Ok(())
 	}
@@ -54,7 +74,7 @@ impl TileNet<usize> {
 /// update other states. One particular example is to upload just
 /// that square to the GPU to render on a texture.
 pub struct TileNetProxy<'a, T: 'a> {
-	tilenet: &'a mut TileNet<T>,
+	pub(crate) tilenet: &'a mut TileNet<T>,
 	min_x: usize,
 	max_x: usize,
 	min_y: usize,
@@ -74,8 +94,12 @@ impl<'a, T> TileNetProxy<'a, T>
 	/// Set a box
 	///
 	/// Start should be less than stop
-	pub fn set_box(&mut self, value: &T, start: (usize, usize), stop: (usize, usize)) -> Span {
-		self.tilenet.set_box(value, start, stop);
+	#[deprecated(since = "3.0.0", note = "use `set_box_filled`, which has the same inclusive bounds")]
+	pub fn set_box<P>(&mut self, value: &T, start: P, stop: P) -> Span
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		self.tilenet.set_box_filled(value, start, stop);
 		if start.0 < self.min_x {
 			self.min_x = start.0;
 		}
@@ -91,6 +115,32 @@ impl<'a, T> TileNetProxy<'a, T>
 		self.get_span()
 	}
 
+	/// Set a filled box, see `TileNet::set_box_filled`
+	pub fn set_box_filled<P>(&mut self, value: &T, start: P, stop: P) -> Span
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		self.tilenet.set_box_filled(value, start, stop);
+		let size = self.tilenet.get_size();
+		if start.0 <= stop.0 && start.1 <= stop.1 && start.0 < size.0 && start.1 < size.1 {
+			self.track(start);
+			self.track((min(stop.0, size.0 - 1), min(stop.1, size.1 - 1)));
+		}
+		self.get_span()
+	}
+
+	/// Set the border of a box, see `TileNet::set_box_outline`
+	pub fn set_box_outline<P>(&mut self, value: &T, start: P, stop: P) -> Span
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		self.tilenet.set_box_outline(value, start, stop);
+		for point in box_outline(start, stop) {
+			self.track(point);
+		}
+		self.get_span()
+	}
+
 	/// Set an entire row
 	pub fn set_row(&mut self, value: &T, row: usize) -> Span {
 		self.tilenet.set_row(value, row);
@@ -119,9 +169,78 @@ impl<'a, T> TileNetProxy<'a, T>
 		self.get_span()
 	}
 
+	/// Set a filled box, reporting boxes that do not fit, see `TileNet::try_set_box_filled`
+	///
+	/// The span is left untouched when an error is returned.
+	pub fn try_set_box_filled<P>(&mut self, value: &T, start: P, stop: P) -> Result<Span, TileNetError>
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		self.tilenet.check_box(start, stop)?;
+		Ok(self.set_box_filled(value, start, stop))
+	}
+
+	/// Set the border of a box, reporting boxes that do not fit, see `TileNet::try_set_box_outline`
+	///
+	/// The span is left untouched when an error is returned.
+	pub fn try_set_box_outline<P>(&mut self, value: &T, start: P, stop: P) -> Result<Span, TileNetError>
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		self.tilenet.check_box(start, stop)?;
+		Ok(self.set_box_outline(value, start, stop))
+	}
+
+	/// Set an entire row, reporting rows outside of the net
+	///
+	/// The span is left untouched when an error is returned.
+	pub fn try_set_row(&mut self, value: &T, row: usize) -> Result<Span, TileNetError> {
+		self.tilenet.index((0, row))?;
+		Ok(self.set_row(value, row))
+	}
+
+	/// Set an entire column, reporting columns outside of the net
+	///
+	/// The span is left untouched when an error is returned.
+	///
+	/// ```
+	/// use tile_net::{TileNet, TileNetError};
+	/// let mut net: TileNet<usize> = TileNet::new(4, 3);
+	/// let mut proxy = net.prepare();
+	/// assert_eq!(proxy.try_set_col(&1, 4), Err(TileNetError::OutOfBounds { coord: (4, 0), size: (4, 3) }));
+	/// assert_eq!(proxy.try_set_box_filled(&1, (1, 1), (2, 1)).map(|x| (x.0, x.2)), Ok((1, 2)));
+	/// assert!(proxy.try_set_row(&1, 2).is_ok());
+	/// assert_eq!(proxy.get_span(), (0, 1, 4, 2));
+	/// ```
+	pub fn try_set_col(&mut self, value: &T, col: usize) -> Result<Span, TileNetError> {
+		self.tilenet.index((col, 0))?;
+		Ok(self.set_col(value, col))
+	}
+
+	/// Set a single grid point, reporting out-of-bounds coordinates
+	///
+	/// The span is left untouched when an error is returned.
+	pub fn try_set<P>(&mut self, value: &T, p: P) -> Result<Span, TileNetError>
+		where P: Into<GridCoord>
+	{
+		let p = p.into().to_tuple();
+		self.tilenet.try_set(value, p)?;
+		self.track(p);
+		Ok(self.get_span())
+	}
+
 	/// Set a single grid point
-	pub fn set(&mut self, value: &T, p: (usize, usize)) -> Span {
+	pub fn set<P>(&mut self, value: &T, p: P) -> Span
+		where P: Into<GridCoord>
+	{
+		let p = p.into().to_tuple();
 		self.tilenet.set(value, p);
+		self.track(p);
+		self.get_span()
+	}
+
+	// Grow the span to include a point.
+	pub(crate) fn track(&mut self, p: (usize, usize)) {
 		if p.0 < self.min_x {
 			self.min_x = p.0;
 		}
@@ -134,7 +253,6 @@ impl<'a, T> TileNetProxy<'a, T>
 		if p.1 > self.max_y && p.1 < self.tilenet.get_size().1 {
 			self.max_y = p.1;
 		}
-		self.get_span()
 	}
 }
 
@@ -153,46 +271,277 @@ impl<T> TileNet<T>
 		}
 	}
 
-	/// Get the raw array behind the tilenet
-	pub fn get_raw(&self) -> &[T] {
-		self.map.as_slice()
-	}
-
 	/// Set a box in the tilenet
-	pub fn set_box(&mut self, value: &T, start: (usize, usize), stop: (usize, usize)) {
-		for i in start.1..stop.1 {
+	#[deprecated(since = "3.0.0", note = "use `set_box_filled`, which has the same inclusive bounds")]
+	pub fn set_box<P>(&mut self, value: &T, start: P, stop: P)
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		self.batch(|net| {
+			for i in start.1..stop.1 {
+				for j in start.0..stop.0 {
+					net.set(value, (j, i));
+				}
+			}
 			for j in start.0..stop.0 {
-				self.set(value, (j, i));
+				net.set(value, (j, stop.1));
+			}
+			for i in start.1..stop.1 {
+				net.set(value, (stop.0, i));
 			}
+			net.set(value, (stop.0, stop.1));
+		});
+	}
+
+	/// Set every tile in the box from `start` to `stop`, both corners included
+	///
+	/// Parts of the box outside of the net are ignored, as is a box with
+	/// `start` past `stop` on either axis.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(4, 3);
+	/// net.set_box_filled(&1, (1, 0), (2, 1));
+	/// net.set_box_filled(&2, (3, 1), (9, 9));
+	/// assert_eq!(net.get_raw(), &[0, 1, 1, 0,
+	///                             0, 1, 1, 2,
+	///                             0, 0, 0, 2]);
+	/// ```
+	pub fn set_box_filled<P>(&mut self, value: &T, start: P, stop: P)
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		let (cols, rows) = self.get_size();
+		if start.0 >= cols || start.0 > stop.0 {
+			return;
 		}
-		for j in start.0..stop.0 {
-			self.set(value, (j, stop.1));
+		let stop_x = min(stop.0, cols - 1);
+		for y in start.1..min(stop.1.saturating_add(1), rows) {
+			for tile in &mut self.map[start.0 + y * cols..stop_x + 1 + y * cols] {
+				tile.clone_from(value);
+			}
 		}
-		for i in start.1..stop.1 {
-			self.set(value, (stop.0, i));
+		if start.1 < rows && start.1 <= stop.1 {
+			self.touch((start.0, start.1, stop_x, min(stop.1, rows - 1)));
 		}
-		self.set(value, (stop.0, stop.1));
+	}
+
+	/// Set the border of the box from `start` to `stop`, both corners included
+	///
+	/// Only the tiles on the edge of the box are set, see `set_box_filled`.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(4, 3);
+	/// net.set_box_outline(&1, (0, 0), (3, 2));
+	/// assert_eq!(net.get_raw(), &[1, 1, 1, 1,
+	///                             1, 0, 0, 1,
+	///                             1, 1, 1, 1]);
+	/// ```
+	pub fn set_box_outline<P>(&mut self, value: &T, start: P, stop: P)
+		where P: Into<GridCoord>
+	{
+		let points = box_outline(start.into().to_tuple(), stop.into().to_tuple());
+		self.batch(|net| {
+			for point in points {
+				net.set(value, point);
+			}
+		});
 	}
 
 	/// Set a row
 	pub fn set_row(&mut self, value: &T, row: usize) {
-		for i in 0..self.col_count() {
-			self.set(value, (i, row));
-		}
+		self.batch(|net| {
+			for i in 0..net.col_count() {
+				net.set(value, (i, row));
+			}
+		});
 	}
 
 	/// Set a column
 	pub fn set_col(&mut self, value: &T, col: usize) {
-		for i in 0..self.row_count() {
-			self.set(value, (col, i));
+		self.batch(|net| {
+			for i in 0..net.row_count() {
+				net.set(value, (col, i));
+			}
+		});
+	}
+
+	/// Set every tile in the box from `start` to `stop`, reporting boxes that do not fit
+	///
+	/// Unlike `set_box_filled` nothing is clipped: the whole box must lie
+	/// inside the net, and nothing is written when an error is returned.
+	///
+	/// ```
+	/// use tile_net::{TileNet, TileNetError};
+	/// let mut net: TileNet<usize> = TileNet::new(4, 3);
+	/// assert_eq!(net.try_set_box_filled(&1, (1, 0), (2, 1)), Ok(()));
+	/// assert_eq!(net.try_set_box_filled(&2, (3, 1), (4, 2)),
+	///            Err(TileNetError::OutOfBounds { coord: (4, 2), size: (4, 3) }));
+	/// assert_eq!(net.try_set_box_filled(&2, (2, 0), (1, 0)),
+	///            Err(TileNetError::InvertedBox { start: (2, 0), stop: (1, 0) }));
+	/// assert_eq!(net.get_raw(), &[0, 1, 1, 0,
+	///                             0, 1, 1, 0,
+	///                             0, 0, 0, 0]);
+	/// ```
+	pub fn try_set_box_filled<P>(&mut self, value: &T, start: P, stop: P) -> Result<(), TileNetError>
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		self.check_box(start, stop)?;
+		self.set_box_filled(value, start, stop);
+		Ok(())
+	}
+
+	/// Set the border of the box from `start` to `stop`, reporting boxes that do not fit
+	///
+	/// The box is checked like in `try_set_box_filled`.
+	///
+	/// ```
+	/// use tile_net::{GridCoord, TileNet, TileNetError};
+	/// let mut net: TileNet<usize> = TileNet::new(4, 3);
+	/// assert_eq!(net.try_set_box_outline(&1, GridCoord::new(0, 0), GridCoord::new(2, 2)), Ok(()));
+	/// assert_eq!(net.try_set_box_outline(&2, (1, 1), (4, 1)),
+	///            Err(TileNetError::OutOfBounds { coord: (4, 1), size: (4, 3) }));
+	/// assert_eq!(net.get_raw(), &[1, 1, 1, 0,
+	///                             1, 0, 1, 0,
+	///                             1, 1, 1, 0]);
+	/// ```
+	pub fn try_set_box_outline<P>(&mut self, value: &T, start: P, stop: P) -> Result<(), TileNetError>
+		where P: Into<GridCoord>
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		self.check_box(start, stop)?;
+		self.set_box_outline(value, start, stop);
+		Ok(())
+	}
+
+	/// Set a row, reporting rows outside of the net
+	///
+	/// ```
+	/// use tile_net::{TileNet, TileNetError};
+	/// let mut net: TileNet<usize> = TileNet::new(3, 2);
+	/// assert_eq!(net.try_set_row(&1, 1), Ok(()));
+	/// assert_eq!(net.try_set_row(&1, 2), Err(TileNetError::OutOfBounds { coord: (0, 2), size: (3, 2) }));
+	/// assert_eq!(net.get_raw(), &[0, 0, 0,
+	///                             1, 1, 1]);
+	/// ```
+	pub fn try_set_row(&mut self, value: &T, row: usize) -> Result<(), TileNetError> {
+		self.index((0, row))?;
+		self.set_row(value, row);
+		Ok(())
+	}
+
+	/// Set a column, reporting columns outside of the net
+	///
+	/// ```
+	/// use tile_net::{TileNet, TileNetError};
+	/// let mut net: TileNet<usize> = TileNet::new(3, 2);
+	/// assert_eq!(net.try_set_col(&1, 0), Ok(()));
+	/// assert_eq!(net.try_set_col(&1, 3), Err(TileNetError::OutOfBounds { coord: (3, 0), size: (3, 2) }));
+	/// assert_eq!(net.get_raw(), &[1, 0, 0,
+	///                             1, 0, 0]);
+	/// ```
+	pub fn try_set_col(&mut self, value: &T, col: usize) -> Result<(), TileNetError> {
+		self.index((col, 0))?;
+		self.set_col(value, col);
+		Ok(())
+	}
+
+	// Check that a box is not inverted and lies entirely inside the net.
+	fn check_box(&self, start: (usize, usize), stop: (usize, usize)) -> Result<(), TileNetError> {
+		if start.0 > stop.0 || start.1 > stop.1 {
+			return Err(TileNetError::InvertedBox { start, stop });
+		}
+		self.index(stop).map(|_| ())
+	}
+
+	/// Set every tile
+	pub fn fill(&mut self, value: &T) {
+		for tile in &mut self.map {
+			tile.clone_from(value);
 		}
+		self.touch_all();
 	}
 
 	/// Set a single grid point
-	pub fn set(&mut self, value: &T, p: (usize, usize)) {
-		if let Some(old) = self.get_mut(p) {
-			*old = value.clone();
+	///
+	/// Out-of-bounds coordinates are silently ignored, see `try_set`.
+	pub fn set<P>(&mut self, value: &T, p: P)
+		where P: Into<GridCoord>
+	{
+		if let Some(p) = self.locate(p.into().to_tuple()) {
+			self.map[p.0 + p.1 * self.cols] = value.clone();
+			self.touch((p.0, p.1, p.0, p.1));
+		}
+	}
+
+	/// Set a single grid point, reporting out-of-bounds coordinates
+	pub fn try_set<P>(&mut self, value: &T, p: P) -> Result<(), TileNetError>
+		where P: Into<GridCoord>
+	{
+		let p = p.into().to_tuple();
+		let index = self.index(p)?;
+		self.map[index] = value.clone();
+		self.touch((p.0, p.1, p.0, p.1));
+		Ok(())
+	}
+
+	/// Set a single grid point if its current tile matches `pred`
+	///
+	/// Returns the number of tiles changed, 0 or 1. Out-of-bounds coordinates
+	/// change nothing, and neither does a tile already equal to `value`.
+	pub fn set_if<P, F>(&mut self, value: &T, p: P, pred: F) -> usize
+		where T: PartialEq,
+		      P: Into<GridCoord>,
+		      F: FnMut(&T) -> bool
+	{
+		let p = p.into().to_tuple();
+		self.set_box_if(value, p, p, pred)
+	}
+
+	/// Set the tiles matching `pred` in the box from `start` to `stop`, both corners included
+	///
+	/// Returns the number of tiles changed. The box is clipped like in
+	/// `set_box_filled`. Matching tiles already equal to `value` are left
+	/// alone, so only the tiles that really change count as dirty.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(4, 3);
+	/// net.set_col(&2, 1);
+	/// net.take_dirty();
+	/// // Fill only the empty space
+	/// assert_eq!(net.set_box_if(&1, (1, 0), (3, 1), |x| *x == 0), 4);
+	/// assert_eq!(net.get_raw(), &[0, 2, 1, 1,
+	///                             0, 2, 1, 1,
+	///                             0, 2, 0, 0]);
+	/// assert_eq!(net.take_dirty(), Some((2, 0, 3, 1)));
+	/// assert_eq!(net.set_if(&1, (1, 2), |x| *x == 0), 0);
+	/// ```
+	pub fn set_box_if<P, F>(&mut self, value: &T, start: P, stop: P, mut pred: F) -> usize
+		where T: PartialEq,
+		      P: Into<GridCoord>,
+		      F: FnMut(&T) -> bool
+	{
+		let (start, stop) = (start.into().to_tuple(), stop.into().to_tuple());
+		let (cols, rows) = self.get_size();
+		let mut changed = 0;
+		let mut span: Option<Span> = None;
+		for y in start.1..min(stop.1.saturating_add(1), rows) {
+			for x in start.0..min(stop.0.saturating_add(1), cols) {
+				let tile = &mut self.map[x + y * cols];
+				if *tile != *value && pred(tile) {
+					tile.clone_from(value);
+					changed += 1;
+					span = Some(union(span, (x, y, x, y)));
+				}
+			}
+		}
+		if let Some(span) = span {
+			self.touch(span);
 		}
+		changed
 	}
 }
 
@@ -203,30 +552,89 @@ impl<T> TileNet<T>
 	///
 	/// The tiles will be Default-created
 	pub fn new(x: usize, y: usize) -> TileNet<T> {
-		TileNet {
-			map: vec![T::default(); x * y],
-			cols: x,
-		}
+		TileNet::from_raw(x, vec![T::default(); x * y])
 	}
 
-	/// Resize the grid
+	/// Resize the grid to (cols, rows), keeping the top-left corner in place
 	///
-	/// If the grid grows, new tiles will be Default-created
+	/// If the grid grows, new tiles will be Default-created.
+	/// See `resize_with` for other anchors and fill values.
 	pub fn resize(&mut self, m: (usize, usize)) {
-		let mut new_map: Vec<T> = vec![T::default(); m.0*m.1];
-		let new_cols = m.1;
-		let new_rows = new_map.len() / new_cols;
+		self.resize_with(m, Anchor::TopLeft, T::default());
+	}
+
+	/// Set every tile to Default
+	pub fn clear(&mut self) {
+		self.fill(&T::default());
+	}
+}
 
-		self.map
-			.iter()
-			.enumerate()
-			.map(|x| (x.0 % self.cols, x.0 / self.cols, x.1))
-			.filter(|x| x.0 < new_cols && x.1 < new_rows)
-			.inspect(|x| new_map[x.0 + x.1 * new_cols] = x.2.clone())
-			.count();
+impl<T> TileNet<T>
+    where T: Clone + PartialEq
+{
+	/// Replace every tile equal to `from` by `to`
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::from_iter(2, vec![1, 2, 1, 3].into_iter());
+	/// net.replace_all(&1, &5);
+	/// assert_eq!(net.get_raw(), &[5, 2, 5, 3]);
+	/// net.clear();
+	/// assert_eq!(net.get_raw(), &[0, 0, 0, 0]);
+	/// ```
+	pub fn replace_all(&mut self, from: &T, to: &T) {
+		let cols = self.cols;
+		let mut changed = None;
+		for (index, tile) in self.map.iter_mut().enumerate().filter(|x| *x.1 == *from) {
+			tile.clone_from(to);
+			changed = Some(union(changed, (index % cols, index / cols, index % cols, index / cols)));
+		}
+		if let Some(span) = changed {
+			self.touch(span);
+		}
+	}
+}
 
-		self.map = new_map;
-		self.cols = new_cols;
+impl<T> TileNet<T>
+    where T: Clone
+{
+	/// Resize the grid to (cols, rows) around an anchor, filling new space with `fill`
+	///
+	/// Returns the offset added to the coordinates of the existing tiles, so
+	/// positions depending on them can be shifted along. Tiles moved outside
+	/// of the new size are dropped. When centering, odd differences are
+	/// rounded towards zero.
+	///
+	/// # Panics #
+	/// Panics if the new column count is zero.
+	///
+	/// ```
+	/// use tile_net::{Anchor, TileNet};
+	/// let mut net: TileNet<usize> = TileNet::from_iter(2, 1..5);
+	/// assert_eq!(net.resize_with((4, 3), Anchor::BottomRight, 0), (2, 1));
+	/// assert_eq!(net.get_raw(), &[0, 0, 0, 0,
+	///                             0, 0, 1, 2,
+	///                             0, 0, 3, 4]);
+	/// assert_eq!(net.resize_with((2, 2), Anchor::Center, 0), (-1, 0));
+	/// assert_eq!(net.get_raw(), &[0, 0, 0, 1]);
+	/// ```
+	pub fn resize_with(&mut self, size: (usize, usize), anchor: Anchor, fill: T) -> (isize, isize) {
+		assert!(size.0 > 0, "a TileNet needs at least one column");
+		let old = self.get_size();
+		let offset = anchor.offset(old, size);
+		let mut map = vec![fill; size.0 * size.1];
+		for (index, tile) in self.map.drain(..).enumerate() {
+			let x = (index % old.0) as isize + offset.0;
+			let y = (index / old.0) as isize + offset.1;
+			if x >= 0 && y >= 0 && (x as usize) < size.0 && (y as usize) < size.1 {
+				map[x as usize + y as usize * size.0] = tile;
+			}
+		}
+		self.map = map;
+		self.cols = size.0;
+		self.dirty = None;
+		self.touch_all();
+		offset
 	}
 }
 
@@ -238,13 +646,24 @@ impl<T> TileNet<T>
 	/// Takes a column count and an iterator.
 	/// If the iterator does not describe the entire box
 	/// the remaining elements are filled in by Default.
+	/// This is the same as `lossy_from_iter`, see `try_from_iter` for a checked version.
+	///
+	/// # Panics #
+	/// Panics if `columns` is zero.
 	pub fn from_iter<I>(columns: usize, iter: I) -> TileNet<T>
 		where I: Iterator<Item = T>
 	{
-		let mut tilenet = TileNet {
-			map: vec![],
-			cols: columns,
-		};
+		TileNet::lossy_from_iter(columns, iter)
+	}
+
+	/// Create a tilenet from an iterator, padding the last row with Default
+	///
+	/// # Panics #
+	/// Panics if `columns` is zero.
+	pub fn lossy_from_iter<I>(columns: usize, iter: I) -> TileNet<T>
+		where I: Iterator<Item = T>
+	{
+		let mut tilenet = TileNet::from_raw(columns, vec![]);
 		tilenet.map.extend(iter);
 		let remainder = tilenet.map.len() % tilenet.cols;
 		if remainder != 0 {
@@ -257,6 +676,377 @@ impl<T> TileNet<T>
 }
 
 impl<T> TileNet<T> {
+	/// Wrap an already laid out row-major buffer
+	///
+	/// The caller guarantees that `map.len()` is a multiple of `cols`.
+	pub(crate) fn from_raw(cols: usize, map: Vec<T>) -> TileNet<T> {
+		TileNet {
+			map,
+			cols,
+			border: OutOfBounds::Empty,
+			space: GridSpace::default(),
+			dirty: None,
+			listeners: vec![],
+			pending: None,
+		}
+	}
+
+	/// Get the span of the tiles changed since the last `take_dirty`
+	///
+	/// Unlike `TileNetProxy`, this is tracked for every edit, however it was
+	/// made. Handing out mutable references, like `get_mut` and
+	/// `get_raw_mut` do, counts as changing the tiles behind them.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// assert_eq!(net.take_dirty(), None);
+	/// net.set(&1, (2, 3));
+	/// net.set_col(&1, 7);
+	/// assert_eq!(net.dirty(), Some((2, 0, 7, 9)));
+	/// assert_eq!(net.take_dirty(), Some((2, 0, 7, 9)));
+	/// assert_eq!(net.take_dirty(), None);
+	/// ```
+	pub fn dirty(&self) -> Option<Span> {
+		self.dirty
+	}
+
+	/// Get and reset the span of the tiles changed, see `dirty`
+	pub fn take_dirty(&mut self) -> Option<Span> {
+		self.dirty.take()
+	}
+
+	/// Call a function with the span of every change to the net
+	///
+	/// Every mutating call reports the span of the tiles it changed once,
+	/// after writing them, so a listener reading the net sees the new tiles.
+	/// Tiles behind mutable references, from `get_mut`, `try_get_mut` and
+	/// `get_raw_mut`, are not reported, as the net can not tell when they are
+	/// written; they only count towards `dirty`. Clones of the net start
+	/// without listeners.
+	///
+	/// ```
+	/// use std::sync::mpsc::channel;
+	/// use tile_net::TileNet;
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// let (sender, receiver) = channel();
+	/// net.on_change(move |span| sender.send(span).unwrap());
+	/// net.set_box_filled(&1, (1, 1), (3, 2));
+	/// net.set(&2, (5, 5));
+	/// net.set_row(&3, 9);
+	/// assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![(1, 1, 3, 2), (5, 5, 5, 5), (0, 9, 9, 9)]);
+	/// ```
+	pub fn on_change<F>(&mut self, listener: F)
+		where F: FnMut(Span) + Send + Sync + 'static
+	{
+		self.listeners.push(Box::new(listener));
+	}
+
+	/// Remove every function added by `on_change`
+	pub fn clear_listeners(&mut self) {
+		self.listeners.clear();
+	}
+
+	// Record a change to the tiles in a span, after writing them.
+	pub(crate) fn touch(&mut self, span: Span) {
+		self.mark(span);
+		match self.pending {
+			Some(ref mut pending) => *pending = Some(union(*pending, span)),
+			None => {
+				for listener in &mut self.listeners {
+					listener(span);
+				}
+			}
+		}
+	}
+
+	// Record a change without telling the listeners, for tiles handed out by reference.
+	fn mark(&mut self, span: Span) {
+		self.dirty = Some(union(self.dirty, span));
+	}
+
+	// Make a change out of smaller ones, telling the listeners about the
+	// union of their spans once at the end.
+	pub(crate) fn batch<F>(&mut self, change: F)
+		where F: FnOnce(&mut TileNet<T>)
+	{
+		if self.pending.is_some() {
+			return change(self);
+		}
+		self.pending = Some(None);
+		change(self);
+		if let Some(Some(span)) = self.pending.take() {
+			for listener in &mut self.listeners {
+				listener(span);
+			}
+		}
+	}
+
+	fn touch_all(&mut self) {
+		let (cols, rows) = self.get_size();
+		if cols > 0 && rows > 0 {
+			self.touch((0, 0, cols - 1, rows - 1));
+		}
+	}
+
+	// Get the tiles for a change that is recorded separately.
+	pub(crate) fn raw_untracked(&mut self) -> &mut [T] {
+		self.map.as_mut_slice()
+	}
+
+	/// Decide what lies outside of the net
+	///
+	/// Affects `get`, `collide_set` and the queries on `TileNet`, like
+	/// `cast_points`. Writes are not affected: every setter, painter and
+	/// `get_mut` ignores tiles outside of the net, whatever the border. Nets
+	/// start out with `OutOfBounds::Empty`.
+	///
+	/// ```
+	/// use tile_net::{OutOfBounds, TileNet};
+	/// let mut net: TileNet<usize> = TileNet::new(4, 4);
+	/// net.set_out_of_bounds(OutOfBounds::Custom(1));
+	/// let set = net.collide_set((-2..2).map(|x| (x, 0)));
+	/// assert_eq!(set.cloned().collect::<Vec<_>>(), vec![1, 1, 0, 0]);
+	/// ```
+	pub fn set_out_of_bounds(&mut self, border: OutOfBounds<T>) {
+		self.border = border;
+	}
+
+	/// Get what lies outside of the net
+	pub fn get_out_of_bounds(&self) -> &OutOfBounds<T> {
+		&self.border
+	}
+
+	/// Decide where the net lies in world space
+	///
+	/// The origin and tile size are used by `world_to_grid`,
+	/// `grid_to_world`, `tiles_in_rect`, `tiles_in_radius`, `cast_points`
+	/// and `rect_cast`. Everything else, like `collide_set` and the
+	/// `Collable` solvers, works in grid units, where tile (x, y) covers
+	/// x..x + 1 and y..y + 1. The y axis is used everywhere: it decides the
+	/// ground of `contact_flags`, the row order of views and which way
+	/// `AabbCollider` steps up and slides down slopes. Nets start out with
+	/// `GridSpace::default()`, where a tile is one world unit.
+	pub fn set_space(&mut self, space: GridSpace) {
+		self.space = space;
+	}
+
+	/// Get where the net lies in world space
+	pub fn get_space(&self) -> &GridSpace {
+		&self.space
+	}
+
+	/// Get the tile at a world position, or `None` outside of the net
+	///
+	/// ```
+	/// use tile_net::{GridCoord, GridSpace, TileNet, Vector};
+	/// let mut net: TileNet<usize> = TileNet::new(10, 10);
+	/// net.set_space(GridSpace::new(Vector(-80.0, 0.0), Vector(16.0, 16.0)));
+	/// assert_eq!(net.world_to_grid(Vector(-79.0, 40.0)), Some(GridCoord::new(0, 2)));
+	/// assert_eq!(net.world_to_grid(Vector(-81.0, 40.0)), None);
+	/// let area = net.grid_to_world(GridCoord::new(0, 2));
+	/// assert_eq!((area.pos, area.size), (Vector(-80.0, 32.0), Vector(16.0, 16.0)));
+	/// ```
+	pub fn world_to_grid(&self, position: Vector) -> Option<GridCoord> {
+		let (cols, rows) = self.get_size();
+		self.space
+			.world_to_tile(position)
+			.to_grid()
+			.filter(|x| (x.x as usize) < cols && (x.y as usize) < rows)
+	}
+
+	/// Get the area a tile covers in world space
+	pub fn grid_to_world(&self, tile: GridCoord) -> Rect {
+		self.space.tile_to_world(tile.into())
+	}
+
+	/// Convert every tile, consuming the net
+	///
+	/// The tile of an `OutOfBounds::Custom` border is converted too.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// #[derive(Clone, Debug, PartialEq)]
+	/// enum Tile {
+	///     Air,
+	///     Stone { hardness: u8 },
+	/// }
+	/// let ids: TileNet<u8> = TileNet::from_iter(2, vec![0, 1, 1, 0].into_iter());
+	/// let net = ids.map(|id| match id {
+	///     0 => Tile::Air,
+	///     _ => Tile::Stone { hardness: 3 },
+	/// });
+	/// assert_eq!(net.get((1, 0)), Some(&Tile::Stone { hardness: 3 }));
+	/// ```
+	pub fn map<U, F>(self, mut f: F) -> TileNet<U>
+		where F: FnMut(T) -> U
+	{
+		let map = self.map.into_iter().map(&mut f).collect();
+		TileNet {
+			map,
+			cols: self.cols,
+			border: self.border.map(f),
+			space: self.space,
+			dirty: self.dirty,
+			listeners: vec![],
+			pending: None,
+		}
+	}
+
+	/// Convert every tile into a new net, keeping this one
+	///
+	/// The tile of an `OutOfBounds::Custom` border is converted too.
+	pub fn map_ref<U, F>(&self, mut f: F) -> TileNet<U>
+		where F: FnMut(&T) -> U
+	{
+		let map = self.map.iter().map(&mut f).collect();
+		TileNet {
+			map,
+			cols: self.cols,
+			border: self.border.as_ref().map(f),
+			space: self.space,
+			dirty: self.dirty,
+			listeners: vec![],
+			pending: None,
+		}
+	}
+
+	/// Create a tilenet of the size (cols, rows), computing each tile from its (col, row)
+	///
+	/// # Panics #
+	/// Panics if the column count is zero.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let net = TileNet::from_fn((3, 2), |x, y| x * 10 + y);
+	/// assert_eq!(net.get_raw(), &[0, 10, 20, 1, 11, 21]);
+	/// ```
+	pub fn from_fn<F>(size: (usize, usize), mut f: F) -> TileNet<T>
+		where F: FnMut(usize, usize) -> T
+	{
+		assert!(size.0 > 0, "a TileNet needs at least one column");
+		let mut map = Vec::with_capacity(size.0 * size.1);
+		for y in 0..size.1 {
+			for x in 0..size.0 {
+				map.push(f(x, y));
+			}
+		}
+		TileNet::from_raw(size.0, map)
+	}
+
+	/// Create a tilenet from an iterator that describes whole rows
+	///
+	/// Unlike `from_iter` nothing is padded: an iterator that stops in the
+	/// middle of a row yields `TileNetError::IncompleteRow`.
+	///
+	/// ```
+	/// use tile_net::{TileNet, TileNetError};
+	/// assert!(TileNet::try_from_iter(3, 0..9).is_ok());
+	/// assert_eq!(TileNet::try_from_iter(3, 0..8).err(),
+	///            Some(TileNetError::IncompleteRow { len: 8, cols: 3 }));
+	/// ```
+	pub fn try_from_iter<I>(columns: usize, iter: I) -> Result<TileNet<T>, TileNetError>
+		where I: Iterator<Item = T>
+	{
+		if columns == 0 {
+			return Err(TileNetError::ZeroColumns);
+		}
+		let map: Vec<T> = iter.collect();
+		if map.len() % columns != 0 {
+			return Err(TileNetError::IncompleteRow {
+				len: map.len(),
+				cols: columns,
+			});
+		}
+		Ok(TileNet::from_raw(columns, map))
+	}
+
+	/// Create a tilenet from a row-major buffer, checking its dimensions
+	///
+	/// Like `try_from_iter`, nothing is padded.
+	///
+	/// ```
+	/// use tile_net::{TileNet, TileNetError};
+	/// assert_eq!(TileNet::from_vec(2, vec![1, 2, 3, 4]).unwrap().get_size(), (2, 2));
+	/// assert_eq!(TileNet::from_vec(2, vec![1, 2, 3]).err(),
+	///            Some(TileNetError::IncompleteRow { len: 3, cols: 2 }));
+	/// ```
+	pub fn from_vec(columns: usize, data: Vec<T>) -> Result<TileNet<T>, TileNetError> {
+		TileNet::try_from_iter(columns, data.into_iter())
+	}
+
+	/// Create a tilenet from a list of rows, which must all be equally long
+	///
+	/// ```
+	/// use tile_net::{TileNet, TileNetError};
+	/// let net = TileNet::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+	/// assert_eq!(net.get((0, 1)), Some(&4));
+	/// assert_eq!(TileNet::from_rows(vec![vec![1, 2], vec![3]]).err(),
+	///            Some(TileNetError::RaggedRow { row: 1, len: 1, cols: 2 }));
+	/// ```
+	pub fn from_rows(rows: Vec<Vec<T>>) -> Result<TileNet<T>, TileNetError> {
+		let columns = rows.first().map_or(0, Vec::len);
+		if columns == 0 {
+			return Err(TileNetError::ZeroColumns);
+		}
+		let mut map = Vec::with_capacity(columns * rows.len());
+		for (index, row) in rows.into_iter().enumerate() {
+			if row.len() != columns {
+				return Err(TileNetError::RaggedRow {
+					row: index,
+					len: row.len(),
+					cols: columns,
+				});
+			}
+			map.extend(row);
+		}
+		Ok(TileNet::from_raw(columns, map))
+	}
+
+	fn index(&self, p: (usize, usize)) -> Result<usize, TileNetError> {
+		let size = self.get_size();
+		if p.0 < size.0 && p.1 < size.1 {
+			Ok(p.0 + p.1 * self.cols)
+		} else {
+			Err(TileNetError::OutOfBounds {
+				coord: p,
+				size,
+			})
+		}
+	}
+
+	/// Get a reference to a 2D index, reporting out-of-bounds coordinates
+	pub fn try_get<P>(&self, p: P) -> Result<&T, TileNetError>
+		where P: Into<GridCoord>
+	{
+		let index = self.index(p.into().to_tuple())?;
+		Ok(&self.map[index])
+	}
+
+	/// Get a mutable reference to a tile, reporting out-of-bounds coordinates
+	pub fn try_get_mut<P>(&mut self, p: P) -> Result<&mut T, TileNetError>
+		where P: Into<GridCoord>
+	{
+		let p = p.into().to_tuple();
+		let index = self.index(p)?;
+		self.mark((p.0, p.1, p.0, p.1));
+		Ok(&mut self.map[index])
+	}
+
+	/// Get the raw array behind the tilenet
+	pub fn get_raw(&self) -> &[T] {
+		self.map.as_slice()
+	}
+
+	/// Get the raw array behind the tilenet, mutably
+	pub fn get_raw_mut(&mut self) -> &mut [T] {
+		let (cols, rows) = self.get_size();
+		if cols > 0 && rows > 0 {
+			self.mark((0, 0, cols - 1, rows - 1));
+		}
+		self.map.as_mut_slice()
+	}
+
 	/// Compute the row count
 	pub fn row_count(&self) -> usize {
 		self.map.len() / self.cols
@@ -268,11 +1058,37 @@ impl<T> TileNet<T> {
 	}
 
 	/// Get a reference to a 2D index
-	pub fn get(&self, p: (usize, usize)) -> Option<&T> {
-		if p.0 >= self.cols {
-			None
+	///
+	/// With `OutOfBounds::Wrap`, indices beyond the net are taken modulo its
+	/// size. Indices beyond `u32::MAX` saturate to it before being wrapped,
+	/// see `GridCoord`.
+	pub fn get<P>(&self, p: P) -> Option<&T>
+		where P: Into<GridCoord>
+	{
+		let p = p.into().to_tuple();
+		if p.0 < self.cols {
+			if let Some(tile) = self.map.get(p.0 + p.1 * self.cols) {
+				return Some(tile);
+			}
+		}
+		self.wrap((p.0 as i64, p.1 as i64)).and_then(|x| self.map.get(x.0 + x.1 * self.cols))
+	}
+
+	// Wrap an index into the net if the net wraps around.
+	pub(crate) fn wrap(&self, p: (i64, i64)) -> Option<(usize, usize)> {
+		match self.border {
+			OutOfBounds::Wrap => self.wrap_around(p),
+			_ => None,
+		}
+	}
+
+	// Wrap an index into the net whatever its border, unless the net is empty.
+	pub(crate) fn wrap_around(&self, p: (i64, i64)) -> Option<(usize, usize)> {
+		let (cols, rows) = self.get_size();
+		if cols > 0 && rows > 0 {
+			Some((p.0.rem_euclid(cols as i64) as usize, p.1.rem_euclid(rows as i64) as usize))
 		} else {
-			self.map.get(p.0 + p.1 * self.cols)
+			None
 		}
 	}
 
@@ -287,51 +1103,191 @@ impl<T> TileNet<T> {
 	}
 
 	/// Create a proxy view with a span from the center using a float position
+	///
+	/// This is the same as `saturating_view_center_f32`.
 	pub fn view_center_f32(&self, position: (f32, f32), span: (usize, usize)) -> TileView<T> {
+		self.saturating_view_center_f32(position, span)
+	}
+
+	/// Create a proxy view with a span from the center using a float position
+	///
+	/// Negative positions are clamped to 0 and the view is clipped to the net.
+	pub fn saturating_view_center_f32<'a>(&'a self,
+	                                      position: (f32, f32),
+	                                      span: (usize, usize))
+	                                      -> TileView<'a, T> {
 		let position = (position.0.max(0.0) as usize, position.1.max(0.0) as usize);
-		self.view_center(position, span)
+		self.saturating_view_center(position, span)
+	}
+
+	/// Create a proxy view with a span from the center using a float position
+	///
+	/// Fails if the position is negative or not finite, or if the view does not
+	/// fit inside the net.
+	pub fn try_view_center_f32<'a>(&'a self,
+	                               position: (f32, f32),
+	                               span: (usize, usize))
+	                               -> Result<TileView<'a, T>, TileNetError> {
+		let valid = |x: f32| x >= 0.0 && x.is_finite();
+		if !valid(position.0) || !valid(position.1) {
+			return Err(TileNetError::InvalidPosition { position });
+		}
+		self.try_view_center((position.0 as usize, position.1 as usize), span)
 	}
 
 	/// Create a proxy view with a span from the center using an integer position
-	pub fn view_center(&self, position: (usize, usize), span: (usize, usize)) -> TileView<T> {
-		let left = position.0.checked_sub(span.0).unwrap_or(0);
-		let top = position.1.checked_sub(span.1).unwrap_or(0);
-		let right = position.0 + span.0;
-		let bottom = position.1 + span.1;
+	///
+	/// This is the same as `saturating_view_center`.
+	pub fn view_center<P>(&self, position: P, span: (usize, usize)) -> TileView<'_, T>
+		where P: Into<GridCoord>
+	{
+		self.saturating_view_center(position, span)
+	}
+
+	/// Create a proxy view with a span from the center using an integer position
+	///
+	/// The view is clipped to the net, so it may be smaller than requested.
+	pub fn saturating_view_center<'a, P>(&'a self, position: P, span: (usize, usize)) -> TileView<'a, T>
+		where P: Into<GridCoord>
+	{
+		let position = position.into().to_tuple();
+		let left = position.0.saturating_sub(span.0);
+		let top = position.1.saturating_sub(span.1);
+		let right = position.0.saturating_add(span.0);
+		let bottom = position.1.saturating_add(span.1);
 		TileView::new(self, (left, right, top, bottom))
 	}
 
+	/// Create a proxy view with a span from the center using an integer position
+	///
+	/// Fails with `TileNetError::OutOfBounds` instead of clipping the view.
+	pub fn try_view_center<'a, P>(&'a self, position: P, span: (usize, usize)) -> Result<TileView<'a, T>, TileNetError>
+		where P: Into<GridCoord>
+	{
+		let position = position.into().to_tuple();
+		if position.0 < span.0 || position.1 < span.1 {
+			return Err(TileNetError::OutOfBounds {
+				coord: (position.0.saturating_sub(span.0), position.1.saturating_sub(span.1)),
+				size: self.get_size(),
+			});
+		}
+		self.try_view_box((position.0 - span.0,
+		                   position.0.saturating_add(span.0),
+		                   position.1 - span.1,
+		                   position.1.saturating_add(span.1)))
+	}
+
 	/// Create a view box that iterates over tiles within that box
+	///
+	/// The box is clipped to the net.
 	pub fn view_box(&self, rectangle: (usize, usize, usize, usize)) -> TileView<T> {
 		TileView::new(self, rectangle)
 	}
 
+	/// Create a view box, failing if the box does not fit inside the net
+	pub fn try_view_box<'a>(&'a self,
+	                        rectangle: (usize, usize, usize, usize))
+	                        -> Result<TileView<'a, T>, TileNetError> {
+		let size = self.get_size();
+		if rectangle.1 > size.0 || rectangle.3 > size.1 {
+			return Err(TileNetError::OutOfBounds {
+				coord: (rectangle.1.saturating_sub(1), rectangle.3.saturating_sub(1)),
+				size,
+			});
+		}
+		Ok(TileView::new(self, rectangle))
+	}
+
 	/// Get a mutable reference to a tile
-	pub fn get_mut(&mut self, p: (usize, usize)) -> Option<&mut T> {
-		if p.0 >= self.cols {
-			None
+	///
+	/// Like every write, this only reaches tiles inside of the net: unlike
+	/// `get`, it gives `None` past the edge even with `OutOfBounds::Wrap`.
+	pub fn get_mut<P>(&mut self, p: P) -> Option<&mut T>
+		where P: Into<GridCoord>
+	{
+		let p = self.locate(p.into().to_tuple())?;
+		self.mark((p.0, p.1, p.0, p.1));
+		self.map.get_mut(p.0 + p.1 * self.cols)
+	}
+
+	// Find the tile to write to. Writes never wrap around.
+	fn locate(&self, p: (usize, usize)) -> Option<(usize, usize)> {
+		if p.0 < self.cols && p.1 < self.row_count() {
+			Some(p)
 		} else {
-			self.map.get_mut(p.0 + p.1 * self.cols)
+			None
 		}
 	}
 
 	/// Create an iterator of tiles from an iterator over indices
 	pub fn collide_set<I>(&self, list: I) -> TileSet<T, I>
 		where I: Iterator<Item = (i32, i32)>
+	{
+		self.collide_set_with(list, &self.border)
+	}
+
+	/// Create an iterator of tiles, with its own policy for indices outside of the net
+	pub fn collide_set_with<'a, I>(&'a self, list: I, border: &'a OutOfBounds<T>) -> TileSet<'a, T, I>
+		where I: Iterator<Item = (i32, i32)>
 	{
 		TileSet {
 			tilenet: self,
 			points: list,
 			last_coord: (0, 0),
+			border,
+		}
+	}
+
+	/// Create an iterator of tiles from indices tagged with their source point
+	///
+	/// See `AttributedTileSet`.
+	pub fn collide_set_attributed<'a, I>(&'a self, list: I) -> AttributedTileSet<'a, T, I>
+		where I: Iterator<Item = ((i32, i32), usize)>
+	{
+		AttributedTileSet {
+			tilenet: self,
+			points: list,
+			last_coord: (0, 0),
+			last_point: 0,
 		}
 	}
 }
 
+// Smallest span containing both a span and another, optional one.
+pub(crate) fn union(a: Option<Span>, b: Span) -> Span {
+	match a {
+		Some(a) => (min(a.0, b.0), min(a.1, b.1), a.2.max(b.2), a.3.max(b.3)),
+		None => b,
+	}
+}
+
+// Tiles on the edge of a box with inclusive corners.
+fn box_outline(start: (usize, usize), stop: (usize, usize)) -> Vec<(usize, usize)> {
+	let mut tiles = vec![];
+	if start.0 > stop.0 || start.1 > stop.1 {
+		return tiles;
+	}
+	for x in start.0..stop.0 + 1 {
+		tiles.push((x, start.1));
+		if stop.1 != start.1 {
+			tiles.push((x, stop.1));
+		}
+	}
+	for y in start.1 + 1..stop.1 {
+		tiles.push((start.0, y));
+		if stop.0 != start.0 {
+			tiles.push((stop.0, y));
+		}
+	}
+	tiles
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 
 	#[test]
+	#[allow(deprecated)]
 	fn test_proxy() {
 		let mut net: TileNet<usize> = TileNet::new(15, 10);
 		let mut net = net.prepare();
@@ -339,4 +1295,225 @@ mod tests {
 		let span = net.set_box(&2, (3, 3), (14, 6));
 		assert_eq![span, (2, 2, 14, 6)];
 	}
+
+	#[test]
+	fn set_boxes() {
+		let mut net: TileNet<usize> = TileNet::new(5, 4);
+		let span = net.prepare().set_box_filled(&1, (3, 2), (8, 8));
+		assert_eq!(span, (3, 2, 4, 3));
+		assert_eq!(net.prepare().set_box_filled(&1, (3, 2), (2, 2)), (5, 4, 0, 0));
+		let span = net.prepare().set_box_outline(&2, (0, 0), (2, 2));
+		assert_eq!(span, (0, 0, 2, 2));
+		assert_eq!(net.get_raw(), &[2, 2, 2, 0, 0,
+		                            2, 0, 2, 0, 0,
+		                            2, 2, 2, 1, 1,
+		                            0, 0, 0, 1, 1]);
+		net.set_box_outline(&3, (4, 0), (4, 1));
+		assert_eq!(net.get((4, 0)), Some(&3));
+		assert_eq!(net.get((4, 1)), Some(&3));
+	}
+
+	#[test]
+	fn dirty() {
+		let mut net: TileNet<usize> = TileNet::new(8, 6);
+		net.set_box_filled(&1, (6, 4), (9, 9));
+		assert_eq!(net.take_dirty(), Some((6, 4, 7, 5)));
+		net.replace_all(&2, &3);
+		assert_eq!(net.take_dirty(), None);
+		net.set(&2, (1, 1));
+		net.set(&2, (3, 2));
+		net.replace_all(&2, &3);
+		assert_eq!(net.take_dirty(), Some((1, 1, 3, 2)));
+		let src: TileNet<usize> = TileNet::new(2, 2);
+		net.blit(&src, (0, 2, 0, 2), (7, 0));
+		assert_eq!(net.take_dirty(), Some((7, 0, 7, 1)));
+		net.flip_region_y((0, 2, 2, 6));
+		assert_eq!(net.take_dirty(), Some((0, 2, 1, 5)));
+		net.set(&1, (9, 9));
+		assert_eq!(net.take_dirty(), None);
+		net.clear();
+		assert_eq!(net.take_dirty(), Some((0, 0, 7, 5)));
+		assert_eq!(net.set_if(&1, (9, 9), |_| true), 0);
+		assert_eq!(net.set_box_if(&1, (2, 2), (1, 1), |_| true), 0);
+		assert_eq!(net.take_dirty(), None);
+		assert_eq!(net.set_if(&1, (5, 5), |x| *x == 0), 1);
+		assert_eq!(net.take_dirty(), Some((5, 5, 5, 5)));
+		// Tiles that already hold the value are not changed
+		assert_eq!(net.set_box_if(&1, (0, 0), (5, 5), |x| *x <= 1), 35);
+		assert_eq!(net.take_dirty(), Some((0, 0, 5, 5)));
+		assert_eq!(net.set_box_if(&1, (4, 4), (5, 5), |_| true), 0);
+		assert_eq!(net.take_dirty(), None);
+	}
+
+	#[test]
+	fn view_lengths() {
+		let mut net: TileNet<usize> = TileNet::from_iter(5, 0..20);
+		let mut view = net.view_box((1, 4, 2, 9));
+		assert_eq!(view.len(), 6);
+		view.next();
+		assert_eq!(view.len(), 5);
+		assert_eq!(view.by_ref().count(), 5);
+		assert_eq!((view.len(), view.next()), (0, None));
+		assert_eq!(net.view_box((3, 2, 0, 4)).len(), 0);
+		assert_eq!(net.view_all().len(), net.view_all().count());
+		net.set_out_of_bounds(OutOfBounds::Wrap);
+		assert_eq!(net.view_box((3, 8, 2, 6)).len(), 20);
+		assert_eq!(net.view_box((3, 8, 2, 6)).count(), 20);
+		let points = vec![(-1, 0), (2, 2), (9, 1)];
+		assert_eq!(net.collide_set(points.clone().into_iter()).size_hint(), (0, Some(3)));
+		net.set_out_of_bounds(OutOfBounds::Custom(7));
+		assert_eq!(net.collide_set(points.into_iter()).with_coords().size_hint(), (3, Some(3)));
+	}
+
+	#[test]
+	fn listeners() {
+		use std::sync::{Arc, Mutex};
+		let spans = Arc::new(Mutex::new(vec![]));
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		let log = spans.clone();
+		net.on_change(move |span| log.lock().unwrap().push(span));
+		net.fill(&1);
+		let mut copy = net.clone();
+		copy.set(&2, (0, 0));
+		net.set_row(&2, 1);
+		net.set_line(&3, (0, 0), (3, 3));
+		net.take_dirty();
+		*net.get_mut((3, 1)).unwrap() = 3;
+		assert_eq!(net.take_dirty(), Some((3, 1, 3, 1)));
+		net.clear_listeners();
+		net.set(&2, (0, 0));
+		assert_eq!(*spans.lock().unwrap(), vec![(0, 0, 3, 3), (0, 1, 3, 1), (0, 0, 3, 3)]);
+	}
+
+	#[test]
+	fn resize() {
+		let mut net: TileNet<usize> = TileNet::from_iter(3, 1..7);
+		net.resize((2, 3));
+		assert_eq!(net.get_size(), (2, 3));
+		assert_eq!(net.get_raw(), &[1, 2, 4, 5, 0, 0]);
+		assert_eq!(net.resize_with((4, 1), Anchor::Bottom, 9), (1, -2));
+		assert_eq!(net.get_raw(), &[9, 0, 0, 9]);
+	}
+
+	#[test]
+	fn fill() {
+		let mut net: TileNet<usize> = TileNet::new(3, 2);
+		net.fill(&4);
+		assert_eq!(net.get_raw(), &[4; 6]);
+		net.set(&1, (1, 1));
+		net.replace_all(&4, &2);
+		assert_eq!(net.get_raw(), &[2, 2, 2, 2, 1, 2]);
+		net.clear();
+		assert_eq!(net.get_raw(), &[0; 6]);
+	}
+
+	#[test]
+	fn map() {
+		let mut net: TileNet<usize> = TileNet::from_iter(2, 0..4);
+		net.set_out_of_bounds(OutOfBounds::Custom(7));
+		let doubled = net.map_ref(|x| *x * 2);
+		assert_eq!(doubled.get_raw(), &[0, 2, 4, 6]);
+		assert_eq!(doubled.get_out_of_bounds(), &OutOfBounds::Custom(14));
+		let solid = net.map(|x| x % 2 == 1);
+		assert_eq!(solid.get_size(), (2, 2));
+		assert_eq!(solid.get_raw(), &[false, true, false, true]);
+		assert_eq!(solid.get_out_of_bounds(), &OutOfBounds::Custom(true));
+	}
+
+	#[test]
+	fn from_fn() {
+		let net = TileNet::from_fn((4, 3), |x, y| (x + y) % 2 == 0);
+		assert_eq!(net.get_size(), (4, 3));
+		assert_eq!(net.get((3, 2)), Some(&false));
+		assert_eq!(net.get((2, 2)), Some(&true));
+		assert_eq!(TileNet::from_fn((2, 0), |_, _| 1).get_size(), (2, 0));
+	}
+
+	#[test]
+	fn checked_constructors() {
+		assert_eq!(TileNet::<usize>::from_vec(0, vec![]).err(), Some(TileNetError::ZeroColumns));
+		assert_eq!(TileNet::<usize>::from_rows(vec![]).err(), Some(TileNetError::ZeroColumns));
+		assert_eq!(TileNet::from_rows(vec![vec![1], vec![2], vec![3, 4]]).err(),
+		           Some(TileNetError::RaggedRow { row: 2, len: 2, cols: 1 }));
+		let net = TileNet::from_rows(vec![vec![1, 2], vec![3, 4]]).unwrap();
+		assert_eq!(net.get_raw(), TileNet::from_vec(2, vec![1, 2, 3, 4]).unwrap().get_raw());
+	}
+
+	#[test]
+	fn wrap() {
+		let mut net: TileNet<usize> = TileNet::new(4, 3);
+		assert_eq!(net.get((4, 0)), None);
+		net.set_out_of_bounds(OutOfBounds::Wrap);
+		assert_eq!(net.get_mut((5, 7)), None);
+		net.set(&3, (5, 7));
+		net.set_row(&3, 4);
+		net.set_box_filled(&3, (4, 0), (5, 1));
+		assert_eq!(net.get_raw(), &[0; 12]);
+		net.set(&3, (1, 1));
+		assert_eq!(net.get((5, 7)), Some(&3));
+		assert!(net.try_get((5, 7)).is_err());
+		let set = net.collide_set(vec![(-3, -2), (9, 4)].into_iter());
+		assert_eq!(set.cloned().collect::<Vec<_>>(), vec![3, 3]);
+		assert_eq!(net.view_box((0, 6, 1, 2)).filter(|x| *x.0 == 3).map(|x| (x.1, x.2)).collect::<Vec<_>>(),
+		           vec![(1, 1), (5, 1)]);
+		// Columns beyond u32::MAX saturate before they are wrapped
+		let mut net: TileNet<usize> = TileNet::from_iter(7, 0..7);
+		net.set_out_of_bounds(OutOfBounds::Wrap);
+		assert_eq!(net.get((usize::MAX, 0)), Some(&3));
+		assert_eq!(net.get((u32::MAX as usize, 0)), Some(&3));
+	}
+
+	#[test]
+	fn attributed_border() {
+		let mut net: TileNet<usize> = TileNet::new(4, 4);
+		net.set_out_of_bounds(OutOfBounds::Custom(7));
+		let points = vec![(-1, 0), (0, -1), (0, 0)];
+		let plain: Vec<usize> = net.collide_set(points.clone().into_iter()).cloned().collect();
+		let attributed = net.collide_set_attributed(points.into_iter().map(|x| (x, 0)));
+		assert_eq!(plain, vec![7, 7, 0]);
+		assert_eq!(attributed.cloned().collect::<Vec<_>>(), plain);
+		net.set_out_of_bounds(OutOfBounds::Wrap);
+		net.set(&3, (3, 0));
+		let attributed = net.collide_set_attributed(vec![((-1, 4), 0)].into_iter());
+		assert_eq!(attributed.cloned().collect::<Vec<_>>(), vec![3]);
+	}
+
+	#[test]
+	fn wrap_override() {
+		let mut net: TileNet<usize> = TileNet::from_iter(3, 0..9);
+		let wrap = OutOfBounds::Wrap;
+		let list = vec![(-1, 0), (0, -1), (4, 4)];
+		let set = net.collide_set_with(list.clone().into_iter(), &wrap);
+		assert_eq!(set.cloned().collect::<Vec<_>>(), vec![2, 6, 4]);
+		assert_eq!(net.collide_set(list.clone().into_iter()).count(), 0);
+		// The override wins over the border of the net
+		net.set_out_of_bounds(OutOfBounds::Custom(7));
+		let set = net.collide_set_with(list.into_iter(), &wrap);
+		assert_eq!(set.cloned().collect::<Vec<_>>(), vec![2, 6, 4]);
+	}
+
+	#[test]
+	fn grid_coords() {
+		let mut net: TileNet<usize> = TileNet::new(4, 3);
+		net.set(&2, GridCoord { x: 1, y: 2 });
+		assert_eq!(net.get((1, 2)), Some(&2));
+		assert_eq!(net.try_get(GridCoord::new(1, 3)).err(),
+		           Some(TileNetError::OutOfBounds { coord: (1, 3), size: (4, 3) }));
+		assert_eq!(net.view_center(GridCoord::new(1, 2), (1, 1)).count(), 4);
+		let mut set = net.collide_set(vec![(-1, 0), (1, 2)].into_iter());
+		assert_eq!(set.next(), Some(&2));
+		assert_eq!(set.get_coord().to_grid(), Some(GridCoord::new(1, 2)));
+		// Painting takes grid coordinates as well
+		net.set_box_filled(&3, GridCoord::new(2, 0), GridCoord::new(3, 1));
+		net.set_line(&4, GridCoord::new(0, 0), GridCoord::new(0, 2));
+		net.set_circle_filled(&5, GridCoord::new(3, 2), 0);
+		let mut other: TileNet<usize> = TileNet::new(4, 3);
+		other.blit(&net, (0, 4, 0, 3), GridCoord::new(1, 1));
+		assert_eq!(net.get_raw(), &[4, 0, 3, 3,
+		                            4, 0, 3, 3,
+		                            4, 2, 0, 5]);
+		assert_eq!(other.get_raw(), &[0, 0, 0, 0,
+		                              0, 4, 0, 3,
+		                              0, 4, 0, 3]);
+	}
 }
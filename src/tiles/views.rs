@@ -0,0 +1,173 @@
+use std::cmp::min;
+use super::{OutOfBounds, TileNet};
+use super::super::{TileNetError, YAxis};
+
+/// Iterator over every `step`th tile of a rectangle, in both axes
+///
+/// Created by `TileNet::view_box_strided`. Yields the same items as
+/// `TileView`, starting at the top left corner of the rectangle on screen,
+/// which is in its last row if the net counts rows upwards.
+#[derive(Clone, Debug)]
+pub struct StridedView<'a, T>
+	where T: 'a
+{
+	tilenet: &'a TileNet<T>,
+	rectangle: (usize, usize, usize, usize),
+	step: usize,
+	current: (usize, usize),
+}
+
+impl<T> TileNet<T> {
+	/// Create a view box that only visits every `step`th tile in each axis
+	///
+	/// Drawing a minimap or a zoomed out view needs one tile out of many;
+	/// this skips the rest instead of iterating over all of them. The box is
+	/// clipped to the net like in `view_box`.
+	///
+	/// # Panics #
+	/// Panics if `step` is 0.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let net: TileNet<usize> = TileNet::from_iter(10, 0..100);
+	/// let minimap: Vec<usize> = net.view_box_strided((0, 10, 0, 10), 4).map(|x| *x.0).collect();
+	/// assert_eq!(minimap, vec![0, 4, 8, 40, 44, 48, 80, 84, 88]);
+	/// ```
+	pub fn view_box_strided(&self, rectangle: (usize, usize, usize, usize), step: usize) -> StridedView<'_, T> {
+		assert!(step > 0, "the step of a strided view must not be 0");
+		let mut rectangle = rectangle;
+		if !matches!(*self.get_out_of_bounds(), OutOfBounds::Wrap) {
+			rectangle.1 = min(rectangle.1, self.get_size().0);
+			rectangle.3 = min(rectangle.3, self.get_size().1);
+		}
+		StridedView {
+			tilenet: self,
+			rectangle,
+			step,
+			current: (rectangle.0, rectangle.2),
+		}
+	}
+}
+
+impl<T> TileNet<T> {
+	/// Write the tiles of a box into a buffer, one row after the other
+	///
+	/// Fills `out` directly, for instance a staging buffer for a texture
+	/// upload, instead of iterating over a `TileView`. Each tile is converted
+	/// with `f`. The box must fit inside the net and the buffer must hold at
+	/// least its area; elements after that are left as they are. Nothing is
+	/// written if either check fails.
+	///
+	/// ```
+	/// use tile_net::TileNet;
+	/// let net: TileNet<usize> = TileNet::from_iter(4, 0..16);
+	/// let mut texels = [0u8; 4];
+	/// net.fill_buffer((1, 3, 2, 4), &mut texels, |x| *x as u8 * 10).unwrap();
+	/// assert_eq!(texels, [90, 100, 130, 140]);
+	/// assert!(net.fill_buffer((0, 3, 0, 3), &mut texels, |x| *x as u8).is_err());
+	/// ```
+	pub fn fill_buffer<U, F>(&self,
+	                         rectangle: (usize, usize, usize, usize),
+	                         out: &mut [U],
+	                         f: F)
+	                         -> Result<(), TileNetError>
+		where F: Fn(&T) -> U
+	{
+		let size = self.get_size();
+		if rectangle.1 > size.0 || rectangle.3 > size.1 {
+			return Err(TileNetError::OutOfBounds {
+				coord: (rectangle.1.saturating_sub(1), rectangle.3.saturating_sub(1)),
+				size,
+			});
+		}
+		let width = rectangle.1.saturating_sub(rectangle.0);
+		let needed = width * rectangle.3.saturating_sub(rectangle.2);
+		if out.len() < needed {
+			return Err(TileNetError::BufferTooSmall {
+				len: out.len(),
+				needed,
+			});
+		}
+		if width == 0 {
+			return Ok(());
+		}
+		let raw = self.get_raw();
+		for (row, chunk) in (rectangle.2..rectangle.3).zip(out.chunks_mut(width)) {
+			let start = row * size.0 + rectangle.0;
+			for (slot, tile) in chunk.iter_mut().zip(&raw[start..start + width]) {
+				*slot = f(tile);
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<'a, T> Iterator for StridedView<'a, T>
+	where T: 'a
+{
+	type Item = (&'a T, usize, usize);
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.current.1 >= self.rectangle.3 || self.rectangle.0 >= self.rectangle.1 {
+			return None;
+		}
+		// Rows are counted from the top of the screen, like in `TileView`
+		let y = match self.tilenet.get_space().y_axis {
+			YAxis::Down => self.current.1,
+			YAxis::Up => self.rectangle.2 + self.rectangle.3 - 1 - self.current.1,
+		};
+		let tile = self.tilenet.get((self.current.0, y)).map(|x| (x, self.current.0, y));
+
+		self.current.0 = self.current.0.saturating_add(self.step);
+		if self.current.0 >= self.rectangle.1 {
+			self.current.1 = self.current.1.saturating_add(self.step);
+			self.current.0 = self.rectangle.0;
+		}
+		tile
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn strided_view_clips() {
+		let net = TileNet::sample();
+		let coords: Vec<(usize, usize)> = net.view_box_strided((5, 100, 3, 100), 3).map(|x| (x.1, x.2)).collect();
+		assert_eq!(coords, vec![(5, 3), (8, 3), (5, 6), (8, 6), (5, 9), (8, 9)]);
+		assert_eq!(net.view_box_strided((4, 4, 0, 10), 2).count(), 0);
+		assert_eq!(net.view_box_strided((0, 10, 0, 10), 1).count(), net.view_all().count());
+	}
+
+	#[test]
+	fn strided_view_y_up() {
+		use super::super::super::GridSpace;
+		let mut net: TileNet<usize> = TileNet::from_iter(10, 0..100);
+		net.set_space(GridSpace::default().with_y_axis(YAxis::Up));
+		let tiles = |rectangle, step| net.view_box_strided(rectangle, step).map(|x| *x.0).collect::<Vec<_>>();
+		assert_eq!(tiles((0, 2, 0, 2), 1), vec![10, 11, 0, 1]);
+		assert_eq!(tiles((0, 2, 0, 2), 1), net.view_box((0, 2, 0, 2)).map(|x| *x.0).collect::<Vec<_>>());
+		assert_eq!(tiles((0, 10, 0, 10), 4), vec![90, 94, 98, 50, 54, 58, 10, 14, 18]);
+	}
+
+	#[test]
+	fn fill_buffer_checks_sizes() {
+		let net = TileNet::sample();
+		let mut out = vec![1; 7];
+		assert_eq!(net.fill_buffer((8, 10, 9, 12), &mut out, |x| *x),
+		           Err(TileNetError::OutOfBounds { coord: (9, 11), size: (10, 10) }));
+		assert_eq!(net.fill_buffer((0, 4, 0, 2), &mut out, |x| *x),
+		           Err(TileNetError::BufferTooSmall { len: 7, needed: 8 }));
+		assert_eq!(out, vec![1; 7]);
+		net.fill_buffer((8, 10, 8, 10), &mut out, |x| *x).unwrap();
+		assert_eq!(out, vec![89, 90, 99, 100, 1, 1, 1]);
+		net.fill_buffer((3, 3, 0, 10), &mut out, |_| 0).unwrap();
+		assert_eq!(out[0], 89);
+	}
+
+	#[test]
+	#[should_panic(expected = "must not be 0")]
+	fn zero_step() {
+		TileNet::sample().view_box_strided((0, 1, 0, 1), 0);
+	}
+}
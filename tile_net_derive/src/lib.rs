@@ -0,0 +1,133 @@
+//! Derive macro for simple `tile_net::Collable` implementations
+//!
+//! Enable the `derive` feature of `tile_net` and use it through
+//! `tile_net::Collable`, rather than depending on this crate directly.
+//!
+//! `#[derive(Collable)]` needs three annotated fields:
+//!
+//! * `#[position]`: a `Vector`, the offset of the hitbox;
+//! * `#[velocity]`: a `Vector`, the move queued for the next `solve`;
+//! * `#[hitbox]`: the points of the body relative to the position, anything
+//!   that derefs to `[(f32, f32)]`, like a `Vec` or an array.
+//!
+//! The implementation works with any tile implementing `TileKind` and any
+//! state. `resolve` applies the move if none of its tiles are solid, nor lie
+//! in an `OutOfBounds::Solid` border, and otherwise halves it and tries
+//! again, giving up on moves shorter than a thousandth of a tile. `enqueue`
+//! replaces the velocity, which lets `solve_axes` slide along walls by
+//! solving one axis at a time.
+//!
+//! ```
+//! extern crate tile_net;
+//! use tile_net::{AxisOrder, Collable, OutOfBounds, TileNet, Vector};
+//!
+//! #[derive(Collable)]
+//! struct Player {
+//!     #[position]
+//!     pos: Vector,
+//!     #[velocity]
+//!     vel: Vector,
+//!     #[hitbox]
+//!     corners: [(f32, f32); 4],
+//! }
+//!
+//! fn main() {
+//!     let mut net: TileNet<usize> = TileNet::new(10, 10);
+//!     net.set_col(&1, 5);
+//!     let mut player = Player {
+//!         pos: Vector(2.0, 2.0),
+//!         vel: Vector(4.0, 1.0),
+//!         corners: [(0.0, 0.0), (0.99, 0.0), (0.0, 0.99), (0.99, 0.99)],
+//!     };
+//!     let report = player.solve_axes(&net, AxisOrder::XThenY, &mut ());
+//!     assert!(report.blocked_x() && !report.blocked_y());
+//!     assert!(player.pos.0 < 4.01 && player.pos.0 > 3.9);
+//!     assert_eq!(player.pos.1, 3.0);
+//!     // The border of the net stops the player too
+//!     net.set_out_of_bounds(OutOfBounds::Solid);
+//!     player.vel = Vector(-10.0, 0.0);
+//!     player.solve(&net, &mut ());
+//!     assert!(player.pos.0 >= 0.0);
+//! }
+//! ```
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use syn::{Data, DeriveInput, Fields, GenericParam, Ident};
+
+/// Implement `Collable` from the `#[position]`, `#[velocity]` and `#[hitbox]` fields
+#[proc_macro_derive(Collable, attributes(position, velocity, hitbox))]
+pub fn derive_collable(input: TokenStream) -> TokenStream {
+	let input = syn::parse_macro_input!(input as DeriveInput);
+	match expand(&input) {
+		Ok(tokens) => tokens.into(),
+		Err(error) => error.to_compile_error().into(),
+	}
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+	let fields = match input.data {
+		Data::Struct(ref data) => {
+			match data.fields {
+				Fields::Named(ref fields) => &fields.named,
+				_ => return Err(syn::Error::new_spanned(input, "Collable can only be derived for structs with named fields")),
+			}
+		}
+		_ => return Err(syn::Error::new_spanned(input, "Collable can only be derived for structs")),
+	};
+	// Find the single field carrying an attribute
+	let find = |attribute: &str| -> syn::Result<&Ident> {
+		let mut found = fields.iter().filter(|x| x.attrs.iter().any(|x| x.path().is_ident(attribute)));
+		match (found.next(), found.next()) {
+			(Some(field), None) => Ok(field.ident.as_ref().expect("named fields have names")),
+			(None, _) => Err(syn::Error::new_spanned(input, format!("Collable needs a field marked #[{}]", attribute))),
+			(Some(_), Some(second)) => Err(syn::Error::new_spanned(second, format!("only one field can be marked #[{}]", attribute))),
+		}
+	};
+	let (position, velocity, hitbox) = (find("position")?, find("velocity")?, find("hitbox")?);
+
+	let name = &input.ident;
+	let mut generics = input.generics.clone();
+	generics.params.push(GenericParam::Type(syn::parse_quote!(__Tile: ::tile_net::TileKind)));
+	generics.params.push(GenericParam::Type(syn::parse_quote!(__State)));
+	let (impl_generics, _, _) = generics.split_for_impl();
+	let (_, type_generics, where_clause) = input.generics.split_for_impl();
+	Ok(quote! {
+		impl #impl_generics ::tile_net::Collable<__Tile, __State> for #name #type_generics #where_clause {
+			fn points(&self) -> ::tile_net::Points {
+				::tile_net::Points::new(self.#position, &self.#hitbox)
+			}
+
+			fn queued(&self) -> ::tile_net::Vector {
+				self.#velocity
+			}
+
+			fn enqueue(&mut self, vector: ::tile_net::Vector) {
+				self.#velocity = vector;
+			}
+
+			fn resolve<I>(&mut self, mut set: ::tile_net::TileSet<__Tile, I>, _state: &mut __State) -> bool
+				where I: Iterator<Item = (i32, i32)>
+			{
+				let free = |x: Option<&__Tile>| match x {
+					Some(tile) => !::tile_net::TileKind::is_solid(tile),
+					None => false,
+				};
+				if set.with_border().all(free) {
+					self.#position = self.#position + self.#velocity;
+					true
+				} else if self.#velocity.norm2sq() > 1e-6 {
+					self.#velocity.scale(0.5);
+					false
+				} else {
+					self.#velocity = ::tile_net::Vector(0.0, 0.0);
+					true
+				}
+			}
+		}
+	})
+}